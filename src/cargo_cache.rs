@@ -0,0 +1,199 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+
+// cargo-cache remove-dir name along with equivalent wipe folder
+const REMOVE_DIRS: [(&str, &str); 7] = [
+    ("all", "git,registry"),
+    ("git-db", "db"),
+    ("git-repos", "checkouts"),
+    ("registry", "registry"),
+    ("registry-crate-cache", "cache"),
+    ("registry-index", "index"),
+    ("registry-sources", "src"),
+];
+
+/// translate command line of cargo-cache to arguments of cargo trim. Program
+/// name and cache subcommand name are not part of passed arguments. Flag
+/// which has no equivalent returns error instead of being silently ignored
+///
+/// # Errors
+/// Return error if flag is unknown or not supported by cargo trim
+pub fn translate_cargo_cache(args: &[String]) -> Result<Vec<String>> {
+    let mut flags = Vec::new();
+    let mut sub_command = Vec::new();
+    let mut remove_dirs = Vec::new();
+    let mut older_than = None;
+    let mut args = args.iter().map(String::as_str);
+    while let Some(arg) = args.next() {
+        // value of long flag can be passed after =
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(value)),
+            _ => (arg, None),
+        };
+        let mut value = || {
+            inline_value
+                .or_else(|| args.next())
+                .with_context(|| format!("{flag} of cargo-cache requires value"))
+        };
+        match flag {
+            "-a" | "--autoclean" => flags.extend(["--wipe", "src,checkouts"]),
+            "-e" | "--autoclean-expensive" => {
+                flags.extend(["--wipe", "src,checkouts", "--gc", "db"]);
+            }
+            "-g" | "--gc" => flags.extend(["--gc", "db", "--gc", "index"]),
+            "-i" | "--info" | "-l" | "--list-dirs" => flags.push("--query"),
+            "-n" | "--dry-run" => flags.push("--dry-run"),
+            "-f" | "--fsck" => sub_command.push("verify"),
+            "-k" | "--keep-duplicate-crates" => {
+                flags.extend(["--keep-versions", value()?]);
+            }
+            "-o" | "--remove-if-older-than" => older_than = Some(older_than_duration(value()?)?),
+            "-r" | "--remove-dir" => {
+                for name in value()?.split(',') {
+                    let (_, wipe) = REMOVE_DIRS
+                        .iter()
+                        .find(|(remove_dir, _)| *remove_dir == name.trim())
+                        .with_context(|| format!("Unknown cargo-cache directory {name}"))?;
+                    remove_dirs.push(*wipe);
+                }
+            }
+            "-t" | "--top-cache-items" => flags.extend(["--top", value()?]),
+            "clean-unref" => flags.push("--orphan"),
+            "registry" | "registries" | "r" => sub_command.extend(["registry", "--query"]),
+            "trim" => {
+                let (limit_flag, limit) = match args.next() {
+                    Some(limit_flag) => match limit_flag.split_once('=') {
+                        Some((limit_flag, limit)) => (limit_flag, Some(limit)),
+                        None => (limit_flag, args.next()),
+                    },
+                    None => ("", None),
+                };
+                match (limit_flag, limit) {
+                    ("-l" | "--limit", Some(limit)) => flags.extend(["--keep-under", limit]),
+                    _ => anyhow::bail!("trim of cargo-cache is only supported with --limit"),
+                }
+            }
+            _ => anyhow::bail!("{arg} of cargo-cache has no equivalent in cargo trim"),
+        }
+    }
+    let mut translated = flags
+        .into_iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    if let Some(older_than) = older_than {
+        // wipe removes whole folder so age is only respected when crates are
+        // cleaned individually
+        let age_clean = match remove_dirs.as_slice() {
+            [] => anyhow::bail!("--remove-if-older-than of cargo-cache requires --remove-dir"),
+            dirs if dirs.contains(&"git,registry") => vec!["--all"],
+            dirs if dirs
+                .iter()
+                .all(|dir| ["registry", "cache", "src"].contains(dir)) =>
+            {
+                vec!["registry", "--all"]
+            }
+            dirs if dirs.iter().all(|dir| ["db", "checkouts"].contains(dir)) => {
+                vec!["git", "--all"]
+            }
+            _ => anyhow::bail!(
+                "--remove-if-older-than of cargo-cache is only supported when removed directories \
+                 are either all registry or all git directories"
+            ),
+        };
+        translated.extend(["--older-than".to_string(), older_than]);
+        sub_command.splice(0..0, age_clean);
+    } else if !remove_dirs.is_empty() {
+        translated.extend(["--wipe".to_string(), remove_dirs.join(",")]);
+    }
+    // plain cargo-cache invocation shows cache size
+    if translated.is_empty() && sub_command.is_empty() {
+        translated.push("--query".to_string());
+    }
+    translated.extend(sub_command.into_iter().map(ToString::to_string));
+    Ok(translated)
+}
+
+// convert cargo-cache date in form of YYYY.MM.DD or HH:MM:SS of today to
+// duration in seconds elapsed since then. Time is interpreted as UTC
+fn older_than_duration(date: &str) -> Result<String> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let parts = |separator| {
+        date.split(separator)
+            .map(|part| part.parse::<u64>().ok())
+            .collect::<Option<Vec<_>>>()
+    };
+    let seconds = match (parts('.'), parts(':')) {
+        (Some(date_parts), _) if date_parts.len() == 3 => {
+            let days = days_from_civil(date_parts[0], date_parts[1], date_parts[2])
+                .with_context(|| format!("Invalid date {date}"))?;
+            days * 86400
+        }
+        (_, Some(time_parts)) if time_parts.len() == 3 => {
+            now - now % 86400 + time_parts[0] * 3600 + time_parts[1] * 60 + time_parts[2]
+        }
+        _ => anyhow::bail!("Date {date} must be in form of YYYY.MM.DD or HH:MM:SS"),
+    };
+    Ok(format!("{}s", now.saturating_sub(seconds)))
+}
+
+// convert civil date to days since epoch. Return none for invalid or pre epoch
+// date
+fn days_from_civil(year: u64, month: u64, day: u64) -> Option<u64> {
+    if !(1970..=9999).contains(&year) || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year % 400;
+    let shifted_month = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * shifted_month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    (era * 146_097 + day_of_era).checked_sub(719_468)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{days_from_civil, translate_cargo_cache};
+
+    fn translate(args: &str) -> String {
+        let args = args
+            .split_whitespace()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        translate_cargo_cache(&args).unwrap().join(" ")
+    }
+
+    #[test]
+    fn test_translate_cargo_cache() {
+        assert_eq!(translate(""), "--query");
+        assert_eq!(
+            translate("--autoclean -n"),
+            "--wipe src,checkouts --dry-run"
+        );
+        assert_eq!(translate("-k 3"), "--keep-versions 3");
+        assert_eq!(
+            translate("--remove-dir=git-db,registry-sources"),
+            "--wipe db,src"
+        );
+        assert_eq!(translate("trim --limit 5GB"), "--keep-under 5GB");
+        assert_eq!(translate("-f"), "verify");
+        assert!(
+            translate("-r all -o 2020.01.01").starts_with("--older-than ")
+                && translate("-r all -o 2020.01.01").ends_with("s --all")
+        );
+        assert!(translate("-r git-db -o 12:00:00").ends_with("s git --all"));
+        let unsupported = ["toolchain".to_string()];
+        assert!(translate_cargo_cache(&unsupported).is_err());
+    }
+
+    #[test]
+    fn test_days_from_civil() {
+        assert_eq!(days_from_civil(1970, 1, 1), Some(0));
+        assert_eq!(days_from_civil(2000, 3, 1), Some(11017));
+        assert_eq!(days_from_civil(2024, 2, 29), Some(19782));
+        assert_eq!(days_from_civil(1969, 12, 31), None);
+    }
+}
@@ -0,0 +1,81 @@
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use clap::ValueEnum;
+
+// colors are enabled by default so library users keep colored output
+static COLOR_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// When human readable output is colored
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+/// enable or disable colored output. Stream is stderr when human readable
+/// output is written to stderr such as in ndjson mode
+pub(crate) fn set_color(choice: ColorChoice, stderr: bool) {
+    let enabled = match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            let is_terminal = if stderr {
+                std::io::stderr().is_terminal()
+            } else {
+                std::io::stdout().is_terminal()
+            };
+            // NO_COLOR is only honored when it is set to non empty value
+            let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+            is_terminal && !no_color
+        }
+    };
+    COLOR_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// remove color escape sequence from text if colored output is disabled.
+/// Other escape sequence such as one clearing screen is kept
+pub(crate) fn paint(text: String) -> String {
+    if COLOR_ENABLED.load(Ordering::Relaxed) {
+        text
+    } else {
+        strip_color(&text)
+    }
+}
+
+// remove SGR escape sequence in form of ESC [ params m
+fn strip_color(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("\x1b[") {
+        stripped.push_str(&rest[..start]);
+        let sequence = &rest[start + 2..];
+        let params_len = sequence
+            .find(|c: char| !c.is_ascii_digit() && c != ';')
+            .unwrap_or(sequence.len());
+        if sequence[params_len..].starts_with('m') {
+            rest = &sequence[params_len + 1..];
+        } else {
+            stripped.push_str("\x1b[");
+            rest = sequence;
+        }
+    }
+    stripped.push_str(rest);
+    stripped
+}
+
+#[cfg(test)]
+mod test {
+    use owo_colors::OwoColorize;
+
+    use super::strip_color;
+
+    #[test]
+    fn test_strip_color() {
+        let text = format!("{} {} done", "Removed".red(), "serde".bold().blue());
+        assert_eq!(strip_color(&text), "Removed serde done");
+        assert_eq!(strip_color("\x1b[2J\x1b[Hscreen"), "\x1b[2J\x1b[Hscreen");
+        assert_eq!(strip_color("plain"), "plain");
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser;
+use owo_colors::OwoColorize;
+use serde::Serialize;
+
+use crate::command::OutputFormat;
+use crate::report::{SummaryFile, print_json};
+use crate::utils::{convert_pretty, parse_size, print_dash, query_print};
+
+#[derive(Debug, Parser)]
+#[command(
+    about = "Aggregate summary files collected from many machines into fleet level report",
+    arg_required_else_help = true
+)]
+pub(crate) struct Aggregate {
+    #[arg(
+        help = "Json summary files written with --summary-file",
+        value_name = "file",
+        required = true
+    )]
+    files: Vec<PathBuf>,
+    #[arg(
+        long = "format",
+        short = 'f',
+        value_enum,
+        default_value_t = OutputFormat::Table,
+        help = "Output format of fleet report"
+    )]
+    format: OutputFormat,
+    #[arg(
+        long = "threshold",
+        help = "List machines whose cache size is over threshold such as 500MB or 10GB",
+        value_name = "size",
+        value_parser = parse_size
+    )]
+    threshold: Option<u64>,
+    #[arg(
+        long = "top",
+        short = 't',
+        help = "Number of crates with highest size across fleet to show",
+        default_value_t = 10
+    )]
+    top: usize,
+}
+
+/// Cache size of single machine
+#[derive(Serialize)]
+struct MachineReport {
+    machine: String,
+    total: u64,
+}
+
+/// Size of crate summed across all machines where crate is present
+#[derive(Serialize)]
+struct FleetCrateReport {
+    id: String,
+    size: u64,
+    machines: usize,
+}
+
+/// Fleet level report created from multiple summary files
+#[derive(Serialize)]
+struct FleetReport {
+    machines: Vec<MachineReport>,
+    total: u64,
+    top_crates: Vec<FleetCrateReport>,
+    threshold: Option<u64>,
+    over_threshold: Vec<String>,
+}
+
+impl Aggregate {
+    pub(super) fn run(&self) -> Result<()> {
+        let mut summaries = Vec::new();
+        for file in &self.files {
+            let summary = SummaryFile::read(file)?;
+            // summary without machine name is identified by its file name
+            let machine = summary.machine().cloned().unwrap_or_else(|| {
+                file.file_stem().map_or_else(
+                    || file.display().to_string(),
+                    |stem| stem.to_string_lossy().to_string(),
+                )
+            });
+            summaries.push((machine, summary));
+        }
+        let fleet_report = fleet_report(&summaries, self.top, self.threshold);
+        if self.format == OutputFormat::Json {
+            print_json(&fleet_report)?;
+        } else {
+            show_fleet_report(&fleet_report);
+        }
+        Ok(())
+    }
+}
+
+// merge summary of all machines into fleet report
+fn fleet_report(
+    summaries: &[(String, SummaryFile)],
+    top: usize,
+    threshold: Option<u64>,
+) -> FleetReport {
+    let mut machines = Vec::new();
+    let mut crates: HashMap<String, (u64, BTreeSet<&String>)> = HashMap::new();
+    for (machine, summary) in summaries {
+        machines.push(MachineReport {
+            machine: machine.clone(),
+            total: summary.cache().total(),
+        });
+        for crate_report in summary.crates() {
+            let entry = crates.entry(crate_report.key()).or_default();
+            entry.0 += crate_report.size();
+            entry.1.insert(machine);
+        }
+    }
+    machines.sort_by(|a, b| b.total.cmp(&a.total).then(a.machine.cmp(&b.machine)));
+    let mut top_crates = crates
+        .into_iter()
+        .map(|(id, (size, machine_set))| FleetCrateReport {
+            id,
+            size,
+            machines: machine_set.len(),
+        })
+        .collect::<Vec<_>>();
+    top_crates.sort_by(|a, b| b.size.cmp(&a.size).then(a.id.cmp(&b.id)));
+    top_crates.truncate(top);
+    let over_threshold = threshold
+        .map(|threshold| {
+            machines
+                .iter()
+                .filter(|machine| machine.total > threshold)
+                .map(|machine| machine.machine.clone())
+                .collect()
+        })
+        .unwrap_or_default();
+    FleetReport {
+        total: machines.iter().map(|machine| machine.total).sum(),
+        machines,
+        top_crates,
+        threshold,
+        over_threshold,
+    }
+}
+
+// show fleet report in table format
+fn show_fleet_report(fleet_report: &FleetReport) {
+    human_println!("{}", "Cache size per machine".bold());
+    for machine in &fleet_report.machines {
+        query_print(
+            &format!("   {}", machine.machine),
+            &convert_pretty(machine.total),
+        );
+    }
+    print_dash(crate::utils::query_full_width());
+    human_println!("{}", "Crates with highest size across fleet".bold());
+    for crate_report in &fleet_report.top_crates {
+        query_print(
+            &format!(
+                "   {} ({} machines)",
+                crate_report.id, crate_report.machines
+            ),
+            &convert_pretty(crate_report.size),
+        );
+    }
+    print_dash(crate::utils::query_full_width());
+    if let Some(threshold) = fleet_report.threshold {
+        human_println!(
+            "{}",
+            format!("Machines over {}", convert_pretty(threshold).trim()).bold()
+        );
+        for machine in &fleet_report.over_threshold {
+            human_println!("   {}", machine.red());
+        }
+        print_dash(crate::utils::query_full_width());
+    }
+    query_print(
+        &format!(
+            "Total cache size of {} machines",
+            fleet_report.machines.len()
+        ),
+        &convert_pretty(fleet_report.total),
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::fleet_report;
+    use crate::report::SummaryFile;
+
+    fn summary(total: u64, crates: &[(&str, u64)]) -> SummaryFile {
+        let crates = crates
+            .iter()
+            .map(|(name, size)| {
+                format!(
+                    r#"{{"id":null,"name":"{name}","version":"1.0.0","size":{size},"category":"registry","source":null,"path":[],"last_used":null}}"#
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        serde_json::from_str(&format!(
+            r#"{{"cache":{{"folders":[],"total":{total}}},"crates":[{crates}]}}"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_fleet_report() {
+        let summaries = [
+            (
+                String::from("alpha"),
+                summary(300, &[("serde", 100), ("syn", 50)]),
+            ),
+            (
+                String::from("beta"),
+                summary(500, &[("serde", 100), ("tokio", 120)]),
+            ),
+        ];
+        let report = fleet_report(&summaries, 2, Some(400));
+        assert_eq!(report.total, 800);
+        assert_eq!(report.machines[0].machine, "beta");
+        assert_eq!(report.machines[1].machine, "alpha");
+        assert_eq!(report.top_crates.len(), 2);
+        assert_eq!(report.top_crates[0].id, "serde@1.0.0");
+        assert_eq!(report.top_crates[0].size, 200);
+        assert_eq!(report.top_crates[0].machines, 2);
+        assert_eq!(report.top_crates[1].id, "tokio@1.0.0");
+        assert_eq!(report.over_threshold, vec![String::from("beta")]);
+    }
+}
@@ -0,0 +1,156 @@
+use std::fs::{self, File, FileTimes};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use owo_colors::OwoColorize;
+
+use crate::crate_detail::{CrateDetail, CrateMetaData, SOURCE_ARCHIVE_EXTENSION};
+use crate::freed_space::measure_before_removal;
+use crate::protect::skip_protected;
+use crate::stats_file::StatsFile;
+use crate::utils::{convert_pretty, delete_folder, get_size, parse_duration};
+
+#[derive(Debug, Parser)]
+#[command(
+    about = "Compress registry source directories which are not used for duration into tarball \
+             stored next to them"
+)]
+pub(crate) struct Archive {
+    #[arg(
+        long = "dry-run",
+        short = 'n',
+        help = "Run command in dry run mode to see what would be done"
+    )]
+    dry_run: bool,
+    #[arg(
+        long = "unused-for",
+        short = 'u',
+        help = "Archive registry sources whose files are not read or modified within duration \
+                such as 90d",
+        value_name = "duration",
+        value_parser = parse_duration
+    )]
+    unused_for: Duration,
+}
+
+impl Archive {
+    pub(super) fn run(&self, crate_detail: &CrateDetail, stats_file: &mut StatsFile) -> Result<()> {
+        let mut unused = crate_detail
+            .registry_crates_source()
+            .iter()
+            .filter(|crate_metadata| {
+                crate_metadata.is_unused_for(self.unused_for) && !skip_protected(crate_metadata)
+            })
+            .collect::<Vec<_>>();
+        unused.sort();
+        let mut size_cleaned = 0;
+        let mut crate_archived = 0;
+        for crate_metadata in unused {
+            for source_dir in crate_metadata.path().iter().filter(|path| path.is_dir()) {
+                if let Some(size_freed) = self.archive_source(crate_metadata, source_dir)? {
+                    size_cleaned += size_freed;
+                    crate_archived += 1;
+                }
+            }
+        }
+        stats_file.record(
+            "registry",
+            "archive",
+            size_cleaned,
+            crate_archived,
+            self.dry_run,
+        );
+        human_println!(
+            "{}",
+            format!(
+                "{crate_archived} registry sources archived which reclaimed {}",
+                convert_pretty(size_cleaned)
+            )
+            .blue()
+        );
+        Ok(())
+    }
+
+    // compress source directory into tarball and remove directory. Return size
+    // reclaimed by replacing directory with tarball or None if directory is
+    // not removed
+    fn archive_source(
+        &self,
+        crate_metadata: &CrateMetaData,
+        source_dir: &Path,
+    ) -> Result<Option<u64>> {
+        let source_size = get_size(source_dir).unwrap_or(0);
+        if self.dry_run {
+            if !delete_folder(source_dir, self.dry_run)? {
+                return Ok(None);
+            }
+            human_println!(
+                "{} {} ({})",
+                "Dry run: Archived".yellow(),
+                crate_metadata.full_name(),
+                convert_pretty(source_size).trim()
+            );
+            return Ok(Some(source_size));
+        }
+        // registry source is measured before tarball is added to it so freed space
+        // accounts for size of tarball
+        measure_before_removal(source_dir);
+        let tarball = archive_path(source_dir);
+        let (parent, file_name) = source_dir
+            .parent()
+            .zip(source_dir.file_name())
+            .context("Failed to get parent directory of registry source")?;
+        let status = Command::new("tar")
+            .arg("-czf")
+            .arg(&tarball)
+            .arg("-C")
+            .arg(parent)
+            .arg(file_name)
+            .status()
+            .context("Failed to run tar")?;
+        if !status.success() {
+            // partially written tarball is removed so directory stays only copy
+            let _ = fs::remove_file(&tarball);
+            anyhow::bail!("Failed to archive {}", source_dir.display());
+        }
+        // tarball keeps last use time of source so it is not treated as recently
+        // used crate
+        if let Some(last_used) = crate_metadata.last_used() {
+            File::options()
+                .write(true)
+                .open(&tarball)
+                .and_then(|file| {
+                    file.set_times(
+                        FileTimes::new()
+                            .set_accessed(last_used)
+                            .set_modified(last_used),
+                    )
+                })
+                .context("Failed to set time of archived registry source")?;
+        }
+        // tarball is kept when directory cannot be removed completely since it
+        // can be only complete copy of source
+        if !delete_folder(source_dir, self.dry_run)? {
+            return Ok(None);
+        }
+        let tarball_size = get_size(&tarball).unwrap_or(0);
+        human_println!(
+            "{} {} ({} -> {})",
+            "Archived".green(),
+            crate_metadata.full_name(),
+            convert_pretty(source_size).trim(),
+            convert_pretty(tarball_size).trim()
+        );
+        Ok(Some(source_size.saturating_sub(tarball_size)))
+    }
+}
+
+// path of tarball which store archived registry source directory
+fn archive_path(source_dir: &Path) -> PathBuf {
+    let mut tarball = source_dir.as_os_str().to_os_string();
+    tarball.push(SOURCE_ARCHIVE_EXTENSION);
+    PathBuf::from(tarball)
+}
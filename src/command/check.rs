@@ -0,0 +1,81 @@
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+use owo_colors::OwoColorize;
+
+use crate::dir_path::DirPath;
+use crate::utils::{convert_pretty, get_size, parse_size};
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Category {
+    All,
+    Bin,
+    Registry,
+    RegistryCache,
+    RegistryIndex,
+    RegistrySrc,
+    Git,
+    GitCheckout,
+    GitDb,
+}
+
+#[derive(Debug, Parser)]
+#[command(
+    about = "Check cache size and exit with nonzero code when it exceeds maximum size",
+    arg_required_else_help = true
+)]
+pub(crate) struct Check {
+    #[arg(
+        long = "category",
+        short = 'c',
+        value_enum,
+        default_value_t = Category::All,
+        help = "Category whose size is checked. All checks whole cargo home"
+    )]
+    category: Category,
+    #[arg(
+        long = "max-size",
+        short = 'm',
+        help = "Maximum allowed size such as 2GB",
+        value_name = "size",
+        value_parser = parse_size
+    )]
+    max_size: u64,
+}
+
+impl Check {
+    /// print size of category and return true if it exceeds maximum size
+    pub(super) fn run(&self, dir_path: &DirPath) -> bool {
+        let (name, path) = self.category_path(dir_path);
+        let size = get_size(&path).unwrap_or(0);
+        let exceeded = size > self.max_size;
+        let message = format!(
+            "{name} size {} {} {}",
+            convert_pretty(size).trim(),
+            if exceeded { "exceeds" } else { "is under" },
+            convert_pretty(self.max_size).trim()
+        );
+        if exceeded {
+            human_println!("{}", message.red());
+        } else {
+            human_println!("{}", message.green());
+        }
+        exceeded
+    }
+
+    // display name and directory of category
+    fn category_path(&self, dir_path: &DirPath) -> (&'static str, PathBuf) {
+        let (name, path) = match self.category {
+            Category::All => ("Cargo home", dir_path.cargo_home()),
+            Category::Bin => (".cargo/bin", dir_path.bin_dir()),
+            Category::Registry => (".cargo/registry", dir_path.registry_dir()),
+            Category::RegistryCache => (".cargo/registry/cache", dir_path.cache_dir()),
+            Category::RegistryIndex => (".cargo/registry/index", dir_path.index_dir()),
+            Category::RegistrySrc => (".cargo/registry/src", dir_path.src_dir()),
+            Category::Git => (".cargo/git", dir_path.git_dir()),
+            Category::GitCheckout => (".cargo/git/checkouts", dir_path.checkout_dir()),
+            Category::GitDb => (".cargo/git/db", dir_path.db_dir()),
+        };
+        (name, path.clone())
+    }
+}
@@ -0,0 +1,47 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::{Parser, ValueEnum};
+
+use crate::completion_index::CompletionIndex;
+use crate::dir_path::DirPath;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum CompleteKind {
+    All,
+    Git,
+    Registry,
+}
+
+#[derive(Debug, Parser)]
+#[command(
+    hide = true,
+    about = "List cached crate names starting with prefix one per line for shell completion"
+)]
+pub(crate) struct Complete {
+    #[arg(value_enum, help = "Cache whose crate names are listed")]
+    kind: CompleteKind,
+    #[arg(default_value = "", help = "Prefix of crate name")]
+    prefix: String,
+}
+
+impl Complete {
+    // output is read by completion script so it is printed even in quiet mode
+    pub(super) fn run(&self, cargo_home: Option<PathBuf>) -> Result<()> {
+        let dir_path = DirPath::new(cargo_home)?;
+        let index = CompletionIndex::load(&dir_path);
+        let names = match self.kind {
+            CompleteKind::All => {
+                let mut names = index.registry(&self.prefix);
+                names.extend(index.git(&self.prefix));
+                names
+            }
+            CompleteKind::Git => index.git(&self.prefix),
+            CompleteKind::Registry => index.registry(&self.prefix),
+        };
+        for name in names {
+            println!("{name}");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+use std::fmt::Write as _;
+
+use clap::{CommandFactory, Parser, ValueEnum};
+
+use super::Command;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Shell {
+    Bash,
+    Fish,
+    Zsh,
+}
+
+#[derive(Debug, Parser)]
+#[command(
+    about = "Print shell completion script which completes subcommand along with cached crate \
+             names for registry --remove, git --remove and info"
+)]
+pub(crate) struct Completion {
+    #[arg(value_enum, help = "Shell for which completion script is generated")]
+    shell: Shell,
+}
+
+impl Completion {
+    // script is printed to stdout so it can be sourced directly
+    pub(super) fn run(&self) {
+        let program = clap::crate_name!();
+        let subcommands = Command::command()
+            .get_subcommands()
+            .filter(|subcommand| !subcommand.is_hide_set())
+            .map(|subcommand| subcommand.get_name().to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let script = match self.shell {
+            Shell::Bash => bash_script(program, &subcommands),
+            Shell::Fish => fish_script(program, &subcommands),
+            Shell::Zsh => format!(
+                "autoload -U +X bashcompinit && bashcompinit\n{}",
+                bash_script(program, &subcommands)
+            ),
+        };
+        print!("{script}");
+    }
+}
+
+// bash completion for both cargo-trim and cargo trim. Existing cargo completion
+// is used for other cargo subcommand
+fn bash_script(program: &str, subcommands: &str) -> String {
+    format!(
+        r#"_cargo_trim() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}"
+    local prev="${{COMP_WORDS[COMP_CWORD-1]}}"
+    local start=1
+    [[ "${{COMP_WORDS[1]}}" == trim ]] && start=2
+    local sub="" word
+    for word in "${{COMP_WORDS[@]:start:COMP_CWORD-start}}"; do
+        case " {subcommands} " in
+            *" $word "*) sub="$word"; break ;;
+        esac
+    done
+    local kind=""
+    case "$sub:$prev" in
+        registry:-r|registry:--remove) kind=registry ;;
+        git:-r|git:--remove) kind=git ;;
+        info:*) [[ "$cur" != -* ]] && kind=all ;;
+    esac
+    if [[ -n "$kind" ]]; then
+        COMPREPLY=($(compgen -W "$({program} __complete "$kind" "$cur" 2>/dev/null)" -- "$cur"))
+    elif [[ -z "$sub" && "$cur" != -* ]]; then
+        COMPREPLY=($(compgen -W "{subcommands}" -- "$cur"))
+    fi
+}}
+complete -F _cargo_trim {program}
+
+_cargo_trim_cargo() {{
+    if [[ "${{COMP_WORDS[1]}}" == trim ]]; then
+        _cargo_trim
+    elif declare -F _cargo >/dev/null; then
+        _cargo "$@"
+    fi
+}}
+declare -F _cargo >/dev/null || {{ declare -F _completion_loader >/dev/null && _completion_loader cargo; }}
+complete -F _cargo_trim_cargo cargo
+"#
+    )
+}
+
+// fish completion for both cargo-trim and cargo trim
+fn fish_script(program: &str, subcommands: &str) -> String {
+    let mut script = String::new();
+    for (command, condition) in [
+        (program, ""),
+        ("cargo", "__fish_seen_subcommand_from trim; and "),
+    ] {
+        let _ = write!(
+            script,
+            "complete -c {command} -n '{condition}not __fish_seen_subcommand_from {subcommands}' \
+             -f -a '{subcommands}'\n\
+             complete -c {command} -n '{condition}__fish_seen_subcommand_from registry' -s r -l \
+             remove -x -a '({program} __complete registry (commandline -ct))'\n\
+             complete -c {command} -n '{condition}__fish_seen_subcommand_from git' -s r -l remove \
+             -x -a '({program} __complete git (commandline -ct))'\n\
+             complete -c {command} -n '{condition}__fish_seen_subcommand_from info' -f -a \
+             '({program} __complete all (commandline -ct))'\n"
+        );
+    }
+    script
+}
@@ -25,6 +25,12 @@ pub(crate) struct Config {
     location: bool,
     #[arg(long = "print", short = 'p', help = "Display config file content")]
     print: bool,
+    #[arg(
+        long = "validate",
+        help = "Check config file for unknown keys, missing or unreadable paths and conflicting \
+                retention"
+    )]
+    validate: bool,
 }
 
 impl Config {
@@ -32,25 +38,44 @@ impl Config {
         if self.directory {
             let read_directory = config_file.directory();
             for name in read_directory {
-                println!("{name}");
+                human_println!("{name}");
             }
         }
         if self.ignore {
             let read_ignore_file_name = config_file.ignore_file_name();
             for name in read_ignore_file_name {
-                println!("{name}");
+                human_println!("{name}");
             }
         }
         if self.location {
-            println!(
-                "{}: {config_file_location:?}",
+            human_println!(
+                "{}: {}",
                 "Config file location".blue(),
+                config_file_location.display()
             );
+            if let Some(project_config) = config_file.project_config_location() {
+                human_println!(
+                    "{}: {}",
+                    "Project config file location".blue(),
+                    project_config.display()
+                );
+            }
         }
         if self.print {
-            let content = toml::to_string_pretty(config_file)
+            let content = config_file
+                .to_toml()
                 .context("Failed to convert struct to pretty toml")?;
-            println!("{content}");
+            human_println!("{content}");
+        }
+        if self.validate {
+            let problems = config_file.validate()?;
+            for problem in &problems {
+                human_println!("{} {problem}", "Warning:".yellow());
+            }
+            if !problems.is_empty() {
+                anyhow::bail!("{} problems found in config", problems.len());
+            }
+            human_println!("{}", "Config file is valid".green());
         }
         Ok(())
     }
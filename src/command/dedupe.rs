@@ -0,0 +1,256 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::hash::Hasher;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use owo_colors::OwoColorize;
+
+use crate::crate_detail::{CrateDetail, CrateMetaData};
+use crate::package_lock::lock_for_removal;
+use crate::stats_file::StatsFile;
+use crate::utils::{convert_pretty, file_identity};
+
+// suffix of temporary hardlink which is renamed over duplicate file
+const LINK_SUFFIX: &str = ".cargo-trim-dedupe";
+
+#[derive(Debug, Parser)]
+#[command(
+    about = "Replace byte identical files of registry sources and git checkouts with hardlinks \
+             to single copy"
+)]
+pub(crate) struct Dedupe {
+    #[arg(
+        long = "dry-run",
+        short = 'n',
+        help = "Run command in dry run mode to see what would be done"
+    )]
+    dry_run: bool,
+}
+
+impl Dedupe {
+    pub(super) fn run(&self, crate_detail: &CrateDetail, stats_file: &mut StatsFile) -> Result<()> {
+        let mut files_by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+        for crate_metadata in crate_detail
+            .registry_crates_source()
+            .iter()
+            .chain(crate_detail.git_crates_archive())
+        {
+            for path in crate_metadata.path() {
+                list_files(path, &mut files_by_size)?;
+            }
+        }
+        let git_checkouts: Vec<&PathBuf> = crate_detail
+            .git_crates_archive()
+            .iter()
+            .flat_map(CrateMetaData::path)
+            .collect();
+        // saved size and linked file count of registry and git stats
+        let mut saved: BTreeMap<&str, (u64, usize)> =
+            BTreeMap::from([("registry", (0, 0)), ("git", (0, 0))]);
+        for (size, files) in files_by_size {
+            // empty file do not take any space and unique size cannot have duplicate
+            if size == 0 || files.len() < 2 {
+                continue;
+            }
+            for duplicates in group_identical(files)? {
+                let (original, duplicates) = duplicates
+                    .split_first()
+                    .context("Failed to get original file of duplicates")?;
+                for duplicate in duplicates {
+                    if self.link(original, duplicate)? {
+                        let category = if git_checkouts
+                            .iter()
+                            .any(|checkout| duplicate.starts_with(checkout))
+                        {
+                            "git"
+                        } else {
+                            "registry"
+                        };
+                        if let Some((size_saved, file_linked)) = saved.get_mut(category) {
+                            *size_saved += size;
+                            *file_linked += 1;
+                        }
+                    }
+                }
+            }
+        }
+        for (category, (size_saved, file_linked)) in &saved {
+            stats_file.record(category, "dedupe", *size_saved, *file_linked, self.dry_run);
+        }
+        let size_saved = saved.values().map(|(size_saved, _)| size_saved).sum();
+        let file_linked: usize = saved.values().map(|(_, file_linked)| file_linked).sum();
+        human_println!(
+            "{}",
+            format!(
+                "{file_linked} duplicate files replaced with hardlink which saved {}",
+                convert_pretty(size_saved)
+            )
+            .blue()
+        );
+        Ok(())
+    }
+
+    // replace duplicate with hardlink of original. Hardlink is created next to
+    // duplicate and renamed over it so duplicate is never missing. Return false
+    // when link cannot be created such as for file on other filesystem
+    fn link(&self, original: &Path, duplicate: &Path) -> Result<bool> {
+        if self.dry_run {
+            human_println!(
+                "{} {} to {}",
+                "Dry run: Linked".yellow(),
+                duplicate.display(),
+                original.display()
+            );
+            return Ok(true);
+        }
+        lock_for_removal(duplicate)?;
+        let mut temporary_link = duplicate.as_os_str().to_os_string();
+        temporary_link.push(LINK_SUFFIX);
+        let temporary_link = PathBuf::from(temporary_link);
+        let linked = fs::hard_link(original, &temporary_link)
+            .and_then(|()| fs::rename(&temporary_link, duplicate));
+        if let Err(err) = linked {
+            let _ = fs::remove_file(&temporary_link);
+            human_println!(
+                "{}",
+                format!(
+                    "WARNING: Failed to link {} to {}: {err}",
+                    duplicate.display(),
+                    original.display()
+                )
+                .yellow()
+            );
+            return Ok(false);
+        }
+        Ok(true)
+    }
+}
+
+// list regular files inside path recursively grouped by their size. Symlink is
+// skipped since it does not store content
+fn list_files(path: &Path, files_by_size: &mut HashMap<u64, Vec<PathBuf>>) -> Result<()> {
+    let metadata = fs::symlink_metadata(path)
+        .context(format!("Failed to read metadata of {}", path.display()))?;
+    if metadata.is_dir() {
+        for entry in
+            fs::read_dir(path).context(format!("Failed to read directory {}", path.display()))?
+        {
+            list_files(&entry?.path(), files_by_size)?;
+        }
+    } else if metadata.is_file()
+        // cargo writes .cargo-ok while extracting so it is never shared
+        && path.file_name().is_some_and(|file_name| file_name != ".cargo-ok")
+    {
+        files_by_size
+            .entry(metadata.len())
+            .or_default()
+            .push(path.to_path_buf());
+    }
+    Ok(())
+}
+
+// group files of same size by content and permissions. Hardlink shares
+// permissions so files with different permissions are never grouped. Files
+// which are already hardlink of same file are kept only once and group with
+// single file is dropped
+fn group_identical(files: Vec<PathBuf>) -> Result<Vec<Vec<PathBuf>>> {
+    let mut by_hash: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    let mut identities = Vec::new();
+    for file in files {
+        let identity = file_identity(&fs::metadata(&file)?);
+        if identity.is_some() && identities.contains(&identity) {
+            continue;
+        }
+        identities.push(identity);
+        by_hash.entry(content_hash(&file)?).or_default().push(file);
+    }
+    let mut groups = Vec::new();
+    for mut files in by_hash.into_values() {
+        files.sort();
+        // hash collision is ruled out by comparing content with first file
+        while let Some((first, rest)) = files.split_first() {
+            let first_content = fs::read(first)?;
+            let first_permissions = fs::metadata(first)?.permissions();
+            let mut group = vec![first.clone()];
+            let mut remaining = Vec::new();
+            for file in rest {
+                if fs::metadata(file)?.permissions() == first_permissions
+                    && fs::read(file)? == first_content
+                {
+                    group.push(file.clone());
+                } else {
+                    remaining.push(file.clone());
+                }
+            }
+            if group.len() > 1 {
+                groups.push(group);
+            }
+            files = remaining;
+        }
+    }
+    Ok(groups)
+}
+
+// hash content of file without reading whole file into memory
+fn content_hash(path: &Path) -> Result<u64> {
+    let mut reader =
+        BufReader::new(File::open(path).context(format!("Failed to open {}", path.display()))?);
+    let mut hasher = DefaultHasher::new();
+    let mut buffer = [0; 8192];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.write(&buffer[..read]);
+    }
+    Ok(hasher.finish())
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::{Dedupe, group_identical};
+    use crate::utils::file_identity;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dedupe_same_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("cargo_trim_dedupe_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let original = dir.join("original.rs");
+        let duplicate = dir.join("duplicate.rs");
+        let executable = dir.join("executable.sh");
+        for file in [&original, &duplicate, &executable] {
+            fs::write(file, "fn main() {}").unwrap();
+        }
+        fs::set_permissions(&executable, fs::Permissions::from_mode(0o755)).unwrap();
+        fs::set_permissions(&original, fs::Permissions::from_mode(0o644)).unwrap();
+        fs::set_permissions(&duplicate, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let groups = group_identical(vec![
+            original.clone(),
+            duplicate.clone(),
+            executable.clone(),
+        ])
+        .unwrap();
+        assert_eq!(groups, vec![vec![duplicate.clone(), original.clone()]]);
+        let dedupe = Dedupe { dry_run: false };
+        assert!(dedupe.link(&groups[0][0], &groups[0][1]).unwrap());
+        let identity = |path| file_identity(&fs::metadata(path).unwrap());
+        assert_eq!(identity(&original), identity(&duplicate));
+        assert_ne!(identity(&original), identity(&executable));
+        assert_eq!(
+            fs::metadata(&executable).unwrap().permissions().mode() & 0o777,
+            0o755
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
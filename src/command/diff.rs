@@ -0,0 +1,222 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+
+use crate::crate_detail::{CrateDetail, CrateMetaData};
+use crate::dir_path::DirPath;
+use crate::utils::{convert_pretty, format_timestamp, print_dash};
+
+#[derive(Debug, Parser)]
+#[command(about = "Compare current scan against previously saved scan")]
+pub(crate) struct Diff {
+    #[arg(
+        long = "file",
+        short = 'f',
+        help = "Saved scan file to compare against. Defaults to scan saved with --save",
+        value_name = "file"
+    )]
+    file: Option<PathBuf>,
+    #[arg(
+        long = "save",
+        short = 's',
+        help = "Save current scan after comparing so next diff is compared against it"
+    )]
+    save: bool,
+}
+
+// size of each crate stored in category at time of scan
+#[derive(Serialize, Deserialize, Default)]
+struct SavedScan {
+    taken: String,
+    category: BTreeMap<String, BTreeMap<String, u64>>,
+}
+
+impl SavedScan {
+    // create saved scan from current crate detail
+    fn new(crate_detail: &CrateDetail) -> Self {
+        let category = [
+            ("bin", crate_detail.bin()),
+            ("registry_source", crate_detail.registry_crates_source()),
+            ("registry_archive", crate_detail.registry_crates_archive()),
+            ("git_checkout", crate_detail.git_crates_archive()),
+            ("git_db", crate_detail.git_crates_source()),
+        ]
+        .into_iter()
+        .map(|(name, crates)| (name.to_string(), crate_sizes(crates)))
+        .collect();
+        Self {
+            taken: format_timestamp(SystemTime::now()),
+            category,
+        }
+    }
+
+    // read saved scan from file
+    fn read(file: &Path) -> Result<Self> {
+        let content = fs::read_to_string(file)
+            .with_context(|| format!("Failed to read saved scan {}", file.display()))?;
+        toml::from_str(&content).context("failed to convert string to saved scan")
+    }
+
+    // write saved scan to file
+    fn write(&self, file: &Path) -> Result<()> {
+        let serialized =
+            toml::to_string_pretty(&self).context("Scan cannot be converted to toml")?;
+        fs::write(file, serialized)
+            .with_context(|| format!("Failed to write saved scan {}", file.display()))
+    }
+
+    // total size of category
+    fn category_size(&self, category: &str) -> u64 {
+        self.category
+            .get(category)
+            .map_or(0, |crates| crates.values().sum())
+    }
+
+    // size of each crate summed across all category
+    fn crate_sizes(&self) -> BTreeMap<&String, u64> {
+        let mut sizes = BTreeMap::new();
+        for crates in self.category.values() {
+            for (name, size) in crates {
+                *sizes.entry(name).or_default() += size;
+            }
+        }
+        sizes
+    }
+}
+
+impl Diff {
+    pub(super) fn run(&self, dir_path: &DirPath, crate_detail: &CrateDetail) -> Result<()> {
+        let file = self.file.as_ref().unwrap_or_else(|| dir_path.scan_file());
+        let current = SavedScan::new(crate_detail);
+        if file.exists() {
+            let saved = SavedScan::read(file)?;
+            show_diff(&saved, &current);
+        } else if !self.save {
+            human_println!(
+                "{}",
+                format!(
+                    "No saved scan found at {}. Run cargo trim diff --save to save current scan",
+                    file.display()
+                )
+                .yellow()
+            );
+        }
+        if self.save {
+            current.write(file)?;
+            human_println!("{} {}", "Saved current scan to".blue(), file.display());
+        }
+        Ok(())
+    }
+}
+
+// show size change of each category along with added and removed crates
+fn show_diff(saved: &SavedScan, current: &SavedScan) {
+    let dash_len = 70;
+    human_println!("Comparing scan of {} with {}", saved.taken, current.taken);
+    human_println!(
+        "{}",
+        format!(
+            "{:<22}{:>16}{:>16}{:>16}",
+            "CATEGORY", "BEFORE", "AFTER", "CHANGE"
+        )
+        .bold()
+    );
+    print_dash(dash_len);
+    let (mut before_total, mut after_total) = (0, 0);
+    for category in [
+        "bin",
+        "registry_source",
+        "registry_archive",
+        "git_checkout",
+        "git_db",
+    ] {
+        let before = saved.category_size(category);
+        let after = current.category_size(category);
+        human_println!(
+            "{:<22}{:>16}{:>16}{:>16}",
+            category.replace('_', " "),
+            convert_pretty(before),
+            convert_pretty(after),
+            size_change(before, after)
+        );
+        before_total += before;
+        after_total += after;
+    }
+    print_dash(dash_len);
+    human_println!(
+        "{:<22}{:>16}{:>16}{:>16}",
+        "total",
+        convert_pretty(before_total),
+        convert_pretty(after_total),
+        size_change(before_total, after_total)
+    );
+    print_dash(dash_len);
+
+    let saved_crates = saved.crate_sizes();
+    let current_crates = current.crate_sizes();
+    show_crates(
+        "Added crates",
+        &current_crates,
+        &saved_crates,
+        &"+".green().to_string(),
+    );
+    show_crates(
+        "Removed crates",
+        &saved_crates,
+        &current_crates,
+        &"-".red().to_string(),
+    );
+}
+
+// show crates present in first but not in second, largest crate first
+fn show_crates(
+    title: &str,
+    first: &BTreeMap<&String, u64>,
+    second: &BTreeMap<&String, u64>,
+    marker: &str,
+) {
+    let mut crates = first
+        .iter()
+        .filter(|(name, _)| !second.contains_key(*name))
+        .collect::<Vec<_>>();
+    crates.sort_by(|a, b| b.1.cmp(a.1));
+    human_println!("{}", format!("{title} ({})", crates.len()).bold());
+    for (name, size) in crates {
+        human_println!("{marker} {name:<52}{:>16}", convert_pretty(*size));
+    }
+}
+
+// signed size difference between before and after
+fn size_change(before: u64, after: u64) -> String {
+    if after >= before {
+        format!("+{}", convert_pretty(after - before).trim())
+    } else {
+        format!("-{}", convert_pretty(before - after).trim())
+    }
+}
+
+// size of each crate keyed by full name
+fn crate_sizes(crates: &HashSet<CrateMetaData>) -> BTreeMap<String, u64> {
+    let mut sizes = BTreeMap::new();
+    for crate_metadata in crates {
+        *sizes.entry(crate_metadata.full_name()).or_default() += crate_metadata.size();
+    }
+    sizes
+}
+
+#[cfg(test)]
+mod test {
+    use super::size_change;
+
+    #[test]
+    fn test_size_change() {
+        assert_eq!(size_change(1000, 3000), "+2.000 kB");
+        assert_eq!(size_change(3000, 1000), "-2.000 kB");
+    }
+}
@@ -0,0 +1,35 @@
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use owo_colors::OwoColorize;
+
+use crate::crate_detail::CrateDetail;
+use crate::list_crate::CrateList;
+use crate::report::inventory_csv;
+
+#[derive(Debug, Parser)]
+#[command(about = "Export inventory of bin, registry and git crates as csv")]
+pub(crate) struct Export {
+    #[arg(
+        long = "output",
+        short = 'o',
+        help = "File where csv inventory is written. Print to stdout if not provided",
+        value_name = "file"
+    )]
+    output: Option<PathBuf>,
+}
+
+impl Export {
+    pub(super) fn run(&self, crate_list: &CrateList, crate_detail: &CrateDetail) -> Result<()> {
+        let content = inventory_csv(crate_list, crate_detail);
+        if let Some(output) = &self.output {
+            fs::write(output, content).context("Failed to write csv inventory to file")?;
+            human_println!("{} {}", "Exported inventory to".blue(), output.display());
+        } else {
+            human_print!("{content}");
+        }
+        Ok(())
+    }
+}
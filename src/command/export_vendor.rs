@@ -0,0 +1,240 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use owo_colors::OwoColorize;
+
+use crate::crate_detail::{CrateDetail, CrateMetaData};
+use crate::crate_id::CrateId;
+use crate::dir_path::DirPath;
+use crate::index_dir::{index_cache_dirs, index_entry};
+use crate::list_crate::{CrateList, lock_registry_crates};
+use crate::registries::is_crates_io_index;
+use crate::registry_dir::index_cache_file;
+
+#[derive(Debug, Parser)]
+#[command(
+    about = "Copy cached .crate archives required by lock file of project into local registry \
+             directory which can be used to build without network",
+    arg_required_else_help = true
+)]
+pub(crate) struct ExportVendor {
+    #[arg(help = "Directory where local registry is created", value_name = "dir")]
+    directory: PathBuf,
+    #[arg(
+        long = "project",
+        short = 'p',
+        help = "Path or directory name of project whose Cargo.lock is exported",
+        value_name = "name|path",
+        default_value = "."
+    )]
+    project: String,
+}
+
+impl ExportVendor {
+    pub(super) fn run(
+        &self,
+        dir_path: &DirPath,
+        crate_list: &CrateList,
+        crate_detail: &CrateDetail,
+    ) -> Result<()> {
+        let project = crate_list.resolve_project(&self.project)?;
+        anyhow::ensure!(
+            project.join("Cargo.lock").exists(),
+            "Project {} do not have Cargo.lock",
+            project.display()
+        );
+        // local registry replaces only crates.io source so index cache of
+        // other registry is never used
+        let index_cache_dirs: Vec<_> = index_cache_dirs(dir_path.index_dir(), crate_detail)?
+            .into_iter()
+            .filter(|(source, _)| is_crates_io_index(source))
+            .collect();
+        let (locked_crates, skipped) = crates_io_locked(&lock_registry_crates(&project)?);
+        let mut exported = 0;
+        let mut missing = Vec::new();
+        let mut index_files: BTreeMap<PathBuf, Vec<String>> = BTreeMap::new();
+        for locked_id in &locked_crates {
+            // index entry is written even without archive since cargo resolves
+            // whole lock file including crates of other platform which are never
+            // downloaded
+            let entry = index_cache_dirs.iter().find_map(|(_, cache_dir)| {
+                index_entry(
+                    &index_cache_file(cache_dir, locked_id.name()),
+                    &locked_id.version().to_string(),
+                )
+            });
+            if let Some(entry) = entry {
+                index_files
+                    .entry(index_cache_file(
+                        &self.directory.join("index"),
+                        locked_id.name(),
+                    ))
+                    .or_default()
+                    .push(entry.to_string());
+            }
+            let archive = crate_detail
+                .registry_crates_archive()
+                .iter()
+                .filter_map(|crate_metadata| Some((crate_metadata.crate_id()?, crate_metadata)))
+                .find(|(crate_id, _)| {
+                    is_crates_io_index(crate_id.registry())
+                        && crate_id.name() == locked_id.name()
+                        && crate_id.version() == locked_id.version()
+                })
+                .and_then(|(_, crate_metadata)| crate_metadata.path().first());
+            if let Some(archive) = archive {
+                copy_archive(archive, &self.directory.join(locked_id.archive_name()))?;
+                exported += 1;
+            } else {
+                missing.push(locked_id.to_string());
+            }
+        }
+        for (index_file, entries) in &index_files {
+            write_index_file(index_file, entries)?;
+        }
+        human_println!(
+            "{}",
+            format!(
+                "{exported} crates exported to local registry {}",
+                self.directory.display()
+            )
+            .blue()
+        );
+        if !missing.is_empty() {
+            human_println!(
+                "{}",
+                format!(
+                    "WARNING: {} crates required by lock file are not present in cache. They are \
+                     only needed when building for platform which uses them",
+                    missing.len()
+                )
+                .yellow()
+            );
+            for full_name in &missing {
+                human_println!("   {full_name}");
+            }
+        }
+        if !skipped.is_empty() {
+            human_println!(
+                "{}",
+                format!(
+                    "WARNING: {} crates from registry other than crates.io are not exported \
+                     since local registry only replaces crates.io",
+                    skipped.len()
+                )
+                .yellow()
+            );
+            for full_name in &skipped {
+                human_println!("   {full_name}");
+            }
+        }
+        let directory = self
+            .directory
+            .canonicalize()
+            .unwrap_or_else(|_| self.directory.clone());
+        human_println!("Add following to .cargo/config.toml of offline machine");
+        human_println!(
+            "[source.crates-io]\nreplace-with = \"trim-vendor\"\n\n[source.trim-vendor]\n\
+             local-registry = {:?}",
+            directory.display().to_string()
+        );
+        Ok(())
+    }
+}
+
+// split crates locked by lock file into crates.io crates which are exported and
+// crates of other registry which are skipped. crates.io crate is listed for
+// both git and sparse index so each version is only returned once
+fn crates_io_locked(locked_crates: &[CrateMetaData]) -> (Vec<CrateId>, BTreeSet<String>) {
+    let mut seen = BTreeSet::new();
+    let mut crates_io = Vec::new();
+    let mut skipped = BTreeSet::new();
+    for locked_id in locked_crates.iter().filter_map(CrateMetaData::crate_id) {
+        if !is_crates_io_index(locked_id.registry()) {
+            skipped.insert(format!("{locked_id} ({})", locked_id.registry()));
+        } else if seen.insert((locked_id.name().clone(), locked_id.version().clone())) {
+            crates_io.push(locked_id);
+        }
+    }
+    (crates_io, skipped)
+}
+
+// copy .crate archive unless same archive is already exported
+fn copy_archive(archive: &Path, destination: &Path) -> Result<()> {
+    if destination.metadata().map(|metadata| metadata.len()).ok()
+        == archive.metadata().map(|metadata| metadata.len()).ok()
+    {
+        return Ok(());
+    }
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent).context("Failed to create local registry directory")?;
+    }
+    fs::copy(archive, destination).context(format!(
+        "Failed to copy {} to local registry",
+        archive.display()
+    ))?;
+    Ok(())
+}
+
+// write index file of crate with one json entry per line. Entries already
+// present from previous export of other project are kept
+fn write_index_file(index_file: &Path, entries: &[String]) -> Result<()> {
+    let mut lines = fs::read_to_string(index_file)
+        .map(|content| content.lines().map(ToString::to_string).collect::<Vec<_>>())
+        .unwrap_or_default();
+    for entry in entries {
+        if !lines.contains(entry) {
+            lines.push(entry.clone());
+        }
+    }
+    if let Some(parent) = index_file.parent() {
+        fs::create_dir_all(parent).context("Failed to create local registry index directory")?;
+    }
+    let mut content = lines.join("\n");
+    content.push('\n');
+    fs::write(index_file, content).context(format!(
+        "Failed to write local registry index file {}",
+        index_file.display()
+    ))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::crates_io_locked;
+    use crate::list_crate::lock_registry_crates;
+
+    #[test]
+    fn test_crates_io_locked() {
+        let project =
+            std::env::temp_dir().join(format!("cargo_trim_export_vendor_{}", std::process::id()));
+        fs::create_dir_all(&project).unwrap();
+        fs::write(
+            project.join("Cargo.lock"),
+            r#"version = 3
+
+[[package]]
+name = "serde"
+version = "1.0.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "internal"
+version = "0.1.0"
+source = "registry+https://example.com/git/index"
+"#,
+        )
+        .unwrap();
+        let (crates_io, skipped) = crates_io_locked(&lock_registry_crates(&project).unwrap());
+        assert_eq!(crates_io.len(), 1);
+        assert_eq!(crates_io[0].to_string(), "serde-1.0.0");
+        assert_eq!(skipped.len(), 1);
+        assert!(skipped.iter().next().unwrap().starts_with("internal-0.1.0"));
+        fs::remove_dir_all(&project).unwrap();
+    }
+}
@@ -1,15 +1,24 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use clap::Parser;
 use owo_colors::OwoColorize;
 
+use crate::command::{OutputFormat, run_git_compress_commands};
 use crate::crate_detail::{CrateDetail, CrateMetaData};
 use crate::dir_path::DirPath;
+use crate::event::{Event, emit};
 use crate::git_dir::GitDir;
 use crate::list_crate::CrateList;
-use crate::utils::{convert_pretty, get_size, print_dash, query_print, show_top_number_crates};
+use crate::report::{QueryReport, print_json, query_git_report};
+use crate::stats_file::StatsFile;
+use crate::utils::{
+    convert_pretty, delete_folder, disk_usage_enabled, get_size, last_modified, print_dash,
+    query_print, show_top_number_crates,
+};
 #[derive(Debug, Parser)]
 #[command(
     about = "Perform operation only to git related cache file",
@@ -19,12 +28,34 @@ use crate::utils::{convert_pretty, get_size, print_dash, query_print, show_top_n
 pub(crate) struct Git {
     #[arg(long = "all", short = 'a', help = "Clean up all git crates")]
     all: bool,
+    #[arg(
+        long = "clean-unused",
+        short = 'u',
+        help = "Clean git checkouts whose revision is not pinned by lock file of any registered \
+                project while keeping git db and used checkouts"
+    )]
+    clean_unused: bool,
     #[arg(
         long = "dry-run",
         short = 'n',
         help = "Run command in dry run mode to see what would be done"
     )]
     dry_run: bool,
+    #[arg(
+        long = "format",
+        short = 'f',
+        value_enum,
+        default_value_t = OutputFormat::Table,
+        help = "Output format of query"
+    )]
+    format: OutputFormat,
+    #[arg(
+        long = "gc",
+        short = 'g',
+        help = "Run aggressive git gc and repack on git db repositories instead of deleting them so \
+                checkouts can still be created offline"
+    )]
+    gc: bool,
     #[arg(
         long = "light",
         short = 'l',
@@ -48,12 +79,42 @@ pub(crate) struct Git {
                 file"
     )]
     orphan: bool,
+    #[arg(
+        long = "prune-checkouts",
+        short = 'p',
+        help = "Clean git checkouts of each repo except most recently modified revision"
+    )]
+    prune_checkouts: bool,
+    #[arg(
+        long = "prune-refs",
+        short = 'b',
+        help = "Prune stale remote refs and delete branches and tags of git db repositories which \
+                do not contain any revision used by checkout or lock file, then run git gc on \
+                them"
+    )]
+    prune_refs: bool,
     #[arg(
         long = "query",
         short = 'q',
         help = "Return size of different .cargo/git cache folders"
     )]
     query: bool,
+    #[arg(
+        long = "remove",
+        short = 'r',
+        help = "Remove git crates matching name, name-version or glob pattern such as serde*",
+        value_name = "crate"
+    )]
+    remove: Option<Vec<String>>,
+    #[arg(
+        long = "shallow",
+        short = 's',
+        help = "Write shallow copy of git db repositories which only contain revisions used by \
+                checkout or lock file into directory. Git db of cargo home is left untouched \
+                since cargo cannot fetch into shallow repository",
+        value_name = "dir"
+    )]
+    shallow: Option<PathBuf>,
     #[arg(
         long = "top",
         short = 't',
@@ -71,34 +132,73 @@ impl Git {
         crate_list: &CrateList,
         crate_detail: &CrateDetail,
         git_crates_location: &GitDir,
+        stats_file: &mut StatsFile,
         directory_is_empty: bool,
     ) -> Result<()> {
         let dry_run = self.dry_run;
 
         if self.light_cleanup {
+            let checkout_size = get_size(dir_path.checkout_dir()).unwrap_or(0);
             let light_cleanup_success = light_cleanup_git(dir_path.checkout_dir(), dry_run);
-            if !light_cleanup_success {
-                println!("Failed to delete some folder during light cleanup");
+            if light_cleanup_success {
+                stats_file.record("git", "light", checkout_size, 1, dry_run);
+            } else {
+                emit(&Event::Error {
+                    message: String::from("Failed to delete some folder during light cleanup"),
+                });
+                human_println!("Failed to delete some folder during light cleanup");
             }
         }
 
+        if self.gc {
+            gc_git_db(crate_detail, stats_file, dry_run)?;
+        }
+
+        if self.prune_checkouts {
+            prune_git_checkouts(crate_detail, stats_file, dry_run)?;
+        }
+
+        if self.prune_refs {
+            prune_git_refs(crate_list, crate_detail, stats_file, dry_run)?;
+        }
+
+        if let Some(shallow_dir) = &self.shallow {
+            shallow_git_db(crate_list, crate_detail, shallow_dir, dry_run)?;
+        }
+
         if let Some(number) = self.top {
             top_crates_git(crate_detail, number);
         }
 
         if self.query {
-            let final_size = query_size_git(dir_path, crate_list, crate_detail);
-            query_print("Total size", &convert_pretty(final_size));
+            if self.format == OutputFormat::Json {
+                print_json(&QueryReport::new(vec![query_git_report(
+                    dir_path,
+                    crate_list,
+                    crate_detail,
+                )]))?;
+            } else {
+                let final_size = query_size_git(dir_path, crate_list, crate_detail);
+                query_print("Total size", &convert_pretty(final_size));
+                if disk_usage_enabled() {
+                    super::query_disk_usage(&QueryReport::new(vec![query_git_report(
+                        dir_path,
+                        crate_list,
+                        crate_detail,
+                    )]));
+                }
+            }
         }
 
         if self.old {
             let (sized_cleaned, total_crate_removed) = clean_git(
                 git_crates_location,
                 crate_list.old_git(),
-                crate_detail,
+                stats_file,
+                "old",
                 dry_run,
             );
-            println!(
+            human_println!(
                 "{}",
                 format!(
                     "{total_crate_removed} old crates removed which had occupied {}",
@@ -109,36 +209,22 @@ impl Git {
         }
 
         if self.old_orphan {
-            if directory_is_empty {
-                let warning_text = "WARNING: You have not initialized any directory as rust \
-                                    project directory. This command will clean all old crates \
-                                    even if they are not orphan crates. Run command 'cargo trim \
-                                    init' to initialize current directory as rust project \
-                                    directory or pass cargo trim set -d <directory> for setting \
-                                    rust project directory";
-                println!("{}", warning_text.yellow());
-                let mut input = String::new();
-                print!("Do you want to continue? (y/N) ");
-                std::io::stdout()
-                    .flush()
-                    .context("failed to flush output stream")?;
-                std::io::stdin()
-                    .read_line(&mut input)
-                    .context("error: unable to read user input")?;
-                let input = input.trim().to_ascii_lowercase();
-                // if answer is any instead of yes and y return
-                if !["y", "yes"].contains(&input.as_str()) {
-                    return Ok(());
-                }
+            if directory_is_empty
+                && !confirm_without_directory(
+                    "This command will clean all old crates even if they are not orphan crates.",
+                )?
+            {
+                return Ok(());
             }
             let (sized_cleaned, total_crate_removed) = clean_git(
                 git_crates_location,
                 &crate_list.list_old_orphan_git(),
-                crate_detail,
+                stats_file,
+                "old-orphan",
                 dry_run,
             );
 
-            println!(
+            human_println!(
                 "{}",
                 format!(
                     "{total_crate_removed} crates which are both old and orphan crate removed \
@@ -150,36 +236,23 @@ impl Git {
         }
 
         if self.orphan {
-            if directory_is_empty {
-                let warning_text = "WARNING: You have not initialized any directory as rust \
-                                    project directory. This command will clean all crates since \
-                                    all crates are classified as orphan crate. Run command 'cargo \
-                                    trim init' to initialize current directory as rust project \
-                                    directory or pass cargo trim set -d <directory> for setting \
-                                    rust project directory";
-                println!("{}", warning_text.yellow());
-                let mut input = String::new();
-                print!("Do you want to continue? (y/N) ");
-                std::io::stdout()
-                    .flush()
-                    .context("failed to flush output stream")?;
-                std::io::stdin()
-                    .read_line(&mut input)
-                    .context("error: unable to read user input")?;
-                let input = input.trim().to_ascii_lowercase();
-                // If answer is not y or yes then return
-                if !["y", "yes"].contains(&input.as_str()) {
-                    return Ok(());
-                }
+            if directory_is_empty
+                && !confirm_without_directory(
+                    "This command will clean all crates since all crates are classified as \
+                     orphan crate.",
+                )?
+            {
+                return Ok(());
             }
             let (sized_cleaned, total_crate_removed) = clean_git(
                 git_crates_location,
                 crate_list.orphan_git(),
-                crate_detail,
+                stats_file,
+                "orphan",
                 dry_run,
             );
 
-            println!(
+            human_println!(
                 "{}",
                 format!(
                     "{total_crate_removed} orphan crates removed which had occupied {}",
@@ -189,14 +262,52 @@ impl Git {
             );
         }
 
+        if self.clean_unused {
+            if directory_is_empty
+                && !confirm_without_directory(
+                    "This command will clean all git checkouts since no revision is pinned by \
+                     any project.",
+                )?
+            {
+                return Ok(());
+            }
+            // git db is kept so removed checkout can be created again offline
+            let unused_checkouts = crate_list
+                .orphan_git()
+                .iter()
+                .filter(|crate_metadata| {
+                    crate_metadata
+                        .git_id()
+                        .is_some_and(|git_id| !git_id.is_database())
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            let (sized_cleaned, total_crate_removed) = clean_git(
+                git_crates_location,
+                &unused_checkouts,
+                stats_file,
+                "unused",
+                dry_run,
+            );
+            human_println!(
+                "{}",
+                format!(
+                    "{total_crate_removed} unused git checkouts removed which had occupied {}",
+                    convert_pretty(sized_cleaned)
+                )
+                .blue()
+            );
+        }
+
         if self.all {
             let (sized_cleaned, total_crate_removed) = clean_git(
                 git_crates_location,
                 crate_list.installed_git(),
-                crate_detail,
+                stats_file,
+                "all",
                 dry_run,
             );
-            println!(
+            human_println!(
                 "{}",
                 format!(
                     "Total size of {total_crate_removed} crates removed :- {}",
@@ -206,14 +317,512 @@ impl Git {
             );
         }
 
+        if let Some(patterns) = &self.remove {
+            let matched_crates = crate_list
+                .installed_git()
+                .iter()
+                .filter(|crate_metadata| {
+                    patterns
+                        .iter()
+                        .any(|pattern| crate_metadata.matches(pattern))
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            let (sized_cleaned, total_crate_removed) = clean_git(
+                git_crates_location,
+                &matched_crates,
+                stats_file,
+                "remove",
+                dry_run,
+            );
+            human_println!(
+                "{}",
+                format!(
+                    "{total_crate_removed} matched crates removed which had occupied {}",
+                    convert_pretty(sized_cleaned)
+                )
+                .blue()
+            );
+        }
+
         Ok(())
     }
 }
 
+// Warn that no directory is registered as rust project directory and ask
+// confirmation to continue
+fn confirm_without_directory(consequence: &str) -> Result<bool> {
+    let warning_text = format!(
+        "WARNING: You have not initialized any directory as rust project directory. \
+         {consequence} Run command 'cargo trim init' to initialize current directory as rust \
+         project directory or pass cargo trim set -d <directory> for setting rust project \
+         directory"
+    );
+    human_println!("{}", warning_text.yellow());
+    let mut input = String::new();
+    human_print!("Do you want to continue? (y/N) ");
+    std::io::stdout()
+        .flush()
+        .context("failed to flush output stream")?;
+    std::io::stdin()
+        .read_line(&mut input)
+        .context("error: unable to read user input")?;
+    let input = input.trim().to_ascii_lowercase();
+    // If answer is not y or yes then return
+    Ok(["y", "yes"].contains(&input.as_str()))
+}
+
 // Perform light cleanup of git and return if light clean was success or not
 pub(super) fn light_cleanup_git(checkout_dir: &Path, dry_run: bool) -> bool {
     // delete checkout dir
-    crate::utils::delete_folder(checkout_dir, dry_run).is_ok()
+    matches!(crate::utils::delete_folder(checkout_dir, dry_run), Ok(true))
+}
+
+// Run aggressive git gc on all git db repositories and record space reclaimed
+fn gc_git_db(crate_detail: &CrateDetail, stats_file: &mut StatsFile, dry_run: bool) -> Result<()> {
+    let mut size_cleaned = 0;
+    let mut repo_compressed = 0;
+    let mut git_db = crate_detail
+        .git_crates_source()
+        .iter()
+        .flat_map(CrateMetaData::path)
+        .collect::<Vec<_>>();
+    git_db.sort();
+    for repo_path in git_db {
+        let size_before = get_size(repo_path).unwrap_or(0);
+        if !dry_run {
+            human_println!(
+                "{}",
+                format!("Compressing git db {}", repo_path.display()).blue()
+            );
+        }
+        run_git_compress_commands(repo_path, dry_run, true)?;
+        if !dry_run {
+            size_cleaned += size_before.saturating_sub(get_size(repo_path).unwrap_or(0));
+        }
+        repo_compressed += 1;
+    }
+    stats_file.record("git", "gc", size_cleaned, repo_compressed, dry_run);
+    human_println!(
+        "{}",
+        format!(
+            "{repo_compressed} git db repositories compressed which reclaimed {}",
+            convert_pretty(size_cleaned)
+        )
+        .blue()
+    );
+    Ok(())
+}
+
+// Remove every checkout of git repo except latest modified revision. Checkout
+// of single repo are stored as revision folder inside same repo folder
+fn prune_git_checkouts(
+    crate_detail: &CrateDetail,
+    stats_file: &mut StatsFile,
+    dry_run: bool,
+) -> Result<()> {
+    let mut repo_checkouts: HashMap<&Path, Vec<(&Path, u64)>> = HashMap::new();
+    for crate_metadata in crate_detail.git_crates_archive() {
+        for checkout in crate_metadata.path() {
+            if let Some(repo) = checkout.parent() {
+                repo_checkouts
+                    .entry(repo)
+                    .or_default()
+                    .push((checkout, crate_metadata.size()));
+            }
+        }
+    }
+    let mut size_cleaned = 0;
+    let mut checkout_removed = 0;
+    for checkouts in repo_checkouts.values_mut() {
+        // sort by newest modified checkout first
+        checkouts.sort_by_key(|(checkout, _)| Reverse(last_modified(&[checkout.to_path_buf()])));
+        for (checkout, size) in checkouts.iter().skip(1) {
+            if delete_folder(checkout, dry_run)? {
+                size_cleaned += size;
+                checkout_removed += 1;
+            }
+        }
+    }
+    stats_file.record("git", "prune", size_cleaned, checkout_removed, dry_run);
+    human_println!(
+        "{}",
+        format!(
+            "{checkout_removed} old git checkouts removed which had occupied {}",
+            convert_pretty(size_cleaned)
+        )
+        .blue()
+    );
+    Ok(())
+}
+
+// List path of every git db repository along with revisions of it which are
+// used by checkout or lock file
+fn git_db_used_revs<'a>(
+    crate_list: &CrateList,
+    crate_detail: &'a CrateDetail,
+) -> Vec<(&'a Path, Vec<String>)> {
+    let used_git_ids = crate_detail
+        .git_crates_archive()
+        .iter()
+        .chain(crate_list.used_git())
+        .filter_map(CrateMetaData::git_id)
+        .collect::<Vec<_>>();
+    let mut git_db = crate_detail
+        .git_crates_source()
+        .iter()
+        .filter_map(|crate_metadata| Some((crate_metadata.git_id()?, crate_metadata.path())))
+        .collect::<Vec<_>>();
+    git_db.sort_by_key(|(_, paths)| *paths);
+    let mut repo_revs = Vec::new();
+    for (db_id, paths) in git_db {
+        let mut used_revs = used_git_ids
+            .iter()
+            .filter(|git_id| git_id.same_repository(&db_id))
+            .map(|git_id| git_id.rev().clone())
+            .collect::<Vec<_>>();
+        used_revs.sort_unstable();
+        used_revs.dedup();
+        for repo_path in paths {
+            repo_revs.push((repo_path.as_path(), used_revs.clone()));
+        }
+    }
+    repo_revs
+}
+
+// Delete refs of every git db repository which do not contain revision used by
+// checkout or lock file. Repository whose revisions are not used at all is
+// skipped since it is removed completely by orphan clean instead
+fn prune_git_refs(
+    crate_list: &CrateList,
+    crate_detail: &CrateDetail,
+    stats_file: &mut StatsFile,
+    dry_run: bool,
+) -> Result<()> {
+    let mut size_cleaned = 0;
+    let mut repo_pruned = 0;
+    for (repo_path, used_revs) in git_db_used_revs(crate_list, crate_detail) {
+        let size_before = get_size(repo_path).unwrap_or(0);
+        if prune_repo_refs(repo_path, &used_revs, dry_run)?.is_none() {
+            continue;
+        }
+        run_git_compress_commands(repo_path, dry_run, true)?;
+        if !dry_run {
+            size_cleaned += size_before.saturating_sub(get_size(repo_path).unwrap_or(0));
+        }
+        repo_pruned += 1;
+    }
+    stats_file.record("git", "prune-refs", size_cleaned, repo_pruned, dry_run);
+    human_println!(
+        "{}",
+        format!(
+            "{repo_pruned} git db repositories pruned which reclaimed {}",
+            convert_pretty(size_cleaned)
+        )
+        .blue()
+    );
+    Ok(())
+}
+
+// Write shallow copy of every git db repository into shallow directory which
+// only contain history of used revisions. Cargo fails to update shallow git db
+// so git db of cargo home is never rewritten in place
+fn shallow_git_db(
+    crate_list: &CrateList,
+    crate_detail: &CrateDetail,
+    shallow_dir: &Path,
+    dry_run: bool,
+) -> Result<()> {
+    if !dry_run {
+        std::fs::create_dir_all(shallow_dir).context("Failed to create shallow directory")?;
+    }
+    let mut original_size = 0;
+    let mut shallow_size = 0;
+    let mut repo_shallowed = 0;
+    for (repo_path, used_revs) in git_db_used_revs(crate_list, crate_detail) {
+        let Some(repo_name) = repo_path.file_name() else {
+            continue;
+        };
+        let destination = shallow_dir.join(repo_name);
+        if destination.exists() {
+            human_println!(
+                "{}",
+                format!(
+                    "WARNING: Skipped git db {} since {} already exists",
+                    repo_path.display(),
+                    destination.display()
+                )
+                .yellow()
+            );
+            continue;
+        }
+        if !shallow_copy(repo_path, &used_revs, &destination, dry_run)? {
+            continue;
+        }
+        if !dry_run {
+            original_size += get_size(repo_path).unwrap_or(0);
+            shallow_size += get_size(&destination).unwrap_or(0);
+        }
+        repo_shallowed += 1;
+    }
+    let message = if dry_run {
+        format!(
+            "{repo_shallowed} git db repositories would be shallowed into {}",
+            shallow_dir.display()
+        )
+    } else {
+        format!(
+            "{repo_shallowed} git db repositories shallowed into {} which take {} instead of {}",
+            shallow_dir.display(),
+            convert_pretty(shallow_size).trim(),
+            convert_pretty(original_size).trim()
+        )
+    };
+    human_println!("{}", message.blue());
+    Ok(())
+}
+
+// Copy git db repository to destination and shallow copy to used revisions.
+// Return false when repository do not contain any used revision so no copy is
+// written
+fn shallow_copy(
+    repo_path: &Path,
+    used_revs: &[String],
+    destination: &Path,
+    dry_run: bool,
+) -> Result<bool> {
+    if dry_run {
+        let Some(used_commits) = prune_repo_refs(repo_path, used_revs, true)? else {
+            return Ok(false);
+        };
+        shallow_repo(destination, &used_commits, true)?;
+        return Ok(true);
+    }
+    let destination_arg = std::path::absolute(destination)
+        .context("Failed to get absolute path of shallow copy")?
+        .to_string_lossy()
+        .to_string();
+    let copied = git_succeeds(
+        repo_path,
+        &[
+            "clone",
+            "--mirror",
+            "--no-local",
+            "--quiet",
+            ".",
+            &destination_arg,
+        ],
+    ) && git_succeeds(destination, &["remote", "remove", "origin"]);
+    anyhow::ensure!(copied, "Failed to copy git db {}", repo_path.display());
+    let result = shallow_copied_repo(destination, used_revs);
+    // copy without used revision or copy which failed is not kept
+    if !matches!(result, Ok(true)) {
+        std::fs::remove_dir_all(destination).context(format!(
+            "Failed to remove shallow copy {}",
+            destination.display()
+        ))?;
+    }
+    result
+}
+
+// Shallow copied repository to used revisions and verify used revisions are
+// still present after gc
+fn shallow_copied_repo(repo_path: &Path, used_revs: &[String]) -> Result<bool> {
+    let Some(used_commits) = prune_repo_refs(repo_path, used_revs, false)? else {
+        return Ok(false);
+    };
+    shallow_repo(repo_path, &used_commits, false)?;
+    // copy is outside of cargo home so it is compressed without measuring freed
+    // space
+    anyhow::ensure!(
+        git_succeeds(repo_path, &["gc", "--prune=now", "--aggressive", "--quiet"]),
+        "Failed to compress shallow copy {}",
+        repo_path.display()
+    );
+    verify_used_commits(repo_path, &used_commits)?;
+    Ok(true)
+}
+
+// Prune remote refs and delete branches and tags which do not point to used
+// revision. Used revision without any ref is stored in refs/commit same as
+// cargo does for rev dependency so it is not removed by gc. Return full hash of
+// used revisions or None when repository do not contain any used revision so
+// it is left untouched
+fn prune_repo_refs(
+    repo_path: &Path,
+    used_revs: &[String],
+    dry_run: bool,
+) -> Result<Option<Vec<String>>> {
+    let mut used_commits = Vec::new();
+    for rev in used_revs {
+        let commit = format!("{rev}^{{commit}}");
+        if let Ok(lines) = git_lines(repo_path, &["rev-parse", "--verify", "--quiet", &commit]) {
+            used_commits.extend(lines);
+        }
+    }
+    if used_commits.is_empty() {
+        human_println!(
+            "{}",
+            format!(
+                "Skipped git db {} since none of its revision is used",
+                repo_path.display()
+            )
+            .yellow()
+        );
+        return Ok(None);
+    }
+    if !dry_run {
+        human_println!(
+            "{}",
+            format!("Pruning refs of git db {}", repo_path.display()).blue()
+        );
+        // remote can be unreachable when offline so failure is not an error
+        for remote in git_lines(repo_path, &["remote"])? {
+            if !git_succeeds(repo_path, &["remote", "prune", &remote]) {
+                human_println!(
+                    "{}",
+                    format!(
+                        "WARNING: Failed to prune remote {remote} of {}",
+                        repo_path.display()
+                    )
+                    .yellow()
+                );
+            }
+        }
+    }
+    let mut stale_refs = Vec::new();
+    let mut referenced_commits = Vec::new();
+    // annotated tag is compared using commit it points to
+    for line in git_lines(
+        repo_path,
+        &[
+            "for-each-ref",
+            "--format=%(refname) %(objectname) %(*objectname)",
+        ],
+    )? {
+        let mut parts = line.split_whitespace();
+        let Some(git_ref) = parts.next() else {
+            continue;
+        };
+        match parts.find(|commit| used_commits.iter().any(|used| used == commit)) {
+            Some(commit) => referenced_commits.push(commit.to_string()),
+            None => stale_refs.push(git_ref.to_string()),
+        }
+    }
+    for commit in &used_commits {
+        if referenced_commits.contains(commit) {
+            continue;
+        }
+        let git_ref = format!("refs/commit/{commit}");
+        if dry_run {
+            human_println!(
+                "{} Created ref {git_ref} in {}",
+                "Dry run:".yellow(),
+                repo_path.display()
+            );
+        } else if !git_succeeds(repo_path, &["update-ref", &git_ref, commit]) {
+            anyhow::bail!("Failed to create ref {git_ref} in {}", repo_path.display());
+        }
+    }
+    for git_ref in stale_refs {
+        if dry_run {
+            human_println!(
+                "{} Deleted ref {git_ref} of {}",
+                "Dry run:".yellow(),
+                repo_path.display()
+            );
+        } else if !git_succeeds(repo_path, &["update-ref", "-d", &git_ref]) {
+            anyhow::bail!("Failed to delete ref {git_ref} of {}", repo_path.display());
+        }
+    }
+    Ok(Some(used_commits))
+}
+
+// Mark used revisions as shallow boundary of repository so gc removes their
+// parent commits same as shallow clone of depth one. Boundary already present
+// in shallow file is kept so earlier shallow clone is not broken
+fn shallow_repo(repo_path: &Path, used_commits: &[String], dry_run: bool) -> Result<()> {
+    if dry_run {
+        human_println!(
+            "{} Shallowed {} to {} revisions",
+            "Dry run:".yellow(),
+            repo_path.display(),
+            used_commits.len()
+        );
+        return Ok(());
+    }
+    let shallow_file = repo_path.join("shallow");
+    let mut boundaries = match std::fs::read_to_string(&shallow_file) {
+        Ok(content) => content
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(ToString::to_string)
+            .collect(),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(err) => {
+            return Err(err).context(format!(
+                "Failed to read shallow file of {}",
+                repo_path.display()
+            ));
+        }
+    };
+    boundaries.extend(used_commits.iter().cloned());
+    boundaries.sort_unstable();
+    boundaries.dedup();
+    let mut content = boundaries.join("\n");
+    content.push('\n');
+    std::fs::write(shallow_file, content).context(format!(
+        "Failed to write shallow file of {}",
+        repo_path.display()
+    ))?;
+    Ok(())
+}
+
+// Check every used revision still resolve to commit after gc of shallowed
+// repository so broken repository is reported instead of failing later build
+fn verify_used_commits(repo_path: &Path, used_commits: &[String]) -> Result<()> {
+    for commit in used_commits {
+        let commit_object = format!("{commit}^{{commit}}");
+        if !git_succeeds(
+            repo_path,
+            &["rev-parse", "--verify", "--quiet", &commit_object],
+        ) {
+            anyhow::bail!(
+                "Used revision {commit} cannot be resolved in {} after shallow",
+                repo_path.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+// Run git command in repository and return non empty lines of its output
+fn git_lines(repo_path: &Path, args: &[&str]) -> Result<Vec<String>> {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .current_dir(repo_path)
+        .output()
+        .context(format!("Failed to execute git {}", args.join(" ")))?;
+    anyhow::ensure!(
+        output.status.success(),
+        "Failed to execute git {} in {}",
+        args.join(" "),
+        repo_path.display()
+    );
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(ToString::to_string)
+        .collect())
+}
+
+// Run git command in repository and return if it exited successfully
+fn git_succeeds(repo_path: &Path, args: &[&str]) -> bool {
+    std::process::Command::new("git")
+        .args(args)
+        .current_dir(repo_path)
+        .output()
+        .is_ok_and(|output| output.status.success())
 }
 
 // Show top git crates
@@ -257,8 +866,106 @@ pub(super) fn query_size_git(
 pub(super) fn clean_git(
     git_crates_location: &GitDir,
     crate_metadata_list: &[CrateMetaData],
-    crate_detail: &CrateDetail,
+    stats_file: &mut StatsFile,
+    reason: &str,
     dry_run: bool,
 ) -> (u64, usize) {
-    git_crates_location.remove_crate_list(crate_detail, crate_metadata_list, dry_run)
+    let (size_cleaned, crate_removed) =
+        git_crates_location.remove_crate_list(crate_metadata_list, dry_run);
+    stats_file.record("git", reason, size_cleaned, crate_removed, dry_run);
+    (size_cleaned, crate_removed)
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::path::Path;
+
+    use super::{git_lines, git_succeeds, prune_repo_refs, shallow_repo};
+
+    // create bare repository with linear history of three commits on master,
+    // stale branch and tag. Return hash of commits from oldest to newest
+    fn create_repo(dir: &Path) -> Vec<String> {
+        let work = dir.join("work");
+        fs::create_dir_all(&work).unwrap();
+        assert!(git_succeeds(
+            &work,
+            &["init", "--quiet", "--initial-branch=master"]
+        ));
+        for content in ["one", "two", "three"] {
+            fs::write(work.join("file"), content).unwrap();
+            assert!(git_succeeds(&work, &["add", "file"]));
+            assert!(git_succeeds(
+                &work,
+                &[
+                    "-c",
+                    "user.name=test",
+                    "-c",
+                    "user.email=test@example.com",
+                    "commit",
+                    "--quiet",
+                    "-m",
+                    content
+                ]
+            ));
+        }
+        let commits = git_lines(&work, &["rev-list", "--reverse", "HEAD"]).unwrap();
+        assert!(git_succeeds(&work, &["branch", "stale", &commits[0]]));
+        assert!(git_succeeds(&work, &["tag", "v1", &commits[0]]));
+        assert!(git_succeeds(
+            dir,
+            &["clone", "--bare", "--quiet", "work", "db"]
+        ));
+        commits
+    }
+
+    #[test]
+    fn test_prune_repo_refs() {
+        let dir =
+            std::env::temp_dir().join(format!("cargo_trim_prune_refs_{}", std::process::id()));
+        let commits = create_repo(&dir);
+        let repo = dir.join("db");
+        let short_rev = commits[1][..7].to_string();
+        let unknown_rev = String::from("0000000");
+        assert!(
+            prune_repo_refs(&repo, std::slice::from_ref(&unknown_rev), false)
+                .unwrap()
+                .is_none()
+        );
+        // dry run do not change any ref
+        let refs = git_lines(&repo, &["for-each-ref"]).unwrap();
+        prune_repo_refs(&repo, std::slice::from_ref(&short_rev), true).unwrap();
+        assert_eq!(git_lines(&repo, &["for-each-ref"]).unwrap(), refs);
+
+        let used_commits = prune_repo_refs(&repo, &[short_rev], false)
+            .unwrap()
+            .unwrap();
+        assert_eq!(used_commits, [commits[1].clone()]);
+        assert_eq!(
+            git_lines(&repo, &["for-each-ref", "--format=%(refname)"]).unwrap(),
+            [format!("refs/commit/{}", commits[1])]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_shallow_repo() {
+        let dir = std::env::temp_dir().join(format!("cargo_trim_shallow_{}", std::process::id()));
+        let commits = create_repo(&dir);
+        let repo = dir.join("db");
+        fs::write(repo.join("shallow"), format!("{}\n", commits[2])).unwrap();
+        shallow_repo(&repo, &[commits[1].clone()], false).unwrap();
+        let mut expected = [commits[1].clone(), commits[2].clone()];
+        expected.sort_unstable();
+        assert_eq!(
+            fs::read_to_string(repo.join("shallow")).unwrap(),
+            format!("{}\n", expected.join("\n"))
+        );
+        // history behind boundary is not reachable from used revision
+        assert_eq!(
+            git_lines(&repo, &["rev-list", &commits[1]]).unwrap(),
+            [commits[1].clone()]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
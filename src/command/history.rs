@@ -0,0 +1,139 @@
+use clap::Parser;
+use owo_colors::OwoColorize;
+
+use crate::history_file::{HistoryFile, SizeSnapshot};
+use crate::utils::{convert_pretty, print_dash};
+
+// bars used to draw sparkline from lowest to highest value
+const SPARK_BARS: [char; 8] = [
+    '\u{2581}', '\u{2582}', '\u{2583}', '\u{2584}', '\u{2585}', '\u{2586}', '\u{2587}', '\u{2588}',
+];
+
+#[derive(Debug, Parser)]
+#[command(about = "Show cache size recorded after each scan to see growth over time")]
+pub(crate) struct History {
+    #[arg(
+        long = "limit",
+        short = 'l',
+        help = "Number of latest snapshots to show",
+        value_name = "number",
+        default_value_t = 20
+    )]
+    limit: usize,
+    #[arg(
+        long = "sparkline",
+        short = 's',
+        help = "Show total cache size of snapshots as sparkline instead of table"
+    )]
+    sparkline: bool,
+}
+
+impl History {
+    pub(super) fn run(&self, history_file: &HistoryFile) {
+        let snapshots = history_file.snapshots();
+        let snapshots = &snapshots[snapshots.len().saturating_sub(self.limit)..];
+        if snapshots.is_empty() {
+            human_println!("{}", "No cache size history is recorded yet".yellow());
+            return;
+        }
+        if self.sparkline {
+            show_sparkline(snapshots);
+        } else {
+            show_table(snapshots);
+        }
+    }
+}
+
+// show size of each category of snapshot along with change from previous
+fn show_table(snapshots: &[SizeSnapshot]) {
+    let dash_len = 88;
+    human_println!(
+        "{}",
+        format!(
+            "{:<22}{:>13}{:>13}{:>13}{:>13}{:>14}",
+            "TAKEN", "REGISTRY", "GIT", "BIN", "TOTAL", "CHANGE"
+        )
+        .bold()
+    );
+    print_dash(dash_len);
+    let mut previous_total = None;
+    for snapshot in snapshots {
+        let total = snapshot.total();
+        let change = previous_total.map_or_else(String::new, |previous| {
+            if total >= previous {
+                format!("+{}", convert_pretty(total - previous).trim())
+            } else {
+                format!("-{}", convert_pretty(previous - total).trim())
+            }
+        });
+        human_println!(
+            "{:<22}{:>13}{:>13}{:>13}{:>13}{:>14}",
+            snapshot.taken(),
+            convert_pretty(snapshot.registry()),
+            convert_pretty(snapshot.git()),
+            convert_pretty(snapshot.bin()),
+            convert_pretty(total),
+            change
+        );
+        previous_total = Some(total);
+    }
+    print_dash(dash_len);
+}
+
+// show total size of snapshots as single line sparkline with range
+fn show_sparkline(snapshots: &[SizeSnapshot]) {
+    let totals = snapshots
+        .iter()
+        .map(SizeSnapshot::total)
+        .collect::<Vec<_>>();
+    let min = totals.iter().copied().min().unwrap_or(0);
+    let max = totals.iter().copied().max().unwrap_or(0);
+    human_println!(
+        "{} {}",
+        sparkline(&totals),
+        format!(
+            "min {} max {} latest {}",
+            convert_pretty(min).trim(),
+            convert_pretty(max).trim(),
+            convert_pretty(totals.last().copied().unwrap_or(0)).trim()
+        )
+        .blue()
+    );
+    if let (Some(first), Some(last)) = (snapshots.first(), snapshots.last()) {
+        human_println!("{} .. {}", first.taken(), last.taken());
+    }
+}
+
+// convert values to sparkline where lowest value use smallest bar
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn sparkline(values: &[u64]) -> String {
+    let min = values.iter().copied().min().unwrap_or(0);
+    let max = values.iter().copied().max().unwrap_or(0);
+    let range = max - min;
+    values
+        .iter()
+        .map(|&value| {
+            if range == 0 {
+                SPARK_BARS[0]
+            } else {
+                let position = (value - min) as f64 / range as f64 * (SPARK_BARS.len() - 1) as f64;
+                SPARK_BARS[position.round() as usize]
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::sparkline;
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[0, 7, 14]), "\u{2581}\u{2585}\u{2588}");
+        assert_eq!(sparkline(&[5, 5]), "\u{2581}\u{2581}");
+    }
+}
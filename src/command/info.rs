@@ -0,0 +1,206 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+
+use clap::Parser;
+use owo_colors::OwoColorize;
+use semver::Version;
+
+use crate::crate_detail::{CrateDetail, CrateMetaData};
+use crate::registries::Registries;
+use crate::utils::{convert_pretty, format_timestamp, last_modified, print_dash, query_print};
+
+#[derive(Debug, Parser)]
+#[command(
+    about = "Show every cached version of crate with size, path, registry and last modified time",
+    arg_required_else_help = true
+)]
+pub(crate) struct Info {
+    #[arg(
+        help = "Name of crate. Version can be also provided in form of name-version. Glob pattern \
+                such as serde* is also supported"
+    )]
+    crate_name: String,
+}
+
+// size and path of single cached version of crate stored in two different
+// directory. For registry crate first is source and second is archive whereas
+// for git crate first is checkout and second is database
+#[derive(Default)]
+struct CachedVersion {
+    first_size: u64,
+    second_size: u64,
+    path: Vec<PathBuf>,
+}
+
+impl CachedVersion {
+    fn total_size(&self) -> u64 {
+        self.first_size + self.second_size
+    }
+}
+
+impl Info {
+    pub(super) fn run(&self, crate_detail: &CrateDetail, registries: &Registries) {
+        let registry_versions = self.collect_versions(
+            crate_detail.registry_crates_source(),
+            crate_detail.registry_crates_archive(),
+            |crate_metadata| {
+                let registry = crate_metadata
+                    .source()
+                    .map_or_else(String::new, |source| registries.name(source));
+                (
+                    crate_metadata.name().clone(),
+                    crate_metadata.version().cloned(),
+                    registry,
+                )
+            },
+        );
+        let git_versions = self.collect_versions(
+            crate_detail.git_crates_archive(),
+            crate_detail.git_crates_source(),
+            git_version_key,
+        );
+        if registry_versions.is_empty() && git_versions.is_empty() {
+            human_println!(
+                "{}",
+                format!("No crate matching {} is cached", self.crate_name).red()
+            );
+            return;
+        }
+        let mut total_size = 0;
+        for ((name, version, registry), cached_version) in &registry_versions {
+            let version = version
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default();
+            human_println!("{} {version} ({registry})", name.bold());
+            show_cached_version(cached_version, "Source", "Archive");
+            total_size += cached_version.total_size();
+        }
+        for ((name, _, source), cached_version) in &git_versions {
+            human_println!("{} ({source})", name.bold());
+            show_cached_version(cached_version, "Checkout", "Database");
+            total_size += cached_version.total_size();
+        }
+        query_print(
+            &format!(
+                "Total size of {} cached versions",
+                registry_versions.len() + git_versions.len()
+            ),
+            &convert_pretty(total_size),
+        );
+    }
+
+    // group crates of two crate detail maps matching crate name by key so size
+    // of both directory is shown together for each version
+    fn collect_versions<F>(
+        &self,
+        first: &HashSet<CrateMetaData>,
+        second: &HashSet<CrateMetaData>,
+        key: F,
+    ) -> BTreeMap<(String, Option<Version>, String), CachedVersion>
+    where
+        F: Fn(&CrateMetaData) -> (String, Option<Version>, String),
+    {
+        let mut versions: BTreeMap<_, CachedVersion> = BTreeMap::new();
+        for (is_first, crates) in [(true, first), (false, second)] {
+            for crate_metadata in crates
+                .iter()
+                .filter(|crate_metadata| crate_metadata.matches(&self.crate_name))
+            {
+                let cached_version = versions.entry(key(crate_metadata)).or_default();
+                if is_first {
+                    cached_version.first_size += crate_metadata.size();
+                } else {
+                    cached_version.second_size += crate_metadata.size();
+                }
+                cached_version
+                    .path
+                    .extend(crate_metadata.path().iter().cloned());
+            }
+        }
+        versions
+    }
+}
+
+// key of git crate. Checkout and database of same repository share directory
+// name made of crate name and url hash so revision is not part of key
+fn git_version_key(crate_metadata: &CrateMetaData) -> (String, Option<Version>, String) {
+    let name = crate_metadata.git_id().map_or_else(
+        || crate_metadata.name().clone(),
+        |git_id| git_id.name().clone(),
+    );
+    let source = crate_metadata
+        .source()
+        .map_or_else(String::new, ToString::to_string);
+    (name, None, source)
+}
+
+// show size, path and last modified time of single cached version
+fn show_cached_version(cached_version: &CachedVersion, first_name: &str, second_name: &str) {
+    query_print(
+        &format!("   \u{251c} {first_name} size"),
+        &convert_pretty(cached_version.first_size),
+    );
+    query_print(
+        &format!("   \u{251c} {second_name} size"),
+        &convert_pretty(cached_version.second_size),
+    );
+    for path in &cached_version.path {
+        human_println!("   \u{251c} {}", path.display());
+    }
+    let modified = last_modified(&cached_version.path)
+        .map_or_else(|| String::from("unknown"), format_timestamp);
+    human_println!("   \u{2514} Last modified {modified}");
+    print_dash(crate::utils::query_full_width());
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+    use std::str::FromStr;
+
+    use url::Url;
+
+    use super::{Info, git_version_key};
+    use crate::crate_detail::CrateMetaData;
+    use crate::crate_id::GitId;
+
+    #[test]
+    fn test_git_versions_group_checkout_and_database() {
+        let url = Url::from_str("https://github.com/foo/foo").unwrap();
+        let database = HashSet::from([CrateMetaData::new_git(
+            GitId::new(
+                url.clone(),
+                String::from("foo-1ecc6299db9ec823"),
+                String::from("HEAD"),
+            ),
+            10,
+        )]);
+        let checkouts = HashSet::from([
+            CrateMetaData::new_git(
+                GitId::new(
+                    url.clone(),
+                    String::from("foo-1ecc6299db9ec823"),
+                    String::from("a1b2c3d"),
+                ),
+                5,
+            ),
+            CrateMetaData::new_git(
+                GitId::new(
+                    url.clone(),
+                    String::from("foo-1ecc6299db9ec823"),
+                    String::from("e4f5a6b"),
+                ),
+                3,
+            ),
+        ]);
+        let info = Info {
+            crate_name: String::from("foo*"),
+        };
+        let versions = info.collect_versions(&checkouts, &database, git_version_key);
+        assert_eq!(versions.len(), 1);
+        let cached_version = versions.values().next().unwrap();
+        assert_eq!(cached_version.first_size, 8);
+        assert_eq!(cached_version.second_size, 10);
+    }
+}
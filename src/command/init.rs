@@ -1,7 +1,30 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
 use anyhow::{Context, Result};
 use clap::Parser;
+use owo_colors::OwoColorize;
 
 use crate::config_file::ConfigFile;
+use crate::dir_path::DirPath;
+use crate::trash::trash_available;
+use crate::utils::{ask_confirmation, convert_pretty, get_size, parse_duration};
+
+// name of profile storing retention chosen in wizard
+const WIZARD_PROFILE: &str = "default";
+
+// folder inside home directory where rust projects are commonly kept
+const PROJECT_FOLDERS: [&str; 9] = [
+    "code",
+    "dev",
+    "git",
+    "projects",
+    "repos",
+    "rust",
+    "src",
+    "work",
+    "workspace",
+];
 
 #[derive(Debug, Parser)]
 #[command(about = "Initialize current working directory as cargo trim directory")]
@@ -12,10 +35,20 @@ pub(crate) struct Init {
         help = "Run command in dry run mode to see what would be done"
     )]
     dry_run: bool,
+    #[arg(
+        long = "wizard",
+        short = 'w',
+        help = "Interactively detect cargo home, choose project directories and retention and \
+                write config file"
+    )]
+    wizard: bool,
 }
 
 impl Init {
-    pub(super) fn run(&self, config_file: &mut ConfigFile) -> Result<()> {
+    pub(super) fn run(&self, dir_path: &DirPath, config_file: &mut ConfigFile) -> Result<()> {
+        if self.wizard {
+            return self.run_wizard(dir_path, config_file);
+        }
         config_file.add_directory(
             std::env::current_dir()
                 .context("Current working directory is invalid")?
@@ -26,4 +59,137 @@ impl Init {
         )?;
         Ok(())
     }
+
+    // ask user about cargo home, project directories and retention one after
+    // another and write answer to config file
+    fn run_wizard(&self, dir_path: &DirPath, config_file: &mut ConfigFile) -> Result<()> {
+        let dry_run = self.dry_run;
+        let cargo_home = dir_path.cargo_home();
+        human_println!(
+            "Using cargo home {} ({})",
+            cargo_home.display().blue(),
+            convert_pretty(get_size(cargo_home).unwrap_or(0)).trim()
+        );
+        // cargo home of environment differ from one cargo trim was built with
+        if let Some(env_home) = std::env::var_os("CARGO_HOME").map(PathBuf::from) {
+            if &env_home != cargo_home
+                && env_home.is_dir()
+                && !config_file
+                    .cargo_home()
+                    .iter()
+                    .any(|path| Path::new(path) == env_home)
+                && ask_confirmation(&format!(
+                    "CARGO_HOME points to {}. Trim it on every run?",
+                    env_home.display()
+                ))?
+            {
+                config_file.add_cargo_home(&path_str(&env_home)?, dry_run, true)?;
+            }
+        }
+
+        let registered = config_file.directory();
+        for candidate in candidate_directories()? {
+            let candidate_str = path_str(&candidate)?;
+            if registered.contains(&candidate_str) {
+                continue;
+            }
+            let project_count = config_file
+                .list_cargo_toml(&candidate)
+                .map_or(0, |location| location.location_path().len());
+            if project_count == 0 {
+                continue;
+            }
+            if ask_confirmation(&format!(
+                "Register {} containing {project_count} rust projects?",
+                candidate.display()
+            ))? {
+                config_file.add_directory(&candidate_str, dry_run, true)?;
+            }
+        }
+
+        if trash_available() && !config_file.trash() {
+            let question = "Move deleted files to trash instead of removing them permanently?";
+            if ask_confirmation(question)? {
+                config_file.set_trash(true, dry_run, true)?;
+            }
+        }
+
+        let keep_versions = loop {
+            let answer = ask_input(
+                "Number of newest versions of each registry crate to keep (empty to keep all)",
+            )?;
+            if answer.is_empty() {
+                break None;
+            }
+            match answer.parse::<usize>() {
+                Ok(number) if number > 0 => break Some(number),
+                _ => human_println!("{}", "Enter positive number".red()),
+            }
+        };
+        let older_than = loop {
+            let answer =
+                ask_input("Clean crates not used within duration such as 30d (empty to keep all)")?;
+            if answer.is_empty() {
+                break None;
+            }
+            match parse_duration(&answer) {
+                Ok(_) => break Some(answer),
+                Err(err) => human_println!("{}", format!("{err:#}").red()),
+            }
+        };
+        if keep_versions.is_some() || older_than.is_some() {
+            config_file.set_profile_retention(
+                WIZARD_PROFILE,
+                keep_versions,
+                older_than.as_deref(),
+                dry_run,
+                true,
+            )?;
+            human_println!(
+                "Run {} or set {} to apply retention",
+                format!("cargo trim --profile {WIZARD_PROFILE}").blue(),
+                format!("TRIM_PROFILE={WIZARD_PROFILE}").blue()
+            );
+        }
+        if !dry_run {
+            human_println!("{}", "Config file written".green());
+        }
+        Ok(())
+    }
+}
+
+// current directory along with commonly used project folder of home directory
+// which exists
+fn candidate_directories() -> Result<Vec<PathBuf>> {
+    let mut candidates =
+        vec![std::env::current_dir().context("Current working directory is invalid")?];
+    if let Some(home_dir) = dirs_next::home_dir() {
+        for folder in PROJECT_FOLDERS {
+            let path = home_dir.join(folder);
+            if path.is_dir() && !candidates.contains(&path) {
+                candidates.push(path);
+            }
+        }
+    }
+    Ok(candidates)
+}
+
+// ask user question and return trimmed answer
+fn ask_input(question: &str) -> Result<String> {
+    let mut input = String::new();
+    human_print!("{question}: ");
+    std::io::stdout()
+        .flush()
+        .context("failed to flush output stream")?;
+    std::io::stdin()
+        .read_line(&mut input)
+        .context("error: unable to read user input")?;
+    Ok(input.trim().to_string())
+}
+
+// convert path to str since config file stores string
+fn path_str(path: &Path) -> Result<String> {
+    path.to_str()
+        .map(ToString::to_string)
+        .context("failed to convert directory to str")
 }
@@ -1,8 +1,63 @@
-use clap::Parser;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::SystemTime;
+
+use anyhow::Result;
+use clap::{Parser, ValueEnum};
 use owo_colors::OwoColorize;
+use serde::Serialize;
 
+use crate::command::OutputFormat;
+use crate::crate_detail::CrateMetaData;
+use crate::deprecated_crate::{is_badge_deprecated, superseded_by};
+use crate::install_metadata::{InstallMetadata, InstalledPackage};
 use crate::list_crate::CrateList;
-use crate::utils::crate_list_type;
+use crate::registries::Registries;
+use crate::report::{CrateReport, crate_reports, print_json};
+use crate::terminal::{available_width, fit_width};
+use crate::utils::{
+    CrateSort, convert_pretty, crate_list_type, format_age, print_dash, sort_crates,
+};
+
+// width below which path column is not shrunk to fit terminal
+const MIN_PATH_WIDTH: usize = 20;
+
+/// Installed binary along with package which installed it
+#[derive(Serialize)]
+struct BinReport<'a> {
+    name: &'a String,
+    size: u64,
+    package: Option<InstalledPackage>,
+}
+
+/// All cached versions of crate grouped under its name
+#[derive(Serialize)]
+struct GroupReport {
+    name: String,
+    category: String,
+    versions: usize,
+    size: u64,
+}
+
+/// Single requested list of json output
+#[derive(Serialize)]
+#[serde(untagged)]
+enum ListReport<'a> {
+    Crates(Vec<CrateReport>),
+    Groups(Vec<GroupReport>),
+    Bins(Vec<BinReport<'a>>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum ListColumn {
+    Name,
+    Version,
+    Size,
+    Path,
+    Age,
+    Registry,
+}
 
 #[derive(Debug, Parser)]
 #[command(about = "List out crates", arg_required_else_help = true)]
@@ -10,6 +65,44 @@ use crate::utils::crate_list_type;
 pub(crate) struct List {
     #[arg(long = "all", short = 'a', help = "List out all installed crate")]
     all: bool,
+    #[arg(
+        long = "bin",
+        short = 'b',
+        help = "List out installed binaries along with crate, version and features of package \
+                which installed them"
+    )]
+    bin: bool,
+    #[arg(
+        long = "columns",
+        short = 'c',
+        value_enum,
+        value_delimiter = ',',
+        help = "Columns shown in table such as -c name,version,size,path,age,registry. Defaults to \
+                name with version and size",
+        value_name = "column"
+    )]
+    columns: Option<Vec<ListColumn>>,
+    #[arg(
+        long = "deprecated",
+        help = "List out deprecated crates which are renamed or superseded by other crate"
+    )]
+    deprecated: bool,
+    #[arg(
+        long = "format",
+        short = 'f',
+        value_enum,
+        default_value_t = OutputFormat::Table,
+        help = "Output format of list"
+    )]
+    format: OutputFormat,
+    #[arg(
+        long = "group",
+        short = 'g',
+        help = "Group all versions of crate under single row with version count and combined \
+                size sorted by size",
+        conflicts_with = "columns"
+    )]
+    group: bool,
     #[arg(long = "old", short = 'o', help = "List out old crates")]
     old: bool,
     #[arg(
@@ -20,46 +113,268 @@ pub(crate) struct List {
     old_orphan: bool,
     #[arg(long = "orphan", short = 'x', help = "List out orphan crates")]
     orphan: bool,
+    #[arg(
+        long = "project",
+        short = 'p',
+        help = "List out crates referenced by lock file of project. Project can be path or \
+                directory name of project present in registered directory",
+        value_name = "name|path"
+    )]
+    project: Option<String>,
+    #[arg(
+        long = "untracked-bin",
+        help = "List out binaries which are not recorded in install metadata of cargo such as \
+                leftover of failed install or manually copied binary"
+    )]
+    untracked_bin: bool,
     #[arg(long = "used", short = 'u', help = "List out used crates")]
     used: bool,
+    #[arg(
+        long = "yanked",
+        help = "List out crates whose cached version is yanked in registry index"
+    )]
+    yanked: bool,
 }
 
 impl List {
-    pub(super) fn run(&self, crate_list: &CrateList, directory_is_empty: bool) {
+    pub(super) fn run(
+        &self,
+        crate_list: &CrateList,
+        registries: &Registries,
+        cargo_home: &Path,
+        directory_is_empty: bool,
+    ) -> Result<()> {
+        let install_metadata = InstallMetadata::load(cargo_home)?;
+        if self.format == OutputFormat::Json {
+            return self.run_json(crate_list, &install_metadata);
+        }
+        let show = |crates: &[CrateMetaData], title: &str| match &self.columns {
+            _ if self.group => show_group_table(crates, title),
+            Some(columns) => show_column_table(crates, title, columns, registries),
+            None => crate_list_type(crates, title),
+        };
         if self.all {
-            list_all(crate_list);
+            list_all(crate_list, &show);
+        }
+        if self.bin {
+            list_bin(crate_list, &install_metadata);
+        }
+        if self.deprecated {
+            list_deprecated(crate_list, &show);
         }
         if self.old {
-            list_old(crate_list);
+            list_old(crate_list, &show);
         }
         if self.old_orphan {
-            list_old_orphan(crate_list, directory_is_empty);
+            list_old_orphan(crate_list, directory_is_empty, &show);
         }
         if self.orphan {
-            list_orphan(crate_list, directory_is_empty);
+            list_orphan(crate_list, directory_is_empty, &show);
+        }
+        if let Some(project) = &self.project {
+            list_project(crate_list, project, &show)?;
+        }
+        if self.untracked_bin {
+            show(
+                &install_metadata.untracked_bins(crate_list.installed_bin()),
+                "UNTRACKED BINARY",
+            );
         }
         if self.used {
-            list_used(crate_list, directory_is_empty);
+            list_used(crate_list, directory_is_empty, &show);
+        }
+        if self.yanked {
+            list_yanked(crate_list, &show);
         }
+        Ok(())
     }
 }
 
-fn list_all(crate_list: &CrateList) {
-    crate_list_type(crate_list.installed_registry(), "REGISTRY INSTALLED CRATE");
-    crate_list_type(crate_list.installed_git(), "GIT INSTALLED CRATE");
+impl List {
+    // list out crates in json format where each requested list is stored in
+    // separate key
+    fn run_json(&self, crate_list: &CrateList, install_metadata: &InstallMetadata) -> Result<()> {
+        let mut report = BTreeMap::new();
+        let lists = [
+            (
+                self.all,
+                "all",
+                crate_list.installed_registry().clone(),
+                crate_list.installed_git().clone(),
+            ),
+            (
+                self.deprecated,
+                "deprecated",
+                crate_list.deprecated_registry().clone(),
+                Vec::new(),
+            ),
+            (
+                self.old,
+                "old",
+                crate_list.old_registry().clone(),
+                crate_list.old_git().clone(),
+            ),
+            (
+                self.old_orphan,
+                "old_orphan",
+                crate_list.list_old_orphan_registry(),
+                crate_list.list_old_orphan_git(),
+            ),
+            (
+                self.orphan,
+                "orphan",
+                crate_list.orphan_registry().clone(),
+                crate_list.orphan_git().clone(),
+            ),
+            (
+                self.used,
+                "used",
+                crate_list.used_registry().clone(),
+                crate_list.used_git().clone(),
+            ),
+            (
+                self.yanked,
+                "yanked",
+                crate_list.yanked_registry().clone(),
+                Vec::new(),
+            ),
+        ];
+        for (requested, key, registry_list, git_list) in lists {
+            if requested {
+                report.insert(key, self.list_report(&registry_list, &git_list));
+            }
+        }
+        if self.bin {
+            let bins = crate_list
+                .installed_bin()
+                .iter()
+                .map(|bin| BinReport {
+                    name: bin.name(),
+                    size: bin.size(),
+                    package: install_metadata.package_of(bin.name()),
+                })
+                .collect::<Vec<_>>();
+            report.insert("bin", ListReport::Bins(bins));
+        }
+        if self.untracked_bin {
+            let untracked = install_metadata.untracked_bins(crate_list.installed_bin());
+            report.insert(
+                "untracked_bin",
+                ListReport::Crates(crate_reports(&untracked, "bin")),
+            );
+        }
+        if let Some(project) = &self.project {
+            let project_path = crate_list.resolve_project(project)?;
+            let (project_registry, project_git) = crate_list.project_crates(&project_path)?;
+            report.insert("project", self.list_report(&project_registry, &project_git));
+        }
+        print_json(&report)
+    }
+
+    // json report of registry and git crates of single list
+    fn list_report(
+        &self,
+        registry_list: &[CrateMetaData],
+        git_list: &[CrateMetaData],
+    ) -> ListReport<'static> {
+        if self.group {
+            let mut groups = group_reports(registry_list, "registry");
+            groups.append(&mut group_reports(git_list, "git"));
+            ListReport::Groups(groups)
+        } else {
+            let mut crates = crate_reports(registry_list, "registry");
+            crates.append(&mut crate_reports(git_list, "git"));
+            ListReport::Crates(crates)
+        }
+    }
+}
+
+fn list_all(crate_list: &CrateList, show: &impl Fn(&[CrateMetaData], &str)) {
+    show(crate_list.installed_registry(), "REGISTRY INSTALLED CRATE");
+    show(crate_list.installed_git(), "GIT INSTALLED CRATE");
+}
+
+// list installed binaries along with package which installed them. Binary not
+// present in install metadata is installed manually
+fn list_bin(crate_list: &CrateList, install_metadata: &InstallMetadata) {
+    let headers = ["BINARY", "PACKAGE", "FEATURES", "SIZE"].map(String::from);
+    let rows = crate_list
+        .installed_bin()
+        .iter()
+        .map(|bin| {
+            let package = install_metadata.package_of(bin.name());
+            vec![
+                bin.name().clone(),
+                package.as_ref().map_or_else(
+                    || String::from("-"),
+                    |package| format!("{} {}", package.name(), package.version()),
+                ),
+                package
+                    .map(|package| package.features().join(","))
+                    .unwrap_or_default(),
+                convert_pretty(bin.size()).trim().to_string(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let total_size = crate_list
+        .installed_bin()
+        .iter()
+        .map(CrateMetaData::size)
+        .sum();
+    let total = format!(
+        "Total no of binaries:- {} ({})",
+        rows.len(),
+        convert_pretty(total_size).trim()
+    );
+    print_table("INSTALLED BINARY", &headers, &rows, Some(2), &total);
+}
+
+fn list_deprecated(crate_list: &CrateList, show: &impl Fn(&[CrateMetaData], &str)) {
+    show(
+        crate_list.deprecated_registry(),
+        "REGISTRY DEPRECATED CRATE",
+    );
+    let mut listed_name = Vec::new();
+    for crate_metadata in crate_list.deprecated_registry() {
+        let name = crate_metadata.name();
+        if listed_name.contains(&name) {
+            continue;
+        }
+        listed_name.push(name);
+        if let Some(replacement) = superseded_by(name) {
+            // check if crate is also cached under its new name
+            let replacement_cached = crate_list
+                .installed_registry()
+                .iter()
+                .any(|installed| installed.name() == replacement);
+            if replacement_cached {
+                human_println!(
+                    "{name} is superseded by {replacement} which is also present in cache"
+                );
+            } else {
+                human_println!("{name} is superseded by {replacement}");
+            }
+        } else if is_badge_deprecated(name) {
+            human_println!("{name} is marked deprecated by maintenance badge of its manifest");
+        }
+    }
 }
 
-fn list_old(crate_list: &CrateList) {
-    crate_list_type(crate_list.old_registry(), "REGISTRY OLD CRATE");
-    crate_list_type(crate_list.old_git(), "GIT OLD CRATE");
+fn list_old(crate_list: &CrateList, show: &impl Fn(&[CrateMetaData], &str)) {
+    show(crate_list.old_registry(), "REGISTRY OLD CRATE");
+    show(crate_list.old_git(), "GIT OLD CRATE");
 }
 
-fn list_old_orphan(crate_list: &CrateList, directory_is_empty: bool) {
-    crate_list_type(
+fn list_old_orphan(
+    crate_list: &CrateList,
+    directory_is_empty: bool,
+    show: &impl Fn(&[CrateMetaData], &str),
+) {
+    show(
         &crate_list.list_old_orphan_registry(),
         "REGISTRY OLD+ORPHAN CRATE",
     );
-    crate_list_type(&crate_list.list_old_orphan_git(), "GIT OLD+ORPHAN CRATE");
+    show(&crate_list.list_old_orphan_git(), "GIT OLD+ORPHAN CRATE");
     // print waning if no directory present in config file
     if directory_is_empty {
         let warning_text = "WARNING: You have not initialized any directory as rust project \
@@ -67,13 +382,17 @@ fn list_old_orphan(crate_list: &CrateList, directory_is_empty: bool) {
                             they are not orphan crates. Run command 'cargo trim init' to \
                             initialize current directory as rust project directory or pass cargo \
                             trim set -d <directory> for setting rust project directory";
-        println!("{}", warning_text.yellow());
+        human_println!("{}", warning_text.yellow());
     }
 }
 
-fn list_orphan(crate_list: &CrateList, directory_is_empty: bool) {
-    crate_list_type(crate_list.orphan_registry(), "REGISTRY ORPHAN CRATE");
-    crate_list_type(crate_list.orphan_git(), "GIT ORPHAN CRATE");
+fn list_orphan(
+    crate_list: &CrateList,
+    directory_is_empty: bool,
+    show: &impl Fn(&[CrateMetaData], &str),
+) {
+    show(crate_list.orphan_registry(), "REGISTRY ORPHAN CRATE");
+    show(crate_list.orphan_git(), "GIT ORPHAN CRATE");
     // print warning if directory config is empty
     if directory_is_empty {
         let warning_text = "WARNING: You have not initialized any directory as rust project \
@@ -81,13 +400,29 @@ fn list_orphan(crate_list: &CrateList, directory_is_empty: bool) {
                             'cargo trim init' to initialize current directory as rust project \
                             directory or pass cargo trim set -d <directory> for setting rust \
                             project directory";
-        println!("{}", warning_text.yellow());
+        human_println!("{}", warning_text.yellow());
     }
 }
 
-fn list_used(crate_list: &CrateList, directory_is_empty: bool) {
-    crate_list_type(crate_list.used_registry(), "REGISTRY USED CRATE");
-    crate_list_type(crate_list.used_git(), "GIT USED CRATE");
+fn list_project(
+    crate_list: &CrateList,
+    project: &str,
+    show: &impl Fn(&[CrateMetaData], &str),
+) -> Result<()> {
+    let project_path = crate_list.resolve_project(project)?;
+    let (project_registry, project_git) = crate_list.project_crates(&project_path)?;
+    show(&project_registry, "REGISTRY PROJECT CRATE");
+    show(&project_git, "GIT PROJECT CRATE");
+    Ok(())
+}
+
+fn list_used(
+    crate_list: &CrateList,
+    directory_is_empty: bool,
+    show: &impl Fn(&[CrateMetaData], &str),
+) {
+    show(crate_list.used_registry(), "REGISTRY USED CRATE");
+    show(crate_list.used_git(), "GIT USED CRATE");
     // print warning if directory config is empty
     if directory_is_empty {
         let warning_text = "WARNING: You have not initialized any directory as rust project \
@@ -95,6 +430,184 @@ fn list_used(crate_list: &CrateList, directory_is_empty: bool) {
                             trim init' to initialize current directory as rust project directory \
                             or pass cargo trim set -d <directory> for setting rust project \
                             directory";
-        println!("{}", warning_text.yellow());
+        human_println!("{}", warning_text.yellow());
+    }
+}
+
+fn list_yanked(crate_list: &CrateList, show: &impl Fn(&[CrateMetaData], &str)) {
+    show(crate_list.yanked_registry(), "REGISTRY YANKED CRATE");
+}
+
+// show table with columns chosen by user. Each column is as wide as its widest
+// cell while path column is truncated to fit terminal
+fn show_column_table(
+    crates: &[CrateMetaData],
+    title: &str,
+    columns: &[ListColumn],
+    registries: &Registries,
+) {
+    let mut crates = crates.to_vec();
+    sort_crates(&mut crates, CrateSort::Name);
+    let headers = columns
+        .iter()
+        .map(|column| format!("{column:?}").to_uppercase())
+        .collect::<Vec<_>>();
+    let rows = crates
+        .iter()
+        .map(|crate_metadata| {
+            columns
+                .iter()
+                .map(|column| column_value(crate_metadata, *column, registries))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let total_size = crates.iter().map(CrateMetaData::size).sum();
+    let total = format!(
+        "Total no of crates:- {} ({})",
+        crates.len(),
+        convert_pretty(total_size).trim()
+    );
+    let shrunk_column = columns
+        .iter()
+        .position(|column| column == &ListColumn::Path);
+    print_table(title, &headers, &rows, shrunk_column, &total);
+}
+
+// group crates by name along with number of versions and combined size. Largest
+// group is listed first
+fn group_crates(crates: &[CrateMetaData]) -> Vec<(String, usize, u64)> {
+    let mut groups: BTreeMap<String, (usize, u64)> = BTreeMap::new();
+    for crate_metadata in crates {
+        let (versions, size) = groups.entry(crate_metadata.base_name()).or_default();
+        *versions += 1;
+        *size += crate_metadata.size();
+    }
+    let mut groups = groups
+        .into_iter()
+        .map(|(name, (versions, size))| (name, versions, size))
+        .collect::<Vec<_>>();
+    groups.sort_by_key(|(_, _, size)| Reverse(*size));
+    groups
+}
+
+fn group_reports(crates: &[CrateMetaData], category: &str) -> Vec<GroupReport> {
+    group_crates(crates)
+        .into_iter()
+        .map(|(name, versions, size)| GroupReport {
+            name,
+            category: category.to_string(),
+            versions,
+            size,
+        })
+        .collect()
+}
+
+// show crates grouped by name with version count and combined size
+fn show_group_table(crates: &[CrateMetaData], title: &str) {
+    let headers = ["NAME", "VERSIONS", "SIZE"].map(String::from);
+    let groups = group_crates(crates);
+    let rows = groups
+        .iter()
+        .map(|(name, versions, size)| {
+            vec![
+                name.clone(),
+                versions.to_string(),
+                convert_pretty(*size).trim().to_string(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let total_size = crates.iter().map(CrateMetaData::size).sum();
+    let total = format!(
+        "Total no of crates:- {} with {} versions ({})",
+        groups.len(),
+        crates.len(),
+        convert_pretty(total_size).trim()
+    );
+    print_table(title, &headers, &rows, None, &total);
+}
+
+// print table with column sized to widest cell. Shrunk column is narrowed to
+// fit terminal width
+fn print_table(
+    title: &str,
+    headers: &[String],
+    rows: &[Vec<String>],
+    shrunk_column: Option<usize>,
+    total: &str,
+) {
+    let mut widths = headers
+        .iter()
+        .map(|header| header.chars().count())
+        .collect::<Vec<_>>();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    if let Some(shrunk_pos) = shrunk_column {
+        let reserved = widths.iter().sum::<usize>() - widths[shrunk_pos] + headers.len() + 1;
+        if let Some(available) = available_width(reserved) {
+            widths[shrunk_pos] = widths[shrunk_pos].min(available.max(MIN_PATH_WIDTH));
+        }
+    }
+    let dash_len = widths.iter().sum::<usize>() + headers.len() + 1;
+    let inner_width = dash_len - 2;
+    print_dash(dash_len);
+    human_println!("|{:^inner_width$}|", title.bold());
+    print_dash(dash_len);
+    print_row(headers, &widths);
+    print_dash(dash_len);
+    for row in rows {
+        print_row(row, &widths);
+    }
+    print_dash(dash_len);
+    human_println!("|{:^inner_width$}|", total.blue());
+    print_dash(dash_len);
+}
+
+// print single row of column table where cell is truncated to column width
+fn print_row(cells: &[String], widths: &[usize]) {
+    let line = cells
+        .iter()
+        .zip(widths)
+        .map(|(cell, width)| format!("{:<width$}", fit_width(cell, *width)))
+        .collect::<Vec<_>>()
+        .join("|");
+    human_println!("|{line}|");
+}
+
+// value of column for crate. Version of git crate is its revision
+fn column_value(
+    crate_metadata: &CrateMetaData,
+    column: ListColumn,
+    registries: &Registries,
+) -> String {
+    let crate_id = crate_metadata.crate_id();
+    let git_id = crate_metadata.git_id();
+    match column {
+        ListColumn::Name => crate_metadata.base_name(),
+        ListColumn::Version => crate_id.as_ref().map_or_else(
+            || {
+                git_id
+                    .map(|git_id| git_id.rev().clone())
+                    .unwrap_or_default()
+            },
+            |crate_id| crate_id.version().to_string(),
+        ),
+        ListColumn::Size => convert_pretty(crate_metadata.size()).trim().to_string(),
+        ListColumn::Path => crate_metadata
+            .path()
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+        ListColumn::Age => crate_metadata
+            .last_used()
+            .and_then(|last_used| SystemTime::now().duration_since(last_used).ok())
+            .map_or_else(|| "-".to_string(), |age| format_age(age.as_secs())),
+        ListColumn::Registry => crate_metadata
+            .source()
+            .map(|source| registries.name(source))
+            .unwrap_or_default(),
     }
 }
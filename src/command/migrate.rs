@@ -0,0 +1,48 @@
+use anyhow::Result;
+use clap::{Parser, ValueEnum};
+use owo_colors::OwoColorize;
+
+use crate::cargo_cache::translate_cargo_cache;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Tool {
+    CargoCache,
+}
+
+#[derive(Debug, Parser)]
+#[command(
+    about = "Translate command line of other cache cleaning tool to equivalent cargo trim command"
+)]
+pub(crate) struct Migrate {
+    #[arg(
+        long = "from",
+        value_enum,
+        help = "Tool whose command line is translated",
+        value_name = "tool"
+    )]
+    from: Tool,
+    #[arg(
+        help = "Arguments passed to other tool without program and subcommand name such as -a -k \
+                3",
+        allow_hyphen_values = true,
+        trailing_var_arg = true,
+        value_name = "args"
+    )]
+    args: Vec<String>,
+}
+
+impl Migrate {
+    pub(super) fn run(&self) -> Result<()> {
+        match self.from {
+            Tool::CargoCache => {
+                let translated = translate_cargo_cache(&self.args)?;
+                human_println!("{}", format!("cargo trim {}", translated.join(" ")).blue());
+                human_println!(
+                    "cargo-cache has no config file so there is nothing else to import. Binary \
+                     installed or linked as cargo-cache accepts same arguments directly"
+                );
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,340 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use owo_colors::OwoColorize;
+use url::Url;
+
+use crate::dir_path::DirPath;
+use crate::registries::is_crates_io_index;
+use crate::stats_file::StatsFile;
+use crate::utils::{
+    convert_pretty, delete_folder, get_size, last_modified, print_dash, query_print,
+};
+
+// download url of crates.io recorded in config.json of its sparse index
+const CRATES_IO_DL: &str = "https://static.crates.io/crates";
+
+#[derive(Debug, Parser)]
+#[command(
+    about = "Detect registry content duplicated under old and new cargo cache layout and remove \
+             obsolete layout"
+)]
+pub(crate) struct MigrateLayout {
+    #[arg(
+        long = "dry-run",
+        short = 'n',
+        help = "Run command in dry run mode to see what would be done"
+    )]
+    dry_run: bool,
+    #[arg(
+        long = "remove",
+        short = 'r',
+        help = "Remove content of obsolete layout once new layout is populated"
+    )]
+    remove: bool,
+}
+
+/// registry folder name which is used under index, cache and src directory
+/// in form of host-hash
+struct RegistryLayout {
+    name: String,
+    folders: Vec<PathBuf>,
+}
+
+impl RegistryLayout {
+    // return folder of layout present inside parent directory
+    fn folder_in(&self, parent: &Path) -> Option<&PathBuf> {
+        self.folders
+            .iter()
+            .find(|folder| folder.starts_with(parent))
+    }
+
+    // content file name of layout present inside parent directory
+    fn content_in(&self, parent: &Path) -> Result<Vec<String>> {
+        let mut content = Vec::new();
+        if let Some(folder) = self.folder_in(parent) {
+            for entry in fs::read_dir(folder).context("failed to read registry layout folder")? {
+                content.push(entry?.file_name().to_string_lossy().to_string());
+            }
+        }
+        Ok(content)
+    }
+}
+
+impl MigrateLayout {
+    pub(super) fn run(&self, dir_path: &DirPath, stats_file: &mut StatsFile) -> Result<()> {
+        let dry_run = self.dry_run;
+        let content_dirs = [dir_path.cache_dir(), dir_path.src_dir()];
+        let registry_groups = list_layout_groups(
+            dir_path.index_dir(),
+            &[
+                dir_path.index_dir(),
+                dir_path.cache_dir(),
+                dir_path.src_dir(),
+            ],
+        )?;
+        let mut obsolete_found = false;
+        let mut size_cleaned = 0;
+        let mut layout_removed = 0;
+        for (registry, mut layouts) in registry_groups {
+            if layouts.len() < 2 {
+                continue;
+            }
+            obsolete_found = true;
+            // most recently modified layout is the one used by current cargo version
+            layouts.sort_by_key(|layout| last_modified(&layout_children(layout)));
+            let current_layout = layouts.pop().context("failed to get current layout")?;
+            human_println!("{}", format!("Registry {registry}").bold());
+            query_print(
+                &format!("   Current layout {}", current_layout.name),
+                &convert_pretty(layout_size(&current_layout)),
+            );
+            let mut current_populated = false;
+            for content_dir in &content_dirs {
+                current_populated =
+                    !current_layout.content_in(content_dir)?.is_empty() || current_populated;
+            }
+            for layout in &layouts {
+                let obsolete_size = layout_size(layout);
+                query_print(
+                    &format!("   Obsolete layout {}", layout.name),
+                    &convert_pretty(obsolete_size),
+                );
+                for content_dir in &content_dirs {
+                    print_duplication(&current_layout, layout, content_dir)?;
+                }
+                if self.remove {
+                    if current_populated {
+                        for folder in &layout.folders {
+                            delete_folder(folder, dry_run)?;
+                        }
+                        size_cleaned += obsolete_size;
+                        layout_removed += 1;
+                    } else {
+                        human_println!(
+                            "{}",
+                            format!(
+                                "WARNING: current layout {} is not populated yet so obsolete \
+                                 layout {} is not removed",
+                                current_layout.name, layout.name
+                            )
+                            .yellow()
+                        );
+                    }
+                }
+            }
+            print_dash(crate::utils::query_full_width());
+        }
+        if !obsolete_found {
+            human_println!("{}", "No obsolete registry layout found".blue());
+        }
+        if self.remove {
+            stats_file.record(
+                "registry",
+                "migrate-layout",
+                size_cleaned,
+                layout_removed,
+                dry_run,
+            );
+            human_println!(
+                "{}",
+                format!(
+                    "{layout_removed} obsolete layouts removed which had occupied {}",
+                    convert_pretty(size_cleaned)
+                )
+                .blue()
+            );
+        }
+        Ok(())
+    }
+}
+
+// print count and size of obsolete layout content which is also present in
+// current layout
+fn print_duplication(
+    current_layout: &RegistryLayout,
+    obsolete_layout: &RegistryLayout,
+    content_dir: &Path,
+) -> Result<()> {
+    let Some(obsolete_folder) = obsolete_layout.folder_in(content_dir) else {
+        return Ok(());
+    };
+    let current_content = current_layout.content_in(content_dir)?;
+    let obsolete_content = obsolete_layout.content_in(content_dir)?;
+    let mut duplicate_count = 0;
+    let mut duplicate_size = 0;
+    for content in obsolete_content
+        .iter()
+        .filter(|content| current_content.contains(content))
+    {
+        duplicate_count += 1;
+        duplicate_size += get_size(&obsolete_folder.join(content)).unwrap_or(0);
+    }
+    let dir_name = content_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    query_print(
+        &format!(
+            "      {duplicate_count} of {} {dir_name} entries duplicated",
+            obsolete_content.len()
+        ),
+        &convert_pretty(duplicate_size),
+    );
+    Ok(())
+}
+
+// group registry folder present in all directories by index of registry which
+// they belong to. Folder whose index cannot be resolved is kept in its own
+// group so it is never removed as obsolete layout of other registry
+fn list_layout_groups(
+    index_dir: &Path,
+    dirs: &[&PathBuf],
+) -> Result<BTreeMap<String, Vec<RegistryLayout>>> {
+    let mut layouts: BTreeMap<String, RegistryLayout> = BTreeMap::new();
+    for dir in dirs {
+        if !dir.exists() {
+            continue;
+        }
+        for entry in fs::read_dir(dir).context("failed to read registry directory")? {
+            let entry = entry?.path();
+            if !entry.is_dir() {
+                continue;
+            }
+            let name = entry
+                .file_name()
+                .context("failed to get registry folder name")?
+                .to_string_lossy()
+                .to_string();
+            layouts
+                .entry(name.clone())
+                .or_insert_with(|| RegistryLayout {
+                    name,
+                    folders: Vec::new(),
+                })
+                .folders
+                .push(entry);
+        }
+    }
+    let mut groups: BTreeMap<String, Vec<RegistryLayout>> = BTreeMap::new();
+    for (name, layout) in layouts {
+        let registry = registry_identity(&index_dir.join(&name))
+            .unwrap_or_else(|| format!("{name} (unresolved index)"));
+        groups.entry(registry).or_default().push(layout);
+    }
+    Ok(groups)
+}
+
+// resolve identity of registry from its index folder. Git index is identified
+// by url of FETCH_HEAD and sparse index by download and api url of its
+// config.json. Both git and sparse index of crates.io resolve to crates.io
+fn registry_identity(index_folder: &Path) -> Option<String> {
+    if let Ok(content) = fs::read_to_string(index_folder.join(".git").join("FETCH_HEAD")) {
+        let url = Url::parse(content.split_whitespace().last()?).ok()?;
+        if is_crates_io_index(&url) {
+            return Some(String::from("crates.io"));
+        }
+        return Some(url.to_string());
+    }
+    let content = fs::read_to_string(index_folder.join("config.json")).ok()?;
+    let config: serde_json::Value = serde_json::from_str(&content).ok()?;
+    let dl = config.get("dl")?.as_str()?;
+    let api = config
+        .get("api")
+        .and_then(serde_json::Value::as_str)
+        .unwrap_or_default();
+    if dl.starts_with(CRATES_IO_DL) {
+        return Some(String::from("crates.io"));
+    }
+    Some(format!("{dl} {api}").trim().to_string())
+}
+
+// list folders of layout along with their direct children used for finding
+// last modified time of layout
+fn layout_children(layout: &RegistryLayout) -> Vec<PathBuf> {
+    let mut children = layout.folders.clone();
+    for folder in &layout.folders {
+        if let Ok(entries) = fs::read_dir(folder) {
+            children.extend(entries.filter_map(|entry| Some(entry.ok()?.path())));
+        }
+    }
+    children
+}
+
+// total size of all folders of layout
+fn layout_size(layout: &RegistryLayout) -> u64 {
+    layout
+        .folders
+        .iter()
+        .map(|folder| get_size(folder).unwrap_or(0))
+        .sum()
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::list_layout_groups;
+
+    #[test]
+    fn test_layout_groups_by_registry() {
+        let cargo_home = std::env::temp_dir().join(format!(
+            "cargo_trim_layout_group_test_{}",
+            std::process::id()
+        ));
+        let index_dir = cargo_home.join("registry").join("index");
+        let cache_dir = cargo_home.join("registry").join("cache");
+        let sparse_indexes = [
+            ("my.host.com-1111", "https://my.host.com/first/dl"),
+            ("my.host.com-2222", "https://my.host.com/second/dl"),
+            ("index.crates.io-3333", "https://static.crates.io/crates"),
+        ];
+        for (name, dl) in sparse_indexes {
+            fs::create_dir_all(index_dir.join(name)).unwrap();
+            fs::write(
+                index_dir.join(name).join("config.json"),
+                format!("{{\"dl\":\"{dl}\"}}"),
+            )
+            .unwrap();
+            fs::create_dir_all(cache_dir.join(name)).unwrap();
+        }
+        let git_index = index_dir.join("github.com-4444").join(".git");
+        fs::create_dir_all(&git_index).unwrap();
+        fs::write(
+            git_index.join("FETCH_HEAD"),
+            "abc\t\tbranch 'master' of https://github.com/rust-lang/crates.io-index",
+        )
+        .unwrap();
+        fs::create_dir_all(cache_dir.join("unknown.host.com-5555")).unwrap();
+
+        let groups = list_layout_groups(&index_dir, &[&index_dir, &cache_dir]).unwrap();
+        fs::remove_dir_all(&cargo_home).unwrap();
+
+        let group_names = |registry: &str| {
+            groups[registry]
+                .iter()
+                .map(|layout| layout.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(groups.len(), 4);
+        assert_eq!(
+            group_names("crates.io"),
+            ["github.com-4444", "index.crates.io-3333"]
+        );
+        assert_eq!(
+            group_names("https://my.host.com/first/dl"),
+            ["my.host.com-1111"]
+        );
+        assert_eq!(
+            group_names("https://my.host.com/second/dl"),
+            ["my.host.com-2222"]
+        );
+        assert_eq!(
+            group_names("unknown.host.com-5555 (unresolved index)"),
+            ["unknown.host.com-5555"]
+        );
+    }
+}
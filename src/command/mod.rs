@@ -1,29 +1,89 @@
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use anyhow::{Context, Result};
-use clap::{Parser, ValueEnum};
+use clap::{ArgAction, Parser, ValueEnum};
 use owo_colors::OwoColorize;
+use regex::Regex;
 
+use crate::color::{ColorChoice, set_color};
 use crate::command::git::clean_git;
 use crate::command::registry::clean_registry;
 use crate::config_file::ConfigFile;
+use crate::container::{distrobox_home, podman_volume_home};
 use crate::crate_detail::CrateDetail;
+use crate::deprecated_crate::read_maintenance_badges;
 use crate::dir_path::DirPath;
+use crate::dry_run_summary::{print_dry_run_summary, take_dry_run_total};
+use crate::environment::Environment;
+use crate::event::{
+    Event, emit, enable_ndjson, enable_quiet, error_emitted, ndjson_enabled, quiet_enabled,
+};
+use crate::freed_space::{measure_before_removal, print_freed_space, track_freed_space};
 use crate::git_dir::GitDir;
+use crate::history_file::{HistoryFile, SizeSnapshot};
 use crate::list_crate::CrateList;
+use crate::log_file::{LogLevel, log, open_json_log_file, open_log_file};
+use crate::nested_cargo_home::{NestedCargoHome, list_nested_cargo_homes};
+use crate::package_lock::{LockMode, set_lock_cargo_home, set_lock_mode, unlock_package_cache};
+use crate::progress::{enable_progress, finish_progress, start_deletion, start_scan};
+use crate::protect::enable_protection;
+use crate::registries::{Registries, is_crates_io_index};
 use crate::registry_dir::RegistryDir;
-use crate::utils::{convert_pretty, delete_folder, get_size, print_dash, query_print};
+use crate::removal_failure::print_removal_failures;
+use crate::report::{
+    QueryReport, Reporter, print_json, query_bin_report, query_git_report, query_registry_report,
+};
+use crate::restore_manifest::RestoreManifest;
+use crate::run_summary::{clean_recorded, enable_summary_mode, print_freed_bytes, print_summary};
+use crate::size_cache::SizeCache;
+use crate::stats_file::{CleanAction, StatsFile};
+use crate::terminal::enable_wide_mode;
+use crate::trash::enable_trash_mode;
+use crate::utils::{
+    CrateSort, SizeUnit, convert_pretty, delete_folder, disk_usage_enabled, enable_disk_usage_mode,
+    enable_interactive_mode, enable_nice_mode, get_size, parse_duration, parse_size, print_dash,
+    query_print, set_crate_sort, set_size_unit, set_verbosity,
+};
 
+mod aggregate;
+mod archive;
+mod check;
 mod clear;
+mod complete;
+mod completion;
 mod config;
+mod dedupe;
+mod diff;
+mod export;
+mod export_vendor;
 mod git;
+mod history;
+mod info;
 mod init;
 mod list;
+mod migrate;
+mod migrate_layout;
+mod outdated_bins;
+mod project;
+mod protect;
+mod query;
 mod registry;
+mod repair;
+mod restore;
+mod rustup;
+mod schedule;
 mod set;
+mod stats;
+mod target;
+mod tui;
+mod unarchive;
+mod uninstall;
 mod unset;
+mod verify;
+mod watch;
 
 #[derive(Debug, Parser)]
 enum SubCommand {
@@ -35,6 +95,35 @@ enum SubCommand {
     List(list::List),
     Git(git::Git),
     Registry(registry::Registry),
+    Project(project::Project),
+    Stats(stats::Stats),
+    Export(export::Export),
+    Target(target::Target),
+    Query(query::Query),
+    Migrate(migrate::Migrate),
+    MigrateLayout(migrate_layout::MigrateLayout),
+    Tui(tui::Tui),
+    Aggregate(aggregate::Aggregate),
+    Rustup(rustup::Rustup),
+    Watch(watch::Watch),
+    Schedule(schedule::Schedule),
+    Info(info::Info),
+    History(history::History),
+    Diff(diff::Diff),
+    Check(check::Check),
+    Restore(restore::Restore),
+    Protect(protect::Protect),
+    Verify(verify::Verify),
+    Repair(repair::Repair),
+    Uninstall(uninstall::Uninstall),
+    OutdatedBins(outdated_bins::OutdatedBins),
+    Archive(archive::Archive),
+    Unarchive(unarchive::Unarchive),
+    Dedupe(dedupe::Dedupe),
+    ExportVendor(export_vendor::ExportVendor),
+    Completion(completion::Completion),
+    #[command(name = "__complete")]
+    Complete(complete::Complete),
 }
 
 #[derive(Debug, Parser)]
@@ -45,10 +134,46 @@ enum SubCommand {
     author=clap::crate_authors!(),
     about=clap::crate_description!()
 )]
-#[allow(clippy::struct_excessive_bools)]
-pub(crate) struct Command {
+#[allow(clippy::struct_excessive_bools, clippy::struct_field_names)]
+pub struct Command {
     #[arg(long = "all", short = 'a', help = "Clean up all registry & git crates")]
     all: bool,
+    #[arg(
+        long = "binary-units",
+        global = true,
+        help = "Show size in base 1024 units such as KiB, MiB and GiB matching du -h and Windows \
+                Explorer"
+    )]
+    binary_units: bool,
+    #[arg(
+        long = "cargo-home",
+        help = "Operate on provided cargo home instead of cargo home of current user. Pass \
+                multiple times to trim several cargo homes in one run",
+        value_name = "path",
+        conflicts_with_all = ["distrobox", "podman_volume"]
+    )]
+    cargo_home: Vec<PathBuf>,
+    #[arg(
+        long = "color",
+        global = true,
+        value_enum,
+        default_value_t = ColorChoice::Auto,
+        help = "Color human readable output. Auto disables color when output is not terminal or \
+                NO_COLOR is set",
+        value_name = "when"
+    )]
+    color: ColorChoice,
+    #[arg(
+        long = "deprecated",
+        help = "Clean deprecated registry crates which are renamed or superseded by other crate"
+    )]
+    deprecated: bool,
+    #[arg(
+        long = "deprecated-badge",
+        help = "Also detect cached crates.io crates whose author set maintenance badge status to \
+                deprecated in Cargo.toml. Used by --deprecated and list output"
+    )]
+    deprecated_badge: bool,
     #[arg(
         long = "directory",
         short = 'd',
@@ -56,12 +181,64 @@ pub(crate) struct Command {
         env = "TRIM_DIRECTORY"
     )]
     directory: Option<Vec<String>>,
+    #[arg(
+        long = "disk-usage",
+        global = true,
+        help = "Report allocated disk usage along with apparent size in query output so size can \
+                be compared with du"
+    )]
+    disk_usage: bool,
+    #[arg(
+        long = "distrobox",
+        help = "Operate on cargo home of distrobox container instead of cargo home of current user",
+        value_name = "name",
+        conflicts_with = "podman_volume"
+    )]
+    distrobox: Option<String>,
+    #[arg(
+        long = "duplicates",
+        help = "Clean duplicate versions of registry crates except newest version and versions \
+                used by lock file of registered projects"
+    )]
+    duplicates: bool,
     #[arg(
         long = "dry-run",
         short = 'n',
         help = "Run command in dry run mode to see what would be done"
     )]
     dry_run: bool,
+    #[arg(
+        long = "exit-code",
+        global = true,
+        help = "Exit with 3 when there is nothing to trim, 4 when some paths cannot be removed and \
+                5 when cargo home is over --keep-under size or check maximum size instead of 0"
+    )]
+    exit_code: bool,
+    #[arg(
+        long = "filter-regex",
+        global = true,
+        help = "Restrict listed and cleaned registry and git crates to crates whose full \
+                name-version matches regex",
+        value_name = "regex",
+        value_parser = Regex::new
+    )]
+    filter_regex: Option<Regex>,
+    #[arg(
+        long = "force",
+        global = true,
+        help = "Remove registry and git cache even when cargo or rustc is running or package cache \
+                lock is held",
+        conflicts_with = "wait"
+    )]
+    force: bool,
+    #[arg(
+        long = "format",
+        short = 'f',
+        value_enum,
+        default_value_t = OutputFormat::Table,
+        help = "Output format of query"
+    )]
+    format: OutputFormat,
     #[arg(
         long = "gc",
         short = 'g',
@@ -76,6 +253,27 @@ pub(crate) struct Command {
         env = "TRIM_IGNORE"
     )]
     ignore: Option<Vec<String>>,
+    #[arg(
+        long = "interactive",
+        global = true,
+        help = "Show each crate with its path and size and ask y/n/a/q confirmation before \
+                removing it"
+    )]
+    interactive: bool,
+    #[arg(
+        long = "keep-under",
+        help = "Clean orphan, least recently used and largest crates first until cargo home size \
+                is under provided size such as 5GB",
+        value_name = "size",
+        value_parser = parse_size
+    )]
+    keep_under: Option<u64>,
+    #[arg(
+        long = "keep-versions",
+        help = "Clean registry crates except newest provided number of versions of each crate",
+        value_name = "number"
+    )]
+    keep_versions: Option<usize>,
     #[arg(
         long = "light",
         short = 'l',
@@ -83,6 +281,53 @@ pub(crate) struct Command {
                 internet"
     )]
     light_cleanup: bool,
+    #[arg(
+        long = "log-file",
+        global = true,
+        help = "Append timestamped log of every deleted path, skipped crate and error to file. \
+                Scanned crates are also logged with -v",
+        value_name = "path"
+    )]
+    log_file: Option<PathBuf>,
+    #[arg(
+        long = "log-json",
+        global = true,
+        help = "Append json line with timestamp, path, category, size and dry run flag of every \
+                deleted path to file",
+        value_name = "path"
+    )]
+    log_json: Option<PathBuf>,
+    #[arg(
+        long = "markdown",
+        help = "Write markdown report of clean actions and cache size of current run to file",
+        value_name = "file"
+    )]
+    markdown: Option<PathBuf>,
+    #[arg(
+        long = "ndjson",
+        visible_alias = "porcelain",
+        global = true,
+        help = "Stream every scanned crate, deleted path, freed bytes and error as single json line \
+                to stdout. Human readable output is written to stderr"
+    )]
+    ndjson: bool,
+    #[arg(
+        long = "nested-cargo-home",
+        help = "Clean registry and git cache of cargo home nested inside project directory"
+    )]
+    nested_cargo_home: bool,
+    #[arg(
+        long = "nice",
+        help = "Lower process priority and throttle scan and deletion io to keep system responsive",
+        env = "TRIM_NICE"
+    )]
+    nice: bool,
+    #[arg(
+        long = "no-cache",
+        help = "Ignore cached crate size and rescan every crate directory",
+        env = "TRIM_NO_CACHE"
+    )]
+    no_cache: bool,
     #[arg(
         long,
         help = "Do not scan hidden folder for current command. Takes precedence over \
@@ -99,6 +344,14 @@ pub(crate) struct Command {
     no_scan_target_folder: bool,
     #[arg(long = "old", short = 'o', help = "Clean old cache crates")]
     old: bool,
+    #[arg(
+        long = "older-than",
+        help = "Only clean crates whose files are not modified or accessed within duration such \
+                as 30d or 6w. Supported units are s, m, h, d and w",
+        value_name = "duration",
+        value_parser = parse_duration
+    )]
+    older_than: Option<Duration>,
     #[arg(
         long = "old-orphan",
         short = 'z',
@@ -112,12 +365,69 @@ pub(crate) struct Command {
                 generated till now"
     )]
     orphan: bool,
+    #[arg(
+        long = "podman-volume",
+        help = "Operate on podman volume used as cargo home inside container instead of cargo \
+                home of current user",
+        value_name = "name"
+    )]
+    podman_volume: Option<String>,
+    #[arg(
+        long = "prerelease",
+        help = "Clean pre-release versions of registry crates such as alpha, beta or rc when \
+                stable version of same crate is also cached except versions used by lock file of \
+                registered projects"
+    )]
+    prerelease: bool,
+    #[arg(
+        long = "profile",
+        help = "Use named profile of config file for current command",
+        value_name = "name",
+        env = "TRIM_PROFILE"
+    )]
+    profile: Option<String>,
+    #[arg(
+        long = "project",
+        short = 'p',
+        help = "Project used for project related operation. Project can be path or directory name \
+                of project present in registered directory",
+        value_name = "name|path"
+    )]
+    project: Option<String>,
     #[arg(
         long = "query",
         short = 'q',
         help = "Return size of different .cargo/cache folders"
     )]
     query: bool,
+    #[arg(
+        long = "quiet",
+        global = true,
+        conflicts_with = "interactive",
+        help = "Only print number of bytes freed by clean operation. Errors, ndjson events and log \
+                file are still written"
+    )]
+    quiet: bool,
+    #[arg(
+        long = "refetch",
+        help = "Fetch dependencies of lock file of every registered project after clean so cache \
+                needed for current work is populated again"
+    )]
+    refetch: bool,
+    #[arg(
+        long = "registry",
+        global = true,
+        help = "Restrict listed and cleaned crates to registry with provided name, host or index \
+                url. Name is read from registries table of cargo config",
+        value_name = "name"
+    )]
+    registry: Option<String>,
+    #[arg(
+        long = "reverse",
+        global = true,
+        help = "Reverse order of crates in top and list output"
+    )]
+    reverse: bool,
     #[arg(
         long = "scan-hidden-folder",
         help = "Scan hidden folder for current command",
@@ -130,36 +440,185 @@ pub(crate) struct Command {
         env = "TRIM_SCAN_TARGET_FOLDER"
     )]
     scan_target_folder: bool,
+    #[arg(
+        long = "sort",
+        global = true,
+        value_enum,
+        help = "Sort crates in top and list output. Top output is sorted by size and list output \
+                by name by default"
+    )]
+    sort: Option<CrateSort>,
     #[arg(
         long = "top",
         short = 't',
         help = "Show certain number of top crates which have highest size"
     )]
     top: Option<usize>,
+    #[arg(
+        long = "trash",
+        help = "Move deleted file and folder to system trash instead of removing permanently",
+        env = "TRIM_TRASH"
+    )]
+    trash: bool,
+    #[arg(
+        long = "unit",
+        global = true,
+        value_enum,
+        default_value_t = SizeUnit::Auto,
+        help = "Unit used to show size. Auto picks largest unit for each size",
+        value_name = "unit"
+    )]
+    unit: SizeUnit,
+    #[arg(
+        long = "unused",
+        help = "Clean crates which are only referenced by project and not by any other project",
+        requires = "project"
+    )]
+    unused: bool,
+    #[arg(
+        long = "unused-for",
+        help = "Clean all registry and git crates whose files are not read or modified within \
+                duration such as 90d. Same as --all with --older-than",
+        value_name = "duration",
+        value_parser = parse_duration,
+        conflicts_with = "older_than"
+    )]
+    unused_for: Option<Duration>,
     #[arg(
         long = "update",
         short = 'u',
         help = "Generate and Update Cargo.lock file present inside config directory folder path"
     )]
     update: bool,
-    #[arg(long = "wipe", short = 'w', help = "Wipe folder", value_enum)]
+    #[arg(
+        long = "summary",
+        global = true,
+        conflicts_with_all = ["interactive", "quiet"],
+        help = "Only print table of removed item count and freed space of each category at end of \
+                run"
+    )]
+    summary: bool,
+    #[arg(
+        long = "summary-file",
+        help = "Write json summary of clean actions and cache size of current run to file",
+        value_name = "file"
+    )]
+    summary_file: Option<PathBuf>,
+    #[arg(
+        long = "verbose",
+        short = 'v',
+        action = ArgAction::Count,
+        global = true,
+        help = "Increase dry run verbosity. Use -v to list crates and -vv to list every path with \
+                size. Log file also contains scanned crates with -v"
+    )]
+    verbose: u8,
+    #[arg(
+        long = "wait",
+        global = true,
+        help = "Wait for running cargo and rustc to finish and release package cache lock before \
+                removing registry and git cache"
+    )]
+    wait: bool,
+    #[arg(
+        long = "wide",
+        global = true,
+        help = "Show full crate name and path in table instead of truncating them to terminal width"
+    )]
+    wide: bool,
+    #[arg(
+        long = "wipe",
+        short = 'w',
+        help = "Wipe folder. Multiple folders can be wiped at once such as -w registry-src,git-db. \
+                registry-src, registry-cache, registry-index, git-db and git-checkout can be used \
+                as alias of src, cache, index, db and checkouts",
+        value_enum,
+        value_delimiter = ','
+    )]
     wipe: Option<Vec<Wipe>>,
+    #[arg(
+        long = "yanked",
+        help = "Clean registry crates whose cached version is yanked in registry index"
+    )]
+    yanked: bool,
     #[command(subcommand)]
     sub_command: Option<SubCommand>,
 }
 
-#[derive(Clone, ValueEnum, Debug)]
+#[derive(Clone, ValueEnum, Debug, PartialEq, Eq, PartialOrd, Ord)]
 enum Wipe {
     Git,
+    #[value(alias = "git-checkout")]
     Checkouts,
+    #[value(alias = "git-db")]
     Db,
     Registry,
+    #[value(alias = "registry-cache")]
     Cache,
+    #[value(alias = "registry-index")]
     Index,
     IndexCache,
+    #[value(alias = "registry-src")]
     Src,
 }
 
+impl Wipe {
+    // folder removed by wipe
+    fn path<'a>(&self, dir_path: &'a DirPath) -> &'a Path {
+        match self {
+            Self::Git => dir_path.git_dir(),
+            Self::Checkouts => dir_path.checkout_dir(),
+            Self::Db => dir_path.db_dir(),
+            Self::Registry => dir_path.registry_dir(),
+            Self::Cache => dir_path.cache_dir(),
+            Self::Index | Self::IndexCache => dir_path.index_dir(),
+            Self::Src => dir_path.src_dir(),
+        }
+    }
+
+    // check if folder of wipe is removed by other wipe of same or parent folder.
+    // Index cache wipe only remove part of index folder so it do not cover index
+    fn covered_by(&self, other: &Self, dir_path: &DirPath) -> bool {
+        self != other
+            && *other != Self::IndexCache
+            && self.path(dir_path).starts_with(other.path(dir_path))
+    }
+}
+
+/// Outcome of cargo trim run which decides exit code when --exit-code is
+/// passed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// Crates are trimmed or command do not trim anything
+    Success,
+    /// Clean is requested but there is nothing to trim
+    NothingToTrim,
+    /// Some paths cannot be removed
+    PartialFailure,
+    /// Cargo home is over --keep-under size after clean or over check maximum
+    /// size
+    OverBudget,
+}
+
+impl Outcome {
+    /// exit code of outcome
+    #[must_use]
+    pub fn exit_code(self) -> u8 {
+        match self {
+            Self::Success => 0,
+            Self::NothingToTrim => 3,
+            Self::PartialFailure => 4,
+            Self::OverBudget => 5,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum, Debug, PartialEq, Eq)]
+pub(crate) enum OutputFormat {
+    Table,
+    Json,
+}
+
 #[derive(Clone, ValueEnum, Debug)]
 enum GitCompress {
     AggressiveCheckout,
@@ -177,22 +636,356 @@ enum GitCompressAction {
 }
 
 impl Command {
+    /// Run parsed command and return its outcome
+    ///
+    /// # Errors
+    /// Return error if cargo home cannot be scanned or clean operation fails
+    pub fn run(&self) -> Result<Outcome> {
+        // completion is answered without scanning cargo home to keep latency low
+        match &self.sub_command {
+            Some(SubCommand::Completion(completion)) => {
+                completion.run();
+                return Ok(Outcome::Success);
+            }
+            Some(SubCommand::Complete(complete)) => {
+                complete.run(self.cargo_homes()?.into_iter().next())?;
+                return Ok(Outcome::Success);
+            }
+            _ => {}
+        }
+        if self.ndjson {
+            enable_ndjson();
+        }
+        if self.quiet {
+            enable_quiet();
+        }
+        if self.summary {
+            enable_summary_mode();
+        }
+        set_color(self.color, self.ndjson);
+        // verbosity is set before log file is opened so debug lines are filtered
+        set_verbosity(self.verbose);
+        if self.wait {
+            set_lock_mode(LockMode::Wait);
+        } else if self.force {
+            set_lock_mode(LockMode::Force);
+        }
+        if let Some(log_file) = &self.log_file {
+            open_log_file(log_file)?;
+            log(
+                LogLevel::Info,
+                &format!("started {}", std::env::args().collect::<Vec<_>>().join(" ")),
+            );
+        }
+        if let Some(log_json) = &self.log_json {
+            open_json_log_file(log_json)?;
+        }
+        // aggregate only reads summary files so local cargo home is never scanned
+        if let Some(SubCommand::Aggregate(aggregate)) = &self.sub_command {
+            aggregate.run()?;
+            return Ok(Outcome::Success);
+        }
+        let result = self.cargo_homes().and_then(|cargo_homes| {
+            if cargo_homes.len() > 1 && !self.only_config() {
+                self.execute_all(&cargo_homes)
+            } else {
+                self.execute(cargo_homes.into_iter().next())
+            }
+        });
+        // progress line is left when operation fails midway
+        finish_progress();
+        if result.is_ok() && (self.keep_under.is_some() || clean_recorded()) {
+            print_summary(self.dry_run);
+            // stdout is reserved for event in ndjson mode
+            if self.quiet && !self.ndjson {
+                print_freed_bytes();
+            }
+        }
+        if let Err(err) = &result {
+            emit(&Event::Error {
+                message: format!("{err:#}"),
+            });
+        }
+        result
+    }
+
+    /// exit code of outcome. Outcome other than success is only reported when
+    /// --exit-code is passed or check subcommand is used so existing scripts
+    /// keep working. Partial failure is always reported since path which
+    /// cannot be removed used to abort whole run with error
+    #[must_use]
+    pub fn exit_code(&self, outcome: Outcome) -> u8 {
+        if self.exit_code
+            || outcome == Outcome::PartialFailure
+            || matches!(self.sub_command, Some(SubCommand::Check(_)))
+        {
+            outcome.exit_code()
+        } else {
+            0
+        }
+    }
+
+    // cargo homes to operate on. Container option or --cargo-home take priority
+    // over cargo home list of config file. Empty list means cargo home of
+    // current user
+    fn cargo_homes(&self) -> Result<Vec<PathBuf>> {
+        // Resolve host side cargo home of container if provided
+        let cargo_homes = if let Some(volume) = &self.podman_volume {
+            vec![podman_volume_home(volume)?]
+        } else if let Some(container) = &self.distrobox {
+            vec![distrobox_home(container)?]
+        } else if !self.cargo_home.is_empty() {
+            self.cargo_home.clone()
+        } else {
+            let config_file = ConfigFile::init(DirPath::new(None)?.config_file())?;
+            config_file.cargo_home().iter().map(PathBuf::from).collect()
+        };
+        for cargo_home in &cargo_homes {
+            anyhow::ensure!(
+                cargo_home.is_dir(),
+                "Cargo home {cargo_home:?} does not exist"
+            );
+        }
+        Ok(cargo_homes)
+    }
+
+    // subcommand which only read or change config file is performed once even
+    // when multiple cargo homes are used
+    fn only_config(&self) -> bool {
+        matches!(
+            self.sub_command,
+            Some(
+                SubCommand::Init(_)
+                    | SubCommand::Clear(_)
+                    | SubCommand::Config(_)
+                    | SubCommand::Set(_)
+                    | SubCommand::Unset(_)
+                    | SubCommand::Protect(_)
+                    | SubCommand::Schedule(_)
+                    | SubCommand::Migrate(_)
+            )
+        )
+    }
+
+    // perform command on every cargo home one after another and print size of
+    // each cargo home along with combined total. Failure of one cargo home do
+    // not stop trimming of other
+    fn execute_all(&self, cargo_homes: &[PathBuf]) -> Result<Outcome> {
+        let mut outcome = Outcome::NothingToTrim;
+        let mut totals = Vec::new();
+        for cargo_home in cargo_homes {
+            human_println!("{}", format!("Cargo home {}", cargo_home.display()).bold());
+            let size_before = get_size(cargo_home)?;
+            let home_outcome = match self.execute(Some(cargo_home.clone())) {
+                Ok(home_outcome) => home_outcome,
+                Err(err) => {
+                    emit(&Event::Error {
+                        message: format!("{}: {err:#}", cargo_home.display()),
+                    });
+                    Outcome::PartialFailure
+                }
+            };
+            finish_progress();
+            // nothing is removed in dry run so reclaimable space is used
+            let freed = if self.dry_run {
+                take_dry_run_total()
+            } else {
+                size_before.saturating_sub(get_size(cargo_home)?)
+            };
+            totals.push((cargo_home, size_before, freed));
+            outcome = combine_outcome(outcome, home_outcome);
+        }
+        print_cargo_home_totals(&totals, self.dry_run);
+        Ok(outcome)
+    }
+
+    // perform all operation of command
     #[allow(clippy::too_many_lines)]
-    pub(crate) fn run(&self) -> Result<()> {
+    fn execute(&self, cargo_home: Option<PathBuf>) -> Result<Outcome> {
         let dry_run = self.dry_run;
 
+        if self.nice {
+            enable_nice_mode();
+        }
+        if self.disk_usage {
+            enable_disk_usage_mode();
+        }
+        if self.wide {
+            enable_wide_mode();
+        }
+        set_crate_sort(self.sort, self.reverse);
+        set_size_unit(self.unit, self.binary_units);
+        // progress line would be mixed with confirmation prompt in interactive mode
+        if self.interactive {
+            enable_interactive_mode();
+        } else if !ndjson_enabled() && !quiet_enabled() {
+            enable_progress();
+        }
+
         // List out all required path
-        let dir_path = DirPath::new()?;
+        let dir_path = DirPath::new(cargo_home)?;
+        set_lock_cargo_home(dir_path.cargo_home());
+        if !dry_run {
+            track_freed_space(&dir_path);
+        }
 
         // Read config file data
         let mut config_file = ConfigFile::init(dir_path.config_file())?;
 
+        // Detect environment and brief about its capability on first run. Dry
+        // run never writes config file so briefing is shown again on next run
+        if config_file.environment().is_none() {
+            let environment = Environment::detect(&dir_path);
+            environment.print_briefing();
+            if !dry_run {
+                config_file.set_environment(environment)?;
+            }
+        }
+
+        // Project config of current directory is merged over config file
+        let current_dir = std::env::current_dir().context("Failed to get current directory")?;
+        config_file.use_project_config(&current_dir)?;
+
+        if let Some(profile) = &self.profile {
+            config_file.use_profile(profile)?;
+        }
+        // keep versions of config, profile and project config is only used by
+        // plain trim so running read only subcommand never removes crates
+        let config_keep_versions = config_file
+            .keep_versions()
+            .or(config_file.project_keep_versions())
+            .filter(|_| self.sub_command.is_none());
+        let keep_versions = self.keep_versions.or(config_keep_versions);
+        let older_than = self
+            .unused_for
+            .or(self.older_than)
+            .or(config_file.older_than());
+
+        if self.trash || config_file.trash() {
+            enable_trash_mode()?;
+        }
+
+        // Read lifetime stats of cargo trim
+        let mut stats_file = StatsFile::init(dir_path.stats_file())?;
+
+        // Read size cache of crates
+        let size_cache = SizeCache::init(dir_path.size_cache_file(), !self.no_cache)?;
+
         // create new CrateDetail struct
-        let mut crate_detail = CrateDetail::new(dir_path.index_dir(), dir_path.db_dir())?;
+        start_scan();
+        let mut crate_detail =
+            CrateDetail::new(dir_path.index_dir(), dir_path.db_dir(), size_cache)?;
+
+        // deprecated crates are detected while listing crates so badges are
+        // read before crate list is created
+        if self.deprecated_badge {
+            let crates_io_src_dirs = fs::read_dir(dir_path.src_dir())
+                .map(|entries| {
+                    entries
+                        .flatten()
+                        .map(|entry| entry.path())
+                        .filter(|src_dir| {
+                            src_dir.file_name().is_some_and(|name| {
+                                crate_detail
+                                    .source_url_from_path(&dir_path.index_dir().join(name))
+                                    .is_ok_and(|url| is_crates_io_index(&url))
+                            })
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            read_maintenance_badges(&crates_io_src_dirs);
+        }
 
         // List out crates
-        let crate_list =
+        let mut crate_list =
             crate::list_crate::CrateList::create_list(&dir_path, &config_file, &mut crate_detail)?;
+        finish_progress();
+        crate_detail.save_size_cache()?;
+
+        let mut history_file = HistoryFile::init(dir_path.history_file())?;
+
+        // Protected crates are skipped by every clean and wipe operation
+        enable_protection(&config_file.protect(), &crate_detail, dir_path.index_dir());
+        if let Some(regex) = &self.filter_regex {
+            crate_list.retain_matching(regex);
+        }
+
+        // Restrict crates to single registry if provided
+        let registries = Registries::load(dir_path.cargo_home())?;
+        if let Some(registry) = &self.registry {
+            crate_list.retain_registry(|source| registries.matches(registry, source));
+            if crate_list.installed_registry().is_empty() {
+                human_println!(
+                    "{}",
+                    format!("WARNING: No crates found for registry {registry:?}").yellow()
+                );
+            }
+        }
+
+        // stream scanned crates for ndjson consumer
+        for (category, list) in [
+            ("bin", crate_list.installed_bin()),
+            ("registry", crate_list.installed_registry()),
+            ("git", crate_list.installed_git()),
+        ] {
+            for crate_metadata in list {
+                emit(&Event::scanned(category, crate_metadata));
+            }
+        }
+
+        // warn about crates which collide in case insensitive file system
+        for (crate_metadata, other) in crate_list.case_collisions() {
+            human_println!(
+                "{}",
+                format!(
+                    "WARNING: {:?} and {:?} only differ by case and collide in case insensitive \
+                     file system",
+                    crate_metadata.name(),
+                    other.name()
+                )
+                .yellow()
+            );
+        }
+
+        // warn about registry entries which are not counted as crate
+        for path in crate_detail.unparseable() {
+            human_println!(
+                "{}",
+                format!(
+                    "WARNING: {} has unparseable name and version and is not counted as crate",
+                    path.display()
+                )
+                .yellow()
+            );
+        }
+
+        // warn about cargo home with its own cache inside project directory. It
+        // is only listed when nested cargo home is reported or cleaned and
+        // warning is printed to stderr so json document is not mixed
+        let nested_cargo_homes = if self.nested_cargo_home || self.query {
+            list_nested_cargo_homes(crate_list.cargo_toml_location().location_path())
+                .unwrap_or_else(|err| {
+                    human_eprintln!(
+                        "{}",
+                        format!("WARNING: failed to list nested cargo home: {err:#}").yellow()
+                    );
+                    Vec::new()
+                })
+        } else {
+            Vec::new()
+        };
+        for nested_cargo_home in &nested_cargo_homes {
+            human_eprintln!(
+                "{}",
+                format!(
+                    "WARNING: {} is nested cargo home with its own cache of {}",
+                    nested_cargo_home.path().display(),
+                    convert_pretty(nested_cargo_home.size())
+                )
+                .yellow()
+            );
+        }
 
         if let Some(directories) = &self.directory {
             for directory in directories {
@@ -232,12 +1025,34 @@ impl Command {
                 dir_path.checkout_dir(),
                 dir_path.src_dir(),
                 dir_path.index_dir(),
+                &mut stats_file,
                 dry_run,
             );
         }
+        let mut registry_crates_location = crate::registry_dir::RegistryDir::new(
+            dir_path.index_dir(),
+            crate_list.installed_registry(),
+            older_than,
+            RestoreManifest::new(dir_path.restore_file()),
+        )?;
+
         if let Some(wipes) = &self.wipe {
-            for wipe in wipes {
-                wipe_directory(wipe, &dir_path, dry_run);
+            let mut wipes = wipes.clone();
+            wipes.sort();
+            wipes.dedup();
+            for wipe in &wipes {
+                // folder inside other wiped folder is removed along with it
+                if wipes.iter().any(|other| wipe.covered_by(other, &dir_path)) {
+                    continue;
+                }
+                wipe_directory(
+                    wipe,
+                    &dir_path,
+                    &crate_detail,
+                    &mut registry_crates_location,
+                    &mut stats_file,
+                    dry_run,
+                );
             }
         }
 
@@ -251,18 +1066,97 @@ impl Command {
         }
 
         if self.query {
-            query_size(&dir_path, &crate_list, &crate_detail);
+            if self.format == OutputFormat::Json {
+                print_json(&QueryReport::new(vec![
+                    query_bin_report(&dir_path, &crate_list),
+                    query_registry_report(&dir_path, &crate_list, &crate_detail),
+                    query_git_report(&dir_path, &crate_list, &crate_detail),
+                ]))?;
+            } else {
+                query_size(
+                    &dir_path,
+                    &crate_list,
+                    &crate_detail,
+                    &registries,
+                    &nested_cargo_homes,
+                );
+                if disk_usage_enabled() {
+                    query_disk_usage(&QueryReport::new(vec![
+                        query_bin_report(&dir_path, &crate_list),
+                        query_registry_report(&dir_path, &crate_list, &crate_detail),
+                        query_git_report(&dir_path, &crate_list, &crate_detail),
+                    ]));
+                }
+            }
         }
 
-        let mut registry_crates_location = crate::registry_dir::RegistryDir::new(
-            dir_path.cache_dir(),
-            dir_path.src_dir(),
-            dir_path.index_dir(),
-            crate_list.installed_registry(),
-        )?;
+        let git_crates_location = crate::git_dir::GitDir::new(older_than);
 
-        let git_crates_location =
-            crate::git_dir::GitDir::new(dir_path.checkout_dir(), dir_path.db_dir())?;
+        if self.deprecated {
+            deprecated_clean(
+                &crate_list,
+                &mut registry_crates_location,
+                &crate_detail,
+                &mut stats_file,
+                dry_run,
+            )?;
+        }
+
+        if self.yanked {
+            yanked_clean(
+                &crate_list,
+                &mut registry_crates_location,
+                &crate_detail,
+                &mut stats_file,
+                dry_run,
+            )?;
+        }
+
+        if self.duplicates {
+            duplicates_clean(
+                &crate_list,
+                &mut registry_crates_location,
+                &crate_detail,
+                &mut stats_file,
+                config_file.directory().is_empty(),
+                dry_run,
+            )?;
+        }
+
+        if self.prerelease {
+            prerelease_clean(
+                &crate_list,
+                &mut registry_crates_location,
+                &crate_detail,
+                &mut stats_file,
+                dry_run,
+            )?;
+        }
+
+        if let Some(keep) = keep_versions {
+            keep_versions_clean(
+                &crate_list,
+                keep,
+                &mut registry_crates_location,
+                &crate_detail,
+                &mut stats_file,
+                dry_run,
+            )?;
+        }
+
+        let mut over_budget = false;
+        if let Some(budget) = self.keep_under {
+            over_budget = keep_under_clean(
+                &dir_path,
+                &crate_list,
+                budget,
+                &mut registry_crates_location,
+                &git_crates_location,
+                &crate_detail,
+                &mut stats_file,
+                dry_run,
+            )?;
+        }
 
         if self.old {
             old_clean(
@@ -270,6 +1164,7 @@ impl Command {
                 &mut registry_crates_location,
                 &git_crates_location,
                 &crate_detail,
+                &mut stats_file,
                 dry_run,
             )?;
         }
@@ -280,6 +1175,7 @@ impl Command {
                 &mut registry_crates_location,
                 &git_crates_location,
                 &crate_detail,
+                &mut stats_file,
                 config_file.directory().is_empty(),
                 dry_run,
             )?;
@@ -291,28 +1187,53 @@ impl Command {
                 &mut registry_crates_location,
                 &git_crates_location,
                 &crate_detail,
+                &mut stats_file,
                 config_file.directory().is_empty(),
                 dry_run,
             )?;
         }
 
-        if self.all {
+        if self.unused {
+            if let Some(project) = &self.project {
+                project_unused_clean(
+                    &crate_list,
+                    project,
+                    &mut registry_crates_location,
+                    &git_crates_location,
+                    &crate_detail,
+                    &mut stats_file,
+                    dry_run,
+                )?;
+            }
+        }
+
+        if self.nested_cargo_home {
+            nested_cargo_home_clean(&nested_cargo_homes, &mut stats_file, dry_run)?;
+        }
+
+        if self.all || self.unused_for.is_some() {
             remove_all(
                 &crate_list,
                 &mut registry_crates_location,
                 &git_crates_location,
                 &crate_detail,
+                &mut stats_file,
                 dry_run,
             )?;
         }
 
         if let Some(sub_command) = &self.sub_command {
             match &sub_command {
-                SubCommand::Init(init) => init.run(&mut config_file)?,
+                SubCommand::Init(init) => init.run(&dir_path, &mut config_file)?,
                 SubCommand::Clear(clear) => clear.run(&mut config_file)?,
                 SubCommand::Config(config) => config.run(&config_file, dir_path.config_file())?,
                 SubCommand::List(list) => {
-                    list.run(&crate_list, config_file.directory().is_empty());
+                    list.run(
+                        &crate_list,
+                        &registries,
+                        dir_path.cargo_home(),
+                        config_file.directory().is_empty(),
+                    )?;
                 }
                 SubCommand::Set(set) => set.run(&mut config_file)?,
                 SubCommand::Unset(unset) => unset.run(&mut config_file)?,
@@ -322,6 +1243,7 @@ impl Command {
                         &crate_list,
                         &crate_detail,
                         &git_crates_location,
+                        &mut stats_file,
                         config_file.directory().is_empty(),
                     )?;
                 }
@@ -330,14 +1252,128 @@ impl Command {
                         &dir_path,
                         &crate_list,
                         &crate_detail,
+                        &registries,
                         &mut registry_crates_location,
+                        &mut stats_file,
                         config_file.directory().is_empty(),
                     )?;
                 }
+                SubCommand::Project(project) => {
+                    project.run(
+                        &mut config_file,
+                        &crate_list,
+                        &crate_detail,
+                        &mut registry_crates_location,
+                        &git_crates_location,
+                        &mut stats_file,
+                    )?;
+                }
+                SubCommand::Stats(stats) => stats.run(&stats_file, &crate_list, &crate_detail),
+                SubCommand::Export(export) => export.run(&crate_list, &crate_detail)?,
+                SubCommand::Target(target) => {
+                    target.run(&mut config_file, &crate_list, &mut stats_file)?;
+                }
+                SubCommand::Query(query) => query.run(&dir_path, &crate_list)?,
+                SubCommand::MigrateLayout(migrate_layout) => {
+                    migrate_layout.run(&dir_path, &mut stats_file)?;
+                }
+                SubCommand::Rustup(rustup) => {
+                    rustup.run(&dir_path, &crate_list, &mut stats_file)?;
+                }
+                SubCommand::Watch(watch) => watch.run(&dir_path, self.profile.as_deref())?,
+                SubCommand::Schedule(schedule) => schedule.run()?,
+                SubCommand::Migrate(migrate) => migrate.run()?,
+                SubCommand::Info(info) => info.run(&crate_detail, &registries),
+                SubCommand::History(history) => history.run(&history_file),
+                SubCommand::Diff(diff) => diff.run(&dir_path, &crate_detail)?,
+                SubCommand::Check(check) => over_budget = check.run(&dir_path),
+                SubCommand::Restore(restore) => {
+                    restore.run(&dir_path, &crate_detail, &registries)?;
+                }
+                SubCommand::Protect(protect) => protect.run(&mut config_file)?,
+                SubCommand::Verify(verify) => {
+                    verify.run(&dir_path, &crate_detail, &mut stats_file)?;
+                }
+                SubCommand::Repair(repair) => {
+                    repair.run(&dir_path, &crate_detail, &registries, &mut stats_file)?;
+                }
+                SubCommand::Uninstall(uninstall) => {
+                    uninstall.run(&dir_path, &crate_list, &mut stats_file)?;
+                }
+                SubCommand::OutdatedBins(outdated_bins) => {
+                    outdated_bins.run(dir_path.cargo_home())?;
+                }
+                SubCommand::Archive(archive) => archive.run(&crate_detail, &mut stats_file)?,
+                SubCommand::Unarchive(unarchive) => unarchive.run(&crate_detail)?,
+                SubCommand::Dedupe(dedupe) => dedupe.run(&crate_detail, &mut stats_file)?,
+                SubCommand::ExportVendor(export_vendor) => {
+                    export_vendor.run(&dir_path, &crate_list, &crate_detail)?;
+                }
+                // completion is handled before cargo home is scanned
+                SubCommand::Completion(_) | SubCommand::Complete(_) | SubCommand::Aggregate(_) => {}
+                SubCommand::Tui(tui) => {
+                    tui.run(
+                        &dir_path,
+                        &config_file,
+                        &crate_list,
+                        &crate_detail,
+                        &mut registry_crates_location,
+                        &git_crates_location,
+                        &mut stats_file,
+                    )?;
+                }
             }
         }
 
-        Ok(())
+        if self.refetch {
+            let cargo_toml_location = crate_list.cargo_toml_location().location_path();
+            refetch_projects(cargo_toml_location, dir_path.cargo_home(), dry_run);
+        }
+
+        // Record size of each cache category to see growth over time. Only real
+        // run is recorded after clean so history shows size left in cache
+        if !dry_run
+            && !matches!(
+                self.sub_command,
+                Some(SubCommand::History(_) | SubCommand::Diff(_))
+            )
+        {
+            let snapshot = if stats_file.session().is_empty() {
+                SizeSnapshot::new(&crate_detail)
+            } else {
+                SizeSnapshot::measure(&dir_path)
+            };
+            history_file.record(snapshot)?;
+        }
+
+        print_dry_run_summary(&dir_path);
+        print_freed_space();
+        print_removal_failures();
+
+        Reporter::new(self.summary_file.clone(), self.markdown.clone()).report(
+            &dir_path,
+            &crate_list,
+            &crate_detail,
+            &stats_file,
+        )?;
+        stats_file.save()?;
+
+        let clean_requested = self.keep_under.is_some() || !stats_file.session().is_empty();
+        let removed = stats_file
+            .session()
+            .iter()
+            .map(CleanAction::removed)
+            .sum::<usize>();
+        let outcome = if error_emitted() {
+            Outcome::PartialFailure
+        } else if over_budget {
+            Outcome::OverBudget
+        } else if clean_requested && removed == 0 {
+            Outcome::NothingToTrim
+        } else {
+            Outcome::Success
+        };
+        Ok(outcome)
     }
 }
 
@@ -378,13 +1414,11 @@ fn git_compress(
                     git_folder.push(".git");
                     if git_folder.exists() {
                         if !dry_run {
-                            println!(
+                            human_println!(
                                 "{}",
                                 format!(
                                     "Compressing {} registry index",
-                                    file_name
-                                        .to_str()
-                                        .context("Failed to get compress file name")?
+                                    file_name.to_string_lossy()
                                 )
                                 .blue()
                             );
@@ -403,7 +1437,7 @@ fn git_compress(
                     {
                         let rev_path = rev?.path();
                         if !dry_run {
-                            println!("{}", "Compressing git checkout".blue());
+                            human_println!("{}", "Compressing git checkout".blue());
                         }
                         run_git_compress_commands(&rev_path, dry_run, is_aggressive)?;
                     }
@@ -413,22 +1447,31 @@ fn git_compress(
                 for entry in fs::read_dir(db_dir).context("failed to read db dir")? {
                     let repo_path = entry?.path();
                     if !dry_run {
-                        println!("{}", "Compressing git db".blue());
+                        human_println!("{}", "Compressing git db".blue());
                     }
                     run_git_compress_commands(&repo_path, dry_run, is_aggressive)?;
                 }
             }
         }
     }
-    println!("{}", "Git compress task completed".blue());
+    human_println!("{}", "Git compress task completed".blue());
     Ok(())
 }
 
 // run combination of commands which git compress a index of registry
-fn run_git_compress_commands(repo_path: &Path, dry_run: bool, is_aggressive: bool) -> Result<()> {
+pub(super) fn run_git_compress_commands(
+    repo_path: &Path,
+    dry_run: bool,
+    is_aggressive: bool,
+) -> Result<()> {
     if dry_run {
-        println!("{} git compressing {repo_path:?}", "Dry run:".yellow());
+        human_println!(
+            "{} git compressing {}",
+            "Dry run:".yellow(),
+            repo_path.display()
+        );
     } else {
+        measure_before_removal(repo_path);
         let mut commands = vec![
             // Pack unpacked objects in a repository
             (vec!["repack", "-a", "-d"], "Repack unpacked objects"),
@@ -471,7 +1514,7 @@ fn run_git_compress_commands(repo_path: &Path, dry_run: bool, is_aggressive: boo
                 .current_dir(repo_path)
                 .output()
                 .context(format!("Failed to execute {position} command"))?;
-            println!(
+            human_println!(
                 "{:70}.......Step {position}/{total_len}",
                 format!("  {symbol} {message}")
             );
@@ -480,35 +1523,103 @@ fn run_git_compress_commands(repo_path: &Path, dry_run: bool, is_aggressive: boo
     Ok(())
 }
 // light cleanup registry directory
-fn light_cleanup(checkout_dir: &Path, src_dir: &Path, index_dir: &Path, dry_run: bool) {
+fn light_cleanup(
+    checkout_dir: &Path,
+    src_dir: &Path,
+    index_dir: &Path,
+    stats_file: &mut StatsFile,
+    dry_run: bool,
+) {
     let mut light_cleanup_success = true;
     // light cleanup registry
-    light_cleanup_success =
-        registry::light_cleanup_registry(src_dir, index_dir, dry_run) && light_cleanup_success;
+    let src_size = get_size(src_dir).unwrap_or(0);
+    let registry_cleanup_success = registry::light_cleanup_registry(src_dir, index_dir, dry_run);
+    if registry_cleanup_success {
+        stats_file.record("registry", "light", src_size, 1, dry_run);
+    }
+    light_cleanup_success = registry_cleanup_success && light_cleanup_success;
     // light cleanup git
-    light_cleanup_success = git::light_cleanup_git(checkout_dir, dry_run) && light_cleanup_success;
+    let checkout_size = get_size(checkout_dir).unwrap_or(0);
+    let git_cleanup_success = git::light_cleanup_git(checkout_dir, dry_run);
+    if git_cleanup_success {
+        stats_file.record("git", "light", checkout_size, 1, dry_run);
+    }
+    light_cleanup_success = git_cleanup_success && light_cleanup_success;
     if !light_cleanup_success {
-        println!("Failed to delete some folder during light cleanup");
+        emit(&Event::Error {
+            message: String::from("Failed to delete some folder during light cleanup"),
+        });
+        human_println!("Failed to delete some folder during light cleanup");
     }
 }
 
 // wipe certain directory
-fn wipe_directory(wipe: &Wipe, dir_path: &DirPath, dry_run: bool) {
-    let has_failed = match wipe {
+fn wipe_directory(
+    wipe: &Wipe,
+    dir_path: &DirPath,
+    crate_detail: &CrateDetail,
+    registry_crates_location: &mut RegistryDir,
+    stats_file: &mut StatsFile,
+    dry_run: bool,
+) {
+    let wipe_path = wipe.path(dir_path);
+    let size_before = get_size(wipe_path).unwrap_or(0);
+    if !dry_run {
+        // crate archives removed by wipe can be downloaded again by restore
+        if matches!(wipe, Wipe::Registry | Wipe::Cache) {
+            let archives = crate_detail
+                .registry_crates_archive()
+                .iter()
+                .collect::<Vec<_>>();
+            if let Err(err) = registry_crates_location.record_restore(crate_detail, &archives) {
+                let message = format!(
+                    "Failed to record crates of {wipe:?} directory in restore manifest so it is \
+                     not removed: {err:#}"
+                );
+                emit(&Event::Error {
+                    message: message.clone(),
+                });
+                human_println!("{message}");
+                return;
+            }
+        }
+        start_deletion(size_before);
+    }
+    let is_removed = match wipe {
         Wipe::Git => delete_folder(dir_path.git_dir(), dry_run),
         Wipe::Checkouts => delete_folder(dir_path.checkout_dir(), dry_run),
         Wipe::Db => delete_folder(dir_path.db_dir(), dry_run),
         Wipe::Registry => delete_folder(dir_path.registry_dir(), dry_run),
         Wipe::Cache => delete_folder(dir_path.cache_dir(), dry_run),
         Wipe::Index => delete_folder(dir_path.index_dir(), dry_run),
-        Wipe::IndexCache => crate::utils::delete_index_cache(dir_path.index_dir(), dry_run),
+        Wipe::IndexCache => {
+            crate::utils::delete_index_cache(dir_path.index_dir(), dry_run).map(|()| true)
+        }
         Wipe::Src => delete_folder(dir_path.src_dir(), dry_run),
-    }
-    .is_err();
+    };
+    let has_failed = !matches!(is_removed, Ok(true));
+    finish_progress();
     if has_failed {
-        println!("Failed to remove {wipe:?} directory");
+        emit(&Event::Error {
+            message: format!("Failed to remove {wipe:?} directory"),
+        });
+        human_println!("Failed to remove {wipe:?} directory");
     } else {
-        println!("{} {wipe:?} directory", "Removed".red());
+        // index cache wipe only remove part of index directory so calculate freed
+        // size from difference
+        let size_freed = if dry_run {
+            0
+        } else {
+            size_before.saturating_sub(get_size(wipe_path).unwrap_or(0))
+        };
+        stats_file.record(
+            &format!("{wipe:?}").to_lowercase(),
+            "wipe",
+            size_freed,
+            1,
+            dry_run,
+        );
+        human_println!("{} {wipe:?} directory", "Removed".red());
     }
 }
 
@@ -530,19 +1641,14 @@ fn update_cargo_toml(cargo_toml_location: &[PathBuf], dry_run: bool) -> Result<(
         // helps so we may not need to generate lock file again for workspace project
         if cargo_lock.exists() {
             if dry_run {
-                println!(
-                    "{} Updating lockfile at path {location:?}",
+                human_println!(
+                    "{} Updating lockfile at path {}",
                     "Dry run:".yellow(),
+                    location.display()
                 );
             } else {
-                let message = format!(
-                    "Updating {}",
-                    cargo_lock
-                        .to_str()
-                        .context("Failed to convert Cargo.lock file path to str")?
-                        .blue()
-                );
-                println!("{message}");
+                let message = format!("Updating {}", cargo_lock.display().blue());
+                human_println!("{message}");
                 std::process::Command::new("cargo")
                     .arg("update")
                     .current_dir(location)
@@ -551,10 +1657,95 @@ fn update_cargo_toml(cargo_toml_location: &[PathBuf], dry_run: bool) -> Result<(
             }
         }
     }
-    println!("{}", "Successfully updated all Cargo.lock".blue());
+    human_println!("{}", "Successfully updated all Cargo.lock".blue());
     Ok(())
 }
 
+// Fetch dependencies of every project which has lock file into cargo home
+fn refetch_projects(cargo_toml_location: &[PathBuf], cargo_home: &Path, dry_run: bool) {
+    // cargo fetch waits for package cache lock held by cargo trim
+    unlock_package_cache();
+    let mut fetched = 0;
+    for location in cargo_toml_location {
+        // workspace member share lock file of workspace root which is fetched once
+        if !location.join("Cargo.lock").exists() {
+            continue;
+        }
+        if dry_run {
+            human_println!(
+                "{} Fetching dependencies of {}",
+                "Dry run:".yellow(),
+                location.display()
+            );
+            continue;
+        }
+        human_println!("Fetching dependencies of {}", location.display().blue());
+        let status = std::process::Command::new("cargo")
+            .arg("fetch")
+            .arg("--locked")
+            .env("CARGO_HOME", cargo_home)
+            .current_dir(location)
+            .status();
+        if status.is_ok_and(|status| status.success()) {
+            fetched += 1;
+        } else {
+            emit(&Event::Error {
+                message: format!("Failed to fetch dependencies of {}", location.display()),
+            });
+            human_println!("Failed to fetch dependencies of {}", location.display());
+        }
+    }
+    if !dry_run {
+        human_println!(
+            "{}",
+            format!("Fetched dependencies of {fetched} projects").blue()
+        );
+    }
+}
+
+// combine outcome of multiple cargo homes. Failure take priority over over
+// budget and nothing to trim is only reported when no cargo home is trimmed
+fn combine_outcome(first: Outcome, second: Outcome) -> Outcome {
+    let rank = |outcome: Outcome| match outcome {
+        Outcome::NothingToTrim => 0,
+        Outcome::Success => 1,
+        Outcome::OverBudget => 2,
+        Outcome::PartialFailure => 3,
+    };
+    if rank(second) > rank(first) {
+        second
+    } else {
+        first
+    }
+}
+
+// print size and freed space of every cargo home along with combined total
+fn print_cargo_home_totals(totals: &[(&PathBuf, u64, u64)], dry_run: bool) {
+    let dash_len = 76;
+    let freed_title = if dry_run { "RECLAIMABLE" } else { "FREED" };
+    print_dash(dash_len);
+    human_println!(
+        "{}",
+        format!("{:<44}{:>16}{:>16}", "CARGO HOME", "SIZE", freed_title).bold()
+    );
+    print_dash(dash_len);
+    for (cargo_home, size, freed) in totals {
+        human_println!(
+            "{:<44}{:>16}{:>16}",
+            cargo_home.display(),
+            convert_pretty(*size),
+            convert_pretty(*freed)
+        );
+    }
+    print_dash(dash_len);
+    human_println!(
+        "{:<44}{:>16}{:>16}",
+        "total",
+        convert_pretty(totals.iter().map(|(_, size, _)| size).sum()),
+        convert_pretty(totals.iter().map(|(_, _, freed)| freed).sum())
+    );
+}
+
 // show top n crates
 fn top_crates(crate_detail: &CrateDetail, number: usize) {
     crate::utils::show_top_number_crates(crate_detail.bin(), "bin", number);
@@ -562,9 +1753,41 @@ fn top_crates(crate_detail: &CrateDetail, number: usize) {
     git::top_crates_git(crate_detail, number);
 }
 
+// show apparent size along with allocated disk usage of queried folders
+pub(super) fn query_disk_usage(query_report: &QueryReport) {
+    let dash_len = 54;
+    print_dash(dash_len);
+    human_println!(
+        "{}",
+        format!("{:<22}{:>16}{:>16}", "FOLDER", "APPARENT", "ON DISK").bold()
+    );
+    print_dash(dash_len);
+    for folder in query_report.folders() {
+        human_println!(
+            "{:<22}{:>16}{:>16}",
+            folder.category(),
+            convert_pretty(folder.size()),
+            convert_pretty(folder.disk_usage().unwrap_or(0))
+        );
+    }
+    print_dash(dash_len);
+    human_println!(
+        "{:<22}{:>16}{:>16}",
+        "total",
+        convert_pretty(query_report.total()),
+        convert_pretty(query_report.total_disk_usage().unwrap_or(0))
+    );
+}
+
 // query size of directory of cargo home folder provide some valuable size
 // information
-fn query_size(dir_path: &DirPath, crate_list: &CrateList, crate_detail: &CrateDetail) {
+fn query_size(
+    dir_path: &DirPath,
+    crate_list: &CrateList,
+    crate_detail: &CrateDetail,
+    registries: &Registries,
+    nested_cargo_homes: &[NestedCargoHome],
+) {
     let mut final_size = 0_u64;
     let bin_dir_size = get_size(dir_path.bin_dir()).unwrap_or(0_u64);
     final_size += bin_dir_size;
@@ -576,9 +1799,282 @@ fn query_size(dir_path: &DirPath, crate_list: &CrateList, crate_detail: &CrateDe
         &convert_pretty(bin_dir_size),
     );
     print_dash(crate::utils::query_full_width());
-    final_size += registry::query_size_registry(dir_path, crate_list, crate_detail);
+    final_size += registry::query_size_registry(dir_path, crate_list, crate_detail, registries);
     final_size += git::query_size_git(dir_path, crate_list, crate_detail);
     query_print("Total size", &convert_pretty(final_size));
+    // nested cargo home is not part of .cargo so it is shown separately
+    if !nested_cargo_homes.is_empty() {
+        let nested_size = nested_cargo_homes.iter().map(NestedCargoHome::size).sum();
+        print_dash(crate::utils::query_full_width());
+        query_print(
+            &format!(
+                "Total size of {} nested cargo home caches:",
+                nested_cargo_homes.len()
+            ),
+            &convert_pretty(nested_size),
+        );
+    }
+}
+
+// Clean registry and git cache of cargo home nested inside project directory
+fn nested_cargo_home_clean(
+    nested_cargo_homes: &[NestedCargoHome],
+    stats_file: &mut StatsFile,
+    dry_run: bool,
+) -> Result<()> {
+    let mut size_cleaned = 0;
+    for nested_cargo_home in nested_cargo_homes {
+        for cache in nested_cargo_home.caches() {
+            delete_folder(cache, dry_run)?;
+        }
+        size_cleaned += nested_cargo_home.size();
+    }
+    stats_file.record(
+        "nested_cargo_home",
+        "all",
+        size_cleaned,
+        nested_cargo_homes.len(),
+        dry_run,
+    );
+    human_println!(
+        "{}",
+        format!(
+            "{} nested cargo home caches removed which had occupied {}",
+            nested_cargo_homes.len(),
+            convert_pretty(size_cleaned)
+        )
+        .blue()
+    );
+    Ok(())
+}
+
+// Clean deprecated crates
+fn deprecated_clean(
+    crate_list: &CrateList,
+    registry_crates_location: &mut RegistryDir,
+    crate_detail: &CrateDetail,
+    stats_file: &mut StatsFile,
+    dry_run: bool,
+) -> Result<()> {
+    let (registry_sized_cleaned, total_registry_crate_removed) = clean_registry(
+        registry_crates_location,
+        crate_list.deprecated_registry(),
+        crate_detail,
+        stats_file,
+        "deprecated",
+        dry_run,
+    )?;
+    human_println!(
+        "{}",
+        format!(
+            "{total_registry_crate_removed} deprecated crates removed which had occupied {}",
+            convert_pretty(registry_sized_cleaned)
+        )
+        .blue()
+    );
+    Ok(())
+}
+
+// Clean yanked crates
+fn yanked_clean(
+    crate_list: &CrateList,
+    registry_crates_location: &mut RegistryDir,
+    crate_detail: &CrateDetail,
+    stats_file: &mut StatsFile,
+    dry_run: bool,
+) -> Result<()> {
+    let (registry_sized_cleaned, total_registry_crate_removed) = clean_registry(
+        registry_crates_location,
+        crate_list.yanked_registry(),
+        crate_detail,
+        stats_file,
+        "yanked",
+        dry_run,
+    )?;
+    human_println!(
+        "{}",
+        format!(
+            "{total_registry_crate_removed} yanked crates removed which had occupied {}",
+            convert_pretty(registry_sized_cleaned)
+        )
+        .blue()
+    );
+    Ok(())
+}
+
+// Clean pre-release versions of registry crates which also have stable
+// version cached
+fn prerelease_clean(
+    crate_list: &CrateList,
+    registry_crates_location: &mut RegistryDir,
+    crate_detail: &CrateDetail,
+    stats_file: &mut StatsFile,
+    dry_run: bool,
+) -> Result<()> {
+    let (registry_sized_cleaned, total_registry_crate_removed) = clean_registry(
+        registry_crates_location,
+        &crate_list.list_prerelease_registry(),
+        crate_detail,
+        stats_file,
+        "prerelease",
+        dry_run,
+    )?;
+    human_println!(
+        "{}",
+        format!(
+            "{total_registry_crate_removed} pre-release versions of crates removed which had \
+             occupied {}",
+            convert_pretty(registry_sized_cleaned)
+        )
+        .blue()
+    );
+    Ok(())
+}
+
+// Clean registry crates except newest keep number of versions
+fn keep_versions_clean(
+    crate_list: &CrateList,
+    keep: usize,
+    registry_crates_location: &mut RegistryDir,
+    crate_detail: &CrateDetail,
+    stats_file: &mut StatsFile,
+    dry_run: bool,
+) -> Result<()> {
+    let (registry_sized_cleaned, total_registry_crate_removed) = clean_registry(
+        registry_crates_location,
+        &crate_list.list_excess_version_registry(keep),
+        crate_detail,
+        stats_file,
+        "keep-versions",
+        dry_run,
+    )?;
+    human_println!(
+        "{}",
+        format!(
+            "{total_registry_crate_removed} crates older than newest {keep} versions removed \
+             which had occupied {}",
+            convert_pretty(registry_sized_cleaned)
+        )
+        .blue()
+    );
+    Ok(())
+}
+
+// Clean duplicate versions of registry crates which are not used by any
+// registered project
+fn duplicates_clean(
+    crate_list: &CrateList,
+    registry_crates_location: &mut RegistryDir,
+    crate_detail: &CrateDetail,
+    stats_file: &mut StatsFile,
+    directory_is_empty: bool,
+    dry_run: bool,
+) -> Result<()> {
+    if directory_is_empty {
+        human_println!(
+            "{}",
+            "WARNING: No directory is registered as rust project directory so only newest \
+             version of each crate is kept"
+                .yellow()
+        );
+    }
+    let (registry_sized_cleaned, total_registry_crate_removed) = clean_registry(
+        registry_crates_location,
+        &crate_list.list_duplicate_version_registry(),
+        crate_detail,
+        stats_file,
+        "duplicates",
+        dry_run,
+    )?;
+    human_println!(
+        "{}",
+        format!(
+            "{total_registry_crate_removed} duplicate versions of crates removed which had \
+             occupied {}",
+            convert_pretty(registry_sized_cleaned)
+        )
+        .blue()
+    );
+    Ok(())
+}
+
+// Evict crates in order of eviction priority until cargo home size is under
+// budget
+#[allow(clippy::too_many_arguments)]
+fn keep_under_clean(
+    dir_path: &DirPath,
+    crate_list: &CrateList,
+    budget: u64,
+    registry_crates_location: &mut RegistryDir,
+    git_crates_location: &GitDir,
+    crate_detail: &CrateDetail,
+    stats_file: &mut StatsFile,
+    dry_run: bool,
+) -> Result<bool> {
+    let cargo_home_size = get_size(dir_path.cargo_home()).unwrap_or(0);
+    if cargo_home_size <= budget {
+        human_println!(
+            "{}",
+            format!(
+                "Cargo home size {} is already under {}",
+                convert_pretty(cargo_home_size).trim(),
+                convert_pretty(budget).trim()
+            )
+            .blue()
+        );
+        return Ok(false);
+    }
+    let mut evict_registry = Vec::new();
+    let mut evict_git = Vec::new();
+    let mut remaining_size = cargo_home_size;
+    for crate_metadata in crate_list.list_eviction_candidates() {
+        if remaining_size <= budget {
+            break;
+        }
+        remaining_size = remaining_size.saturating_sub(crate_metadata.size());
+        if crate_metadata.version().is_some() {
+            evict_registry.push(crate_metadata);
+        } else {
+            evict_git.push(crate_metadata);
+        }
+    }
+    let (registry_sized_cleaned, total_registry_crate_removed) = clean_registry(
+        registry_crates_location,
+        &evict_registry,
+        crate_detail,
+        stats_file,
+        "keep-under",
+        dry_run,
+    )?;
+    let (git_sized_cleaned, total_git_crate_removed) = clean_git(
+        git_crates_location,
+        &evict_git,
+        stats_file,
+        "keep-under",
+        dry_run,
+    );
+    human_println!(
+        "{}",
+        format!(
+            "{} crates removed to keep cargo home under {} which had occupied {}",
+            total_registry_crate_removed + total_git_crate_removed,
+            convert_pretty(budget).trim(),
+            convert_pretty(registry_sized_cleaned + git_sized_cleaned)
+        )
+        .blue()
+    );
+    if remaining_size > budget {
+        human_println!(
+            "{}",
+            format!(
+                "WARNING: Cargo home is still {} after removing all crates. Remaining space is \
+                 used by index, bin and other files",
+                convert_pretty(remaining_size).trim()
+            )
+            .yellow()
+        );
+    }
+    Ok(remaining_size > budget)
 }
 
 // Clean old crates
@@ -587,21 +2083,25 @@ fn old_clean(
     registry_crates_location: &mut RegistryDir,
     git_crates_location: &GitDir,
     crate_detail: &CrateDetail,
+    stats_file: &mut StatsFile,
     dry_run: bool,
 ) -> Result<()> {
     let (registry_sized_cleaned, total_registry_crate_removed) = clean_registry(
         registry_crates_location,
         crate_list.old_registry(),
         crate_detail,
+        stats_file,
+        "old",
         dry_run,
     )?;
     let (git_sized_cleaned, total_git_crate_removed) = clean_git(
         git_crates_location,
         crate_list.old_git(),
-        crate_detail,
+        stats_file,
+        "old",
         dry_run,
     );
-    println!(
+    human_println!(
         "{}",
         format!(
             "{} old crates removed which had occupied {}",
@@ -619,6 +2119,7 @@ fn old_orphan_clean(
     registry_crates_location: &mut RegistryDir,
     git_crates_location: &GitDir,
     crate_detail: &CrateDetail,
+    stats_file: &mut StatsFile,
     directory_is_empty: bool,
     dry_run: bool,
 ) -> Result<()> {
@@ -628,9 +2129,9 @@ fn old_orphan_clean(
                             not orphan crates. Run command 'cargo trim init' to initialize \
                             current directory as rust project directory or pass cargo trim set -d \
                             <directory> for setting rust project directory";
-        println!("{}", warning_text.yellow());
+        human_println!("{}", warning_text.yellow());
         let mut input = String::new();
-        print!("Do you want to continue? (y/N) ");
+        human_print!("Do you want to continue? (y/N) ");
         std::io::stdout()
             .flush()
             .context("failed to flush output stream")?;
@@ -647,16 +2148,19 @@ fn old_orphan_clean(
         registry_crates_location,
         &crate_list.list_old_orphan_registry(),
         crate_detail,
+        stats_file,
+        "old-orphan",
         dry_run,
     )?;
     let (git_sized_cleaned, total_git_crate_removed) = clean_git(
         git_crates_location,
         &crate_list.list_old_orphan_git(),
-        crate_detail,
+        stats_file,
+        "old-orphan",
         dry_run,
     );
 
-    println!(
+    human_println!(
         "{}",
         format!(
             "{} crates which are both old and orphan crate removed which had {}",
@@ -674,6 +2178,7 @@ fn orphan_clean(
     registry_crates_location: &mut RegistryDir,
     git_crates_location: &GitDir,
     crate_detail: &CrateDetail,
+    stats_file: &mut StatsFile,
     directory_is_empty: bool,
     dry_run: bool,
 ) -> Result<()> {
@@ -683,9 +2188,9 @@ fn orphan_clean(
                             classified as orphan crate. Run command 'cargo trim init' to \
                             initialize current directory as rust project directory or pass cargo \
                             trim set -d <directory> for setting rust project directory";
-        println!("{}", warning_text.yellow());
+        human_println!("{}", warning_text.yellow());
         let mut input = String::new();
-        print!("Do you want to continue? (y/N) ");
+        human_print!("Do you want to continue? (y/N) ");
         std::io::stdout()
             .flush()
             .context("failed to flush output stream")?;
@@ -702,16 +2207,19 @@ fn orphan_clean(
         registry_crates_location,
         crate_list.orphan_registry(),
         crate_detail,
+        stats_file,
+        "orphan",
         dry_run,
     )?;
     let (git_sized_cleaned, total_git_crate_removed) = clean_git(
         git_crates_location,
         crate_list.orphan_git(),
-        crate_detail,
+        stats_file,
+        "orphan",
         dry_run,
     );
 
-    println!(
+    human_println!(
         "{}",
         format!(
             "{} orphan crates removed which had occupied {}",
@@ -723,28 +2231,72 @@ fn orphan_clean(
     Ok(())
 }
 
+// Clean crates which are only used by project
+fn project_unused_clean(
+    crate_list: &CrateList,
+    project: &str,
+    registry_crates_location: &mut RegistryDir,
+    git_crates_location: &GitDir,
+    crate_detail: &CrateDetail,
+    stats_file: &mut StatsFile,
+    dry_run: bool,
+) -> Result<()> {
+    let project_path = crate_list.resolve_project(project)?;
+    let (exclusive_registry, exclusive_git) = crate_list.project_exclusive_crates(&project_path)?;
+    let (registry_sized_cleaned, total_registry_crate_removed) = clean_registry(
+        registry_crates_location,
+        &exclusive_registry,
+        crate_detail,
+        stats_file,
+        "project",
+        dry_run,
+    )?;
+    let (git_sized_cleaned, total_git_crate_removed) = clean_git(
+        git_crates_location,
+        &exclusive_git,
+        stats_file,
+        "project",
+        dry_run,
+    );
+    human_println!(
+        "{}",
+        format!(
+            "{} crates only used by project {} removed which had occupied {}",
+            total_git_crate_removed + total_registry_crate_removed,
+            project_path.display(),
+            convert_pretty(git_sized_cleaned + registry_sized_cleaned)
+        )
+        .blue()
+    );
+    Ok(())
+}
+
 // remove all crates
 fn remove_all(
     crate_list: &CrateList,
     registry_crates_location: &mut RegistryDir,
     git_crates_location: &GitDir,
     crate_detail: &CrateDetail,
+    stats_file: &mut StatsFile,
     dry_run: bool,
 ) -> Result<()> {
     let (registry_sized_cleaned, total_registry_crate_removed) = clean_registry(
         registry_crates_location,
         crate_list.installed_registry(),
         crate_detail,
+        stats_file,
+        "all",
         dry_run,
     )?;
     let (git_sized_cleaned, total_git_crate_removed) = clean_git(
         git_crates_location,
         crate_list.installed_git(),
-        crate_detail,
+        stats_file,
+        "all",
         dry_run,
     );
 
-    println!(
+    human_println!(
         "{}",
         format!(
             "Total size of {} crates removed :- {}",
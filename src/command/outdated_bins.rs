@@ -0,0 +1,206 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use owo_colors::OwoColorize;
+use semver::Version;
+use url::Url;
+
+use crate::install_metadata::{InstallMetadata, InstalledPackage};
+use crate::network::{DEFAULT_TIMEOUT, check_reachability};
+use crate::package_lock::unlock_package_cache;
+use crate::utils::print_dash;
+
+// maximum time given to single cargo search so stalled query do not block check
+const SEARCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+// interval between checking whether cargo search has exited
+const SEARCH_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Parser)]
+#[command(
+    about = "Query crates.io for latest version of every package installed by cargo install and \
+             show outdated packages"
+)]
+pub(crate) struct OutdatedBins {
+    #[arg(
+        long = "reinstall",
+        help = "Install outdated packages again with same features using cargo install"
+    )]
+    reinstall: bool,
+}
+
+impl OutdatedBins {
+    pub(super) fn run(&self, cargo_home: &Path) -> Result<()> {
+        let install_metadata = InstallMetadata::load(cargo_home)?;
+        let mut packages = install_metadata.packages();
+        packages.sort_by(|first, second| first.name().cmp(second.name()));
+        // every search would wait for timeout when crates.io is unreachable
+        if packages.iter().any(InstalledPackage::is_from_crates_io) {
+            let crates_io = Url::parse("https://crates.io").context("Invalid crates.io url")?;
+            let reachability =
+                check_reachability(std::slice::from_ref(&crates_io), DEFAULT_TIMEOUT);
+            anyhow::ensure!(
+                reachability.get(&crates_io).copied().unwrap_or(false),
+                "crates.io is unreachable, check network connection and try again"
+            );
+        }
+        let dash_len = 76;
+        print_dash(dash_len);
+        human_println!(
+            "{}",
+            format!("{:<44}{:>16}{:>16}", "PACKAGE", "INSTALLED", "LATEST").bold()
+        );
+        print_dash(dash_len);
+        let mut outdated = Vec::new();
+        for package in &packages {
+            // git and path packages do not have version on crates.io
+            let latest = if package.is_from_crates_io() {
+                // failed search is reported without stopping check of other package
+                latest_version(cargo_home, package.name()).unwrap_or_else(|err| {
+                    human_println!("{}", format!("WARNING: {err:#}").yellow());
+                    None
+                })
+            } else {
+                None
+            };
+            let is_outdated = latest.as_ref().is_some_and(|latest| {
+                Version::parse(package.version()).is_ok_and(|installed| latest > &installed)
+            });
+            let latest = latest.map_or_else(|| String::from("-"), |latest| latest.to_string());
+            let row = format!(
+                "{:<44}{:>16}{:>16}",
+                package.name(),
+                package.version(),
+                latest
+            );
+            if is_outdated {
+                human_println!("{}", row.yellow());
+                outdated.push(package);
+            } else {
+                human_println!("{row}");
+            }
+        }
+        print_dash(dash_len);
+        human_println!(
+            "{}",
+            format!(
+                "{} of {} installed packages are outdated",
+                outdated.len(),
+                packages.len()
+            )
+            .blue()
+        );
+        for package in outdated {
+            if self.reinstall {
+                reinstall(cargo_home, package)?;
+            } else {
+                human_println!("   cargo {}", package.install_args().join(" "));
+            }
+        }
+        Ok(())
+    }
+}
+
+// latest version of crate reported by cargo search. Search results are sorted by
+// relevance so result with exact name is picked. Search which do not finish
+// within timeout is killed and reported as failure
+fn latest_version(cargo_home: &Path, name: &str) -> Result<Option<Version>> {
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| String::from("cargo"));
+    let mut child = Command::new(cargo)
+        .args(["search", "--limit", "10", name])
+        .env("CARGO_HOME", cargo_home)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run cargo search")?;
+    let deadline = Instant::now() + SEARCH_TIMEOUT;
+    while child
+        .try_wait()
+        .context("Failed to wait for cargo search")?
+        .is_none()
+    {
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            anyhow::bail!(
+                "Search of latest version of {name} timed out after {}s",
+                SEARCH_TIMEOUT.as_secs()
+            );
+        }
+        std::thread::sleep(SEARCH_POLL_INTERVAL);
+    }
+    // output of limited search is small enough to fit in pipe buffer
+    let output = child
+        .wait_with_output()
+        .context("Failed to read cargo search output")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Failed to search latest version of {name}: {}",
+            String::from_utf8_lossy(&output.stderr)
+                .lines()
+                .next()
+                .unwrap_or("cargo search failed")
+                .trim()
+        );
+    }
+    Ok(parse_search_output(
+        &String::from_utf8_lossy(&output.stdout),
+        name,
+    ))
+}
+
+// parse version from line of cargo search output in form of
+// `name = "version"    # description`
+fn parse_search_output(output: &str, name: &str) -> Option<Version> {
+    output.lines().find_map(|line| {
+        let (line_name, rest) = line.split_once(" = ")?;
+        if line_name != name {
+            return None;
+        }
+        let version = rest.strip_prefix('"')?.split('"').next()?;
+        Version::parse(version).ok()
+    })
+}
+
+// install package again using cargo install with same features
+fn reinstall(cargo_home: &Path, package: &InstalledPackage) -> Result<()> {
+    // cargo install waits for package cache lock held by cargo trim
+    unlock_package_cache();
+    human_println!(
+        "{}",
+        format!("Reinstalling {} using cargo install", package.name()).blue()
+    );
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| String::from("cargo"));
+    let status = Command::new(cargo)
+        .args(package.install_args())
+        .env("CARGO_HOME", cargo_home)
+        .status()
+        .context("Failed to run cargo install")?;
+    anyhow::ensure!(
+        status.success(),
+        "Failed to reinstall {} using cargo install",
+        package.name()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use semver::Version;
+
+    use super::parse_search_output;
+
+    #[test]
+    fn test_parse_search_output() {
+        let output = "ripgrep_all = \"0.10.6\"    # rga: ripgrep, but also search in PDFs\nripgrep \
+                      = \"14.1.0\"    # ripgrep is a line-oriented search tool\n";
+        assert_eq!(
+            parse_search_output(output, "ripgrep"),
+            Some(Version::new(14, 1, 0))
+        );
+        assert_eq!(parse_search_output(output, "rg"), None);
+    }
+}
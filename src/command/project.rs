@@ -0,0 +1,189 @@
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use owo_colors::OwoColorize;
+
+use crate::command::git::clean_git;
+use crate::command::registry::clean_registry;
+use crate::config_file::ConfigFile;
+use crate::crate_detail::CrateDetail;
+use crate::git_dir::GitDir;
+use crate::list_crate::CrateList;
+use crate::registry_dir::RegistryDir;
+use crate::stats_file::StatsFile;
+use crate::target_dir::target_dir_name;
+use crate::utils::{ask_confirmation, convert_pretty, crate_list_type, delete_folder, get_size};
+
+#[derive(Debug, Subcommand)]
+enum ProjectCommand {
+    Remove(Remove),
+}
+
+#[derive(Debug, Parser)]
+#[command(about = "Perform operation related to registered rust project")]
+pub(crate) struct Project {
+    #[command(subcommand)]
+    project_command: ProjectCommand,
+}
+
+#[derive(Debug, Parser)]
+#[command(about = "Unregister project and optionally reclaim cache exclusively used by it")]
+pub(crate) struct Remove {
+    #[arg(
+        long = "dry-run",
+        short = 'n',
+        help = "Run command in dry run mode to see what would be done"
+    )]
+    dry_run: bool,
+    #[arg(
+        help = "Path or directory name of project present in registered directory",
+        value_name = "name|path"
+    )]
+    project: String,
+    #[arg(
+        long = "reclaim",
+        short = 'r',
+        help = "Remove registry & git crates and target directory which are exclusively used by \
+                project"
+    )]
+    reclaim: bool,
+}
+
+impl Project {
+    pub(super) fn run(
+        &self,
+        config_file: &mut ConfigFile,
+        crate_list: &CrateList,
+        crate_detail: &CrateDetail,
+        registry_crates_location: &mut RegistryDir,
+        git_crates_location: &GitDir,
+        stats_file: &mut StatsFile,
+    ) -> Result<()> {
+        match &self.project_command {
+            ProjectCommand::Remove(remove) => {
+                remove.run(
+                    config_file,
+                    crate_list,
+                    crate_detail,
+                    registry_crates_location,
+                    git_crates_location,
+                    stats_file,
+                )
+            }
+        }
+    }
+}
+
+impl Remove {
+    fn run(
+        &self,
+        config_file: &mut ConfigFile,
+        crate_list: &CrateList,
+        crate_detail: &CrateDetail,
+        registry_crates_location: &mut RegistryDir,
+        git_crates_location: &GitDir,
+        stats_file: &mut StatsFile,
+    ) -> Result<()> {
+        let dry_run = self.dry_run;
+        let project_path = crate_list.resolve_project(&self.project)?;
+
+        if self.reclaim {
+            // exclusive crates needs to be calculated before project is unregistered
+            let (exclusive_registry, exclusive_git) =
+                crate_list.project_exclusive_crates(&project_path)?;
+            // target directory configured outside of project such as absolute
+            // CARGO_TARGET_DIR is shared by other projects so it is never reclaimed
+            let target_dir = project_target_dir(&project_path);
+            let target_size = target_dir
+                .as_ref()
+                .map_or(0, |target_dir| get_size(target_dir).unwrap_or(0));
+            crate_list_type(&exclusive_registry, "REGISTRY EXCLUSIVE CRATE");
+            crate_list_type(&exclusive_git, "GIT EXCLUSIVE CRATE");
+            if let Some(target_dir) = &target_dir {
+                human_println!(
+                    "Target directory {} occupies {}",
+                    target_dir.display(),
+                    convert_pretty(target_size)
+                );
+            } else {
+                human_println!(
+                    "{}",
+                    "WARNING: target directory is outside of project and may be shared so it is \
+                     not removed"
+                        .yellow()
+                );
+            }
+            if dry_run || ask_confirmation("Do you want to remove above crates and target?")? {
+                let (registry_sized_cleaned, total_registry_crate_removed) = clean_registry(
+                    registry_crates_location,
+                    &exclusive_registry,
+                    crate_detail,
+                    stats_file,
+                    "project",
+                    dry_run,
+                )?;
+                let (git_sized_cleaned, total_git_crate_removed) = clean_git(
+                    git_crates_location,
+                    &exclusive_git,
+                    stats_file,
+                    "project",
+                    dry_run,
+                );
+                let mut target_size_cleaned = 0;
+                if let Some(target_dir) = target_dir.filter(|target_dir| target_dir.exists()) {
+                    if delete_folder(&target_dir, dry_run)? {
+                        target_size_cleaned = target_size;
+                        stats_file.record("target", "project", target_size, 1, dry_run);
+                    }
+                }
+                human_println!(
+                    "{}",
+                    format!(
+                        "{} crates and target directory exclusively used by project removed which \
+                         had occupied {}",
+                        total_git_crate_removed + total_registry_crate_removed,
+                        convert_pretty(
+                            git_sized_cleaned + registry_sized_cleaned + target_size_cleaned
+                        )
+                    )
+                    .blue()
+                );
+            }
+        }
+
+        // unregister project if it is directly registered in config file
+        let registered_directory = config_file
+            .directory()
+            .iter()
+            .find(|directory| {
+                std::path::Path::new(directory)
+                    .canonicalize()
+                    .is_ok_and(|directory| directory == project_path)
+            })
+            .cloned();
+        if let Some(directory) = registered_directory {
+            config_file.remove_directory(&directory, dry_run, true)?;
+        } else {
+            human_println!(
+                "{}",
+                format!(
+                    "WARNING: {} is not directly registered so it will still be scanned as part \
+                     of registered directory",
+                    project_path.display()
+                )
+                .yellow()
+            );
+        }
+        Ok(())
+    }
+}
+
+// target directory of project when it is present inside project directory
+fn project_target_dir(project_path: &Path) -> Option<PathBuf> {
+    let target_dir_name = PathBuf::from(target_dir_name());
+    let is_inside = target_dir_name
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    is_inside.then(|| project_path.join(target_dir_name))
+}
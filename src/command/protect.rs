@@ -0,0 +1,49 @@
+use anyhow::Result;
+use clap::Parser;
+use owo_colors::OwoColorize;
+
+use crate::config_file::ConfigFile;
+
+#[derive(Debug, Parser)]
+#[command(about = "Protect crates so they are never removed by clean and wipe operations")]
+pub(crate) struct Protect {
+    #[arg(
+        help = "Crate name or name@version to protect. List protected crates when not provided",
+        value_name = "crate"
+    )]
+    crates: Vec<String>,
+    #[arg(
+        long = "dry-run",
+        short = 'n',
+        help = "Run command in dry run mode to see what would be done"
+    )]
+    dry_run: bool,
+    #[arg(
+        long = "remove",
+        short = 'r',
+        help = "Remove crates from protect list instead of adding"
+    )]
+    remove: bool,
+}
+
+impl Protect {
+    pub(super) fn run(&self, config_file: &mut ConfigFile) -> Result<()> {
+        if self.crates.is_empty() {
+            if config_file.protect().is_empty() {
+                human_println!("{}", "No crate is protected".yellow());
+            }
+            for entry in config_file.protect() {
+                human_println!("{entry}");
+            }
+            return Ok(());
+        }
+        for entry in &self.crates {
+            if self.remove {
+                config_file.remove_protect(entry, self.dry_run, true)?;
+            } else {
+                config_file.add_protect(entry, self.dry_run, true)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+use anyhow::Result;
+use clap::Parser;
+use owo_colors::OwoColorize;
+
+use crate::dir_path::DirPath;
+use crate::list_crate::CrateList;
+use crate::utils::{convert_pretty, crate_list_type, list_entry_sizes, print_dash, query_print};
+
+#[derive(Debug, Parser)]
+#[command(
+    about = "Query size information of crate",
+    arg_required_else_help = true
+)]
+pub(crate) struct Query {
+    #[arg(
+        help = "Name of crate. Version can be also provided in form of name-version. Glob pattern \
+                such as serde* is also supported"
+    )]
+    crate_name: String,
+    #[arg(
+        long = "du",
+        help = "Show largest files and sub directories present inside crate source and checkout \
+                directory"
+    )]
+    disk_usage: bool,
+    #[arg(
+        long = "top",
+        short = 't',
+        help = "Number of largest files and sub directories to show",
+        value_name = "number",
+        default_value_t = 10
+    )]
+    top: usize,
+}
+
+impl Query {
+    pub(super) fn run(&self, dir_path: &DirPath, crate_list: &CrateList) -> Result<()> {
+        let matched_crates = crate_list
+            .installed_registry()
+            .iter()
+            .chain(crate_list.installed_git())
+            .filter(|crate_metadata| crate_metadata.matches(&self.crate_name))
+            .cloned()
+            .collect::<Vec<_>>();
+        if matched_crates.is_empty() {
+            human_println!(
+                "{}",
+                format!("No crate matching {} is installed", self.crate_name).red()
+            );
+            return Ok(());
+        }
+        crate_list_type(&matched_crates, "MATCHED CRATE");
+
+        if self.disk_usage {
+            for crate_metadata in &matched_crates {
+                for path in crate_metadata.path() {
+                    // archive file and git db do not contain browsable sources
+                    if !path.is_dir() || path.starts_with(dir_path.db_dir()) {
+                        continue;
+                    }
+                    human_println!("{}", path.display().bold());
+                    let mut entries = list_entry_sizes(path)?;
+                    entries.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+                    for (entry, size) in entries.iter().take(self.top) {
+                        let relative_path = entry.strip_prefix(path).unwrap_or(entry);
+                        query_print(
+                            &format!("   {}", relative_path.display()),
+                            &convert_pretty(*size),
+                        );
+                    }
+                    print_dash(crate::utils::query_full_width());
+                }
+            }
+        }
+        Ok(())
+    }
+}
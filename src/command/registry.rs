@@ -1,15 +1,27 @@
+use std::collections::BTreeMap;
 use std::io::Write;
 use std::path::Path;
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 use clap::Parser;
 use owo_colors::OwoColorize;
 
+use crate::command::OutputFormat;
 use crate::crate_detail::{CrateDetail, CrateMetaData};
 use crate::dir_path::DirPath;
+use crate::event::{Event, emit};
+use crate::index_dir::{IndexDir, list_index_dirs, obsolete_git_index};
 use crate::list_crate::CrateList;
+use crate::network::{DEFAULT_TIMEOUT, check_reachability};
+use crate::registries::Registries;
 use crate::registry_dir::RegistryDir;
-use crate::utils::{convert_pretty, get_size, print_dash, query_print, show_top_number_crates};
+use crate::report::{QueryReport, print_json, query_registry_report};
+use crate::stats_file::StatsFile;
+use crate::utils::{
+    convert_pretty, delete_folder, disk_usage_enabled, get_size, parse_duration, print_dash,
+    query_print, show_top_number_crates,
+};
 
 #[derive(Debug, Parser)]
 #[command(
@@ -20,12 +32,37 @@ use crate::utils::{convert_pretty, get_size, print_dash, query_print, show_top_n
 pub(crate) struct Registry {
     #[arg(long = "all", short = 'a', help = "Clean up all registry crates")]
     all: bool,
+    #[arg(
+        long = "check",
+        help = "Check reachability of registries and list crates which cannot be verified because \
+                their registry is unreachable"
+    )]
+    check: bool,
+    #[arg(
+        long = "deprecated",
+        help = "Clean deprecated registry crates which are renamed or superseded by other crate"
+    )]
+    deprecated: bool,
     #[arg(
         long = "dry-run",
         short = 'n',
         help = "Run command in dry run mode to see what would be done"
     )]
     dry_run: bool,
+    #[arg(
+        long = "format",
+        short = 'f',
+        value_enum,
+        default_value_t = OutputFormat::Table,
+        help = "Output format of query"
+    )]
+    format: OutputFormat,
+    #[arg(
+        long = "keep-versions",
+        help = "Clean registry crates except newest provided number of versions of each crate",
+        value_name = "number"
+    )]
+    keep_versions: Option<usize>,
     #[arg(
         long = "light",
         short = 'l',
@@ -33,6 +70,11 @@ pub(crate) struct Registry {
                 future compilation"
     )]
     light_cleanup: bool,
+    #[arg(
+        long = "obsolete-git-index",
+        help = "Remove crates.io git index clone left over after migration to sparse index"
+    )]
+    obsolete_git_index: bool,
     #[arg(long = "old", short = 'o', help = "Clean old registry cache crates")]
     old: bool,
     #[arg(
@@ -55,6 +97,21 @@ pub(crate) struct Registry {
         help = "Return size of different .cargo/registry cache folders"
     )]
     query: bool,
+    #[arg(
+        long = "remove",
+        short = 'r',
+        help = "Remove registry crates matching name, name-version or glob pattern such as serde*",
+        value_name = "crate"
+    )]
+    remove: Option<Vec<String>>,
+    #[arg(
+        long = "stale-index-cache",
+        help = "Remove sparse index cache files which are not modified within duration such as \
+                30d. Supported units are s, m, h, d and w",
+        value_name = "duration",
+        value_parser = parse_duration
+    )]
+    stale_index_cache: Option<Duration>,
     #[arg(
         long = "top",
         short = 't',
@@ -62,32 +119,131 @@ pub(crate) struct Registry {
         value_name = "number"
     )]
     top: Option<usize>,
+    #[arg(
+        long = "yanked",
+        help = "Clean registry crates whose cached version is yanked in registry index"
+    )]
+    yanked: bool,
 }
 
 impl Registry {
-    #[allow(clippy::too_many_lines)]
+    #[allow(clippy::too_many_arguments, clippy::too_many_lines)]
     pub(super) fn run(
         &self,
         dir_path: &DirPath,
         crate_list: &CrateList,
         crate_detail: &CrateDetail,
+        registries: &Registries,
         registry_crates_location: &mut RegistryDir,
+        stats_file: &mut StatsFile,
         directory_is_empty: bool,
     ) -> Result<()> {
         let dry_run = self.dry_run;
         if self.light_cleanup {
+            let src_size = get_size(dir_path.src_dir()).unwrap_or(0);
             let light_cleanup_success =
                 light_cleanup_registry(dir_path.src_dir(), dir_path.index_dir(), dry_run);
-            if !light_cleanup_success {
-                println!("Failed to delete some folder during light cleanup");
+            if light_cleanup_success {
+                stats_file.record("registry", "light", src_size, 1, dry_run);
+            } else {
+                emit(&Event::Error {
+                    message: String::from("Failed to delete some folder during light cleanup"),
+                });
+                human_println!("Failed to delete some folder during light cleanup");
+            }
+        }
+        if self.obsolete_git_index || self.stale_index_cache.is_some() {
+            let index_dirs = list_index_dirs(dir_path.index_dir())?;
+            if self.obsolete_git_index {
+                clean_obsolete_git_index(dir_path.cargo_home(), &index_dirs, stats_file, dry_run)?;
+            }
+            if let Some(older_than) = self.stale_index_cache {
+                clean_stale_index_cache(&index_dirs, older_than, stats_file, dry_run)?;
             }
         }
         if let Some(number) = self.top {
             top_crates_registry(crate_detail, number);
         }
+        if self.check {
+            check_registry(crate_list);
+        }
         if self.query {
-            let final_size = query_size_registry(dir_path, crate_list, crate_detail);
-            query_print("Total size", &convert_pretty(final_size));
+            if self.format == OutputFormat::Json {
+                print_json(&QueryReport::new(vec![query_registry_report(
+                    dir_path,
+                    crate_list,
+                    crate_detail,
+                )]))?;
+            } else {
+                let final_size =
+                    query_size_registry(dir_path, crate_list, crate_detail, registries);
+                query_print("Total size", &convert_pretty(final_size));
+                if disk_usage_enabled() {
+                    super::query_disk_usage(&QueryReport::new(vec![query_registry_report(
+                        dir_path,
+                        crate_list,
+                        crate_detail,
+                    )]));
+                }
+            }
+        }
+
+        if self.deprecated {
+            let (sized_cleaned, total_crate_removed) = clean_registry(
+                registry_crates_location,
+                crate_list.deprecated_registry(),
+                crate_detail,
+                stats_file,
+                "deprecated",
+                dry_run,
+            )?;
+            human_println!(
+                "{}",
+                format!(
+                    "{total_crate_removed} deprecated crates removed which had occupied {}",
+                    convert_pretty(sized_cleaned)
+                )
+                .blue()
+            );
+        }
+
+        if self.yanked {
+            let (sized_cleaned, total_crate_removed) = clean_registry(
+                registry_crates_location,
+                crate_list.yanked_registry(),
+                crate_detail,
+                stats_file,
+                "yanked",
+                dry_run,
+            )?;
+            human_println!(
+                "{}",
+                format!(
+                    "{total_crate_removed} yanked crates removed which had occupied {}",
+                    convert_pretty(sized_cleaned)
+                )
+                .blue()
+            );
+        }
+
+        if let Some(keep) = self.keep_versions {
+            let (sized_cleaned, total_crate_removed) = clean_registry(
+                registry_crates_location,
+                &crate_list.list_excess_version_registry(keep),
+                crate_detail,
+                stats_file,
+                "keep-versions",
+                dry_run,
+            )?;
+            human_println!(
+                "{}",
+                format!(
+                    "{total_crate_removed} crates older than newest {keep} versions removed which \
+                     had occupied {}",
+                    convert_pretty(sized_cleaned)
+                )
+                .blue()
+            );
         }
 
         if self.old {
@@ -95,9 +251,11 @@ impl Registry {
                 registry_crates_location,
                 crate_list.old_registry(),
                 crate_detail,
+                stats_file,
+                "old",
                 dry_run,
             )?;
-            println!(
+            human_println!(
                 "{}",
                 format!(
                     "{total_crate_removed} old crates removed which had occupied {}",
@@ -115,9 +273,9 @@ impl Registry {
                                     init' to initialize current directory as rust project \
                                     directory or pass cargo trim set -d <directory> for setting \
                                     rust project directory";
-                println!("{}", warning_text.yellow());
+                human_println!("{}", warning_text.yellow());
                 let mut input = String::new();
-                print!("Do you want to continue? (y/N) ");
+                human_print!("Do you want to continue? (y/N) ");
                 std::io::stdout()
                     .flush()
                     .context("failed to flush output stream")?;
@@ -134,10 +292,12 @@ impl Registry {
                 registry_crates_location,
                 &crate_list.list_old_orphan_registry(),
                 crate_detail,
+                stats_file,
+                "old-orphan",
                 dry_run,
             )?;
 
-            println!(
+            human_println!(
                 "{}",
                 format!(
                     "{total_crate_removed} crates which are both old and orphan crate removed \
@@ -156,9 +316,9 @@ impl Registry {
                                     trim init' to initialize current directory as rust project \
                                     directory or pass cargo trim set -d <directory> for setting \
                                     rust project directory";
-                println!("{}", warning_text.yellow());
+                human_println!("{}", warning_text.yellow());
                 let mut input = String::new();
-                print!("Do you want to continue? (y/N) ");
+                human_print!("Do you want to continue? (y/N) ");
                 std::io::stdout()
                     .flush()
                     .context("failed to flush output stream")?;
@@ -175,10 +335,12 @@ impl Registry {
                 registry_crates_location,
                 crate_list.orphan_registry(),
                 crate_detail,
+                stats_file,
+                "orphan",
                 dry_run,
             )?;
 
-            println!(
+            human_println!(
                 "{}",
                 format!(
                     "{total_crate_removed} orphan crates removed which had occupied {}",
@@ -193,9 +355,11 @@ impl Registry {
                 registry_crates_location,
                 crate_list.installed_registry(),
                 crate_detail,
+                stats_file,
+                "all",
                 dry_run,
             )?;
-            println!(
+            human_println!(
                 "{}",
                 format!(
                     "Total size of {total_crate_removed} crates removed :- {}",
@@ -205,6 +369,35 @@ impl Registry {
             );
         }
 
+        if let Some(patterns) = &self.remove {
+            let matched_crates = crate_list
+                .installed_registry()
+                .iter()
+                .filter(|crate_metadata| {
+                    patterns
+                        .iter()
+                        .any(|pattern| crate_metadata.matches(pattern))
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            let (sized_cleaned, total_crate_removed) = clean_registry(
+                registry_crates_location,
+                &matched_crates,
+                crate_detail,
+                stats_file,
+                "remove",
+                dry_run,
+            )?;
+            human_println!(
+                "{}",
+                format!(
+                    "{total_crate_removed} matched crates removed which had occupied {}",
+                    convert_pretty(sized_cleaned)
+                )
+                .blue()
+            );
+        }
+
         Ok(())
     }
 }
@@ -215,13 +408,80 @@ pub(super) fn light_cleanup_registry(src_dir: &Path, index_dir: &Path, dry_run:
     let mut light_cleanup_success = true;
     // delete src dir
     light_cleanup_success =
-        crate::utils::delete_folder(src_dir, dry_run).is_ok() && light_cleanup_success;
+        matches!(crate::utils::delete_folder(src_dir, dry_run), Ok(true)) && light_cleanup_success;
     // Delete out .cache folder also
     light_cleanup_success =
         crate::utils::delete_index_cache(index_dir, dry_run).is_ok() && light_cleanup_success;
     light_cleanup_success
 }
 
+// Remove crates.io git index clone which is replaced by sparse index
+fn clean_obsolete_git_index(
+    cargo_home: &Path,
+    index_dirs: &[IndexDir],
+    stats_file: &mut StatsFile,
+    dry_run: bool,
+) -> Result<()> {
+    let obsolete_index = obsolete_git_index(cargo_home, index_dirs);
+    let mut size_cleaned = 0;
+    for index in &obsolete_index {
+        delete_folder(index.path(), dry_run)?;
+        size_cleaned += index.size();
+    }
+    stats_file.record(
+        "registry",
+        "obsolete-git-index",
+        size_cleaned,
+        obsolete_index.len(),
+        dry_run,
+    );
+    human_println!(
+        "{}",
+        format!(
+            "{} obsolete git index removed which had occupied {}",
+            obsolete_index.len(),
+            convert_pretty(size_cleaned)
+        )
+        .blue()
+    );
+    Ok(())
+}
+
+// Remove sparse index cache files which are not modified within duration.
+// Cargo fetches removed file again when it is required
+fn clean_stale_index_cache(
+    index_dirs: &[IndexDir],
+    older_than: Duration,
+    stats_file: &mut StatsFile,
+    dry_run: bool,
+) -> Result<()> {
+    let mut size_cleaned = 0;
+    let mut file_removed = 0;
+    for index in index_dirs.iter().filter(|index| index.is_sparse()) {
+        for (file, size) in index.stale_cache_files(older_than)? {
+            delete_folder(&file, dry_run)?;
+            size_cleaned += size;
+            file_removed += 1;
+        }
+    }
+    stats_file.record(
+        "registry",
+        "stale-index-cache",
+        size_cleaned,
+        file_removed,
+        dry_run,
+    );
+    human_println!(
+        "{}",
+        format!(
+            "{file_removed} stale sparse index cache files removed which had occupied {}",
+            convert_pretty(size_cleaned)
+        )
+        .blue()
+    );
+    Ok(())
+}
+
 // Show top registry crates
 pub(super) fn top_crates_registry(crate_detail: &CrateDetail, number: usize) {
     show_top_number_crates(
@@ -236,11 +496,54 @@ pub(super) fn top_crates_registry(crate_detail: &CrateDetail, number: usize) {
     );
 }
 
+// Check reachability of all registries in parallel and print summary of
+// unreachable registries
+pub(super) fn check_registry(crate_list: &CrateList) {
+    let mut registries = crate_list
+        .installed_registry()
+        .iter()
+        .filter_map(CrateMetaData::source)
+        .cloned()
+        .collect::<Vec<_>>();
+    registries.sort();
+    registries.dedup();
+    let reachability = check_reachability(&registries, DEFAULT_TIMEOUT);
+    let mut unverifiable_crates = 0;
+    let mut unreachable_registries = 0;
+    for registry in &registries {
+        if reachability.get(registry).copied().unwrap_or(false) {
+            human_println!("{} {registry}", "Reachable".blue());
+        } else {
+            let crate_count = crate_list
+                .installed_registry()
+                .iter()
+                .filter(|crate_metadata| crate_metadata.source() == Some(registry))
+                .count();
+            human_println!(
+                "{} {registry} ({crate_count} crates unverifiable)",
+                "Unreachable".red()
+            );
+            unreachable_registries += 1;
+            unverifiable_crates += crate_count;
+        }
+    }
+    human_println!(
+        "{}",
+        format!(
+            "{unreachable_registries} of {} registries unreachable, {unverifiable_crates} crates \
+             unverifiable",
+            registries.len()
+        )
+        .blue()
+    );
+}
+
 // Query size of registry
 pub(super) fn query_size_registry(
     dir_path: &DirPath,
     crate_list: &CrateList,
     crate_detail: &CrateDetail,
+    registries: &Registries,
 ) -> u64 {
     let registry_dir_size = get_size(dir_path.registry_dir()).unwrap_or(0);
     query_print(
@@ -261,6 +564,20 @@ pub(super) fn query_size_registry(
         "   \u{251c} Size of .cargo/registry/index folder",
         &convert_pretty(get_size(dir_path.index_dir()).unwrap_or(0_u64)),
     );
+    // sparse and git index have different layout so they are reported separately
+    let index_dirs = list_index_dirs(dir_path.index_dir()).unwrap_or_default();
+    for (kind, sparse) in [("sparse", true), ("git", false)] {
+        let kind_index = index_dirs
+            .iter()
+            .filter(|index| index.is_sparse() == sparse)
+            .collect::<Vec<_>>();
+        if !kind_index.is_empty() {
+            query_print(
+                &format!("   \u{2502}     Size of {} {kind} index", kind_index.len()),
+                &convert_pretty(kind_index.iter().map(|index| index.size()).sum()),
+            );
+        }
+    }
     query_print(
         &format!(
             "   \u{2514} Size of {} .cargo/registry/src folder",
@@ -268,6 +585,21 @@ pub(super) fn query_size_registry(
         ),
         &convert_pretty(get_size(dir_path.src_dir()).unwrap_or(0_u64)),
     );
+    // crates of each registry is stored in its own sub folder of cache and src
+    let mut registry_totals: BTreeMap<String, (usize, u64)> = BTreeMap::new();
+    for crate_metadata in crate_list.installed_registry() {
+        if let Some(source) = crate_metadata.source() {
+            let total = registry_totals.entry(registries.name(source)).or_default();
+            total.0 += 1;
+            total.1 += crate_metadata.size();
+        }
+    }
+    for (name, (count, size)) in registry_totals {
+        query_print(
+            &format!("   Size of {count} crates of {name} registry"),
+            &convert_pretty(size),
+        );
+    }
     print_dash(crate::utils::query_full_width());
     registry_dir_size
 }
@@ -277,7 +609,12 @@ pub(super) fn clean_registry(
     registry_crates_location: &mut RegistryDir,
     crate_metadata_list: &[CrateMetaData],
     crate_detail: &CrateDetail,
+    stats_file: &mut StatsFile,
+    reason: &str,
     dry_run: bool,
 ) -> Result<(u64, usize)> {
-    registry_crates_location.remove_crate_list(crate_detail, crate_metadata_list, dry_run)
+    let (size_cleaned, crate_removed) =
+        registry_crates_location.remove_crate_list(crate_detail, crate_metadata_list, dry_run)?;
+    stats_file.record("registry", reason, size_cleaned, crate_removed, dry_run);
+    Ok((size_cleaned, crate_removed))
 }
@@ -0,0 +1,390 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use clap::{Parser, ValueEnum};
+use owo_colors::OwoColorize;
+
+use super::restore::fetch_crate;
+use crate::crate_detail::{CrateDetail, CrateMetaData};
+use crate::dir_path::DirPath;
+use crate::event::{Event, emit};
+use crate::package_lock::{lock_package_cache, unlock_package_cache};
+use crate::registries::Registries;
+use crate::restore_manifest::RestoreEntry;
+use crate::stats_file::StatsFile;
+use crate::utils::{convert_pretty, delete_folder, get_size};
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum MismatchFix {
+    Delete,
+    Download,
+}
+
+#[derive(Debug, Parser)]
+#[command(
+    about = "Remove temporary files and partial downloads left behind by interrupted cargo"
+)]
+pub(crate) struct Repair {
+    #[arg(
+        long = "dry-run",
+        short = 'n',
+        help = "Run command in dry run mode to see what would be done"
+    )]
+    dry_run: bool,
+    #[arg(
+        long = "fix-mismatched",
+        short = 'f',
+        value_enum,
+        help = "Fix registry crates whose archive is missing. Delete removes extracted source \
+                while download fetches missing archive again",
+        value_name = "action"
+    )]
+    fix_mismatched: Option<MismatchFix>,
+}
+
+impl Repair {
+    pub(super) fn run(
+        &self,
+        dir_path: &DirPath,
+        crate_detail: &CrateDetail,
+        registries: &Registries,
+        stats_file: &mut StatsFile,
+    ) -> Result<()> {
+        // lock is held while leftovers are removed so cargo started in meantime
+        // waits instead of writing to removed folder. Lock files themselves are
+        // never stale since cargo only takes advisory lock on them
+        lock_package_cache()?;
+        let leftovers = list_leftovers(dir_path);
+        if leftovers.is_empty() {
+            human_println!("{}", "No leftover of interrupted cargo found".blue());
+        }
+        let result = self.remove_leftovers(&leftovers, stats_file);
+        unlock_package_cache();
+        result?;
+        // partially extracted source is already handled as leftover
+        let leftover_paths = leftovers
+            .iter()
+            .map(|(path, _)| path)
+            .collect::<HashSet<_>>();
+        let is_leftover = |crate_metadata: &CrateMetaData| {
+            crate_metadata
+                .path()
+                .iter()
+                .all(|path| leftover_paths.contains(path))
+        };
+        let source_only = missing_pair(
+            crate_detail.registry_crates_source(),
+            crate_detail.registry_crates_archive(),
+        )
+        .into_iter()
+        .filter(|crate_metadata| !is_leftover(crate_metadata))
+        .collect::<Vec<_>>();
+        let archive_only = missing_pair(
+            crate_detail.registry_crates_archive(),
+            crate_detail.registry_crates_source(),
+        )
+        .into_iter()
+        .filter(|crate_metadata| !is_leftover(crate_metadata))
+        .collect::<Vec<_>>();
+        self.handle_mismatched(
+            dir_path,
+            crate_detail,
+            registries,
+            &source_only,
+            &archive_only,
+            stats_file,
+        )
+    }
+
+    // report registry crates whose source or archive is missing and apply fix
+    // if requested
+    fn handle_mismatched(
+        &self,
+        dir_path: &DirPath,
+        crate_detail: &CrateDetail,
+        registries: &Registries,
+        source_only: &[&CrateMetaData],
+        archive_only: &[&CrateMetaData],
+        stats_file: &mut StatsFile,
+    ) -> Result<()> {
+        for crate_metadata in source_only {
+            human_println!(
+                r#"{} "{}" has extracted source without archive"#,
+                "Mismatched".yellow(),
+                crate_metadata.full_name()
+            );
+        }
+        for crate_metadata in archive_only {
+            human_println!(
+                r#"{} "{}" has archive without extracted source"#,
+                "Mismatched".yellow(),
+                crate_metadata.full_name()
+            );
+        }
+        human_println!(
+            "{}",
+            format!(
+                "{} sources without archive and {} archives without source found. Archive without \
+                 source is expected after light cleanup and is extracted again by cargo when \
+                 needed",
+                source_only.len(),
+                archive_only.len()
+            )
+            .blue()
+        );
+        match self.fix_mismatched {
+            Some(MismatchFix::Delete) => {
+                // archive without source is valid state of cache so only source
+                // without archive is removed
+                let mut size_cleaned = 0;
+                let mut removed = 0;
+                for crate_metadata in source_only {
+                    let mut is_deleted = true;
+                    for path in crate_metadata.path() {
+                        is_deleted &= delete_folder(path, self.dry_run)?;
+                    }
+                    if is_deleted {
+                        size_cleaned += crate_metadata.size();
+                        removed += 1;
+                    }
+                }
+                stats_file.record(
+                    "registry",
+                    "mismatched",
+                    size_cleaned,
+                    removed,
+                    self.dry_run,
+                );
+                human_println!(
+                    "{}",
+                    format!(
+                        "{removed} mismatched crates removed which had occupied {}",
+                        convert_pretty(size_cleaned)
+                    )
+                    .blue()
+                );
+            }
+            Some(MismatchFix::Download) => {
+                for crate_metadata in source_only {
+                    self.download_archive(dir_path, crate_detail, registries, crate_metadata);
+                }
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    // download missing archive of registry crate whose source is extracted
+    fn download_archive(
+        &self,
+        dir_path: &DirPath,
+        crate_detail: &CrateDetail,
+        registries: &Registries,
+        crate_metadata: &CrateMetaData,
+    ) {
+        let Some(crate_id) = crate_metadata.crate_id() else {
+            return;
+        };
+        let full_name = crate_metadata.full_name();
+        if self.dry_run {
+            human_println!(
+                "{} {full_name} from {}",
+                "Would download".yellow(),
+                registries.name(crate_id.registry())
+            );
+            return;
+        }
+        let entry = RestoreEntry::new(
+            crate_id.name(),
+            &crate_id.version().to_string(),
+            None,
+            crate_id.registry(),
+        );
+        match fetch_crate(dir_path, crate_detail, &entry) {
+            Ok(()) => human_println!("{} {full_name}", "Downloaded".green()),
+            Err(err) => {
+                let message = format!("Failed to download {full_name}: {err:#}");
+                emit(&Event::Error {
+                    message: message.clone(),
+                });
+                human_println!("{}", message.red());
+            }
+        }
+    }
+
+    // remove leftovers and record removed size for each reason
+    fn remove_leftovers(
+        &self,
+        leftovers: &[(PathBuf, &'static str)],
+        stats_file: &mut StatsFile,
+    ) -> Result<()> {
+        let mut reasons = leftovers
+            .iter()
+            .map(|(_, reason)| *reason)
+            .collect::<Vec<_>>();
+        reasons.sort_unstable();
+        reasons.dedup();
+        for reason in reasons {
+            let mut size_cleaned = 0;
+            let mut removed = 0;
+            for (path, _) in leftovers.iter().filter(|(_, other)| *other == reason) {
+                human_println!(r#"{} {reason} "{}""#, "Found".yellow(), path.display());
+                let size = get_size(path).unwrap_or(0);
+                if delete_folder(path, self.dry_run)? {
+                    size_cleaned += size;
+                    removed += 1;
+                }
+            }
+            stats_file.record("repair", reason, size_cleaned, removed, self.dry_run);
+            human_println!(
+                "{}",
+                format!(
+                    "{removed} {reason} entries removed which had occupied {}",
+                    convert_pretty(size_cleaned)
+                )
+                .blue()
+            );
+        }
+        Ok(())
+    }
+}
+
+// list temporary files, partial downloads and partially extracted folders of
+// registry and git cache along with reason of removal
+fn list_leftovers(dir_path: &DirPath) -> Vec<(PathBuf, &'static str)> {
+    let mut leftovers = Vec::new();
+    for dir in [
+        dir_path.cache_dir(),
+        dir_path.src_dir(),
+        dir_path.index_dir(),
+        dir_path.checkout_dir(),
+        dir_path.db_dir(),
+    ] {
+        for path in child_paths(dir) {
+            if is_temporary(&path) {
+                leftovers.push((path, "temporary"));
+            }
+        }
+    }
+    // index cache is written by creating temporary file inside it
+    for index in child_paths(dir_path.index_dir()) {
+        list_temporary(&index.join(".cache"), &mut leftovers);
+    }
+    let content_dirs = [
+        dir_path.cache_dir(),
+        dir_path.src_dir(),
+        dir_path.checkout_dir(),
+    ];
+    for parent in content_dirs.into_iter().flat_map(|dir| child_paths(dir)) {
+        if is_temporary(&parent) {
+            continue;
+        }
+        for path in child_paths(&parent) {
+            if let Some(reason) = leftover_reason(&path) {
+                leftovers.push((path, reason));
+            }
+        }
+    }
+    leftovers.sort();
+    leftovers
+}
+
+// reason why crate archive or extracted folder is leftover of interrupted
+// cargo. Extracted registry crate and git checkout are complete only after
+// cargo creates .cargo-ok file inside them
+fn leftover_reason(path: &Path) -> Option<&'static str> {
+    if is_temporary(path) {
+        Some("temporary")
+    } else if path.is_file() {
+        let is_crate = path
+            .extension()
+            .is_some_and(|extension| extension == "crate");
+        let is_empty = path.metadata().is_ok_and(|metadata| metadata.len() == 0);
+        (is_crate && is_empty).then_some("partial download")
+    } else if path.is_dir() && !path.join(".cargo-ok").exists() {
+        Some("partial extraction")
+    } else {
+        None
+    }
+}
+
+// recursively list temporary files present inside directory
+fn list_temporary(dir: &Path, leftovers: &mut Vec<(PathBuf, &'static str)>) {
+    for path in child_paths(dir) {
+        if is_temporary(&path) {
+            leftovers.push((path, "temporary"));
+        } else if path.is_dir() && !path.is_symlink() {
+            list_temporary(&path, leftovers);
+        }
+    }
+}
+
+// check if path is temporary file or folder created by cargo
+fn is_temporary(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| {
+        let name = name.to_string_lossy();
+        name.starts_with(".tmp") || name.ends_with(".tmp")
+    })
+}
+
+// list crates which are not present in other half of registry cache. Crates
+// are compared using name, version and registry
+fn missing_pair<'a>(
+    crates: &'a HashSet<CrateMetaData>,
+    others: &HashSet<CrateMetaData>,
+) -> Vec<&'a CrateMetaData> {
+    let other_ids = others
+        .iter()
+        .filter_map(CrateMetaData::crate_id)
+        .collect::<HashSet<_>>();
+    let mut missing = crates
+        .iter()
+        .filter(|crate_metadata| {
+            crate_metadata
+                .crate_id()
+                .is_some_and(|crate_id| !other_ids.contains(&crate_id))
+        })
+        .collect::<Vec<_>>();
+    missing.sort();
+    missing
+}
+
+// list path of every entry of directory. Empty list is returned when directory
+// cannot be read
+fn child_paths(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir).map_or_else(
+        |_| Vec::new(),
+        |entries| entries.flatten().map(|entry| entry.path()).collect(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::leftover_reason;
+
+    #[test]
+    fn test_leftover_reason() {
+        let dir = std::env::temp_dir().join(format!("cargo_trim_repair_{}", std::process::id()));
+        let complete = dir.join("complete-0.1.0");
+        let partial = dir.join("partial-0.1.0");
+        fs::create_dir_all(&complete).unwrap();
+        fs::create_dir_all(&partial).unwrap();
+        fs::write(complete.join(".cargo-ok"), "").unwrap();
+        fs::write(dir.join("empty-0.1.0.crate"), "").unwrap();
+        fs::write(dir.join("full-0.1.0.crate"), "content").unwrap();
+        fs::write(dir.join(".tmpa1b2c3"), "content").unwrap();
+        assert_eq!(leftover_reason(&complete), None);
+        assert_eq!(leftover_reason(&partial), Some("partial extraction"));
+        assert_eq!(
+            leftover_reason(&dir.join("empty-0.1.0.crate")),
+            Some("partial download")
+        );
+        assert_eq!(leftover_reason(&dir.join("full-0.1.0.crate")), None);
+        assert_eq!(leftover_reason(&dir.join(".tmpa1b2c3")), Some("temporary"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,272 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use owo_colors::OwoColorize;
+
+use crate::crate_detail::CrateDetail;
+use crate::dir_path::DirPath;
+use crate::event::{Event, emit};
+use crate::package_lock::lock_package_cache;
+use crate::registries::Registries;
+use crate::registry_dir::index_cache_file;
+use crate::restore_manifest::{RestoreEntry, RestoreManifest, index_checksum};
+use crate::sha256::sha256_file;
+use crate::utils::create_private_temp_dir;
+
+#[derive(Debug, Parser)]
+#[command(about = "Download registry crates removed by last trim again using restore manifest")]
+pub(crate) struct Restore {
+    #[arg(
+        long = "dry-run",
+        short = 'n',
+        help = "List crates which would be restored without downloading them"
+    )]
+    dry_run: bool,
+}
+
+impl Restore {
+    pub(super) fn run(
+        &self,
+        dir_path: &DirPath,
+        crate_detail: &CrateDetail,
+        registries: &Registries,
+    ) -> Result<()> {
+        let mut manifest = RestoreManifest::init(dir_path.restore_file())?;
+        let missing = manifest
+            .removed()
+            .iter()
+            .filter(|entry| !is_present(crate_detail, entry))
+            .cloned()
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            human_println!(
+                "{}",
+                "No removed registry crate is left to restore".yellow()
+            );
+            return Ok(());
+        }
+        human_println!(
+            "Restoring {} crates removed by trim at {}",
+            missing.len(),
+            manifest.taken()
+        );
+        let mut failed = Vec::new();
+        for entry in &missing {
+            let full_name = format!("{}-{}", entry.name(), entry.version());
+            if self.dry_run {
+                human_println!(
+                    "{} {full_name} from {} (checksum {})",
+                    "Would restore".yellow(),
+                    registries.name(entry.registry()),
+                    entry.checksum().map_or("unknown", String::as_str)
+                );
+                continue;
+            }
+            match fetch_crate(dir_path, crate_detail, entry) {
+                Ok(()) => human_println!("{} {full_name}", "Restored".green()),
+                Err(err) => {
+                    let message = format!("Failed to restore {full_name}: {err:#}");
+                    emit(&Event::Error {
+                        message: message.clone(),
+                    });
+                    human_println!("{}", message.red());
+                    failed.push(entry.clone());
+                }
+            }
+        }
+        if !self.dry_run {
+            // keep only crates which failed to restore so restore can be retried
+            manifest.retain(|entry| failed.contains(entry))?;
+        }
+        Ok(())
+    }
+}
+
+// check if exact version of crate is already present in registry cache
+fn is_present(crate_detail: &CrateDetail, entry: &RestoreEntry) -> bool {
+    crate_detail
+        .registry_crates_archive()
+        .iter()
+        .any(|crate_metadata| {
+            crate_metadata.crate_id().is_some_and(|crate_id| {
+                crate_id.name() == entry.name()
+                    && &crate_id.version().to_string() == entry.version()
+                    && crate_id.registry() == entry.registry()
+            })
+        })
+}
+
+// download .crate file of entry from download url of its registry into cache
+// directory. Download is verified against checksum recorded in restore
+// manifest or index cache before it is placed in cache directory
+pub(super) fn fetch_crate(
+    dir_path: &DirPath,
+    crate_detail: &CrateDetail,
+    entry: &RestoreEntry,
+) -> Result<()> {
+    let dir_name = crate_detail
+        .registry_dir_name(entry.registry())
+        .context("Registry of crate is not present in cargo home")?;
+    let index = dir_path.index_dir().join(dir_name);
+    let checksum = entry
+        .checksum()
+        .cloned()
+        .or_else(|| {
+            index_checksum(
+                &index_cache_file(&index.join(".cache"), entry.name()),
+                entry.version(),
+            )
+        })
+        .context("Checksum of crate is unknown so download cannot be verified")?;
+    let url = download_url(&registry_dl(&index)?, entry, &checksum);
+    let temp_dir = create_private_temp_dir("cargo-trim-restore")?;
+    let result = download_verified(
+        &url,
+        &temp_dir,
+        &checksum,
+        &dir_path.cache_dir().join(dir_name),
+        entry,
+    );
+    // temporary directory is removed even if download fails
+    let _ = fs::remove_dir_all(&temp_dir);
+    result
+}
+
+// download crate archive into temporary directory and move it to cache
+// directory once its checksum matches
+fn download_verified(
+    url: &str,
+    temp_dir: &Path,
+    checksum: &str,
+    cache_dir: &Path,
+    entry: &RestoreEntry,
+) -> Result<()> {
+    let archive_name = format!("{}-{}.crate", entry.name(), entry.version());
+    let download = temp_dir.join(&archive_name);
+    let output = Command::new("curl")
+        .args([
+            "--fail",
+            "--silent",
+            "--show-error",
+            "--location",
+            "--output",
+        ])
+        .arg(&download)
+        .arg(url)
+        .output()
+        .context("Failed to run curl")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Failed to download {url}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let actual = sha256_file(&download)?;
+    anyhow::ensure!(
+        actual == checksum,
+        "Checksum {actual} of downloaded archive do not match recorded checksum {checksum}"
+    );
+    // archive is moved to cache directory under package cache lock so cargo do
+    // not read partially written archive
+    lock_package_cache()?;
+    fs::create_dir_all(cache_dir).context("Failed to create registry cache directory")?;
+    let partial = cache_dir.join(format!(".tmp-{archive_name}"));
+    fs::copy(&download, &partial).context("Failed to copy archive to registry cache")?;
+    fs::rename(&partial, cache_dir.join(archive_name))
+        .context("Failed to move archive to registry cache")?;
+    Ok(())
+}
+
+// read download url of registry from config.json of index. Sparse index store
+// config.json directly while git index only keep it inside fetched tree
+fn registry_dl(index: &Path) -> Result<String> {
+    let content = if let Ok(content) = fs::read_to_string(index.join("config.json")) {
+        content
+    } else {
+        let output = Command::new("git")
+            .args(["show", "origin/HEAD:config.json"])
+            .current_dir(index)
+            .output()
+            .context("Failed to read config.json of git index")?;
+        anyhow::ensure!(
+            output.status.success(),
+            "Failed to read config.json of index {}",
+            index.display()
+        );
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+    let config = serde_json::from_str::<serde_json::Value>(&content)
+        .context("Failed to parse config.json of index")?;
+    config
+        .get("dl")
+        .and_then(serde_json::Value::as_str)
+        .map(ToString::to_string)
+        .context("Download url is not present in config.json of index")
+}
+
+// download url of crate built from dl template of registry. Template without
+// any marker is used as prefix same as cargo
+fn download_url(dl: &str, entry: &RestoreEntry, checksum: &str) -> String {
+    const MARKERS: [&str; 5] = [
+        "{crate}",
+        "{version}",
+        "{prefix}",
+        "{lowerprefix}",
+        "{sha256-checksum}",
+    ];
+    let name = entry.name();
+    if !MARKERS.iter().any(|marker| dl.contains(marker)) {
+        return format!(
+            "{}/{name}/{}/download",
+            dl.trim_end_matches('/'),
+            entry.version()
+        );
+    }
+    let prefix = match name.len() {
+        1 => String::from("1"),
+        2 => String::from("2"),
+        3 => format!("3/{}", &name[..1]),
+        _ => format!("{}/{}", &name[..2], &name[2..4]),
+    };
+    dl.replace("{crate}", name)
+        .replace("{version}", entry.version())
+        .replace("{lowerprefix}", &prefix.to_lowercase())
+        .replace("{prefix}", &prefix)
+        .replace("{sha256-checksum}", checksum)
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use url::Url;
+
+    use super::download_url;
+    use crate::restore_manifest::RestoreEntry;
+
+    #[test]
+    fn test_download_url() {
+        let registry = Url::from_str("https://index.crates.io/").unwrap();
+        let entry = RestoreEntry::new("Serde", "1.0.0", None, &registry);
+        assert_eq!(
+            download_url("https://static.crates.io/crates", &entry, "abc"),
+            "https://static.crates.io/crates/Serde/1.0.0/download"
+        );
+        assert_eq!(
+            download_url(
+                "https://example.org/{lowerprefix}/{crate}/{crate}-{version}.crate?sum={sha256-checksum}",
+                &entry,
+                "abc"
+            ),
+            "https://example.org/se/rd/Serde/Serde-1.0.0.crate?sum=abc"
+        );
+        let entry = RestoreEntry::new("Rs", "0.1.0", None, &registry);
+        assert_eq!(
+            download_url("https://example.org/{prefix}/{crate}", &entry, "abc"),
+            "https://example.org/2/Rs"
+        );
+    }
+}
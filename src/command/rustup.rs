@@ -0,0 +1,264 @@
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use owo_colors::OwoColorize;
+
+use crate::dir_path::DirPath;
+use crate::event::{Event, emit};
+use crate::freed_space::measure_before_removal;
+use crate::list_crate::CrateList;
+use crate::removal_failure::record_removal_failure;
+use crate::rustup_dir::{RustupDir, Toolchain, project_toolchains};
+use crate::stats_file::StatsFile;
+use crate::utils::{
+    convert_pretty, delete_folder, get_size, parse_duration, print_dash, query_print,
+};
+
+#[derive(Debug, Parser)]
+#[command(
+    about = "Perform operation on toolchains and download cache of rustup home directory",
+    arg_required_else_help = true
+)]
+pub(crate) struct Rustup {
+    #[arg(
+        long = "downloads",
+        short = 'd',
+        help = "Clean download cache and temporary files of rustup"
+    )]
+    downloads: bool,
+    #[arg(
+        long = "dry-run",
+        short = 'n',
+        help = "Run command in dry run mode to see what would be done"
+    )]
+    dry_run: bool,
+    #[arg(
+        long = "older-than",
+        short = 'o',
+        help = "Clean toolchains which are not modified within duration except default toolchain \
+                and toolchain pinned by override or rust-toolchain file of registered project. \
+                Supported units are s, m, h, d and w",
+        value_name = "duration",
+        value_parser = parse_duration
+    )]
+    older_than: Option<Duration>,
+    #[arg(
+        long = "query",
+        short = 'q',
+        help = "Return size of rustup toolchains and download cache along with cargo home"
+    )]
+    query: bool,
+    #[arg(
+        long = "toolchain",
+        short = 't',
+        help = "Clean toolchain with provided name such as nightly-x86_64-unknown-linux-gnu",
+        value_name = "name"
+    )]
+    toolchain: Option<Vec<String>>,
+}
+
+impl Rustup {
+    pub(super) fn run(
+        &self,
+        dir_path: &DirPath,
+        crate_list: &CrateList,
+        stats_file: &mut StatsFile,
+    ) -> Result<()> {
+        let dry_run = self.dry_run;
+        let Some(rustup_dir) = RustupDir::new()? else {
+            human_println!("{}", "Rustup home directory not found".yellow());
+            return Ok(());
+        };
+
+        if self.query {
+            query_rustup(dir_path, &rustup_dir);
+        }
+
+        if self.downloads {
+            let mut size_cleaned = 0;
+            let mut dir_removed = 0;
+            for download_dir in rustup_dir.download_dirs() {
+                if download_dir.exists() {
+                    let size = get_size(&download_dir).unwrap_or(0);
+                    if delete_folder(&download_dir, dry_run)? {
+                        size_cleaned += size;
+                        dir_removed += 1;
+                    }
+                }
+            }
+            stats_file.record("rustup", "downloads", size_cleaned, dir_removed, dry_run);
+            human_println!(
+                "{}",
+                format!(
+                    "Rustup download cache removed which had occupied {}",
+                    convert_pretty(size_cleaned)
+                )
+                .blue()
+            );
+        }
+
+        if self.toolchain.is_some() || self.older_than.is_some() {
+            let reason = if self.older_than.is_some() {
+                "old"
+            } else {
+                "toolchain"
+            };
+            let pinned_toolchains =
+                project_toolchains(crate_list.cargo_toml_location().location_path());
+            let mut size_cleaned = 0;
+            let mut toolchain_removed = 0;
+            for toolchain in rustup_dir.toolchains() {
+                if !self.need_to_be_removed(toolchain) {
+                    continue;
+                }
+                // removing default toolchain would break every cargo invocation
+                if rustup_dir.is_default(toolchain) {
+                    emit(&Event::Skipped {
+                        name: toolchain.name().clone(),
+                        reason: "default toolchain",
+                    });
+                    human_println!(
+                        "{}",
+                        format!("WARNING: Skipping default toolchain {}", toolchain.name())
+                            .yellow()
+                    );
+                    continue;
+                }
+                // toolchain pinned for directory would be installed again by
+                // next cargo invocation in that directory
+                let pinned = if rustup_dir.is_overridden(toolchain) {
+                    Some("directory override")
+                } else if pinned_toolchains.iter().any(|name| toolchain.matches(name)) {
+                    Some("rust-toolchain file")
+                } else {
+                    None
+                };
+                if let Some(reason) = pinned {
+                    emit(&Event::Skipped {
+                        name: toolchain.name().clone(),
+                        reason,
+                    });
+                    human_println!(
+                        "{}",
+                        format!(
+                            "WARNING: Skipping toolchain {} pinned by {reason}",
+                            toolchain.name()
+                        )
+                        .yellow()
+                    );
+                    continue;
+                }
+                if uninstall_toolchain(toolchain, dry_run)? {
+                    size_cleaned += toolchain.size();
+                    toolchain_removed += 1;
+                }
+            }
+            stats_file.record("rustup", reason, size_cleaned, toolchain_removed, dry_run);
+            human_println!(
+                "{}",
+                format!(
+                    "{toolchain_removed} toolchains removed which had occupied {}",
+                    convert_pretty(size_cleaned)
+                )
+                .blue()
+            );
+        }
+        Ok(())
+    }
+
+    // check if toolchain matches both name and age filter
+    fn need_to_be_removed(&self, toolchain: &Toolchain) -> bool {
+        let name_matches = self
+            .toolchain
+            .as_ref()
+            .is_none_or(|toolchains| toolchains.contains(toolchain.name()));
+        let age_matches = self.older_than.is_none_or(|older_than| {
+            toolchain.modified().is_none_or(|modified| {
+                SystemTime::now()
+                    .duration_since(modified)
+                    .is_ok_and(|age| age > older_than)
+            })
+        });
+        name_matches && age_matches
+    }
+}
+
+// uninstall toolchain using rustup so rustup metadata of toolchain is also
+// removed. Return false when rustup failed to uninstall toolchain
+fn uninstall_toolchain(toolchain: &Toolchain, dry_run: bool) -> Result<bool> {
+    if dry_run {
+        return delete_folder(toolchain.path(), dry_run);
+    }
+    measure_before_removal(toolchain.path());
+    let output = Command::new("rustup")
+        .args(["toolchain", "uninstall", toolchain.name()])
+        .output()
+        .context("Failed to run rustup toolchain uninstall")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        record_removal_failure(
+            toolchain.path(),
+            &anyhow::anyhow!(
+                "{}",
+                stderr.lines().last().unwrap_or("rustup failed").trim()
+            ),
+        );
+        return Ok(false);
+    }
+    emit(&Event::Deleted {
+        path: toolchain.path(),
+        size: toolchain.size(),
+        dry_run,
+    });
+    Ok(true)
+}
+
+// print size of rustup toolchains and download cache along with cargo home so
+// whole rust disk usage is shown in single report
+fn query_rustup(dir_path: &DirPath, rustup_dir: &RustupDir) {
+    let rustup_size = get_size(rustup_dir.path()).unwrap_or(0);
+    query_print(
+        &format!("Size of {}", rustup_dir.path().display()),
+        &convert_pretty(rustup_size),
+    );
+    for download_dir in rustup_dir.download_dirs() {
+        query_print(
+            &format!("   \u{251c} {}", download_dir.display()),
+            &convert_pretty(get_size(&download_dir).unwrap_or(0)),
+        );
+    }
+    let toolchain_count = rustup_dir.toolchains().len();
+    for (pos, toolchain) in rustup_dir.toolchains().iter().enumerate() {
+        let (branch, indent) = if pos + 1 == toolchain_count {
+            ("\u{2514}", " ")
+        } else {
+            ("\u{251c}", "\u{2502}")
+        };
+        let default = if rustup_dir.is_default(toolchain) {
+            " (default)"
+        } else {
+            ""
+        };
+        query_print(
+            &format!("   {branch} {}{default}", toolchain.name()),
+            &convert_pretty(toolchain.size()),
+        );
+        query_print(
+            &format!("   {indent}     docs"),
+            &convert_pretty(toolchain.docs_size()),
+        );
+    }
+    print_dash(crate::utils::query_full_width());
+    let cargo_home_size = get_size(dir_path.cargo_home()).unwrap_or(0);
+    query_print(
+        &format!("Size of {}", dir_path.cargo_home().display()),
+        &convert_pretty(cargo_home_size),
+    );
+    print_dash(crate::utils::query_full_width());
+    query_print(
+        "Total size of rust installation",
+        &convert_pretty(rustup_size + cargo_home_size),
+    );
+}
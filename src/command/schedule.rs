@@ -0,0 +1,378 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use owo_colors::OwoColorize;
+
+// name used for systemd unit, launchd label and windows scheduled task
+const SCHEDULE_NAME: &str = "cargo-trim";
+const LAUNCHD_LABEL: &str = "io.github.iamsauravsharma.cargo-trim";
+
+#[derive(Debug, Subcommand)]
+enum ScheduleCommand {
+    Install(Install),
+    Uninstall(Uninstall),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Interval {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+#[derive(Debug, Parser)]
+#[command(
+    about = "Install or uninstall systemd timer, launchd agent or scheduled task running cargo \
+             trim periodically"
+)]
+pub(crate) struct Schedule {
+    #[command(subcommand)]
+    schedule_command: ScheduleCommand,
+}
+
+#[derive(Debug, Parser)]
+#[command(about = "Install periodic run of cargo trim for current user")]
+pub(crate) struct Install {
+    #[arg(
+        long = "dry-run",
+        short = 'n',
+        help = "Print generated schedule entry without installing it"
+    )]
+    dry_run: bool,
+    #[arg(
+        long = "interval",
+        short = 'i',
+        value_enum,
+        default_value_t = Interval::Weekly,
+        help = "Interval between cargo trim run"
+    )]
+    interval: Interval,
+    #[arg(
+        last = true,
+        help = "Arguments passed to cargo trim on each run such as -- --profile ci. Defaults to \
+                --old-orphan",
+        value_name = "policy"
+    )]
+    policy: Vec<String>,
+}
+
+#[derive(Debug, Parser)]
+#[command(about = "Uninstall periodic run of cargo trim installed by schedule install")]
+pub(crate) struct Uninstall {
+    #[arg(
+        long = "dry-run",
+        short = 'n',
+        help = "Run command in dry run mode to see what would be done"
+    )]
+    dry_run: bool,
+}
+
+impl Schedule {
+    pub(super) fn run(&self) -> Result<()> {
+        match &self.schedule_command {
+            ScheduleCommand::Install(install) => install.run(),
+            ScheduleCommand::Uninstall(uninstall) => uninstall.run(),
+        }
+    }
+}
+
+impl Install {
+    fn run(&self) -> Result<()> {
+        let cargo_trim = std::env::current_exe().context("Failed to get cargo trim executable")?;
+        let policy = if self.policy.is_empty() {
+            vec![String::from("--old-orphan")]
+        } else {
+            self.policy.clone()
+        };
+        if cfg!(target_os = "windows") {
+            let task_command = command_line(&cargo_trim, &policy);
+            let args = schtasks_create_args(self.interval, &task_command);
+            if self.dry_run {
+                human_println!("schtasks {}", args.join(" "));
+                return Ok(());
+            }
+            run_scheduler("schtasks", &args)?;
+        } else if cfg!(target_os = "macos") {
+            let plist_file = launchd_plist_file()?;
+            let content = launchd_plist(&cargo_trim, &policy, self.interval);
+            if self.dry_run {
+                human_println!("{}\n{content}", plist_file.display().bold());
+                return Ok(());
+            }
+            write_file(&plist_file, &content)?;
+            run_scheduler("launchctl", &["load", "-w", &plist_file.to_string_lossy()])?;
+        } else {
+            let (service_file, timer_file) = systemd_unit_files()?;
+            let service = systemd_service(&cargo_trim, &policy);
+            let timer = systemd_timer(self.interval);
+            if self.dry_run {
+                human_println!("{}\n{service}", service_file.display().bold());
+                human_println!("{}\n{timer}", timer_file.display().bold());
+                return Ok(());
+            }
+            write_file(&service_file, &service)?;
+            write_file(&timer_file, &timer)?;
+            run_scheduler("systemctl", &["--user", "daemon-reload"])?;
+            run_scheduler(
+                "systemctl",
+                &[
+                    "--user",
+                    "enable",
+                    "--now",
+                    &format!("{SCHEDULE_NAME}.timer"),
+                ],
+            )?;
+        }
+        human_println!(
+            "{}",
+            format!(
+                "Installed {:?} run of cargo trim {}",
+                self.interval,
+                policy.join(" ")
+            )
+            .blue()
+        );
+        Ok(())
+    }
+}
+
+impl Uninstall {
+    fn run(&self) -> Result<()> {
+        let dry_run = self.dry_run;
+        if cfg!(target_os = "windows") {
+            let args = ["/Delete", "/TN", SCHEDULE_NAME, "/F"];
+            if dry_run {
+                human_println!("schtasks {}", args.join(" "));
+                return Ok(());
+            }
+            run_scheduler("schtasks", &args)?;
+        } else if cfg!(target_os = "macos") {
+            let plist_file = launchd_plist_file()?;
+            if dry_run {
+                human_println!("Remove {}", plist_file.display());
+                return Ok(());
+            }
+            run_scheduler(
+                "launchctl",
+                &["unload", "-w", &plist_file.to_string_lossy()],
+            )?;
+            remove_file(&plist_file)?;
+        } else {
+            let (service_file, timer_file) = systemd_unit_files()?;
+            if dry_run {
+                human_println!("Remove {}", service_file.display());
+                human_println!("Remove {}", timer_file.display());
+                return Ok(());
+            }
+            run_scheduler(
+                "systemctl",
+                &[
+                    "--user",
+                    "disable",
+                    "--now",
+                    &format!("{SCHEDULE_NAME}.timer"),
+                ],
+            )?;
+            remove_file(&service_file)?;
+            remove_file(&timer_file)?;
+            run_scheduler("systemctl", &["--user", "daemon-reload"])?;
+        }
+        human_println!("{}", "Uninstalled periodic run of cargo trim".blue());
+        Ok(())
+    }
+}
+
+// path of systemd user service and timer unit file
+fn systemd_unit_files() -> Result<(PathBuf, PathBuf)> {
+    let unit_dir = dirs_next::config_dir()
+        .context("Cannot get config directory location")?
+        .join("systemd")
+        .join("user");
+    Ok((
+        unit_dir.join(format!("{SCHEDULE_NAME}.service")),
+        unit_dir.join(format!("{SCHEDULE_NAME}.timer")),
+    ))
+}
+
+// path of launchd agent plist file of current user
+fn launchd_plist_file() -> Result<PathBuf> {
+    Ok(dirs_next::home_dir()
+        .context("Cannot get home directory location")?
+        .join("Library")
+        .join("LaunchAgents")
+        .join(format!("{LAUNCHD_LABEL}.plist")))
+}
+
+// content of systemd oneshot service running cargo trim
+fn systemd_service(cargo_trim: &Path, policy: &[String]) -> String {
+    format!(
+        "[Unit]\nDescription=Trim cargo home cache\n\n[Service]\nType=oneshot\nExecStart={}\n",
+        command_line(cargo_trim, policy)
+    )
+}
+
+// content of systemd timer triggering cargo trim service
+fn systemd_timer(interval: Interval) -> String {
+    let calendar = match interval {
+        Interval::Daily => "daily",
+        Interval::Weekly => "weekly",
+        Interval::Monthly => "monthly",
+    };
+    format!(
+        "[Unit]\nDescription=Periodically trim cargo home cache\n\n[Timer]\nOnCalendar={calendar}\
+         \nPersistent=true\n\n[Install]\nWantedBy=timers.target\n"
+    )
+}
+
+// content of launchd agent plist running cargo trim at 3 AM of interval
+fn launchd_plist(cargo_trim: &Path, policy: &[String], interval: Interval) -> String {
+    let mut lines = vec![
+        String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#),
+        String::from(
+            r#"<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">"#,
+        ),
+        String::from(r#"<plist version="1.0">"#),
+        String::from("<dict>"),
+        String::from("    <key>Label</key>"),
+        format!("    <string>{LAUNCHD_LABEL}</string>"),
+        String::from("    <key>ProgramArguments</key>"),
+        String::from("    <array>"),
+    ];
+    for arg in
+        std::iter::once(cargo_trim.to_string_lossy().to_string()).chain(policy.iter().cloned())
+    {
+        lines.push(format!("        <string>{}</string>", xml_escape(&arg)));
+    }
+    lines.push(String::from("    </array>"));
+    lines.push(String::from("    <key>StartCalendarInterval</key>"));
+    lines.push(String::from("    <dict>"));
+    match interval {
+        Interval::Daily => {}
+        Interval::Weekly => {
+            lines.push(String::from("        <key>Weekday</key>"));
+            lines.push(String::from("        <integer>0</integer>"));
+        }
+        Interval::Monthly => {
+            lines.push(String::from("        <key>Day</key>"));
+            lines.push(String::from("        <integer>1</integer>"));
+        }
+    }
+    for line in [
+        "        <key>Hour</key>",
+        "        <integer>3</integer>",
+        "        <key>Minute</key>",
+        "        <integer>0</integer>",
+        "    </dict>",
+        "</dict>",
+        "</plist>",
+        "",
+    ] {
+        lines.push(line.to_string());
+    }
+    lines.join("\n")
+}
+
+// arguments of schtasks creating scheduled task running cargo trim
+fn schtasks_create_args(interval: Interval, task_command: &str) -> Vec<String> {
+    let schedule = match interval {
+        Interval::Daily => "DAILY",
+        Interval::Weekly => "WEEKLY",
+        Interval::Monthly => "MONTHLY",
+    };
+    [
+        "/Create",
+        "/SC",
+        schedule,
+        "/ST",
+        "03:00",
+        "/TN",
+        SCHEDULE_NAME,
+        "/TR",
+        task_command,
+        "/F",
+    ]
+    .iter()
+    .map(ToString::to_string)
+    .collect()
+}
+
+// command line of cargo trim with policy where argument containing space is
+// quoted
+fn command_line(cargo_trim: &Path, policy: &[String]) -> String {
+    std::iter::once(cargo_trim.to_string_lossy().to_string())
+        .chain(policy.iter().cloned())
+        .map(|arg| {
+            if arg.contains(char::is_whitespace) {
+                format!("\"{arg}\"")
+            } else {
+                arg
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// escape xml special character of plist string value
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+// write file creating its parent directory if not exists
+fn write_file(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create schedule directory")?;
+    }
+    fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+    human_println!("Written {}", path.display());
+    Ok(())
+}
+
+// remove file if it exists
+fn remove_file(path: &Path) -> Result<()> {
+    if path.exists() {
+        fs::remove_file(path).with_context(|| format!("Failed to remove {}", path.display()))?;
+        human_println!("Removed {}", path.display());
+    }
+    Ok(())
+}
+
+// run scheduler command and fail if command is not successful
+fn run_scheduler<S: AsRef<str>>(program: &str, args: &[S]) -> Result<()> {
+    let status = std::process::Command::new(program)
+        .args(args.iter().map(AsRef::as_ref))
+        .status()
+        .with_context(|| format!("Failed to run {program}"))?;
+    anyhow::ensure!(status.success(), "{program} exited with {status}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::{Interval, command_line, launchd_plist, systemd_timer};
+
+    #[test]
+    fn test_schedule_entry() {
+        assert_eq!(
+            command_line(
+                Path::new("/home/user/.cargo/bin/cargo-trim"),
+                &[String::from("--profile"), String::from("build server")]
+            ),
+            "/home/user/.cargo/bin/cargo-trim --profile \"build server\""
+        );
+        assert!(systemd_timer(Interval::Weekly).contains("OnCalendar=weekly\n"));
+        let plist = launchd_plist(
+            Path::new("/Users/user/.cargo/bin/cargo-trim"),
+            &[String::from("--old-orphan")],
+            Interval::Monthly,
+        );
+        assert!(plist.contains("        <string>--old-orphan</string>\n"));
+        assert!(plist.contains("        <key>Day</key>\n        <integer>1</integer>\n"));
+    }
+}
@@ -4,7 +4,14 @@ use clap::Parser;
 use crate::config_file::ConfigFile;
 #[derive(Debug, Parser)]
 #[command(about = "Set config file values", arg_required_else_help = true)]
+#[allow(clippy::struct_excessive_bools)]
 pub(crate) struct Set {
+    #[arg(
+        long = "cargo-home",
+        help = "Add cargo home which is trimmed along with other cargo homes in one run",
+        value_name = "path"
+    )]
+    cargo_home: Option<Vec<String>>,
     #[arg(
         long = "dry-run",
         short = 'n',
@@ -29,11 +36,23 @@ pub(crate) struct Set {
     scan_hidden_folder: bool,
     #[arg(long = "scan-target-folder", help = "Set scan hidden folder as true")]
     scan_target_folder: bool,
+    #[arg(
+        long = "trash",
+        help = "Move deleted file and folder to trash instead of removing permanently"
+    )]
+    trash: bool,
 }
 
 impl Set {
     pub(super) fn run(&self, config_file: &mut ConfigFile) -> Result<()> {
         let dry_run = self.dry_run;
+        if let Some(cargo_homes) = &self.cargo_home {
+            for cargo_home in cargo_homes {
+                let path_separator = std::path::MAIN_SEPARATOR;
+                let path = cargo_home.trim_end_matches(path_separator);
+                config_file.add_cargo_home(path, dry_run, true)?;
+            }
+        }
         if let Some(directories) = &self.directory {
             for directory in directories {
                 let path_separator = std::path::MAIN_SEPARATOR;
@@ -52,6 +71,9 @@ impl Set {
         if self.scan_target_folder {
             config_file.set_scan_target_folder(true, dry_run, true)?;
         }
+        if self.trash {
+            config_file.set_trash(true, dry_run, true)?;
+        }
 
         Ok(())
     }
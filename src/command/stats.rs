@@ -0,0 +1,185 @@
+use std::collections::{BTreeSet, HashSet};
+use std::time::{Duration, SystemTime};
+
+use clap::Parser;
+use owo_colors::OwoColorize;
+
+use crate::crate_detail::{CrateDetail, CrateMetaData};
+use crate::list_crate::CrateList;
+use crate::stats_file::StatsFile;
+use crate::utils::{convert_pretty, format_age, print_dash, query_full_width, query_print};
+
+#[derive(Debug, Parser)]
+#[command(about = "Show statistics of cargo trim", arg_required_else_help = true)]
+pub(crate) struct Stats {
+    #[arg(
+        long = "cache",
+        short = 'c',
+        help = "Show distinct crates, total versions, average versions per crate, largest crate, \
+                oldest cached item and size share of each category of cache"
+    )]
+    cache: bool,
+    #[arg(
+        long = "lifetime",
+        short = 'l',
+        help = "Show total space freed by cargo trim over its lifetime per category and reason"
+    )]
+    lifetime: bool,
+    #[arg(
+        long = "unused",
+        short = 'u',
+        help = "Show count and size of registry and git crates whose files are not read or \
+                modified for 30, 90 and 180 days"
+    )]
+    unused: bool,
+}
+
+impl Stats {
+    pub(super) fn run(
+        &self,
+        stats_file: &StatsFile,
+        crate_list: &CrateList,
+        crate_detail: &CrateDetail,
+    ) {
+        if self.cache {
+            show_cache_stats(crate_list, crate_detail);
+        }
+        if self.lifetime {
+            show_lifetime_stats(stats_file);
+        }
+        if self.unused {
+            show_unused_stats(crate_list);
+        }
+    }
+}
+
+// show aggregate metrics of registry and git crates present in cache
+#[allow(clippy::cast_precision_loss)]
+fn show_cache_stats(crate_list: &CrateList, crate_detail: &CrateDetail) {
+    let crates = crate_list
+        .installed_registry()
+        .iter()
+        .chain(crate_list.installed_git())
+        .collect::<Vec<_>>();
+    let distinct = crates
+        .iter()
+        .map(|crate_metadata| crate_metadata.base_name())
+        .collect::<BTreeSet<_>>()
+        .len();
+    human_println!("{}", "Cached crates".bold());
+    query_print("   Distinct crates", &distinct.to_string());
+    query_print("   Total versions", &crates.len().to_string());
+    let average = if distinct == 0 {
+        0.0
+    } else {
+        crates.len() as f64 / distinct as f64
+    };
+    query_print("   Average versions per crate", &format!("{average:.2}"));
+    if let Some(largest) = crates
+        .iter()
+        .max_by_key(|crate_metadata| crate_metadata.size())
+    {
+        query_print(
+            &format!("   Largest crate {}", largest.full_name()),
+            &convert_pretty(largest.size()),
+        );
+    }
+    if let Some((oldest, last_used)) = crates
+        .iter()
+        .filter_map(|crate_metadata| Some((crate_metadata, crate_metadata.last_used()?)))
+        .min_by_key(|(_, last_used)| *last_used)
+    {
+        let age = SystemTime::now()
+            .duration_since(last_used)
+            .map_or(0, |age| age.as_secs());
+        query_print(
+            &format!("   Oldest cached item {}", oldest.full_name()),
+            &format_age(age),
+        );
+    }
+    print_dash(query_full_width());
+    let categories = [
+        ("Bin", total_size(crate_detail.bin())),
+        (
+            "Registry source",
+            total_size(crate_detail.registry_crates_source()),
+        ),
+        (
+            "Registry archive",
+            total_size(crate_detail.registry_crates_archive()),
+        ),
+        (
+            "Git checkout",
+            total_size(crate_detail.git_crates_archive()),
+        ),
+        ("Git db", total_size(crate_detail.git_crates_source())),
+    ];
+    let total = categories.iter().map(|(_, size)| size).sum::<u64>();
+    human_println!("{}", "Size share per category".bold());
+    for (category, size) in categories {
+        let share = if total == 0 {
+            0.0
+        } else {
+            size as f64 * 100.0 / total as f64
+        };
+        query_print(
+            &format!("   {category} ({})", convert_pretty(size).trim()),
+            &format!("{share:.1}%"),
+        );
+    }
+    print_dash(query_full_width());
+}
+
+// show crates unused for 30, 90 and 180 days. Each row also contains crates of
+// later row
+fn show_unused_stats(crate_list: &CrateList) {
+    let crates = crate_list
+        .installed_registry()
+        .iter()
+        .chain(crate_list.installed_git())
+        .collect::<Vec<_>>();
+    human_println!("{}", "Crates unused since".bold());
+    for days in [30, 90, 180] {
+        let unused = crates
+            .iter()
+            .filter(|crate_metadata| {
+                crate_metadata.is_unused_for(Duration::from_secs(days * 24 * 60 * 60))
+            })
+            .collect::<Vec<_>>();
+        query_print(
+            &format!("   {days} days ({} crates)", unused.len()),
+            &convert_pretty(
+                unused
+                    .iter()
+                    .map(|crate_metadata| crate_metadata.size())
+                    .sum(),
+            ),
+        );
+    }
+    print_dash(query_full_width());
+}
+
+fn total_size(crates: &HashSet<CrateMetaData>) -> u64 {
+    crates.iter().map(CrateMetaData::size).sum()
+}
+
+// show lifetime stats stored in stats file
+fn show_lifetime_stats(stats_file: &StatsFile) {
+    human_println!("{}", "Space freed per category".bold());
+    for (category, size) in stats_file.category() {
+        query_print(&format!("   {category}"), &convert_pretty(*size));
+    }
+    print_dash(crate::utils::query_full_width());
+    human_println!("{}", "Space freed per reason".bold());
+    for (reason, size) in stats_file.reason() {
+        query_print(&format!("   {reason}"), &convert_pretty(*size));
+    }
+    print_dash(crate::utils::query_full_width());
+    query_print(
+        &format!(
+            "Total space freed by removing {} items",
+            stats_file.total_removed()
+        ),
+        &convert_pretty(stats_file.total_freed()),
+    );
+}
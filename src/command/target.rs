@@ -0,0 +1,501 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use owo_colors::OwoColorize;
+
+use crate::config_file::ConfigFile;
+use crate::list_crate::CrateList;
+use crate::rustup_dir::RustupDir;
+use crate::stats_file::StatsFile;
+use crate::target_dir::{
+    TargetDir, TargetProfile, discover_target_dirs, fingerprint_rustc_hash, list_target_dirs,
+};
+use crate::utils::{
+    ask_confirmation, convert_pretty, delete_folder, format_timestamp, get_size, last_modified,
+    parse_duration, print_dash, query_print,
+};
+
+#[derive(Debug, Parser)]
+#[allow(clippy::struct_excessive_bools)]
+#[command(
+    about = "Perform operation on target directory of rust project directory",
+    arg_required_else_help = true
+)]
+pub(crate) struct Target {
+    #[arg(
+        long = "all",
+        short = 'a',
+        help = "Clean up whole target directory of all projects",
+        conflicts_with_all = ["build_script_outputs_only", "older_than", "profile"]
+    )]
+    all: bool,
+    #[arg(
+        long = "build-script-outputs-only",
+        short = 'b',
+        help = "Only clean build script outputs of target profiles. Native libraries get rebuilt \
+                but rust dependencies are kept. Can be combined with profile and older-than"
+    )]
+    build_script_outputs_only: bool,
+    #[arg(
+        long = "discover",
+        help = "Search directory tree for cargo target directories including target directories \
+                of projects which are not registered. Found target directories are used instead \
+                of registered projects by other flags",
+        value_name = "root"
+    )]
+    discover: Option<PathBuf>,
+    #[arg(
+        long = "dry-run",
+        short = 'n',
+        help = "Run command in dry run mode to see what would be done"
+    )]
+    dry_run: bool,
+    #[arg(
+        long = "incremental-only",
+        short = 'i',
+        help = "Only clean incremental compilation cache of target profiles. With older-than only \
+                cache of crates which are not compiled within duration is cleaned. Can be \
+                combined with profile",
+        conflicts_with_all = ["all", "build_script_outputs_only", "stale_toolchain"]
+    )]
+    incremental_only: bool,
+    #[arg(
+        long = "older-than",
+        short = 'o',
+        help = "Clean target profiles which are not modified within duration. Supported units are \
+                s, m, h, d and w",
+        value_name = "duration",
+        value_parser = parse_duration
+    )]
+    older_than: Option<Duration>,
+    #[arg(
+        long = "profile",
+        short = 'p',
+        help = "Clean target profile folder with provided name such as debug or release",
+        value_name = "profile"
+    )]
+    profile: Option<Vec<String>>,
+    #[arg(
+        long = "query",
+        short = 'q',
+        help = "Return size of target directory and its profiles for all projects"
+    )]
+    query: bool,
+    #[arg(
+        long = "register",
+        help = "Ask to register project of each unregistered target directory found by discover",
+        requires = "discover"
+    )]
+    register: bool,
+    #[arg(
+        long = "stale-toolchain",
+        short = 't',
+        help = "Clean artifacts whose fingerprint is built by toolchain which is no longer \
+                installed. Hash of each installed toolchain is found by building empty crate \
+                with it. Can be combined with profile and older-than",
+        conflicts_with_all = ["all", "build_script_outputs_only"]
+    )]
+    stale_toolchain: bool,
+}
+
+impl Target {
+    pub(super) fn run(
+        &self,
+        config_file: &mut ConfigFile,
+        crate_list: &CrateList,
+        stats_file: &mut StatsFile,
+    ) -> Result<()> {
+        let dry_run = self.dry_run;
+        let target_dirs = if let Some(root) = &self.discover {
+            self.discover(root, config_file, crate_list)?
+        } else {
+            list_target_dirs(crate_list.cargo_toml_location().location_path())?
+        };
+
+        if self.query {
+            query_target_dirs(&target_dirs);
+        }
+
+        if self.all {
+            let mut size_cleaned = 0;
+            for target_dir in &target_dirs {
+                delete_folder(target_dir.path(), dry_run)?;
+                size_cleaned += target_dir.size();
+            }
+            stats_file.record("target", "all", size_cleaned, target_dirs.len(), dry_run);
+            human_println!(
+                "{}",
+                format!(
+                    "{} target directories removed which had occupied {}",
+                    target_dirs.len(),
+                    convert_pretty(size_cleaned)
+                )
+                .blue()
+            );
+        }
+
+        if self.build_script_outputs_only {
+            let mut size_cleaned = 0;
+            let mut profile_cleaned = 0;
+            for target_dir in &target_dirs {
+                for profile in target_dir.profiles() {
+                    if profile.build_size() > 0 && self.need_to_be_removed(profile) {
+                        delete_folder(&profile.build_dir(), dry_run)?;
+                        size_cleaned += profile.build_size();
+                        profile_cleaned += 1;
+                    }
+                }
+            }
+            stats_file.record("target", "build", size_cleaned, profile_cleaned, dry_run);
+            human_println!(
+                "{}",
+                format!(
+                    "Build script outputs of {profile_cleaned} target profiles removed which had \
+                     occupied {}",
+                    convert_pretty(size_cleaned)
+                )
+                .blue()
+            );
+        } else if self.incremental_only {
+            self.clean_incremental(&target_dirs, stats_file)?;
+        } else if self.stale_toolchain {
+            self.clean_stale_toolchain(&target_dirs, stats_file)?;
+        } else if self.profile.is_some() || self.older_than.is_some() {
+            let reason = if self.older_than.is_some() {
+                "old"
+            } else {
+                "profile"
+            };
+            let mut size_cleaned = 0;
+            let mut profile_removed = 0;
+            // size removed of each profile name summed across target directories
+            let mut removed_per_profile = BTreeMap::new();
+            for target_dir in &target_dirs {
+                for profile in target_dir.profiles() {
+                    if self.need_to_be_removed(profile) {
+                        delete_folder(profile.path(), dry_run)?;
+                        size_cleaned += profile.size();
+                        profile_removed += 1;
+                        let (count, size) =
+                            removed_per_profile.entry(profile.name()).or_insert((0, 0));
+                        *count += 1;
+                        *size += profile.size();
+                    }
+                }
+            }
+            stats_file.record("target", reason, size_cleaned, profile_removed, dry_run);
+            human_println!(
+                "{}",
+                format!(
+                    "{profile_removed} target profiles removed which had occupied {}",
+                    convert_pretty(size_cleaned)
+                )
+                .blue()
+            );
+            for (name, (count, size)) in removed_per_profile {
+                query_print(
+                    &format!("   {name} ({count} target directories)"),
+                    &convert_pretty(size),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    // list target directories found inside root along with registration status
+    // of their project and register project when user agrees
+    fn discover(
+        &self,
+        root: &Path,
+        config_file: &mut ConfigFile,
+        crate_list: &CrateList,
+    ) -> Result<Vec<TargetDir>> {
+        let root = root
+            .canonicalize()
+            .context("Failed to find discover root")?;
+        let registered_projects = crate_list
+            .cargo_toml_location()
+            .location_path()
+            .iter()
+            .map(|location| location.canonicalize().unwrap_or_else(|_| location.clone()))
+            .collect::<Vec<_>>();
+        let mut target_dirs = Vec::new();
+        for path in discover_target_dirs(&root)? {
+            // target directory moved by CARGO_TARGET_DIR has no project
+            let project = path
+                .parent()
+                .filter(|parent| parent.join("Cargo.toml").is_file())
+                .map(Path::to_path_buf);
+            let is_registered = project
+                .as_ref()
+                .is_some_and(|project| registered_projects.contains(project));
+            let status = match &project {
+                _ if is_registered => "registered",
+                Some(_) => "unregistered",
+                None => "no project",
+            };
+            let target_dir = TargetDir::from_path(path)?;
+            query_print(
+                &format!("{} ({status})", target_dir.path().display()),
+                &convert_pretty(target_dir.size()),
+            );
+            if let Some(project) = project.filter(|_| self.register && !is_registered) {
+                let project = project.to_string_lossy();
+                if self.dry_run || ask_confirmation(&format!("Register project {project:?}?"))? {
+                    config_file.add_directory(&project, self.dry_run, true)?;
+                }
+            }
+            target_dirs.push(target_dir);
+        }
+        print_dash(crate::utils::query_full_width());
+        query_print(
+            &format!("Found {} target directories", target_dirs.len()),
+            &convert_pretty(target_dirs.iter().map(TargetDir::size).sum()),
+        );
+        Ok(target_dirs)
+    }
+
+    // remove artifacts of profiles built by toolchain which is not installed
+    fn clean_stale_toolchain(
+        &self,
+        target_dirs: &[TargetDir],
+        stats_file: &mut StatsFile,
+    ) -> Result<()> {
+        let dry_run = self.dry_run;
+        let rustc_hashes = installed_rustc_hashes()?;
+        let mut size_cleaned = 0;
+        let mut artifact_removed = 0;
+        for target_dir in target_dirs {
+            for profile in target_dir.profiles() {
+                if !self.need_to_be_removed(profile) {
+                    continue;
+                }
+                for artifact in profile.artifacts_built_by_other_rustc(&rustc_hashes)? {
+                    let size = get_size(&artifact).unwrap_or(0);
+                    if delete_folder(&artifact, dry_run)? {
+                        size_cleaned += size;
+                        artifact_removed += 1;
+                    }
+                }
+            }
+        }
+        stats_file.record(
+            "target",
+            "toolchain",
+            size_cleaned,
+            artifact_removed,
+            dry_run,
+        );
+        human_println!(
+            "{}",
+            format!(
+                "{artifact_removed} artifacts built by uninstalled toolchain removed which \
+                 had occupied {}",
+                convert_pretty(size_cleaned)
+            )
+            .blue()
+        );
+        Ok(())
+    }
+
+    // remove incremental compilation cache of profiles. Age is checked for cache
+    // of each crate since crate compiled recently keeps only its own cache fresh
+    fn clean_incremental(
+        &self,
+        target_dirs: &[TargetDir],
+        stats_file: &mut StatsFile,
+    ) -> Result<()> {
+        let dry_run = self.dry_run;
+        let mut size_cleaned = 0;
+        let mut cache_removed = 0;
+        for target_dir in target_dirs {
+            for profile in target_dir.profiles() {
+                if profile.incremental_size() == 0 || !self.name_matches(profile) {
+                    continue;
+                }
+                let entries = fs::read_dir(profile.incremental_dir())
+                    .context("Failed to read incremental folder of target profile")?;
+                for entry in entries {
+                    let cache = entry?.path();
+                    // new session folder is created inside cache on every compilation
+                    let mut children = vec![cache.clone()];
+                    if let Ok(sessions) = fs::read_dir(&cache) {
+                        children.extend(sessions.flatten().map(|session| session.path()));
+                    }
+                    if self.age_matches(last_modified(&children)) {
+                        let size = get_size(&cache).unwrap_or(0);
+                        if delete_folder(&cache, dry_run)? {
+                            size_cleaned += size;
+                            cache_removed += 1;
+                        }
+                    }
+                }
+            }
+        }
+        stats_file.record(
+            "target",
+            "incremental",
+            size_cleaned,
+            cache_removed,
+            dry_run,
+        );
+        human_println!(
+            "{}",
+            format!(
+                "Incremental compilation cache of {cache_removed} crates removed which had \
+                 occupied {}",
+                convert_pretty(size_cleaned)
+            )
+            .blue()
+        );
+        Ok(())
+    }
+
+    // check if profile matches both profile name and age filter
+    fn need_to_be_removed(&self, profile: &TargetProfile) -> bool {
+        self.name_matches(profile) && self.age_matches(profile.modified())
+    }
+
+    // check if profile name is one of provided profiles
+    fn name_matches(&self, profile: &TargetProfile) -> bool {
+        self.profile
+            .as_ref()
+            .is_none_or(|profiles| profiles.contains(profile.name()))
+    }
+
+    // check if item is not modified within older than duration. Item without
+    // modified time is treated as old
+    fn age_matches(&self, modified: Option<SystemTime>) -> bool {
+        self.older_than.is_none_or(|older_than| {
+            modified.is_none_or(|modified| {
+                SystemTime::now()
+                    .duration_since(modified)
+                    .is_ok_and(|age| age > older_than)
+            })
+        })
+    }
+}
+
+// hash of rustc recorded in fingerprint by every installed toolchain. Only
+// toolchain found in path is used when rustup is not installed
+fn installed_rustc_hashes() -> Result<Vec<u64>> {
+    let toolchains = match RustupDir::new()? {
+        Some(rustup_dir) => rustup_dir
+            .toolchains()
+            .iter()
+            .map(|toolchain| Some(toolchain.name().clone()))
+            .collect(),
+        None => vec![None],
+    };
+    let mut rustc_hashes = Vec::new();
+    for toolchain in toolchains {
+        rustc_hashes.push(probe_rustc_hash(toolchain.as_deref())?);
+    }
+    Ok(rustc_hashes)
+}
+
+// build empty crate with toolchain and read rustc hash from its fingerprint.
+// Failure is an error since unknown hash would remove artifacts of installed
+// toolchain
+fn probe_rustc_hash(toolchain: Option<&str>) -> Result<u64> {
+    let toolchain_name = toolchain.unwrap_or("default toolchain");
+    let probe = std::env::temp_dir().join(format!(
+        "cargo-trim-rustc-probe-{}",
+        toolchain.unwrap_or("default")
+    ));
+    fs::create_dir_all(probe.join("src")).context("Failed to create rustc probe crate")?;
+    fs::write(probe.join("src").join("lib.rs"), "")
+        .context("Failed to write rustc probe crate source")?;
+    fs::write(
+        probe.join("Cargo.toml"),
+        "[package]\nname = \"cargo-trim-rustc-probe\"\nversion = \"0.0.0\"\n",
+    )
+    .context("Failed to write rustc probe crate manifest")?;
+    let mut command = if let Some(toolchain) = toolchain {
+        let mut command = Command::new("rustup");
+        command.args(["run", toolchain, "cargo"]);
+        command
+    } else {
+        Command::new(std::env::var("CARGO").unwrap_or_else(|_| String::from("cargo")))
+    };
+    let output = command
+        .args(["build", "--offline", "--manifest-path"])
+        .arg(probe.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(probe.join("target"))
+        .env_remove("RUSTC")
+        .output();
+    let rustc_hash = fs::read_dir(probe.join("target").join("debug").join(".fingerprint"))
+        .ok()
+        .and_then(|entries| {
+            entries
+                .filter_map(|entry| Some(entry.ok()?.path()))
+                .find_map(|unit_dir| fingerprint_rustc_hash(&unit_dir))
+        });
+    // probe crate is removed even if build fails
+    let _ = fs::remove_dir_all(&probe);
+    let output = output.context(format!(
+        "Failed to build rustc probe crate using {toolchain_name}"
+    ))?;
+    anyhow::ensure!(
+        output.status.success(),
+        "Failed to build rustc probe crate using {toolchain_name}: {}",
+        String::from_utf8_lossy(&output.stderr)
+            .lines()
+            .rfind(|line| !line.trim().is_empty())
+            .unwrap_or("cargo build failed")
+            .trim()
+    );
+    rustc_hash.context(format!(
+        "Failed to read rustc hash from fingerprint of probe crate built using {toolchain_name}"
+    ))
+}
+
+// print size of target directories along with size of their profiles and
+// profile content
+fn query_target_dirs(target_dirs: &[TargetDir]) {
+    let mut total_size = 0;
+    for target_dir in target_dirs {
+        query_print(
+            &format!("Size of {}", target_dir.path().display()),
+            &convert_pretty(target_dir.size()),
+        );
+        let profile_count = target_dir.profiles().len();
+        for (pos, profile) in target_dir.profiles().iter().enumerate() {
+            let branch = if pos + 1 == profile_count {
+                "\u{2514}"
+            } else {
+                "\u{251c}"
+            };
+            let modified = profile.modified().map(format_timestamp).unwrap_or_default();
+            query_print(
+                &format!("   {branch} {} {modified}", profile.name()),
+                &convert_pretty(profile.size()),
+            );
+            let indent = if pos + 1 == profile_count {
+                " "
+            } else {
+                "\u{2502}"
+            };
+            for (part, size) in [
+                ("build", profile.build_size()),
+                ("deps", profile.deps_size()),
+                ("incremental", profile.incremental_size()),
+                ("other", profile.other_size()),
+            ] {
+                query_print(&format!("   {indent}     {part}"), &convert_pretty(size));
+            }
+        }
+        total_size += target_dir.size();
+    }
+    print_dash(crate::utils::query_full_width());
+    query_print(
+        &format!("Total size of {} target directories", target_dirs.len()),
+        &convert_pretty(total_size),
+    );
+}
@@ -0,0 +1,488 @@
+use std::collections::BTreeSet;
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use owo_colors::OwoColorize;
+
+use crate::command::git::clean_git;
+use crate::command::registry::clean_registry;
+use crate::config_file::ConfigFile;
+use crate::crate_detail::{CrateDetail, CrateMetaData};
+use crate::dir_path::DirPath;
+use crate::git_dir::GitDir;
+use crate::list_crate::CrateList;
+use crate::progress::disable_progress;
+use crate::registry_dir::RegistryDir;
+use crate::scan_snapshot::ScanSnapshot;
+use crate::size_cache::SizeCache;
+use crate::stats_file::StatsFile;
+use crate::utils::{
+    convert_pretty, delete_folder, parse_duration, parse_selection, print_dash, query_print,
+};
+
+// number of entries shown in single page
+const PAGE_SIZE: usize = 20;
+
+// duration between two redraw of progress pane
+const REDRAW_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Parser)]
+#[command(about = "Interactively browse registry, git and bin entries and delete selected entries")]
+pub(crate) struct Tui {
+    #[arg(
+        long = "dry-run",
+        short = 'n',
+        help = "Run command in dry run mode to see what would be done"
+    )]
+    dry_run: bool,
+    #[arg(
+        long = "max-scan-age",
+        help = "Rescan before deleting when scan is older than duration or cache directories \
+                changed after scan. Supported units are s, m, h, d and w",
+        value_name = "duration",
+        value_parser = parse_duration,
+        default_value = "15m"
+    )]
+    max_scan_age: Duration,
+}
+
+impl Tui {
+    #[allow(clippy::too_many_arguments, clippy::too_many_lines)]
+    pub(super) fn run(
+        &self,
+        dir_path: &DirPath,
+        config_file: &ConfigFile,
+        crate_list: &CrateList,
+        crate_detail: &CrateDetail,
+        registry_crates_location: &mut RegistryDir,
+        git_crates_location: &GitDir,
+        stats_file: &mut StatsFile,
+    ) -> Result<()> {
+        // progress pane replaces progress line while operation is running
+        disable_progress();
+        let input = spawn_input_reader();
+        let mut entries = list_entries(crate_list);
+        let mut snapshot = ScanSnapshot::new(dir_path);
+        let mut rescanned_detail = None;
+
+        let mut selected = BTreeSet::new();
+        let mut page = 0;
+        loop {
+            if entries.is_empty() {
+                human_println!("{}", "No entries left to browse".blue());
+                return Ok(());
+            }
+            let page_count = entries.len().div_ceil(PAGE_SIZE);
+            page = page.min(page_count - 1);
+            show_page(&entries, &selected, page, page_count);
+            human_print!(
+                "Toggle entries (e.g. 1 3 5-7), [n]ext page, [p]revious page, [d]elete selected, \
+                 [q]uit: "
+            );
+            std::io::stdout()
+                .flush()
+                .context("failed to flush output stream")?;
+            // stop on end of input
+            let Ok(line) = input.recv() else {
+                return Ok(());
+            };
+            match line.trim().to_ascii_lowercase().as_str() {
+                "" | "n" => page = (page + 1) % page_count,
+                "p" => page = (page + page_count - 1) % page_count,
+                "q" => return Ok(()),
+                "d" => {
+                    if selected.is_empty() {
+                        human_println!("{}", "No entries selected".yellow());
+                        continue;
+                    }
+                    // never delete based on outdated scan as cache may be changed by other
+                    // cargo process after scan
+                    if snapshot.is_stale(dir_path, self.max_scan_age) {
+                        human_println!(
+                            "{}",
+                            "Scan is outdated or cache changed after scan, rescanning before \
+                             deleting"
+                                .yellow()
+                        );
+                        let Some((new_detail, new_list)) = rescan(&input, dir_path, config_file)?
+                        else {
+                            human_println!("{}", "Rescan cancelled, nothing is deleted".yellow());
+                            continue;
+                        };
+                        registry_crates_location.set_installed_crate(new_list.installed_registry());
+                        let new_entries = list_entries(&new_list);
+                        selected = selected
+                            .iter()
+                            .filter_map(|&index| {
+                                new_entries
+                                    .iter()
+                                    .position(|entry| entry == &entries[index])
+                            })
+                            .collect();
+                        entries = new_entries;
+                        snapshot = ScanSnapshot::new(dir_path);
+                        rescanned_detail = Some(new_detail);
+                        human_println!(
+                            "{}",
+                            "Selection is updated with latest scan. Review it and delete again"
+                                .yellow()
+                        );
+                        continue;
+                    }
+                    let Some(removed) = self.delete_selected(
+                        &input,
+                        &entries,
+                        &selected,
+                        rescanned_detail.as_ref().unwrap_or(crate_detail),
+                        registry_crates_location,
+                        git_crates_location,
+                        stats_file,
+                    )?
+                    else {
+                        continue;
+                    };
+                    // cache directories changed by own deletion do not make scan stale
+                    snapshot.refresh(dir_path);
+                    // entry which is not removed such as one skipped after cancel stays
+                    // selected so it can be deleted again
+                    entries = entries
+                        .into_iter()
+                        .enumerate()
+                        .filter(|(index, _)| !removed.contains(index))
+                        .map(|(_, entry)| entry)
+                        .collect();
+                    selected = selected
+                        .iter()
+                        .filter(|index| !removed.contains(index))
+                        .map(|&index| index - removed.range(..index).count())
+                        .collect();
+                }
+                selection => {
+                    match parse_selection(selection, entries.len()) {
+                        Ok(indexes) => {
+                            for index in indexes {
+                                if !selected.remove(&index) {
+                                    selected.insert(index);
+                                }
+                            }
+                        }
+                        Err(err) => human_println!("{}", err.to_string().red()),
+                    }
+                }
+            }
+        }
+    }
+
+    // delete selected entries one by one in background thread after
+    // confirmation and return indexes of removed entries. Cancelling stops
+    // deletion before next entry so entry being removed is never left half
+    // removed. Return none when deletion is not confirmed
+    #[allow(clippy::too_many_arguments)]
+    fn delete_selected(
+        &self,
+        input: &Receiver<String>,
+        entries: &[(&str, CrateMetaData)],
+        selected: &BTreeSet<usize>,
+        crate_detail: &CrateDetail,
+        registry_crates_location: &mut RegistryDir,
+        git_crates_location: &GitDir,
+        stats_file: &mut StatsFile,
+    ) -> Result<Option<BTreeSet<usize>>> {
+        let dry_run = self.dry_run;
+        let selected_size = selected
+            .iter()
+            .map(|&index| entries[index].1.size())
+            .sum::<u64>();
+        let question = format!(
+            "Do you want to remove {} selected entries which occupy {}?",
+            selected.len(),
+            convert_pretty(selected_size)
+        );
+        if !dry_run && !ask_confirmation(input, &question)? {
+            return Ok(None);
+        }
+        let operation = Operation::default();
+        let (removed, size_cleaned) = thread::scope(|scope| {
+            let worker = scope.spawn(|| -> Result<(BTreeSet<usize>, u64)> {
+                let mut removed = BTreeSet::new();
+                let mut size_cleaned = 0;
+                for &index in selected {
+                    if operation.cancelled.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    let (category, crate_metadata) = &entries[index];
+                    let crate_metadata_list = std::slice::from_ref(crate_metadata);
+                    let (size, count) = match *category {
+                        "bin" => delete_bin(crate_metadata, stats_file, dry_run)?,
+                        "registry" => clean_registry(
+                            registry_crates_location,
+                            crate_metadata_list,
+                            crate_detail,
+                            stats_file,
+                            "interactive",
+                            dry_run,
+                        )?,
+                        _ => clean_git(
+                            git_crates_location,
+                            crate_metadata_list,
+                            stats_file,
+                            "interactive",
+                            dry_run,
+                        ),
+                    };
+                    if count > 0 {
+                        removed.insert(index);
+                    }
+                    size_cleaned += size;
+                    operation.done.fetch_add(1, Ordering::Relaxed);
+                    operation
+                        .done_size
+                        .fetch_add(crate_metadata.size(), Ordering::Relaxed);
+                }
+                Ok((removed, size_cleaned))
+            });
+            show_progress_pane(input, &operation, "Deleting", |_| {
+                Some((selected.len(), selected_size)).filter(|_| !worker.is_finished())
+            });
+            worker
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+        })?;
+        if operation.cancelled.load(Ordering::Relaxed) {
+            human_println!(
+                "{}",
+                format!(
+                    "Deletion cancelled, {} of {} selected entries processed",
+                    operation.done.load(Ordering::Relaxed),
+                    selected.len()
+                )
+                .yellow()
+            );
+        }
+        human_println!(
+            "{}",
+            format!(
+                "{} entries removed which had occupied {}",
+                removed.len(),
+                convert_pretty(size_cleaned)
+            )
+            .blue()
+        );
+        // entries are kept in dry run so they can be browsed again
+        if dry_run {
+            return Ok(Some(BTreeSet::new()));
+        }
+        Ok(Some(removed))
+    }
+}
+
+// state of operation running in background thread which is shown in progress
+// pane. Cancel flag is set by main thread and checked by operation
+#[derive(Default)]
+struct Operation {
+    done: AtomicUsize,
+    done_size: AtomicU64,
+    cancelled: AtomicBool,
+}
+
+// read lines of stdin in background thread so cancel can be entered while
+// operation is running
+fn spawn_input_reader() -> Receiver<String> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in std::io::stdin().lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+// ask confirmation with answer read by input thread
+fn ask_confirmation(input: &Receiver<String>, question: &str) -> Result<bool> {
+    human_print!("{question} (y/N) ");
+    std::io::stdout()
+        .flush()
+        .context("failed to flush output stream")?;
+    let answer = input.recv().unwrap_or_default().trim().to_ascii_lowercase();
+    Ok(["y", "yes"].contains(&answer.as_str()))
+}
+
+// rescan cargo home in background thread. Scan cannot be stopped in middle so
+// cancelled scan is waited for and its result is discarded. This makes sure
+// scan never runs alongside deletion. Return none when scan is cancelled
+fn rescan(
+    input: &Receiver<String>,
+    dir_path: &DirPath,
+    config_file: &ConfigFile,
+) -> Result<Option<(CrateDetail, CrateList)>> {
+    let dir_path = dir_path.clone();
+    let config_file = config_file.clone();
+    let worker = thread::spawn(move || -> Result<(CrateDetail, CrateList)> {
+        let size_cache = SizeCache::init(dir_path.size_cache_file(), true)?;
+        let mut crate_detail =
+            CrateDetail::new(dir_path.index_dir(), dir_path.db_dir(), size_cache)?;
+        let crate_list = CrateList::create_list(&dir_path, &config_file, &mut crate_detail)?;
+        Ok((crate_detail, crate_list))
+    });
+    let operation = Operation::default();
+    show_progress_pane(input, &operation, "Scanning", |operation| {
+        (!worker.is_finished() && !operation.cancelled.load(Ordering::Relaxed)).then_some((0, 0))
+    });
+    let cancelled = operation.cancelled.load(Ordering::Relaxed);
+    if cancelled {
+        human_println!("{}", "Waiting for cancelled scan to stop".yellow());
+    }
+    let scanned = worker
+        .join()
+        .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+    if cancelled {
+        return Ok(None);
+    }
+    scanned.map(Some)
+}
+
+// delete paths of bin entry and return cleaned size and removed count
+fn delete_bin(
+    crate_metadata: &CrateMetaData,
+    stats_file: &mut StatsFile,
+    dry_run: bool,
+) -> Result<(u64, usize)> {
+    let mut is_removed = true;
+    for path in crate_metadata.path() {
+        is_removed = delete_folder(path, dry_run)? && is_removed;
+    }
+    let (size, count) = if is_removed {
+        (crate_metadata.size(), 1)
+    } else {
+        (0, 0)
+    };
+    stats_file.record("bin", "interactive", size, count, dry_run);
+    Ok((size, count))
+}
+
+// redraw progress pane in stderr until running returns none. Running returns
+// number of entries and size to process which is zero when unknown. Entering
+// c cancels operation
+fn show_progress_pane<F>(input: &Receiver<String>, operation: &Operation, action: &str, running: F)
+where
+    F: Fn(&Operation) -> Option<(usize, u64)>,
+{
+    let started = Instant::now();
+    let is_terminal = std::io::stderr().is_terminal();
+    let mut stderr = std::io::stderr();
+    while let Some((total, total_size)) = running(operation) {
+        if is_terminal {
+            let progress = if total == 0 {
+                String::new()
+            } else {
+                format!(
+                    " {} / {total} entries ({} / {})",
+                    operation.done.load(Ordering::Relaxed),
+                    convert_pretty(operation.done_size.load(Ordering::Relaxed)).trim(),
+                    convert_pretty(total_size).trim()
+                )
+            };
+            let hint = if operation.cancelled.load(Ordering::Relaxed) {
+                "cancelling after current entry"
+            } else {
+                "enter c to cancel"
+            };
+            let _ = write!(
+                stderr,
+                "\r\x1b[2K{action}{progress} {}s, {hint}",
+                started.elapsed().as_secs()
+            );
+            let _ = stderr.flush();
+        }
+        match input.recv_timeout(REDRAW_INTERVAL) {
+            Ok(line) if line.trim().eq_ignore_ascii_case("c") => {
+                operation.cancelled.store(true, Ordering::Relaxed);
+            }
+            // input closed so only wait for operation to finish
+            Err(RecvTimeoutError::Disconnected) => thread::sleep(REDRAW_INTERVAL),
+            Ok(_) | Err(RecvTimeoutError::Timeout) => {}
+        }
+    }
+    if is_terminal {
+        let _ = write!(stderr, "\r\x1b[2K");
+        let _ = stderr.flush();
+    }
+}
+
+// list bin, registry and git entries sorted by size
+fn list_entries(crate_list: &CrateList) -> Vec<(&'static str, CrateMetaData)> {
+    let mut entries = crate_list
+        .installed_bin()
+        .iter()
+        .map(|crate_metadata| ("bin", crate_metadata.clone()))
+        .chain(
+            crate_list
+                .installed_registry()
+                .iter()
+                .map(|crate_metadata| ("registry", crate_metadata.clone())),
+        )
+        .chain(
+            crate_list
+                .installed_git()
+                .iter()
+                .map(|crate_metadata| ("git", crate_metadata.clone())),
+        )
+        .collect::<Vec<_>>();
+    entries.sort_by_key(|(_, crate_metadata)| std::cmp::Reverse(crate_metadata.size()));
+    entries
+}
+
+// show single page of entries along with selection mark
+fn show_page(
+    entries: &[(&str, CrateMetaData)],
+    selected: &BTreeSet<usize>,
+    page: usize,
+    page_count: usize,
+) {
+    print_dash(crate::utils::query_full_width());
+    for (index, (category, crate_metadata)) in entries
+        .iter()
+        .enumerate()
+        .skip(page * PAGE_SIZE)
+        .take(PAGE_SIZE)
+    {
+        let mark = if selected.contains(&index) {
+            "[x]"
+        } else {
+            "[ ]"
+        };
+        let name = crate_metadata.crate_id().map_or_else(
+            || crate_metadata.name().clone(),
+            |crate_id| crate_id.to_string(),
+        );
+        query_print(
+            &format!("{mark} {:>4} {category:<8} {name}", index + 1),
+            &convert_pretty(crate_metadata.size()),
+        );
+    }
+    print_dash(crate::utils::query_full_width());
+    let selected_size = selected
+        .iter()
+        .map(|&index| entries[index].1.size())
+        .sum::<u64>();
+    human_println!(
+        "{}",
+        format!(
+            "Page {} of {page_count}, {} entries selected which occupy {}",
+            page + 1,
+            selected.len(),
+            convert_pretty(selected_size)
+        )
+        .blue()
+    );
+}
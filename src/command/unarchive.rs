@@ -0,0 +1,108 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use owo_colors::OwoColorize;
+
+use crate::crate_detail::{CrateDetail, CrateMetaData, SOURCE_ARCHIVE_EXTENSION};
+
+#[derive(Debug, Parser)]
+#[command(
+    about = "Extract registry sources archived by cargo trim archive back to their directory",
+    arg_required_else_help = true
+)]
+pub(crate) struct Unarchive {
+    #[arg(
+        long = "all",
+        short = 'a',
+        help = "Extract every archived registry source",
+        conflicts_with = "crates"
+    )]
+    all: bool,
+    #[arg(
+        help = "Name, name-version or glob pattern such as serde* of archived registry crate",
+        required_unless_present = "all"
+    )]
+    crates: Vec<String>,
+    #[arg(
+        long = "dry-run",
+        short = 'n',
+        help = "Run command in dry run mode to see what would be done"
+    )]
+    dry_run: bool,
+}
+
+impl Unarchive {
+    pub(super) fn run(&self, crate_detail: &CrateDetail) -> Result<()> {
+        let mut archived = crate_detail
+            .registry_crates_source()
+            .iter()
+            .filter(|crate_metadata| {
+                self.all
+                    || self
+                        .crates
+                        .iter()
+                        .any(|pattern| crate_metadata.matches(pattern))
+            })
+            .flat_map(|crate_metadata| {
+                crate_metadata
+                    .path()
+                    .iter()
+                    .filter(|path| is_archive(path))
+                    .map(move |tarball| (crate_metadata, tarball))
+            })
+            .collect::<Vec<_>>();
+        archived.sort();
+        if archived.is_empty() {
+            human_println!("{}", "No archived registry source matched".yellow());
+            return Ok(());
+        }
+        for (crate_metadata, tarball) in &archived {
+            self.extract_source(crate_metadata, tarball)?;
+        }
+        human_println!(
+            "{}",
+            format!("{} registry sources extracted", archived.len()).blue()
+        );
+        Ok(())
+    }
+
+    // extract tarball next to it and remove tarball afterwards
+    fn extract_source(&self, crate_metadata: &CrateMetaData, tarball: &Path) -> Result<()> {
+        if self.dry_run {
+            human_println!(
+                "{} {}",
+                "Dry run: Extracted".yellow(),
+                crate_metadata.full_name()
+            );
+            return Ok(());
+        }
+        let parent = tarball
+            .parent()
+            .context("Failed to get parent directory of archived registry source")?;
+        let status = Command::new("tar")
+            .arg("-xzf")
+            .arg(tarball)
+            .arg("-C")
+            .arg(parent)
+            .status()
+            .context("Failed to run tar")?;
+        anyhow::ensure!(status.success(), "Failed to extract {}", tarball.display());
+        fs::remove_file(tarball).context(format!(
+            "Failed to remove archived registry source {}",
+            tarball.display()
+        ))?;
+        human_println!("{} {}", "Extracted".green(), crate_metadata.full_name());
+        Ok(())
+    }
+}
+
+// check if path is tarball created by cargo trim archive
+fn is_archive(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .to_str()
+            .is_some_and(|path| path.ends_with(SOURCE_ARCHIVE_EXTENSION))
+}
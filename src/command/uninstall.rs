@@ -0,0 +1,113 @@
+use anyhow::{Result, bail};
+use clap::Parser;
+use owo_colors::OwoColorize;
+
+use crate::crate_detail::CrateMetaData;
+use crate::dir_path::DirPath;
+use crate::install_metadata::InstallMetadata;
+use crate::list_crate::CrateList;
+use crate::stats_file::StatsFile;
+use crate::utils::{convert_pretty, delete_folder};
+
+#[derive(Debug, Parser)]
+#[command(
+    about = "Remove installed binary and its entry from .crates.toml and .crates2.json",
+    arg_required_else_help = true
+)]
+pub(crate) struct Uninstall {
+    #[arg(
+        help = "Name of binary present in bin directory of cargo home",
+        required_unless_present = "untracked"
+    )]
+    bins: Vec<String>,
+    #[arg(
+        long = "dry-run",
+        short = 'n',
+        help = "Run command in dry run mode to see what would be done"
+    )]
+    dry_run: bool,
+    #[arg(
+        long = "untracked",
+        help = "Remove every binary which is not recorded in install metadata of cargo. Binaries \
+                placed by rustup are kept",
+        conflicts_with = "bins"
+    )]
+    untracked: bool,
+}
+
+impl Uninstall {
+    pub(super) fn run(
+        &self,
+        dir_path: &DirPath,
+        crate_list: &CrateList,
+        stats_file: &mut StatsFile,
+    ) -> Result<()> {
+        let mut install_metadata = InstallMetadata::load(dir_path.cargo_home())?;
+        let reason = if self.untracked {
+            "untracked"
+        } else {
+            "uninstall"
+        };
+        let bins = if self.untracked {
+            install_metadata.untracked_bins(crate_list.installed_bin())
+        } else {
+            let mut bins = Vec::new();
+            for bin in &self.bins {
+                let Some(bin_metadata) = crate_list.installed_bin().iter().find(|bin_metadata| {
+                    bin_metadata.name() == bin || bin_metadata.name() == &format!("{bin}.exe")
+                }) else {
+                    bail!(
+                        "Binary {bin:?} is not installed in {}",
+                        dir_path.bin_dir().display()
+                    );
+                };
+                bins.push(bin_metadata.clone());
+            }
+            bins
+        };
+        let mut size_cleaned = 0;
+        let mut bin_removed = 0;
+        for bin_metadata in &bins {
+            if self.remove_bin(bin_metadata, &mut install_metadata)? {
+                size_cleaned += bin_metadata.size();
+                bin_removed += 1;
+            }
+        }
+        stats_file.record("bin", reason, size_cleaned, bin_removed, self.dry_run);
+        Ok(())
+    }
+
+    // remove binary along with its entry in install metadata. Return false if
+    // binary cannot be removed
+    fn remove_bin(
+        &self,
+        bin_metadata: &CrateMetaData,
+        install_metadata: &mut InstallMetadata,
+    ) -> Result<bool> {
+        for path in bin_metadata.path() {
+            if !delete_folder(path, self.dry_run)? {
+                return Ok(false);
+            }
+        }
+        // metadata is only updated after binary is removed so failed removal
+        // keeps cargo aware of installed binary
+        let package = install_metadata.package_of(bin_metadata.name());
+        if !self.dry_run && package.is_some() {
+            install_metadata.remove_bin(bin_metadata.name())?;
+        }
+        let installed_by = package.map_or_else(String::new, |package| {
+            format!(" installed by {} {}", package.name(), package.version())
+        });
+        human_println!(
+            "{} {}{installed_by} ({})",
+            if self.dry_run {
+                "Dry run: Removed".yellow().to_string()
+            } else {
+                "Removed".red().to_string()
+            },
+            bin_metadata.name(),
+            convert_pretty(bin_metadata.size()).trim()
+        );
+        Ok(true)
+    }
+}
@@ -4,7 +4,14 @@ use clap::Parser;
 use crate::config_file::ConfigFile;
 #[derive(Debug, Parser)]
 #[command(about = "Unset values from config file", arg_required_else_help = true)]
+#[allow(clippy::struct_excessive_bools)]
 pub(crate) struct Unset {
+    #[arg(
+        long = "cargo-home",
+        help = "Cargo home to be removed from config file",
+        value_name = "path"
+    )]
+    cargo_home: Option<Vec<String>>,
     #[arg(
         long = "dry-run",
         short = 'n',
@@ -28,11 +35,23 @@ pub(crate) struct Unset {
     scan_hidden_folder: bool,
     #[arg(long = "scan-target-folder", help = "Set scan hidden folder as false")]
     scan_target_folder: bool,
+    #[arg(
+        long = "trash",
+        help = "Remove deleted file and folder permanently instead of moving to trash"
+    )]
+    trash: bool,
 }
 
 impl Unset {
     pub(super) fn run(&self, config_file: &mut ConfigFile) -> Result<()> {
         let dry_run = self.dry_run;
+        if let Some(cargo_homes) = &self.cargo_home {
+            for cargo_home in cargo_homes {
+                let path_separator = std::path::MAIN_SEPARATOR;
+                let path = cargo_home.trim_end_matches(path_separator);
+                config_file.remove_cargo_home(path, dry_run, true)?;
+            }
+        }
         if let Some(directories) = &self.directory {
             for directory in directories {
                 let path_separator = std::path::MAIN_SEPARATOR;
@@ -51,6 +70,9 @@ impl Unset {
         if self.scan_target_folder {
             config_file.set_scan_target_folder(false, dry_run, true)?;
         }
+        if self.trash {
+            config_file.set_trash(false, dry_run, true)?;
+        }
 
         Ok(())
     }
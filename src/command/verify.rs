@@ -0,0 +1,118 @@
+use anyhow::Result;
+use clap::Parser;
+use owo_colors::OwoColorize;
+
+use crate::crate_detail::{CrateDetail, CrateMetaData};
+use crate::dir_path::DirPath;
+use crate::index_dir::index_cache_dirs;
+use crate::registry_dir::index_cache_file;
+use crate::restore_manifest::index_checksum;
+use crate::sha256::sha256_file;
+use crate::stats_file::StatsFile;
+use crate::utils::{convert_pretty, delete_folder};
+
+#[derive(Debug, Parser)]
+#[command(about = "Verify checksum of cached .crate archives against registry index")]
+pub(crate) struct Verify {
+    #[arg(
+        long = "delete",
+        short = 'd',
+        help = "Delete corrupted or truncated archives so cargo downloads them again"
+    )]
+    delete: bool,
+    #[arg(
+        long = "dry-run",
+        short = 'n',
+        help = "Run command in dry run mode to see what would be done"
+    )]
+    dry_run: bool,
+}
+
+impl Verify {
+    pub(super) fn run(
+        &self,
+        dir_path: &DirPath,
+        crate_detail: &CrateDetail,
+        stats_file: &mut StatsFile,
+    ) -> Result<()> {
+        let index_cache_dirs = index_cache_dirs(dir_path.index_dir(), crate_detail)?;
+        let mut archives = crate_detail
+            .registry_crates_archive()
+            .iter()
+            .collect::<Vec<_>>();
+        archives.sort();
+        let mut verified = 0;
+        let mut unverified = 0;
+        let mut corrupted = Vec::new();
+        for crate_metadata in archives {
+            let Some(crate_id) = crate_metadata.crate_id() else {
+                continue;
+            };
+            let version = crate_id.version().to_string();
+            let checksum = index_cache_dirs
+                .iter()
+                .filter(|(source, _)| source == crate_id.registry())
+                .find_map(|(_, cache_dir)| {
+                    index_checksum(&index_cache_file(cache_dir, crate_id.name()), &version)
+                });
+            let Some(checksum) = checksum else {
+                unverified += 1;
+                continue;
+            };
+            // unreadable archive is treated as corrupted
+            let is_corrupted = crate_metadata
+                .path()
+                .iter()
+                .any(|path| sha256_file(path).map_or(true, |digest| digest != checksum));
+            if is_corrupted {
+                human_println!(r#"{} "{crate_id}""#, "Corrupted".red());
+                corrupted.push(crate_metadata);
+            } else {
+                verified += 1;
+            }
+        }
+        human_println!(
+            "{}",
+            format!(
+                "{verified} archives verified, {} corrupted and {unverified} without checksum in \
+                 registry index",
+                corrupted.len()
+            )
+            .blue()
+        );
+        if self.delete {
+            self.delete_corrupted(&corrupted, stats_file)?;
+        }
+        Ok(())
+    }
+
+    // delete corrupted archives so cargo downloads them again
+    fn delete_corrupted(
+        &self,
+        corrupted: &[&CrateMetaData],
+        stats_file: &mut StatsFile,
+    ) -> Result<()> {
+        let mut size_cleaned = 0;
+        let mut removed = 0;
+        for crate_metadata in corrupted {
+            let mut is_deleted = true;
+            for path in crate_metadata.path() {
+                is_deleted &= delete_folder(path, self.dry_run)?;
+            }
+            if is_deleted {
+                size_cleaned += crate_metadata.size();
+                removed += 1;
+            }
+        }
+        stats_file.record("registry", "corrupted", size_cleaned, removed, self.dry_run);
+        human_println!(
+            "{}",
+            format!(
+                "{removed} corrupted archives removed which had occupied {}",
+                convert_pretty(size_cleaned)
+            )
+            .blue()
+        );
+        Ok(())
+    }
+}
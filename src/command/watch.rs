@@ -0,0 +1,159 @@
+use std::ffi::OsString;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use owo_colors::OwoColorize;
+
+use crate::dir_path::DirPath;
+use crate::utils::{convert_pretty, format_timestamp, get_size, parse_duration, parse_size};
+
+#[derive(Debug, Parser)]
+#[command(
+    about = "Watch cargo home size and run trim policy whenever it exceeds threshold",
+    arg_required_else_help = true
+)]
+pub(crate) struct Watch {
+    #[arg(
+        long = "interval",
+        short = 'i',
+        help = "Duration between size checks. Supported units are s, m, h, d and w",
+        value_name = "duration",
+        default_value = "1h",
+        value_parser = parse_duration
+    )]
+    interval: Duration,
+    #[arg(
+        long = "once",
+        help = "Check size only once and exit so watch can be invoked periodically by cron or \
+                systemd timer"
+    )]
+    once: bool,
+    #[arg(
+        long = "threshold",
+        short = 't',
+        help = "Cargo home size such as 10GB above which trim policy is run",
+        value_name = "size",
+        value_parser = parse_size
+    )]
+    threshold: u64,
+    #[arg(
+        last = true,
+        help = "Arguments passed to cargo trim when threshold is exceeded such as -- --old-orphan. \
+                Defaults to --keep-under <threshold>",
+        value_name = "policy"
+    )]
+    policy: Vec<String>,
+}
+
+impl Watch {
+    pub(super) fn run(&self, dir_path: &DirPath, profile: Option<&str>) -> Result<()> {
+        let threshold = self.threshold;
+        let policy = if self.policy.is_empty() {
+            vec![String::from("--keep-under"), threshold.to_string()]
+        } else {
+            self.policy.clone()
+        };
+        let child_args = child_args(&policy, dir_path.cargo_home(), profile)?;
+        let cargo_trim = std::env::current_exe().context("Failed to get cargo trim executable")?;
+        loop {
+            let cargo_home_size = get_size(dir_path.cargo_home()).unwrap_or(0);
+            let checked_at = format_timestamp(std::time::SystemTime::now());
+            if cargo_home_size > threshold {
+                human_println!(
+                    "{}",
+                    format!(
+                        "[{checked_at}] Cargo home size {} exceeds {}, running cargo trim {}",
+                        convert_pretty(cargo_home_size).trim(),
+                        convert_pretty(threshold).trim(),
+                        policy.join(" ")
+                    )
+                    .yellow()
+                );
+                // policy run in separate process so each run scans cargo home freshly
+                let status = std::process::Command::new(&cargo_trim)
+                    .args(&child_args)
+                    .status()
+                    .context("Failed to run trim policy")?;
+                if !status.success() {
+                    human_println!(
+                        "{}",
+                        format!("WARNING: Trim policy exited with {status}").yellow()
+                    );
+                }
+            } else {
+                human_println!(
+                    "[{checked_at}] Cargo home size {} is under {}",
+                    convert_pretty(cargo_home_size).trim(),
+                    convert_pretty(threshold).trim()
+                );
+            }
+            if self.once {
+                return Ok(());
+            }
+            std::thread::sleep(self.interval);
+        }
+    }
+}
+
+// arguments of trim policy process. Watched cargo home and profile are
+// forwarded so policy runs on same cargo home which was watched. Cargo home of
+// podman volume and distrobox container is already resolved to its path
+fn child_args(
+    policy: &[String],
+    cargo_home: &Path,
+    profile: Option<&str>,
+) -> Result<Vec<OsString>> {
+    if let Some(arg) = policy.iter().find(|arg| {
+        [
+            "--cargo-home",
+            "--podman-volume",
+            "--distrobox",
+            "--profile",
+        ]
+        .iter()
+        .any(|option| *arg == option || arg.starts_with(&format!("{option}=")))
+    }) {
+        anyhow::bail!(
+            "{arg} cannot be used in trim policy, pass it before watch subcommand instead"
+        );
+    }
+    let mut args = vec![OsString::from("--cargo-home"), cargo_home.into()];
+    if let Some(profile) = profile {
+        args.push(OsString::from("--profile"));
+        args.push(OsString::from(profile));
+    }
+    args.extend(policy.iter().map(OsString::from));
+    Ok(args)
+}
+
+#[cfg(test)]
+mod test {
+    use std::ffi::OsString;
+    use std::path::Path;
+
+    use super::child_args;
+
+    #[test]
+    fn test_child_args() {
+        let policy = vec![String::from("--old-orphan")];
+        assert_eq!(
+            child_args(&policy, Path::new("/volume/cargo"), Some("ci")).unwrap(),
+            [
+                "--cargo-home",
+                "/volume/cargo",
+                "--profile",
+                "ci",
+                "--old-orphan"
+            ]
+            .map(OsString::from)
+        );
+        assert_eq!(
+            child_args(&policy, Path::new("/home/cargo"), None).unwrap(),
+            ["--cargo-home", "/home/cargo", "--old-orphan"].map(OsString::from)
+        );
+        let policy = vec![String::from("--distrobox=dev"), String::from("--old")];
+        assert!(child_args(&policy, Path::new("/home/cargo"), None).is_err());
+    }
+}
@@ -0,0 +1,110 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+
+use crate::dir_path::DirPath;
+use crate::scan_snapshot::cache_dir_modified;
+use crate::utils::split_name_version;
+
+/// Names of cached registry and git crates used for shell completion. Index is
+/// rebuilt only when cache directories are modified so completion do not wait
+/// for scan of whole cargo home
+#[derive(Serialize, Deserialize, Default)]
+pub(crate) struct CompletionIndex {
+    stamp: Vec<String>,
+    registry: BTreeSet<String>,
+    git: BTreeSet<String>,
+}
+
+impl CompletionIndex {
+    /// load index from file or rebuild it when it is missing or stale. Failure
+    /// to save rebuilt index is ignored since completion must not fail
+    pub(crate) fn load(dir_path: &DirPath) -> Self {
+        let stamp = cache_dir_stamp(dir_path);
+        let saved = fs::read_to_string(dir_path.completion_file())
+            .ok()
+            .and_then(|content| toml::from_str::<Self>(&content).ok());
+        if let Some(index) = saved {
+            if index.stamp == stamp {
+                return index;
+            }
+        }
+        let index = Self::build(dir_path, stamp);
+        if let Ok(content) = toml::to_string(&index) {
+            fs::write(dir_path.completion_file(), content).ok();
+        }
+        index
+    }
+
+    // list name and name-version of registry crates along with folder name of
+    // git crates
+    fn build(dir_path: &DirPath, stamp: Vec<String>) -> Self {
+        let mut index = Self {
+            stamp,
+            ..Self::default()
+        };
+        for parent in [dir_path.cache_dir(), dir_path.src_dir()] {
+            for registry in entry_names(parent) {
+                for full_name in entry_names(&parent.join(registry)) {
+                    let full_name = full_name.trim_end_matches(".crate");
+                    if let Ok((name, _)) = split_name_version(full_name) {
+                        index.registry.insert(name);
+                        index.registry.insert(full_name.to_string());
+                    }
+                }
+            }
+        }
+        for parent in [dir_path.checkout_dir(), dir_path.db_dir()] {
+            index.git.extend(entry_names(parent));
+        }
+        index
+    }
+
+    /// registry crate names starting with prefix
+    pub(crate) fn registry(&self, prefix: &str) -> Vec<&String> {
+        starting_with(&self.registry, prefix)
+    }
+
+    /// git crate names starting with prefix
+    pub(crate) fn git(&self, prefix: &str) -> Vec<&String> {
+        starting_with(&self.git, prefix)
+    }
+}
+
+// modified time of cache directories in form of path=seconds.nanoseconds
+fn cache_dir_stamp(dir_path: &DirPath) -> Vec<String> {
+    cache_dir_modified(dir_path)
+        .into_iter()
+        .map(|(directory, modified)| {
+            let modified = modified
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |duration| duration.as_nanos());
+            format!("{}={modified}", directory.display())
+        })
+        .collect()
+}
+
+// name of every entry of directory. Hidden entries such as lock and temporary
+// files are skipped
+fn entry_names(dir: &Path) -> Vec<String> {
+    fs::read_dir(dir).map_or_else(
+        |_| Vec::new(),
+        |entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .filter(|name| !name.starts_with('.'))
+                .collect()
+        },
+    )
+}
+
+fn starting_with<'a>(names: &'a BTreeSet<String>, prefix: &str) -> Vec<&'a String> {
+    names
+        .iter()
+        .filter(|name| name.starts_with(prefix))
+        .collect()
+}
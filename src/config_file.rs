@@ -1,17 +1,93 @@
+use std::collections::BTreeMap;
 use std::ffi::OsStr;
 use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use std::{env, fs};
 
 use anyhow::{Context, Result};
 use owo_colors::OwoColorize;
 use serde::{Deserialize, Serialize};
 
+use crate::environment::Environment;
+use crate::gitignore::Gitignore;
 use crate::list_crate::CargoTomlLocation;
+use crate::utils::{glob_match, parse_duration};
+
+// name of project local config file
+const PROJECT_CONFIG_FILE: &str = ".cargo-trim.toml";
+
+// keys known by config file, profile and project config
+const CONFIG_KEYS: [&str; 9] = [
+    "cargo_home",
+    "directory",
+    "environment",
+    "ignore_file_name",
+    "profile",
+    "protect",
+    "scan_hidden_folder",
+    "scan_target_folder",
+    "trash",
+];
+const PROFILE_KEYS: [&str; 7] = [
+    "directory",
+    "ignore_file_name",
+    "keep_versions",
+    "older_than",
+    "scan_hidden_folder",
+    "scan_target_folder",
+    "trash",
+];
+const PROJECT_KEYS: [&str; 5] = [
+    "directory",
+    "ignore_file_name",
+    "keep_versions",
+    "older_than",
+    "protect",
+];
+
+/// Stores named profile of config file. Value which is not set in profile is
+/// taken from top level config
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub(crate) struct ConfigProfile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    directory: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ignore_file_name: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scan_hidden_folder: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scan_target_folder: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trash: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keep_versions: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    older_than: Option<String>,
+}
+
+/// Stores project local config read from .cargo-trim.toml. Lists are added to
+/// config file lists and retention values override config file values. It is
+/// never saved to config file
+#[derive(Deserialize, Default, Clone)]
+pub(crate) struct ProjectConfig {
+    #[serde(default)]
+    directory: Vec<String>,
+    #[serde(default)]
+    ignore_file_name: Vec<String>,
+    #[serde(default)]
+    protect: Vec<String>,
+    keep_versions: Option<usize>,
+    older_than: Option<String>,
+    #[serde(skip)]
+    location: PathBuf,
+}
 
 /// Stores config file information
-#[derive(Serialize, Deserialize, Default)]
-pub(crate) struct ConfigFile {
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct ConfigFile {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    cargo_home: Vec<String>,
     #[serde(default)]
     directory: Vec<String>,
     #[serde(default)]
@@ -20,13 +96,34 @@ pub(crate) struct ConfigFile {
     scan_hidden_folder: bool,
     #[serde(default)]
     scan_target_folder: bool,
+    #[serde(default)]
+    trash: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    protect: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    profile: BTreeMap<String, ConfigProfile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    environment: Option<Environment>,
     #[serde(skip)]
     location: PathBuf,
+    #[serde(skip)]
+    active_profile: Option<(String, ConfigProfile)>,
+    #[serde(skip)]
+    keep_versions: Option<usize>,
+    #[serde(skip)]
+    older_than: Option<Duration>,
+    #[serde(skip)]
+    project: Option<ProjectConfig>,
+    #[serde(skip)]
+    project_older_than: Option<Duration>,
 }
 
 impl ConfigFile {
     /// Perform initial config file actions
-    pub(crate) fn init(config_file: &Path) -> Result<Self> {
+    ///
+    /// # Errors
+    /// Return error if config file cannot be read or parsed
+    pub fn init(config_file: &Path) -> Result<Self> {
         let mut buffer = String::new();
         let mut file = fs::File::open(config_file).context("failed to open config file")?;
         file.read_to_string(&mut buffer)
@@ -43,14 +140,134 @@ impl ConfigFile {
         Ok(deserialize_config)
     }
 
-    /// return vector of directory value in config file
-    pub(crate) fn directory(&self) -> &Vec<String> {
-        &self.directory
+    /// use named profile of config file. Values present in profile override
+    /// top level config values and change made to config are saved to profile
+    pub(crate) fn use_profile(&mut self, name: &str) -> Result<()> {
+        let profile = self
+            .profile
+            .get(name)
+            .cloned()
+            .with_context(|| format!("Failed to find profile {name:?} in config file"))?;
+        // store top level value so it can be restored while saving config
+        let base = ConfigProfile {
+            directory: Some(self.directory.clone()),
+            ignore_file_name: Some(self.ignore_file_name.clone()),
+            scan_hidden_folder: Some(self.scan_hidden_folder),
+            scan_target_folder: Some(self.scan_target_folder),
+            trash: Some(self.trash),
+            keep_versions: None,
+            older_than: None,
+        };
+        if let Some(directory) = &profile.directory {
+            self.directory.clone_from(directory);
+        }
+        if let Some(ignore_file_name) = &profile.ignore_file_name {
+            self.ignore_file_name.clone_from(ignore_file_name);
+        }
+        if let Some(scan_hidden_folder) = profile.scan_hidden_folder {
+            self.scan_hidden_folder = scan_hidden_folder;
+        }
+        if let Some(scan_target_folder) = profile.scan_target_folder {
+            self.scan_target_folder = scan_target_folder;
+        }
+        if let Some(trash) = profile.trash {
+            self.trash = trash;
+        }
+        self.keep_versions = profile.keep_versions;
+        self.older_than = profile
+            .older_than
+            .as_deref()
+            .map(parse_duration)
+            .transpose()
+            .with_context(|| format!("Invalid older_than value in profile {name:?}"))?;
+        self.active_profile = Some((name.to_string(), base));
+        Ok(())
+    }
+
+    /// use project local config file .cargo-trim.toml found in directory or its
+    /// nearest parent. Relative directory of project config is resolved from
+    /// folder containing project config
+    pub(crate) fn use_project_config(&mut self, directory: &Path) -> Result<()> {
+        let Some(location) = directory
+            .ancestors()
+            .map(|ancestor| ancestor.join(PROJECT_CONFIG_FILE))
+            .find(|location| location.is_file())
+        else {
+            return Ok(());
+        };
+        let content = fs::read_to_string(&location).with_context(|| {
+            format!("Failed to read project config file {}", location.display())
+        })?;
+        let mut project: ProjectConfig = toml::from_str(&content).with_context(|| {
+            format!("Failed to parse project config file {}", location.display())
+        })?;
+        let project_root = location
+            .parent()
+            .context("Failed to get parent of project config file")?;
+        for directory in &mut project.directory {
+            let path = project_root
+                .join(&directory)
+                .components()
+                .collect::<PathBuf>();
+            *directory = path.to_string_lossy().to_string();
+        }
+        self.project_older_than = project
+            .older_than
+            .as_deref()
+            .map(parse_duration)
+            .transpose()
+            .with_context(|| format!("Invalid older_than value in {}", location.display()))?;
+        project.location = location;
+        self.project = Some(project);
+        Ok(())
+    }
+
+    /// location of project config file in use
+    pub(crate) fn project_config_location(&self) -> Option<&Path> {
+        self.project
+            .as_ref()
+            .map(|project| project.location.as_path())
+    }
+
+    /// number of newest versions of registry crates kept by active profile
+    pub(crate) fn keep_versions(&self) -> Option<usize> {
+        self.keep_versions
+    }
+
+    /// number of newest versions of registry crates kept by project config
+    pub(crate) fn project_keep_versions(&self) -> Option<usize> {
+        self.project
+            .as_ref()
+            .and_then(|project| project.keep_versions)
+    }
+
+    /// duration after which crates are cleaned by active profile or project
+    /// config
+    pub(crate) fn older_than(&self) -> Option<Duration> {
+        self.older_than.or(self.project_older_than)
     }
 
-    /// return vector of ignore file name value in config file
-    pub(crate) fn ignore_file_name(&self) -> &Vec<String> {
-        &self.ignore_file_name
+    /// environment detected during first run
+    pub(crate) fn environment(&self) -> Option<&Environment> {
+        self.environment.as_ref()
+    }
+
+    /// store detected environment in config file
+    pub(crate) fn set_environment(&mut self, environment: Environment) -> Result<()> {
+        self.environment = Some(environment);
+        self.save()
+    }
+
+    /// return vector of directory value in config file along with directory of
+    /// project config
+    pub(crate) fn directory(&self) -> Vec<String> {
+        self.with_project(&self.directory, |project| &project.directory)
+    }
+
+    /// return vector of ignore file name value in config file along with
+    /// ignore file name of project config
+    pub(crate) fn ignore_file_name(&self) -> Vec<String> {
+        self.with_project(&self.ignore_file_name, |project| &project.ignore_file_name)
     }
 
     /// scan hidden folder
@@ -63,6 +280,39 @@ impl ConfigFile {
         self.scan_target_folder
     }
 
+    /// move deleted file and folder to trash
+    pub(crate) fn trash(&self) -> bool {
+        self.trash
+    }
+
+    /// list of crate name or name@version which are never removed including
+    /// crates protected by project config
+    pub(crate) fn protect(&self) -> Vec<String> {
+        self.with_project(&self.protect, |project| &project.protect)
+    }
+
+    // append project config list to config file list
+    fn with_project(
+        &self,
+        values: &[String],
+        project_values: impl Fn(&ProjectConfig) -> &Vec<String>,
+    ) -> Vec<String> {
+        let mut values = values.to_vec();
+        if let Some(project) = &self.project {
+            for value in project_values(project) {
+                if !values.contains(value) {
+                    values.push(value.clone());
+                }
+            }
+        }
+        values
+    }
+
+    /// list of cargo home which are trimmed in one run
+    pub(crate) fn cargo_home(&self) -> &Vec<String> {
+        &self.cargo_home
+    }
+
     /// Set scan hidden folder to value
     pub(crate) fn set_scan_hidden_folder(
         &mut self,
@@ -71,7 +321,7 @@ impl ConfigFile {
         save: bool,
     ) -> Result<()> {
         if dry_run {
-            println!(
+            human_println!(
                 "{} Set scan_hidden_folder to {value:?}",
                 "Dry run:".yellow(),
             );
@@ -80,7 +330,7 @@ impl ConfigFile {
             if save {
                 self.save()?;
             }
-            println!("Set scan_hidden_folder to {value:?}");
+            human_println!("Set scan_hidden_folder to {value:?}");
         }
         Ok(())
     }
@@ -93,7 +343,7 @@ impl ConfigFile {
         save: bool,
     ) -> Result<()> {
         if dry_run {
-            println!(
+            human_println!(
                 "{} Set scan_target_folder to {value:?}",
                 "Dry run:".yellow(),
             );
@@ -102,7 +352,21 @@ impl ConfigFile {
             if save {
                 self.save()?;
             }
-            println!("Set scan_target_folder to {value:?}");
+            human_println!("Set scan_target_folder to {value:?}");
+        }
+        Ok(())
+    }
+
+    /// Set trash to value
+    pub(crate) fn set_trash(&mut self, value: bool, dry_run: bool, save: bool) -> Result<()> {
+        if dry_run {
+            human_println!("{} Set trash to {value:?}", "Dry run:".yellow());
+        } else {
+            self.trash = value;
+            if save {
+                self.save()?;
+            }
+            human_println!("Set trash to {value:?}");
         }
         Ok(())
     }
@@ -110,13 +374,29 @@ impl ConfigFile {
     /// add directory
     pub(crate) fn add_directory(&mut self, path: &str, dry_run: bool, save: bool) -> Result<()> {
         if dry_run {
-            println!("{} Added {path:?}", "Dry run:".yellow());
+            human_println!("{} Added {path:?}", "Dry run:".yellow());
         } else {
             self.directory.push(path.to_string());
             if save {
                 self.save()?;
             }
-            println!("{} {path:?}", "Added".red());
+            human_println!("{} {path:?}", "Added".red());
+        }
+        Ok(())
+    }
+
+    /// add cargo home
+    pub(crate) fn add_cargo_home(&mut self, path: &str, dry_run: bool, save: bool) -> Result<()> {
+        if dry_run {
+            human_println!("{} Added {path:?}", "Dry run:".yellow());
+        } else {
+            if !self.cargo_home.iter().any(|data| data == path) {
+                self.cargo_home.push(path.to_string());
+            }
+            if save {
+                self.save()?;
+            }
+            human_println!("{} {path:?}", "Added".red());
         }
         Ok(())
     }
@@ -129,13 +409,13 @@ impl ConfigFile {
         save: bool,
     ) -> Result<()> {
         if dry_run {
-            println!("{} Added {file_name:?}", "Dry run:".yellow());
+            human_println!("{} Added {file_name:?}", "Dry run:".yellow());
         } else {
             self.ignore_file_name.push(file_name.to_string());
             if save {
                 self.save()?;
             }
-            println!("{} {file_name:?}", "Added".red());
+            human_println!("{} {file_name:?}", "Added".red());
         }
         Ok(())
     }
@@ -143,13 +423,32 @@ impl ConfigFile {
     /// remove directory
     pub(crate) fn remove_directory(&mut self, path: &str, dry_run: bool, save: bool) -> Result<()> {
         if dry_run {
-            println!("{} {} {path:?}", "Dry run:".yellow(), "Removed".red());
+            human_println!("{} {} {path:?}", "Dry run:".yellow(), "Removed".red());
         } else {
             self.directory.retain(|data| data != path);
             if save {
                 self.save()?;
             }
-            println!("{} {path:?}", "Removed".red());
+            human_println!("{} {path:?}", "Removed".red());
+        }
+        Ok(())
+    }
+
+    /// remove cargo home
+    pub(crate) fn remove_cargo_home(
+        &mut self,
+        path: &str,
+        dry_run: bool,
+        save: bool,
+    ) -> Result<()> {
+        if dry_run {
+            human_println!("{} {} {path:?}", "Dry run:".yellow(), "Removed".red());
+        } else {
+            self.cargo_home.retain(|data| data != path);
+            if save {
+                self.save()?;
+            }
+            human_println!("{} {path:?}", "Removed".red());
         }
         Ok(())
     }
@@ -162,13 +461,67 @@ impl ConfigFile {
         save: bool,
     ) -> Result<()> {
         if dry_run {
-            println!("{} {} {file_name:?}", "Dry run:".yellow(), "Removed".red(),);
+            human_println!("{} {} {file_name:?}", "Dry run:".yellow(), "Removed".red());
         } else {
             self.ignore_file_name.retain(|data| data != file_name);
             if save {
                 self.save()?;
             }
-            println!("{} {file_name:?}", "Removed".red());
+            human_println!("{} {file_name:?}", "Removed".red());
+        }
+        Ok(())
+    }
+
+    /// add crate to protect list
+    pub(crate) fn add_protect(&mut self, entry: &str, dry_run: bool, save: bool) -> Result<()> {
+        if dry_run {
+            human_println!("{} Protected {entry:?}", "Dry run:".yellow());
+        } else {
+            if !self.protect.iter().any(|data| data == entry) {
+                self.protect.push(entry.to_string());
+            }
+            if save {
+                self.save()?;
+            }
+            human_println!("{} {entry:?}", "Protected".green());
+        }
+        Ok(())
+    }
+
+    /// remove crate from protect list
+    pub(crate) fn remove_protect(&mut self, entry: &str, dry_run: bool, save: bool) -> Result<()> {
+        if dry_run {
+            human_println!("{} {} {entry:?}", "Dry run:".yellow(), "Unprotected".red());
+        } else {
+            self.protect.retain(|data| data != entry);
+            if save {
+                self.save()?;
+            }
+            human_println!("{} {entry:?}", "Unprotected".red());
+        }
+        Ok(())
+    }
+
+    /// set retention values of named profile. Profile is created if it does not
+    /// exist
+    pub(crate) fn set_profile_retention(
+        &mut self,
+        name: &str,
+        keep_versions: Option<usize>,
+        older_than: Option<&str>,
+        dry_run: bool,
+        save: bool,
+    ) -> Result<()> {
+        if dry_run {
+            human_println!("{} Set retention of profile {name:?}", "Dry run:".yellow());
+        } else {
+            let profile = self.profile.entry(name.to_string()).or_default();
+            profile.keep_versions = keep_versions;
+            profile.older_than = older_than.map(ToString::to_string);
+            if save {
+                self.save()?;
+            }
+            human_println!("Set retention of profile {name:?}");
         }
         Ok(())
     }
@@ -176,18 +529,30 @@ impl ConfigFile {
     /// List out cargo.toml file present directories by recursively analyze all
     /// folder present in directory
     pub(crate) fn list_cargo_toml(&self, path: &Path) -> Result<CargoTomlLocation> {
+        self.list_cargo_toml_with_gitignore(path, &Gitignore::default())
+    }
+
+    // list cargo.toml location of directory. Folder ignored by .gitignore of
+    // directory or any of its parent till registered directory is skipped
+    fn list_cargo_toml_with_gitignore(
+        &self,
+        path: &Path,
+        gitignore: &Gitignore,
+    ) -> Result<CargoTomlLocation> {
         let mut cargo_trim_list = CargoTomlLocation::new();
         if path.exists() {
             if path.is_dir() {
+                let gitignore = gitignore.with_dir(path);
                 for entry in std::fs::read_dir(path)
                     .context("failed to read directory while trying to find cargo.toml")?
                 {
                     let sub = entry?.path();
+                    crate::utils::throttle_io();
                     if sub.is_dir() {
-                        if self.need_to_be_ignored(path)? {
+                        if self.need_to_be_ignored(&sub, &gitignore)? {
                             continue;
                         }
-                        let kids_list = self.list_cargo_toml(&sub)?;
+                        let kids_list = self.list_cargo_toml_with_gitignore(&sub, &gitignore)?;
                         cargo_trim_list.append(kids_list);
                     }
                     if sub.is_file() && sub.file_name() == Some(OsStr::new("Cargo.toml")) {
@@ -201,14 +566,18 @@ impl ConfigFile {
         Ok(cargo_trim_list)
     }
 
-    /// check if directory should be scanned for listing crates or not
-    fn need_to_be_ignored(&self, path: &Path) -> Result<bool> {
+    /// check if directory should be scanned for listing crates or not. Target
+    /// folder is scanned when enabled even if it is listed in .gitignore
+    fn need_to_be_ignored(&self, path: &Path, gitignore: &Gitignore) -> Result<bool> {
         let file_name = path
             .file_name()
             .context("Failed to get need to be ignored path file name")?
-            .to_str()
-            .context("Failed to convert folder name Osstr to str")?;
-        if self.ignore_file_name().contains(&file_name.to_owned()) {
+            .to_string_lossy();
+        if self
+            .ignore_file_name()
+            .iter()
+            .any(|pattern| glob_match(pattern, &file_name))
+        {
             return Ok(true);
         }
         if file_name.starts_with('.') && !self.scan_hidden_folder() {
@@ -217,16 +586,393 @@ impl ConfigFile {
         let target_dir_name = env::var("CARGO_BUILD_TARGET_DIR").unwrap_or_else(|_| {
             env::var("CARGO_TARGET_DIR").unwrap_or_else(|_| String::from("target"))
         });
-        Ok(file_name == target_dir_name && !self.scan_target_folder())
+        if file_name == target_dir_name {
+            return Ok(!self.scan_target_folder());
+        }
+        Ok(gitignore.is_ignored(path, true))
+    }
+
+    /// check config file and project config for unknown keys, registered paths
+    /// which do not exist or cannot be read and conflicting retention. Return
+    /// list of problems along with way to fix them
+    pub(crate) fn validate(&self) -> Result<Vec<String>> {
+        let mut problems = Vec::new();
+        let content = fs::read_to_string(&self.location).context("failed to read config file")?;
+        let value: toml::Value =
+            toml::from_str(&content).context("failed to convert string to Config")?;
+        unknown_keys(&value, &CONFIG_KEYS, "config file", &mut problems);
+        if let Some(profiles) = value.get("profile").and_then(toml::Value::as_table) {
+            for (name, profile) in profiles {
+                unknown_keys(
+                    profile,
+                    &PROFILE_KEYS,
+                    &format!("profile {name:?}"),
+                    &mut problems,
+                );
+            }
+        }
+        for directory in &self.directory {
+            if let Some(problem) = path_problem(directory) {
+                problems.push(format!(
+                    "Directory {directory:?} {problem}. Remove it with cargo trim unset -d \
+                     {directory:?}"
+                ));
+            }
+        }
+        for cargo_home in &self.cargo_home {
+            if let Some(problem) = path_problem(cargo_home) {
+                problems.push(format!(
+                    "Cargo home {cargo_home:?} {problem}. Remove it with cargo trim unset \
+                     --cargo-home {cargo_home:?}"
+                ));
+            }
+        }
+        if let Some(project) = &self.project {
+            let location = project.location.display();
+            let content = fs::read_to_string(&project.location)
+                .with_context(|| format!("Failed to read project config file {location}"))?;
+            let value: toml::Value = toml::from_str(&content)
+                .with_context(|| format!("Failed to parse project config file {location}"))?;
+            unknown_keys(&value, &PROJECT_KEYS, &location.to_string(), &mut problems);
+            for directory in &project.directory {
+                if let Some(problem) = path_problem(directory) {
+                    problems.push(format!(
+                        "Directory {directory:?} of {location} {problem}. Remove it from project \
+                         config"
+                    ));
+                }
+            }
+        }
+        self.retention_problems(&mut problems);
+        let directories = self.directory();
+        for directory in &directories {
+            let path = Path::new(directory);
+            if let Some(parent) = directories
+                .iter()
+                .find(|parent| *parent != directory && path.starts_with(parent))
+            {
+                problems.push(format!(
+                    "Directory {directory:?} is inside directory {parent:?} and is scanned twice. \
+                     Remove one of them"
+                ));
+            }
+            let file_name = path
+                .file_name()
+                .map(OsStr::to_string_lossy)
+                .unwrap_or_default();
+            if let Some(pattern) = self
+                .ignore_file_name()
+                .iter()
+                .find(|pattern| glob_match(pattern, &file_name))
+            {
+                problems.push(format!(
+                    "Directory {directory:?} is never scanned since it matches ignore file name \
+                     {pattern:?}"
+                ));
+            }
+        }
+        for entry in self.protect() {
+            if let Some((name, version)) = entry.split_once('@') {
+                if semver::Version::parse(version).is_err() {
+                    problems.push(format!(
+                        "Protect entry {entry:?} has invalid version. Use {name:?} or \
+                         name@version such as {name}@1.0.0"
+                    ));
+                }
+            }
+        }
+        Ok(problems)
+    }
+
+    // report retention which removes every version, cannot be parsed or is
+    // overridden by other retention
+    fn retention_problems(&self, problems: &mut Vec<String>) {
+        for (name, profile) in &self.profile {
+            if profile.keep_versions == Some(0) {
+                problems.push(format!(
+                    "keep_versions of profile {name:?} is 0 which removes every version. Use at \
+                     least 1"
+                ));
+            }
+            if let Some(Err(err)) = profile.older_than.as_deref().map(parse_duration) {
+                problems.push(format!(
+                    "older_than of profile {name:?} is invalid: {err:#}"
+                ));
+            }
+        }
+        if let Some(project) = &self.project {
+            let location = project.location.display();
+            if project.keep_versions == Some(0) {
+                problems.push(format!(
+                    "keep_versions of {location} is 0 which removes every version. Use at least 1"
+                ));
+            }
+            if let (Some(keep_versions), Some(project_keep_versions)) =
+                (self.keep_versions, project.keep_versions)
+            {
+                if keep_versions != project_keep_versions {
+                    problems.push(format!(
+                        "keep_versions {project_keep_versions} of {location} is overridden by \
+                         keep_versions {keep_versions} of active profile"
+                    ));
+                }
+            }
+        }
+    }
+
+    /// convert config to toml content of config file. When profile is active
+    /// changed value are stored in profile and top level value are restored
+    pub(crate) fn to_toml(&self) -> Result<String> {
+        let mut config = self.clone();
+        if let Some((name, base)) = &self.active_profile {
+            let profile = config.profile.entry(name.clone()).or_default();
+            let fields = [
+                (
+                    &mut profile.directory,
+                    &self.directory,
+                    base.directory.as_ref(),
+                ),
+                (
+                    &mut profile.ignore_file_name,
+                    &self.ignore_file_name,
+                    base.ignore_file_name.as_ref(),
+                ),
+            ];
+            for (profile_value, value, base_value) in fields {
+                if profile_value.is_some() || Some(value) != base_value {
+                    *profile_value = Some(value.clone());
+                }
+            }
+            let fields = [
+                (
+                    &mut profile.scan_hidden_folder,
+                    self.scan_hidden_folder,
+                    base.scan_hidden_folder,
+                ),
+                (
+                    &mut profile.scan_target_folder,
+                    self.scan_target_folder,
+                    base.scan_target_folder,
+                ),
+                (&mut profile.trash, self.trash, base.trash),
+            ];
+            for (profile_value, value, base_value) in fields {
+                if profile_value.is_some() || Some(value) != base_value {
+                    *profile_value = Some(value);
+                }
+            }
+            config.directory = base.directory.clone().unwrap_or_default();
+            config.ignore_file_name = base.ignore_file_name.clone().unwrap_or_default();
+            config.scan_hidden_folder = base.scan_hidden_folder.unwrap_or_default();
+            config.scan_target_folder = base.scan_target_folder.unwrap_or_default();
+            config.trash = base.trash.unwrap_or_default();
+        }
+        toml::to_string_pretty(&config).context("Config cannot to converted to pretty toml")
     }
 
     /// save struct in the config file
     fn save(&self) -> Result<()> {
         let mut buffer = String::new();
-        let serialized =
-            toml::to_string_pretty(&self).context("Config cannot to converted to pretty toml")?;
+        let serialized = self.to_toml()?;
         buffer.push_str(&serialized);
         fs::write(&self.location, buffer).context("Failed to write a value to config file")?;
         Ok(())
     }
 }
+
+// report keys of toml table which are not known
+fn unknown_keys(value: &toml::Value, known: &[&str], source: &str, problems: &mut Vec<String>) {
+    if let Some(table) = value.as_table() {
+        for key in table.keys() {
+            if !known.contains(&key.as_str()) {
+                problems.push(format!(
+                    "Unknown key {key:?} in {source}. Known keys are {}",
+                    known.join(", ")
+                ));
+            }
+        }
+    }
+}
+
+// problem of registered path which is missing or cannot be read
+fn path_problem(path: &str) -> Option<String> {
+    let path = Path::new(path);
+    if !path.exists() {
+        Some(String::from("does not exist"))
+    } else if !path.is_dir() {
+        Some(String::from("is not a directory"))
+    } else if let Err(err) = fs::read_dir(path) {
+        Some(format!("cannot be read ({err})"))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::ConfigFile;
+
+    #[test]
+    fn test_use_profile() {
+        let mut config_file: ConfigFile = toml::from_str(
+            r#"
+directory = ["/home/user/project"]
+scan_hidden_folder = true
+
+[profile.ci]
+directory = ["/builds"]
+keep_versions = 1
+older_than = "7d"
+"#,
+        )
+        .unwrap();
+        config_file.use_profile("ci").unwrap();
+        assert_eq!(config_file.directory(), vec!["/builds".to_string()]);
+        assert!(config_file.scan_hidden_folder());
+        assert_eq!(config_file.keep_versions(), Some(1));
+        assert_eq!(config_file.older_than(), Some(Duration::from_hours(168)));
+        assert!(config_file.use_profile("unknown").is_err());
+
+        // change made while profile is active is saved to profile only
+        config_file
+            .ignore_file_name
+            .push("node_modules".to_string());
+        let saved: ConfigFile = toml::from_str(&config_file.to_toml().unwrap()).unwrap();
+        assert_eq!(saved.directory(), vec!["/home/user/project".to_string()]);
+        assert!(saved.ignore_file_name().is_empty());
+        let profile = saved.profile.get("ci").unwrap();
+        assert_eq!(
+            profile.ignore_file_name,
+            Some(vec!["node_modules".to_string()])
+        );
+        assert_eq!(profile.scan_hidden_folder, None);
+    }
+
+    #[test]
+    fn test_project_config() {
+        let project_dir =
+            std::env::temp_dir().join(format!("cargo_trim_project_config_{}", std::process::id()));
+        let nested_dir = project_dir.join("src");
+        std::fs::create_dir_all(&nested_dir).unwrap();
+        std::fs::write(
+            project_dir.join(".cargo-trim.toml"),
+            r#"
+directory = ["examples"]
+ignore_file_name = ["node_modules"]
+protect = ["openssl-sys"]
+keep_versions = 2
+older_than = "1d"
+"#,
+        )
+        .unwrap();
+        let mut config_file: ConfigFile = toml::from_str(
+            r#"
+directory = ["/home/user/project"]
+protect = ["serde"]
+"#,
+        )
+        .unwrap();
+        config_file.use_project_config(&nested_dir).unwrap();
+        assert_eq!(
+            config_file.directory(),
+            vec![
+                "/home/user/project".to_string(),
+                project_dir.join("examples").to_string_lossy().to_string()
+            ]
+        );
+        assert_eq!(
+            config_file.ignore_file_name(),
+            vec!["node_modules".to_string()]
+        );
+        assert_eq!(
+            config_file.protect(),
+            vec!["serde".to_string(), "openssl-sys".to_string()]
+        );
+        assert_eq!(config_file.project_keep_versions(), Some(2));
+        assert_eq!(config_file.older_than(), Some(Duration::from_hours(24)));
+
+        // project config is never saved to config file
+        let saved: ConfigFile = toml::from_str(&config_file.to_toml().unwrap()).unwrap();
+        assert_eq!(saved.directory(), vec!["/home/user/project".to_string()]);
+        assert!(saved.ignore_file_name().is_empty());
+        std::fs::remove_dir_all(project_dir).unwrap();
+    }
+
+    #[test]
+    fn test_list_cargo_toml() {
+        let root =
+            std::env::temp_dir().join(format!("cargo_trim_list_cargo_toml_{}", std::process::id()));
+        for project in [
+            "app",
+            "workspace",
+            "workspace/member",
+            "ignored",
+            "target/app",
+        ] {
+            std::fs::create_dir_all(root.join(project)).unwrap();
+            std::fs::write(root.join(project).join("Cargo.toml"), "").unwrap();
+        }
+        std::fs::write(root.join(".gitignore"), "/ignored\n").unwrap();
+        let config_file = ConfigFile::default();
+        let mut location = config_file
+            .list_cargo_toml(&root)
+            .unwrap()
+            .location_path()
+            .clone();
+        location.sort();
+        assert_eq!(
+            location,
+            vec![
+                root.join("app"),
+                root.join("workspace"),
+                root.join("workspace/member")
+            ]
+        );
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_validate() {
+        let root = std::env::temp_dir().join(format!("cargo_trim_validate_{}", std::process::id()));
+        let project = root.join("project");
+        std::fs::create_dir_all(project.join("node_modules")).unwrap();
+        let location = root.join("config.toml");
+        std::fs::write(
+            &location,
+            format!(
+                r#"
+directory = [{project:?}, {nested:?}, {missing:?}]
+ignore_file_name = ["node_*"]
+protect = ["serde@latest"]
+scan_hiden_folder = true
+
+[profile.ci]
+keep_versions = 0
+older_than = "7x"
+"#,
+                project = project.to_string_lossy(),
+                nested = project.join("node_modules").to_string_lossy(),
+                missing = root.join("missing").to_string_lossy(),
+            ),
+        )
+        .unwrap();
+        let config_file = ConfigFile::init(&location).unwrap();
+        let problems = config_file.validate().unwrap();
+        let expected = [
+            "Unknown key \"scan_hiden_folder\"",
+            "missing\" does not exist",
+            "keep_versions of profile \"ci\" is 0",
+            "older_than of profile \"ci\" is invalid",
+            "node_modules\" is inside directory",
+            "node_modules\" is never scanned",
+            "Protect entry \"serde@latest\"",
+        ];
+        assert_eq!(problems.len(), expected.len());
+        for (problem, expected) in problems.iter().zip(expected) {
+            assert!(problem.contains(expected), "{problem}");
+        }
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,93 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
+
+/// resolve host side path of podman volume which is used as cargo home inside
+/// container
+pub(crate) fn podman_volume_home(volume: &str) -> Result<PathBuf> {
+    let mount_point = podman_output(&["volume", "inspect", "--format", "{{.Mountpoint}}", volume])
+        .context(format!("Failed to inspect podman volume {volume}"))?;
+    let mount_point = PathBuf::from(mount_point.trim());
+    if !mount_point.exists() {
+        bail!(
+            "Mount point {} of podman volume {volume} is not accessible from host",
+            mount_point.display()
+        );
+    }
+    Ok(mount_point)
+}
+
+/// resolve host side path of cargo home used inside distrobox container.
+/// Distrobox bind mount home directory of container from host so cargo home
+/// present inside container home is also present in host at same location
+pub(crate) fn distrobox_home(container: &str) -> Result<PathBuf> {
+    let env_list = podman_output(&[
+        "inspect",
+        "--type",
+        "container",
+        "--format",
+        "{{range .Config.Env}}{{println .}}{{end}}",
+        container,
+    ])
+    .context(format!("Failed to inspect distrobox container {container}"))?;
+    let cargo_home = cargo_home_from_env(&env_list).context(format!(
+        "Failed to find home directory of distrobox {container}"
+    ))?;
+    if !cargo_home.exists() {
+        bail!(
+            "Cargo home {} of distrobox {container} is not accessible from host",
+            cargo_home.display()
+        );
+    }
+    Ok(cargo_home)
+}
+
+// run podman command and return its stdout
+fn podman_output(args: &[&str]) -> Result<String> {
+    let output = std::process::Command::new("podman")
+        .args(args)
+        .output()
+        .context("Failed to execute podman command")?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    String::from_utf8(output.stdout).context("Failed to convert podman output to string")
+}
+
+// get cargo home from list of environment variable in form of KEY=VALUE.
+// CARGO_HOME takes precedence over .cargo folder inside HOME
+fn cargo_home_from_env(env_list: &str) -> Option<PathBuf> {
+    let mut home = None;
+    for line in env_list.lines() {
+        match line.split_once('=') {
+            Some(("CARGO_HOME", value)) if !value.is_empty() => {
+                return Some(PathBuf::from(value));
+            }
+            Some(("HOME", value)) if !value.is_empty() => {
+                home = Some(PathBuf::from(value).join(".cargo"));
+            }
+            _ => {}
+        }
+    }
+    home
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use super::cargo_home_from_env;
+
+    #[test]
+    fn test_cargo_home_from_env() {
+        assert_eq!(
+            cargo_home_from_env("PATH=/usr/bin\nHOME=/home/user/box\nTERM=xterm"),
+            Some(PathBuf::from("/home/user/box/.cargo"))
+        );
+        assert_eq!(
+            cargo_home_from_env("HOME=/home/user\nCARGO_HOME=/opt/cargo\n"),
+            Some(PathBuf::from("/opt/cargo"))
+        );
+        assert_eq!(cargo_home_from_env("PATH=/usr/bin\nHOME="), None);
+    }
+}
@@ -1,24 +1,37 @@
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 use std::default::Default;
+use std::ffi::{OsStr, OsString};
 use std::fs;
 use std::hash::Hash;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::thread::{self, ScopedJoinHandle};
+use std::time::{Duration, SystemTime};
 
 use anyhow::{Context, Result};
 use semver::Version;
 use serde::Deserialize;
 use url::Url;
 
-use crate::utils::{get_size, split_name_version};
+use crate::crate_id::{CrateId, GitId};
+use crate::dir_path::DirPath;
+use crate::size_cache::SizeCache;
+use crate::utils::{get_size, glob_match, last_used, split_name_version};
+
+/// extension of compressed tarball which store registry source archived by
+/// cargo trim archive next to its extracted directory
+pub(crate) const SOURCE_ARCHIVE_EXTENSION: &str = ".tar.gz";
 
 #[derive(Debug, Clone)]
-pub(crate) struct CrateMetaData {
+pub struct CrateMetaData {
     name: String,
     version: Option<Version>,
     size: u64,
     source: Option<Url>,
+    path: Vec<PathBuf>,
+    last_used: Option<SystemTime>,
+    git_id: Option<GitId>,
 }
 
 impl CrateMetaData {
@@ -33,23 +46,134 @@ impl CrateMetaData {
             version,
             size,
             source,
+            path: Vec::new(),
+            last_used: None,
+            git_id: None,
+        }
+    }
+
+    /// create metadata of git crate. Name of git crate is in form of name-rev
+    pub(crate) fn new_git(git_id: GitId, size: u64) -> Self {
+        Self {
+            name: git_id.to_string(),
+            version: None,
+            size,
+            source: Some(git_id.url().clone()),
+            path: Vec::new(),
+            last_used: None,
+            git_id: Some(git_id),
         }
     }
 
-    pub(crate) fn name(&self) -> &String {
+    #[must_use]
+    pub fn name(&self) -> &String {
         &self.name
     }
 
-    pub(crate) fn version(&self) -> &Option<Version> {
-        &self.version
+    #[must_use]
+    pub fn version(&self) -> Option<&Version> {
+        self.version.as_ref()
     }
 
-    pub(crate) fn size(&self) -> u64 {
+    #[must_use]
+    pub fn size(&self) -> u64 {
         self.size
     }
 
-    pub(crate) fn source(&self) -> &Option<Url> {
-        &self.source
+    #[must_use]
+    pub fn source(&self) -> Option<&Url> {
+        self.source.as_ref()
+    }
+
+    /// path of all file and folder where crate is stored
+    #[must_use]
+    pub fn path(&self) -> &Vec<PathBuf> {
+        &self.path
+    }
+
+    /// registry identity of crate. Return None for git and bin crates
+    pub(crate) fn crate_id(&self) -> Option<CrateId> {
+        Some(CrateId::new(
+            self.source.clone()?,
+            self.name.clone(),
+            self.version.clone()?,
+        ))
+    }
+
+    /// git identity of crate. Return None for registry and bin crates
+    pub(crate) fn git_id(&self) -> Option<GitId> {
+        self.git_id.clone()
+    }
+
+    /// name of registry or git crate without version or revision
+    pub(crate) fn base_name(&self) -> String {
+        self.crate_id().map_or_else(
+            || {
+                self.git_id()
+                    .map_or_else(|| self.name.clone(), |git_id| git_id.name().clone())
+            },
+            |crate_id| crate_id.name().clone(),
+        )
+    }
+
+    /// full name of crate with version. Git crate name already contains
+    /// revision
+    #[must_use]
+    pub fn full_name(&self) -> String {
+        match &self.version {
+            Some(version) => format!("{}-{version}", self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    /// check if crate matches name or name-version glob pattern. Git crate
+    /// name contains revision so only name part is compared
+    #[must_use]
+    pub fn matches(&self, pattern: &str) -> bool {
+        if let Some(crate_id) = self.crate_id() {
+            glob_match(pattern, crate_id.name()) || glob_match(pattern, &crate_id.to_string())
+        } else if let Some(git_id) = self.git_id() {
+            glob_match(pattern, git_id.name()) || glob_match(pattern, &git_id.to_string())
+        } else {
+            glob_match(pattern, &self.name)
+        }
+    }
+
+    /// check if crate is present in protect list. Entry is either crate name or
+    /// name@version where version is only compared for registry crate
+    pub(crate) fn is_protected(&self, protect: &[String]) -> bool {
+        protect.iter().any(|entry| {
+            let (name, version) = entry
+                .split_once('@')
+                .map_or((entry.as_str(), None), |(name, version)| {
+                    (name, Some(version))
+                });
+            if let Some(crate_id) = self.crate_id() {
+                crate_id.name() == name
+                    && version.is_none_or(|version| crate_id.version().to_string() == version)
+            } else if let Some(git_id) = self.git_id() {
+                version.is_none() && git_id.name() == name
+            } else {
+                false
+            }
+        })
+    }
+
+    /// latest modified or accessed time of files where crate is stored
+    #[must_use]
+    pub fn last_used(&self) -> Option<SystemTime> {
+        self.last_used
+    }
+
+    /// check if crate is not used within duration. Crate without any time
+    /// information is treated as unused
+    #[must_use]
+    pub fn is_unused_for(&self, duration: Duration) -> bool {
+        self.last_used.is_none_or(|last_used| {
+            SystemTime::now()
+                .duration_since(last_used)
+                .is_ok_and(|elapsed| elapsed > duration)
+        })
     }
 }
 
@@ -62,17 +186,13 @@ impl PartialOrd for CrateMetaData {
 impl Ord for CrateMetaData {
     fn cmp(&self, other: &Self) -> Ordering {
         match self.name.cmp(&other.name) {
-            Ordering::Equal => {
-                match self.source.cmp(&other.source) {
-                    Ordering::Equal => {
-                        match self.version.cmp(&other.version) {
-                            Ordering::Equal => self.size.cmp(&other.size),
-                            ord => ord,
-                        }
-                    }
+            Ordering::Equal => match self.source.cmp(&other.source) {
+                Ordering::Equal => match self.version.cmp(&other.version) {
+                    Ordering::Equal => self.size.cmp(&other.size),
                     ord => ord,
-                }
-            }
+                },
+                ord => ord,
+            },
             ord => ord,
         }
     }
@@ -102,27 +222,30 @@ struct IndexConfig {
 
 /// stores different crate size and name information
 #[derive(Default)]
-pub(crate) struct CrateDetail {
-    source_info: HashMap<String, Url>,
+pub struct CrateDetail {
+    source_info: HashMap<OsString, Url>,
     bin: HashSet<CrateMetaData>,
     git_crates_source: HashSet<CrateMetaData>,
     registry_crates_source: HashSet<CrateMetaData>,
     git_crates_archive: HashSet<CrateMetaData>,
     registry_crates_archive: HashSet<CrateMetaData>,
+    unparseable: Vec<PathBuf>,
+    size_cache: SizeCache,
 }
 
 impl CrateDetail {
     /// Crate new index info
-    pub(crate) fn new(index_dir: &Path, db_dir: &Path) -> Result<Self> {
+    ///
+    /// # Errors
+    /// Return error if index directory cannot be read
+    pub fn new(index_dir: &Path, db_dir: &Path, size_cache: SizeCache) -> Result<Self> {
         let mut source_info = HashMap::new();
         if index_dir.exists() {
             for entry in fs::read_dir(index_dir)? {
                 let registry_dir = entry?.path();
                 let registry_file_name = registry_dir
                     .file_name()
-                    .context("Failed to get file name of registry dir")?
-                    .to_str()
-                    .context("Failed to convert osstr to str")?;
+                    .context("Failed to get file name of registry dir")?;
                 let mut fetch_head_file = registry_dir.clone();
                 fetch_head_file.push(".git");
                 fetch_head_file.push("FETCH_HEAD");
@@ -135,12 +258,13 @@ impl CrateDetail {
                         .last()
                         .context("Failed to get url part from content")?;
                     source_info.insert(
-                        registry_file_name.to_string(),
+                        registry_file_name.to_os_string(),
                         Url::from_str(url_path).context("Fail FETCH_HEAD url conversion")?,
                     );
                 // Else it is based on sparse registry
                 } else {
-                    let domain = registry_file_name
+                    let lossy_file_name = registry_file_name.to_string_lossy();
+                    let domain = lossy_file_name
                         .rsplitn(2, '-')
                         .last()
                         .context("Failed to get url for sparse registry")?;
@@ -153,7 +277,7 @@ impl CrateDetail {
                     let scheme = scheme_url.scheme();
                     let url = Url::from_str(&format!("{scheme}://{domain}"))
                         .context("Failed sparse registry index url")?;
-                    source_info.insert(registry_file_name.to_string(), url);
+                    source_info.insert(registry_file_name.to_os_string(), url);
                 }
             }
         }
@@ -162,9 +286,7 @@ impl CrateDetail {
                 let git_dir = entry?.path();
                 let git_file_name = git_dir
                     .file_name()
-                    .context("Failed to get file name of git dir")?
-                    .to_str()
-                    .context("Failed to convert osstr to str")?;
+                    .context("Failed to get file name of git dir")?;
                 let mut fetch_head_file = git_dir.clone();
                 fetch_head_file.push("FETCH_HEAD");
                 let content = fs::read_to_string(fetch_head_file)
@@ -174,13 +296,14 @@ impl CrateDetail {
                     .last()
                     .context("Failed to get url part from content")?;
                 source_info.insert(
-                    git_file_name.to_string(),
+                    git_file_name.to_os_string(),
                     Url::from_str(url_path).context("Failed to convert db dir FETCH_HEAD")?,
                 );
             }
         }
         Ok(Self {
             source_info,
+            size_cache,
             ..Default::default()
         })
     }
@@ -189,9 +312,7 @@ impl CrateDetail {
     pub(crate) fn source_url_from_path(&self, path: &Path) -> Result<Url> {
         let file_name = path
             .file_name()
-            .context("Failed to get file name of path")?
-            .to_str()
-            .context("Failed to convert osstr to str")?;
+            .context("Failed to get file name of path")?;
         Ok(self
             .source_info
             .get(file_name)
@@ -199,31 +320,59 @@ impl CrateDetail {
             .clone())
     }
 
+    /// Get name of directory used by registry inside index, cache and src
+    /// directory. Lowest name is returned when registry use multiple directory
+    pub(crate) fn registry_dir_name(&self, registry: &Url) -> Option<&OsStr> {
+        self.source_info
+            .iter()
+            .filter(|(_, url)| *url == registry)
+            .map(|(name, _)| name.as_os_str())
+            .min()
+    }
+
     /// return bin crates metadata
-    pub(crate) fn bin(&self) -> &HashSet<CrateMetaData> {
+    #[must_use]
+    pub fn bin(&self) -> &HashSet<CrateMetaData> {
         &self.bin
     }
 
     /// return git crates source
-    pub(crate) fn git_crates_source(&self) -> &HashSet<CrateMetaData> {
+    #[must_use]
+    pub fn git_crates_source(&self) -> &HashSet<CrateMetaData> {
         &self.git_crates_source
     }
 
     /// return registry crates source metadata
-    pub(crate) fn registry_crates_source(&self) -> &HashSet<CrateMetaData> {
+    #[must_use]
+    pub fn registry_crates_source(&self) -> &HashSet<CrateMetaData> {
         &self.registry_crates_source
     }
 
     /// return git crates archive metadata
-    pub(crate) fn git_crates_archive(&self) -> &HashSet<CrateMetaData> {
+    #[must_use]
+    pub fn git_crates_archive(&self) -> &HashSet<CrateMetaData> {
         &self.git_crates_archive
     }
 
     /// return registry crates archive metadata
-    pub(crate) fn registry_crates_archive(&self) -> &HashSet<CrateMetaData> {
+    #[must_use]
+    pub fn registry_crates_archive(&self) -> &HashSet<CrateMetaData> {
         &self.registry_crates_archive
     }
 
+    /// return path of registry entries whose name and version cannot be parsed
+    pub(crate) fn unparseable(&self) -> &Vec<PathBuf> {
+        &self.unparseable
+    }
+
+    /// save size calculated during listing of crates to size cache file
+    ///
+    /// # Errors
+    /// Return error if size cache file cannot be written
+    pub fn save_size_cache(&mut self) -> Result<()> {
+        self.size_cache.save()
+    }
+
     /// add bin information to crate detail
     fn add_bin(&mut self, bin_metadata: &CrateMetaData) {
         self.bin.insert(bin_metadata.clone());
@@ -255,19 +404,23 @@ impl CrateDetail {
         if bin_dir.exists() {
             for entry in fs::read_dir(bin_dir).context("failed to read bin directory")? {
                 let entry = entry?.path();
-                let bin_size = get_size(&entry).context("failed to get size of bin directory")?;
+                let bin_size = self
+                    .size_cache
+                    .get_size(&entry)
+                    .context("failed to get size of bin directory")?;
                 let file_name = entry
                     .file_name()
                     .context("failed to get file name from bin directory")?;
-                let bin_name = file_name
-                    .to_str()
-                    .context("failed to convert file name of bin to str")?
-                    .to_string();
+                // name is only displayed while real path is used for removal
+                let bin_name = file_name.to_string_lossy().to_string();
                 let bin_metadata = CrateMetaData {
                     name: bin_name,
                     version: None,
                     size: bin_size,
                     source: None,
+                    path: vec![entry.clone()],
+                    last_used: last_used(&entry),
+                    git_id: None,
                 };
                 self.add_bin(&bin_metadata);
                 installed_bin.push(bin_metadata);
@@ -277,41 +430,54 @@ impl CrateDetail {
         Ok(installed_bin)
     }
 
-    /// list all installed registry crates
-    pub(crate) fn list_installed_crate_registry(
-        &mut self,
-        src_dir: &Path,
-        cache_dir: &Path,
-    ) -> Result<Vec<CrateMetaData>> {
+    /// list installed registry crates present in registry src directory
+    fn list_registry_source(&mut self, src_dir: &Path) -> Result<HashSet<CrateMetaData>> {
         let mut installed_crate_registry = HashSet::new();
-        // read src dir to get installed crate
         if src_dir.exists() {
             for entry in fs::read_dir(src_dir).context("failed to read src directory")? {
                 let registry = entry?.path();
                 let source = self.source_url_from_path(&registry)?;
                 for entry in fs::read_dir(registry).context("failed to read registry folder")? {
                     let entry = entry?.path();
-                    let crate_size =
-                        get_size(&entry).context("failed to get registry crate size")?;
+                    let crate_size = self
+                        .size_cache
+                        .get_size(&entry)
+                        .context("failed to get registry crate size")?;
                     let file_name = entry
                         .file_name()
                         .context("failed to get file name from main entry")?;
-                    let crate_name = file_name
-                        .to_str()
-                        .context("Failed to convert crate file name to str")?;
-                    let (name, version) = split_name_version(crate_name)?;
+                    // skip entry which cannot be parsed instead of failing whole listing.
+                    // Source archived by cargo trim belongs to same crate as its directory
+                    let Some(Ok((name, version))) = file_name.to_str().map(|file_name| {
+                        split_name_version(
+                            file_name
+                                .strip_suffix(SOURCE_ARCHIVE_EXTENSION)
+                                .unwrap_or(file_name),
+                        )
+                    }) else {
+                        self.unparseable.push(entry);
+                        continue;
+                    };
                     let crate_metadata = CrateMetaData {
                         name,
                         version: Some(version),
                         size: crate_size,
                         source: Some(source.clone()),
+                        path: vec![entry.clone()],
+                        last_used: last_used(&entry),
+                        git_id: None,
                     };
                     self.add_registry_crate_source(&crate_metadata);
                     update_crate_list(&mut installed_crate_registry, &crate_metadata)?;
                 }
             }
         }
-        // read cache dir to get installed crate
+        Ok(installed_crate_registry)
+    }
+
+    /// list installed registry crates present in registry cache directory
+    fn list_registry_archive(&mut self, cache_dir: &Path) -> Result<HashSet<CrateMetaData>> {
+        let mut installed_crate_registry = HashSet::new();
         if cache_dir.exists() {
             for entry in fs::read_dir(cache_dir).context("failed to read cache dir")? {
                 let registry = entry?.path();
@@ -323,101 +489,189 @@ impl CrateDetail {
                     let file_name = entry
                         .file_name()
                         .context("failed to get file name from cache dir")?;
-                    let crate_size = get_size(&entry).context("failed to get size")?;
-                    let crate_name = file_name
-                        .to_str()
-                        .context("Failed to convert crate file name to str")?;
-                    let (name, version) = split_name_version(crate_name)?;
+                    let crate_size = self
+                        .size_cache
+                        .get_size(&entry)
+                        .context("failed to get size")?;
+                    // skip entry which cannot be parsed instead of failing whole listing
+                    let Some(Ok((name, version))) = file_name.to_str().map(split_name_version)
+                    else {
+                        self.unparseable.push(entry);
+                        continue;
+                    };
                     let crate_metadata = CrateMetaData {
                         name,
                         version: Some(version),
                         size: crate_size,
                         source: Some(source.clone()),
+                        path: vec![entry.clone()],
+                        last_used: last_used(&entry),
+                        git_id: None,
                     };
                     self.add_registry_crate_archive(&crate_metadata);
                     update_crate_list(&mut installed_crate_registry, &crate_metadata)?;
                 }
             }
         }
-        let mut installed_crates = Vec::new();
-        for crate_metadata in installed_crate_registry {
-            installed_crates.push(crate_metadata);
-        }
-        installed_crates.sort();
-        Ok(installed_crates)
+        Ok(installed_crate_registry)
     }
 
-    /// list all installed git crates
-    pub(crate) fn list_installed_crate_git(
-        &mut self,
-        checkout_dir: &Path,
-        db_dir: &Path,
-    ) -> Result<Vec<CrateMetaData>> {
+    /// list installed git crates present in git checkouts directory
+    fn list_git_checkout(&mut self, checkout_dir: &Path) -> Result<HashSet<CrateMetaData>> {
         let mut installed_crate_git = HashSet::new();
         if checkout_dir.exists() {
             // read checkout dir to list crate name in form of crate_name-rev_sha
             for entry in fs::read_dir(checkout_dir).context("failed to read checkout directory")? {
                 let entry = entry?.path();
                 let source = self.source_url_from_path(&entry)?;
-                let file_path = entry
-                    .file_name()
-                    .context("failed to obtain checkout directory sub folder file name")?;
                 for git_sha_entry in
                     fs::read_dir(&entry).context("failed to read checkout dir sub folder")?
                 {
                     let git_sha_entry = git_sha_entry?.path();
-                    let crate_size =
-                        get_size(&git_sha_entry).context("failed to get folder size")?;
-                    let git_sha_file_name = git_sha_entry
-                        .file_name()
-                        .context("failed to get file name")?;
-                    let git_sha = git_sha_file_name
-                        .to_str()
-                        .context("Failed to convert git sha name to str")?;
-                    let file_name = file_path
-                        .to_str()
-                        .context("Failed to convert file path file name to str")?;
-                    let full_name = format!("{file_name}-{git_sha}");
+                    let crate_size = self
+                        .size_cache
+                        .get_size(&git_sha_entry)
+                        .context("failed to get folder size")?;
+                    let git_id = GitId::from_checkout(source.clone(), &git_sha_entry)
+                        .context("failed to get git id of checkout directory")?;
                     let crate_metadata = CrateMetaData {
-                        name: full_name,
-                        version: None,
-                        size: crate_size,
-                        source: Some(source.clone()),
+                        path: vec![git_sha_entry.clone()],
+                        last_used: last_used(&git_sha_entry),
+                        ..CrateMetaData::new_git(git_id, crate_size)
                     };
                     self.add_git_crate_archive(&crate_metadata);
                     update_crate_list(&mut installed_crate_git, &crate_metadata)?;
                 }
             }
         }
+        Ok(installed_crate_git)
+    }
+
+    /// list installed git crates present in git db directory
+    fn list_git_database(&mut self, db_dir: &Path) -> Result<HashSet<CrateMetaData>> {
+        let mut installed_crate_git = HashSet::new();
         // read a database directory to list a git crate in form of crate_name-HEAD
         if db_dir.exists() {
             for entry in fs::read_dir(db_dir).context("failed to read db dir")? {
                 let entry = entry?.path();
                 let source = self.source_url_from_path(&entry)?;
+                // git fetch only writes inside objects and refs folder which do
+                // not change modified time of database folder so size of
+                // database is never taken from size cache
                 let crate_size =
                     get_size(&entry).context("failed to get size of db dir folders")?;
-                let file_name = entry.file_name().context("failed to get file name")?;
-                let file_name = file_name
-                    .to_str()
-                    .context("Failed to convert db dir file name to str")?;
-                let full_name = format!("{file_name}-HEAD");
+                let git_id = GitId::from_database(source, &entry)
+                    .context("failed to get git id of db directory")?;
                 let crate_metadata = CrateMetaData {
-                    name: full_name,
-                    version: None,
-                    size: crate_size,
-                    source: Some(source),
+                    path: vec![entry.clone()],
+                    last_used: last_used(&entry),
+                    ..CrateMetaData::new_git(git_id, crate_size)
                 };
                 self.add_git_crate_source(&crate_metadata);
                 update_crate_list(&mut installed_crate_git, &crate_metadata)?;
             }
         }
-        let mut installed_crates = Vec::new();
-        for crate_metadata in installed_crate_git {
-            installed_crates.push(crate_metadata);
+        Ok(installed_crate_git)
+    }
+
+    /// list installed bin, registry and git crates. Bin, registry src,
+    /// registry cache, git checkouts and git db are independent directory tree
+    /// so each of them is scanned concurrently by separate crate detail and
+    /// result is merged afterwards
+    ///
+    /// # Errors
+    /// Return error if any directory of cargo home cannot be scanned
+    pub fn list_installed(
+        &mut self,
+        dir_path: &DirPath,
+    ) -> Result<(Vec<CrateMetaData>, Vec<CrateMetaData>, Vec<CrateMetaData>)> {
+        let mut bin_detail = self.fork();
+        let mut registry_source_detail = self.fork();
+        let mut registry_archive_detail = self.fork();
+        let mut git_checkout_detail = self.fork();
+        let mut git_database_detail = self.fork();
+        let (installed_bin, registry_source, registry_archive, git_checkout, git_database) =
+            thread::scope(|scope| {
+                let bin = scope.spawn(|| bin_detail.list_installed_bin(dir_path.bin_dir()));
+                let registry_source =
+                    scope.spawn(|| registry_source_detail.list_registry_source(dir_path.src_dir()));
+                let registry_archive = scope
+                    .spawn(|| registry_archive_detail.list_registry_archive(dir_path.cache_dir()));
+                let git_checkout =
+                    scope.spawn(|| git_checkout_detail.list_git_checkout(dir_path.checkout_dir()));
+                let git_database =
+                    scope.spawn(|| git_database_detail.list_git_database(dir_path.db_dir()));
+                (
+                    join_scan(bin),
+                    join_scan(registry_source),
+                    join_scan(registry_archive),
+                    join_scan(git_checkout),
+                    join_scan(git_database),
+                )
+            });
+        for detail in [
+            bin_detail,
+            registry_source_detail,
+            registry_archive_detail,
+            git_checkout_detail,
+            git_database_detail,
+        ] {
+            self.merge(detail);
         }
-        installed_crates.sort();
-        Ok(installed_crates)
+        let installed_crate_registry = merge_crate_list(registry_source?, &registry_archive?)?;
+        let installed_crate_git = merge_crate_list(git_checkout?, &git_database?)?;
+        Ok((
+            installed_bin?,
+            installed_crate_registry,
+            installed_crate_git,
+        ))
     }
+
+    // create empty crate detail which shares source information and size
+    // cache with current crate detail
+    fn fork(&self) -> Self {
+        Self {
+            source_info: self.source_info.clone(),
+            size_cache: self.size_cache.fork(),
+            ..Default::default()
+        }
+    }
+
+    // merge crate detail created by fork into current crate detail
+    fn merge(&mut self, other: Self) {
+        self.bin.extend(other.bin);
+        self.git_crates_source.extend(other.git_crates_source);
+        self.registry_crates_source
+            .extend(other.registry_crates_source);
+        self.git_crates_archive.extend(other.git_crates_archive);
+        self.registry_crates_archive
+            .extend(other.registry_crates_archive);
+        self.unparseable.extend(other.unparseable);
+        self.unparseable.sort();
+        self.size_cache.merge(other.size_cache);
+    }
+}
+
+// wait for scan thread to finish and convert its panic to error
+fn join_scan<T>(handle: ScopedJoinHandle<'_, Result<T>>) -> Result<T> {
+    handle
+        .join()
+        .unwrap_or_else(|_| Err(anyhow::anyhow!("Thread scanning cargo home panicked")))
+}
+
+// merge crates listed from two directory so crate present in both directory
+// is listed once with combined size and path
+fn merge_crate_list(
+    first: HashSet<CrateMetaData>,
+    second: &HashSet<CrateMetaData>,
+) -> Result<Vec<CrateMetaData>> {
+    let mut merged = first;
+    for crate_metadata in second {
+        update_crate_list(&mut merged, crate_metadata)?;
+    }
+    let mut installed_crates = merged.into_iter().collect::<Vec<_>>();
+    installed_crates.sort();
+    Ok(installed_crates)
 }
 
 fn update_crate_list(
@@ -426,16 +680,143 @@ fn update_crate_list(
 ) -> Result<()> {
     let meta_data_exists = hash_set.get(temp_crate_metadata).is_some();
     let mut current_size = temp_crate_metadata.size;
+    let mut current_path = temp_crate_metadata.path.clone();
+    let mut current_last_used = temp_crate_metadata.last_used;
     if meta_data_exists {
-        current_size += hash_set
+        let existing_metadata = hash_set
             .get(temp_crate_metadata)
-            .context("failed to get metadata from hash set")?
-            .size;
+            .context("failed to get metadata from hash set")?;
+        current_size += existing_metadata.size;
+        current_path.extend(existing_metadata.path.iter().cloned());
+        current_path.sort();
+        current_last_used = current_last_used.max(existing_metadata.last_used);
     }
     hash_set.remove(temp_crate_metadata);
     hash_set.insert(CrateMetaData {
         size: current_size,
+        path: current_path,
+        last_used: current_last_used,
         ..temp_crate_metadata.clone()
     });
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::str::FromStr;
+
+    use semver::Version;
+    use url::Url;
+
+    use super::{CrateDetail, CrateMetaData, merge_crate_list};
+    use crate::registry_dir::RegistryDir;
+    use crate::restore_manifest::RestoreManifest;
+    use crate::size_cache::SizeCache;
+
+    #[test]
+    fn test_is_protected() {
+        let crate_metadata = CrateMetaData::new(
+            String::from("openssl-sys"),
+            Some(Version::new(0, 9, 100)),
+            0,
+            Some(Url::from_str("https://index.crates.io/").unwrap()),
+        );
+        assert!(crate_metadata.is_protected(&[String::from("openssl-sys")]));
+        assert!(crate_metadata.is_protected(&[String::from("openssl-sys@0.9.100")]));
+        assert!(!crate_metadata.is_protected(&[String::from("openssl-sys@0.9.99")]));
+        assert!(!crate_metadata.is_protected(&[String::from("openssl")]));
+    }
+
+    #[test]
+    fn test_remove_non_semver_registry_crate() {
+        let cargo_home = std::env::temp_dir().join("cargo_trim_non_semver_crate_test");
+        let _ = fs::remove_dir_all(&cargo_home);
+        let registry = "index.crates.io-1949cf8c6b5b557f";
+        let index_dir = cargo_home.join("registry").join("index");
+        let src_dir = cargo_home.join("registry").join("src");
+        let cache_dir = cargo_home.join("registry").join("cache");
+        fs::create_dir_all(index_dir.join(registry)).unwrap();
+        fs::write(
+            index_dir.join(registry).join("config.json"),
+            r#"{"dl":"https://static.crates.io/crates","api":"https://crates.io"}"#,
+        )
+        .unwrap();
+        let crate_source = src_dir.join(registry).join("old_crate-1.2");
+        let crate_archive = cache_dir.join(registry).join("old_crate-1.2.crate");
+        fs::create_dir_all(&crate_source).unwrap();
+        fs::write(crate_source.join("lib.rs"), "").unwrap();
+        fs::create_dir_all(cache_dir.join(registry)).unwrap();
+        fs::write(&crate_archive, "crate").unwrap();
+        let size_cache = SizeCache::init(&cargo_home.join("size_cache.toml"), false).unwrap();
+        let mut crate_detail =
+            CrateDetail::new(&index_dir, &cargo_home.join("git").join("db"), size_cache).unwrap();
+        let source = crate_detail.list_registry_source(&src_dir).unwrap();
+        let archive = crate_detail.list_registry_archive(&cache_dir).unwrap();
+        let installed = merge_crate_list(source, &archive).unwrap();
+        // directory name is listed with coerced version
+        assert_eq!(installed.len(), 1);
+        assert_eq!(installed[0].version(), Some(&Version::new(1, 2, 0)));
+        let mut registry_dir = RegistryDir::new(
+            &index_dir,
+            &installed,
+            None,
+            RestoreManifest::new(&cargo_home.join("restore.toml")),
+        )
+        .unwrap();
+        let (is_removed, size_freed) = registry_dir
+            .remove_crate(&crate_detail, &installed[0], false)
+            .unwrap();
+        assert!(is_removed);
+        assert_eq!(size_freed, installed[0].size());
+        assert!(!crate_source.exists());
+        assert!(!crate_archive.exists());
+        fs::remove_dir_all(&cargo_home).unwrap();
+    }
+
+    #[test]
+    fn test_remove_archived_registry_crate() {
+        let cargo_home = std::env::temp_dir().join("cargo_trim_archived_crate_test");
+        let _ = fs::remove_dir_all(&cargo_home);
+        let registry = "index.crates.io-1949cf8c6b5b557f";
+        let index_dir = cargo_home.join("registry").join("index");
+        let src_dir = cargo_home.join("registry").join("src");
+        let cache_dir = cargo_home.join("registry").join("cache");
+        fs::create_dir_all(index_dir.join(registry)).unwrap();
+        fs::write(
+            index_dir.join(registry).join("config.json"),
+            r#"{"dl":"https://static.crates.io/crates","api":"https://crates.io"}"#,
+        )
+        .unwrap();
+        let tarball = src_dir.join(registry).join("bar-0.2.0.tar.gz");
+        let crate_archive = cache_dir.join(registry).join("bar-0.2.0.crate");
+        fs::create_dir_all(src_dir.join(registry)).unwrap();
+        fs::write(&tarball, "tarball").unwrap();
+        fs::create_dir_all(cache_dir.join(registry)).unwrap();
+        fs::write(&crate_archive, "crate").unwrap();
+        let size_cache = SizeCache::init(&cargo_home.join("size_cache.toml"), false).unwrap();
+        let mut crate_detail =
+            CrateDetail::new(&index_dir, &cargo_home.join("git").join("db"), size_cache).unwrap();
+        let source = crate_detail.list_registry_source(&src_dir).unwrap();
+        let archive = crate_detail.list_registry_archive(&cache_dir).unwrap();
+        let installed = merge_crate_list(source, &archive).unwrap();
+        // archived source belongs to same crate as .crate file
+        assert_eq!(installed.len(), 1);
+        assert_eq!(installed[0].path().len(), 2);
+        let mut registry_dir = RegistryDir::new(
+            &index_dir,
+            &installed,
+            None,
+            RestoreManifest::new(&cargo_home.join("restore.toml")),
+        )
+        .unwrap();
+        let (is_removed, size_freed) = registry_dir
+            .remove_crate(&crate_detail, &installed[0], false)
+            .unwrap();
+        assert!(is_removed);
+        assert_eq!(size_freed, installed[0].size());
+        assert!(!tarball.exists());
+        assert!(!crate_archive.exists());
+        fs::remove_dir_all(&cargo_home).unwrap();
+    }
+}
@@ -0,0 +1,167 @@
+use std::fmt;
+use std::path::Path;
+
+use semver::Version;
+use url::Url;
+
+/// Stable identity of registry crate
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(crate) struct CrateId {
+    registry: Url,
+    name: String,
+    version: Version,
+}
+
+impl CrateId {
+    pub(crate) fn new(registry: Url, name: String, version: Version) -> Self {
+        Self {
+            registry,
+            name,
+            version,
+        }
+    }
+
+    pub(crate) fn registry(&self) -> &Url {
+        &self.registry
+    }
+
+    pub(crate) fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub(crate) fn version(&self) -> &Version {
+        &self.version
+    }
+
+    /// name of .crate file inside registry cache directory
+    pub(crate) fn archive_name(&self) -> String {
+        format!("{self}.crate")
+    }
+
+    /// package id spec in same form as used by cargo
+    pub(crate) fn pkgid(&self) -> String {
+        format!("registry+{}#{}@{}", self.registry, self.name, self.version)
+    }
+}
+
+impl fmt::Display for CrateId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.name, self.version)
+    }
+}
+
+/// Stable identity of git crate. Git database use HEAD as rev
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(crate) struct GitId {
+    url: Url,
+    name: String,
+    rev: String,
+}
+
+impl GitId {
+    /// rev used for crate stored in git database directory
+    pub(crate) const DATABASE_REV: &'static str = "HEAD";
+
+    pub(crate) fn new(url: Url, name: String, rev: String) -> Self {
+        Self { url, name, rev }
+    }
+
+    /// create git id of checkout directory. Checkout is stored as
+    /// checkouts/name-hash/rev so name is read from parent directory
+    pub(crate) fn from_checkout(url: Url, checkout: &Path) -> Option<Self> {
+        let rev = checkout.file_name()?.to_string_lossy().to_string();
+        let name = checkout
+            .parent()?
+            .file_name()?
+            .to_string_lossy()
+            .to_string();
+        Some(Self::new(url, name, rev))
+    }
+
+    /// create git id of database directory stored as db/name-hash
+    pub(crate) fn from_database(url: Url, database: &Path) -> Option<Self> {
+        let name = database.file_name()?.to_string_lossy().to_string();
+        Some(Self::new(url, name, Self::DATABASE_REV.to_string()))
+    }
+
+    pub(crate) fn url(&self) -> &Url {
+        &self.url
+    }
+
+    pub(crate) fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub(crate) fn rev(&self) -> &String {
+        &self.rev
+    }
+
+    /// check if git id point to git database instead of checkout
+    pub(crate) fn is_database(&self) -> bool {
+        self.rev == Self::DATABASE_REV
+    }
+
+    /// check if git id point to same repository. Url is compared after
+    /// removing trailing slash and .git suffix same as cargo
+    pub(crate) fn same_repository(&self, other: &Self) -> bool {
+        canonical_url(&self.url) == canonical_url(&other.url)
+    }
+
+    /// check if git id is checkout of revision of other git id. Revision
+    /// stored in checkout directory and lock file can have different length
+    pub(crate) fn same_revision(&self, other: &Self) -> bool {
+        self.rev.starts_with(other.rev.as_str()) || other.rev.starts_with(self.rev.as_str())
+    }
+
+    /// package id spec in same form as used by cargo
+    pub(crate) fn pkgid(&self) -> String {
+        format!("git+{}#{}@{}", self.url, self.name, self.rev)
+    }
+}
+
+// canonical form of git url used by cargo to identify repository. GitHub url
+// is case insensitive
+fn canonical_url(url: &Url) -> String {
+    let url = url.as_str().trim_end_matches('/');
+    let url = url.strip_suffix(".git").unwrap_or(url);
+    if url.contains("://github.com/") {
+        url.to_lowercase()
+    } else {
+        url.to_string()
+    }
+}
+
+impl fmt::Display for GitId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.name, self.rev)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+    use std::str::FromStr;
+
+    use url::Url;
+
+    use super::GitId;
+
+    #[test]
+    fn test_git_id_from_directory() {
+        let url = Url::from_str("https://github.com/tokio-rs/tokio").unwrap();
+        let checkout = Path::new("git")
+            .join("checkouts")
+            .join("tokio-util-1a2b3c4d5e6f7a8b")
+            .join("abcdef1");
+        let git_id = GitId::from_checkout(url.clone(), &checkout).unwrap();
+        assert_eq!(git_id.name(), "tokio-util-1a2b3c4d5e6f7a8b");
+        assert_eq!(git_id.rev(), "abcdef1");
+        assert!(!git_id.is_database());
+        let database = Path::new("git")
+            .join("db")
+            .join("tokio-util-1a2b3c4d5e6f7a8b");
+        let git_id = GitId::from_database(url, &database).unwrap();
+        assert_eq!(git_id.name(), "tokio-util-1a2b3c4d5e6f7a8b");
+        assert!(git_id.is_database());
+    }
+}
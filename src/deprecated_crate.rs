@@ -0,0 +1,136 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+/// List of crates which are deprecated, renamed or superseded by another crate
+/// along with crate which should be used instead
+const SUPERSEDED_CRATES: &[(&str, &str)] = &[
+    ("ansi_term", "nu-ansi-term"),
+    ("atty", "is-terminal"),
+    ("difference", "similar"),
+    ("dotenv", "dotenvy"),
+    ("error-chain", "thiserror"),
+    ("failure", "anyhow"),
+    ("failure_derive", "thiserror"),
+    ("futures-preview", "futures"),
+    ("instant", "web-time"),
+    ("mach", "mach2"),
+    ("memmap", "memmap2"),
+    ("net2", "socket2"),
+    ("rustc-serialize", "serde"),
+    ("serde_cbor", "ciborium"),
+    ("stdweb", "web-sys"),
+    ("structopt", "clap"),
+    ("structopt-derive", "clap_derive"),
+    ("tempdir", "tempfile"),
+    ("term_size", "terminal_size"),
+    ("tokio-core", "tokio"),
+    ("tokio-io", "tokio"),
+    ("tokio-timer", "tokio"),
+    ("users", "uzers"),
+    ("yaml-rust", "yaml-rust2"),
+];
+
+/// Part of Cargo.toml published to crates.io used to read maintenance badge
+#[derive(Deserialize)]
+struct Manifest {
+    package: Package,
+    #[serde(default)]
+    badges: Badges,
+}
+
+#[derive(Deserialize)]
+struct Package {
+    name: String,
+}
+
+#[derive(Deserialize, Default)]
+struct Badges {
+    maintenance: Option<Maintenance>,
+}
+
+#[derive(Deserialize)]
+struct Maintenance {
+    status: String,
+}
+
+static BADGE_DEPRECATED: OnceLock<HashSet<String>> = OnceLock::new();
+
+/// return name of crate which supersede provided crate if crate is deprecated
+pub(crate) fn superseded_by(name: &str) -> Option<&'static str> {
+    SUPERSEDED_CRATES
+        .iter()
+        .find(|(deprecated, _)| *deprecated == name)
+        .map(|(_, replacement)| *replacement)
+}
+
+/// read maintenance badge of extracted crates inside source directory of
+/// crates.io and remember crates whose author set badge status to deprecated.
+/// Crate whose source is not extracted is only detected by bundled mapping
+pub(crate) fn read_maintenance_badges(src_dirs: &[PathBuf]) {
+    let deprecated = src_dirs
+        .iter()
+        .filter_map(|src_dir| fs::read_dir(src_dir).ok())
+        .flat_map(Iterator::flatten)
+        .filter_map(|entry| fs::read_to_string(entry.path().join("Cargo.toml")).ok())
+        .filter_map(|content| deprecated_package(&content))
+        .filter(|name| superseded_by(name).is_none())
+        .collect();
+    let _ = BADGE_DEPRECATED.set(deprecated);
+}
+
+/// check if crate is marked deprecated by maintenance badge of its manifest
+pub(crate) fn is_badge_deprecated(name: &str) -> bool {
+    BADGE_DEPRECATED
+        .get()
+        .is_some_and(|deprecated| deprecated.contains(name))
+}
+
+/// check if crate is deprecated by bundled mapping or by crates.io metadata
+pub(crate) fn is_deprecated(name: &str) -> bool {
+    superseded_by(name).is_some() || is_badge_deprecated(name)
+}
+
+// name of package when manifest set status of maintenance badge to deprecated
+fn deprecated_package(content: &str) -> Option<String> {
+    let manifest = toml::from_str::<Manifest>(content).ok()?;
+    manifest
+        .badges
+        .maintenance
+        .is_some_and(|maintenance| maintenance.status == "deprecated")
+        .then_some(manifest.package.name)
+}
+
+#[cfg(test)]
+mod test {
+    use super::deprecated_package;
+
+    #[test]
+    fn test_deprecated_package() {
+        assert_eq!(
+            deprecated_package(
+                "[package]\nname = \"tempdir\"\n\n[badges.maintenance]\nstatus = \"deprecated\"\n"
+            )
+            .as_deref(),
+            Some("tempdir")
+        );
+        assert_eq!(
+            deprecated_package(
+                "[package]\nname = \"foo\"\n\n[badges]\nmaintenance = { status = \"deprecated\" }\n"
+            )
+            .as_deref(),
+            Some("foo")
+        );
+        assert!(
+            deprecated_package(
+                "[package]\nname = \"foo\"\ndescription = \"deprecated in favor of bar\"\n\n\
+                 [badges.maintenance]\nstatus = \"passively-maintained\"\n"
+            )
+            .is_none()
+        );
+        assert!(deprecated_package("[package]\nname = \"ripgrep\"\n").is_none());
+    }
+}
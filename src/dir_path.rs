@@ -4,9 +4,17 @@ use std::path::{Path, PathBuf};
 use anyhow::{Context, Result};
 
 /// Struct for storing Directory path
-pub(crate) struct DirPath {
+#[derive(Clone)]
+pub struct DirPath {
+    cargo_home: PathBuf,
     bin_dir: PathBuf,
     config_file: PathBuf,
+    stats_file: PathBuf,
+    size_cache_file: PathBuf,
+    history_file: PathBuf,
+    scan_file: PathBuf,
+    restore_file: PathBuf,
+    completion_file: PathBuf,
     git_dir: PathBuf,
     checkout_dir: PathBuf,
     db_dir: PathBuf,
@@ -17,8 +25,12 @@ pub(crate) struct DirPath {
 }
 
 impl DirPath {
-    /// set directory path
-    pub(crate) fn new() -> Result<Self> {
+    /// set directory path. Cargo home can be overridden to operate on cargo
+    /// home other than one of current user such as one of container
+    ///
+    /// # Errors
+    /// Return error if config directory or file cannot be created
+    pub fn new(cargo_home: Option<PathBuf>) -> Result<Self> {
         // set config file directory path
         let config_dir = dirs_next::config_dir().context("Cannot get config directory location")?;
         // if config dir not exists create
@@ -32,7 +44,25 @@ impl DirPath {
             fs::File::create(&config_file).context("Failed to create config file")?;
         }
 
-        let home_dir = Path::new(env!("CARGO_HOME")).to_path_buf();
+        // set lifetime stats file path
+        let stats_file = config_dir.join("cargo_trim_stats.toml");
+
+        // set size cache file path
+        let size_cache_file = config_dir.join("cargo_trim_size_cache.toml");
+
+        // set cache size history file path
+        let history_file = config_dir.join("cargo_trim_history.toml");
+
+        // set saved scan file path
+        let scan_file = config_dir.join("cargo_trim_scan.toml");
+
+        // set restore manifest file path
+        let restore_file = config_dir.join("cargo_trim_restore.toml");
+
+        // set crate name completion index file path
+        let completion_file = config_dir.join("cargo_trim_completion.toml");
+
+        let home_dir = cargo_home.unwrap_or_else(|| Path::new(env!("CARGO_HOME")).to_path_buf());
 
         // set bin directory path
         let bin_dir = home_dir.join("bin");
@@ -53,8 +83,15 @@ impl DirPath {
         let index_dir = registry_dir.join("index");
 
         Ok(Self {
+            cargo_home: home_dir,
             bin_dir,
             config_file,
+            stats_file,
+            size_cache_file,
+            history_file,
+            scan_file,
+            restore_file,
+            completion_file,
             git_dir,
             checkout_dir,
             db_dir,
@@ -65,48 +102,99 @@ impl DirPath {
         })
     }
 
+    /// return path of cargo home
+    #[must_use]
+    pub fn cargo_home(&self) -> &PathBuf {
+        &self.cargo_home
+    }
+
     /// return path of bin dir
-    pub(crate) fn bin_dir(&self) -> &PathBuf {
+    #[must_use]
+    pub fn bin_dir(&self) -> &PathBuf {
         &self.bin_dir
     }
 
     /// return path of config file
-    pub(crate) fn config_file(&self) -> &PathBuf {
+    #[must_use]
+    pub fn config_file(&self) -> &PathBuf {
         &self.config_file
     }
 
+    /// return path of stats file
+    #[must_use]
+    pub fn stats_file(&self) -> &PathBuf {
+        &self.stats_file
+    }
+
+    /// return path of size cache file
+    #[must_use]
+    pub fn size_cache_file(&self) -> &PathBuf {
+        &self.size_cache_file
+    }
+
+    /// return path of cache size history file
+    #[must_use]
+    pub fn history_file(&self) -> &PathBuf {
+        &self.history_file
+    }
+
+    /// return path of saved scan file
+    #[must_use]
+    pub fn scan_file(&self) -> &PathBuf {
+        &self.scan_file
+    }
+
+    /// return path of restore manifest file
+    #[must_use]
+    pub fn restore_file(&self) -> &PathBuf {
+        &self.restore_file
+    }
+
+    /// return path of crate name completion index file
+    #[must_use]
+    pub fn completion_file(&self) -> &PathBuf {
+        &self.completion_file
+    }
+
     /// return path of git dir
-    pub(crate) fn git_dir(&self) -> &PathBuf {
+    #[must_use]
+    pub fn git_dir(&self) -> &PathBuf {
         &self.git_dir
     }
 
     /// return path of checkout dir
-    pub(crate) fn checkout_dir(&self) -> &PathBuf {
+    #[must_use]
+    pub fn checkout_dir(&self) -> &PathBuf {
         &self.checkout_dir
     }
 
     /// return path of db dir
-    pub(crate) fn db_dir(&self) -> &PathBuf {
+    #[must_use]
+    pub fn db_dir(&self) -> &PathBuf {
         &self.db_dir
     }
 
     /// return path of registry dir
-    pub(crate) fn registry_dir(&self) -> &PathBuf {
+    #[must_use]
+    pub fn registry_dir(&self) -> &PathBuf {
         &self.registry_dir
     }
 
     /// return path of cache dir
-    pub(crate) fn cache_dir(&self) -> &PathBuf {
+    #[must_use]
+    pub fn cache_dir(&self) -> &PathBuf {
         &self.cache_dir
     }
 
     /// return path of index dir
-    pub(crate) fn index_dir(&self) -> &PathBuf {
+    #[must_use]
+    pub fn index_dir(&self) -> &PathBuf {
         &self.index_dir
     }
 
     /// return path of src dir
-    pub(crate) fn src_dir(&self) -> &PathBuf {
+    #[must_use]
+    pub fn src_dir(&self) -> &PathBuf {
         &self.src_dir
     }
 }
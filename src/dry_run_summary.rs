@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use owo_colors::OwoColorize;
+
+use crate::dir_path::DirPath;
+use crate::utils::{convert_pretty, print_dash, query_full_width, query_print};
+
+// path along with size which would have been removed in dry run. Same path
+// can be reached by multiple clean flags as nothing is removed in dry run so
+// path is used as key to count it only once
+static DRY_RUN_PATHS: Mutex<BTreeMap<PathBuf, u64>> = Mutex::new(BTreeMap::new());
+
+/// record path and its size which would have been removed in dry run
+pub(crate) fn record_dry_run_path(path: &Path, size: u64) {
+    if let Ok(mut paths) = DRY_RUN_PATHS.lock() {
+        paths.insert(path.to_path_buf(), size);
+    }
+}
+
+/// return total reclaimable space recorded till now and clear recorded path so
+/// next cargo home is counted separately
+pub(crate) fn take_dry_run_total() -> u64 {
+    DRY_RUN_PATHS
+        .lock()
+        .map_or(0, |mut paths| std::mem::take(&mut *paths).values().sum())
+}
+
+/// print reclaimable space of each category along with grand total if any path
+/// was recorded in dry run
+pub(crate) fn print_dry_run_summary(dir_path: &DirPath) {
+    let Ok(paths) = DRY_RUN_PATHS.lock() else {
+        return;
+    };
+    if paths.is_empty() {
+        return;
+    }
+    let mut categories = [
+        ("Registry source", dir_path.src_dir(), 0),
+        ("Registry archive", dir_path.cache_dir(), 0),
+        ("Registry index", dir_path.index_dir(), 0),
+        ("Git db", dir_path.db_dir(), 0),
+        ("Git checkout", dir_path.checkout_dir(), 0),
+        ("Bin", dir_path.bin_dir(), 0),
+    ];
+    let mut other_size = 0;
+    for (path, size) in paths.iter() {
+        match categories
+            .iter_mut()
+            .find(|(_, directory, _)| path.starts_with(directory))
+        {
+            Some((_, _, category_size)) => *category_size += size,
+            None => other_size += size,
+        }
+    }
+    print_dash(query_full_width());
+    human_println!("{}", "Dry run summary of reclaimable space".bold());
+    for (name, _, size) in &categories {
+        query_print(&format!("   {name}"), &convert_pretty(*size));
+    }
+    // target and nested cargo home path do not belong to any category
+    if other_size > 0 {
+        query_print("   Other", &convert_pretty(other_size));
+    }
+    print_dash(query_full_width());
+    let total = categories.iter().map(|(_, _, size)| size).sum::<u64>() + other_size;
+    query_print("Total reclaimable space", &convert_pretty(total));
+}
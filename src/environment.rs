@@ -0,0 +1,153 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::SystemTime;
+
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+
+use crate::dir_path::DirPath;
+use crate::trash::trash_available;
+use crate::utils::{convert_pretty, format_timestamp};
+
+/// Environment detected during first run of cargo trim. It is stored in config
+/// file so capability of current system do not need to be guessed by each
+/// command
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Environment {
+    detected_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cargo_version: Option<String>,
+    index: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    filesystem: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    free_space: Option<u64>,
+    global_cache_db: bool,
+    trash_available: bool,
+}
+
+impl Environment {
+    /// detect environment of cargo home
+    pub(crate) fn detect(dir_path: &DirPath) -> Self {
+        let cargo_home = dir_path.cargo_home();
+        Self {
+            detected_at: format_timestamp(SystemTime::now()),
+            cargo_version: cargo_version(),
+            index: index_type(dir_path.index_dir()),
+            filesystem: filesystem(cargo_home),
+            free_space: free_space(cargo_home),
+            global_cache_db: cargo_home.join(".global-cache").exists(),
+            trash_available: trash_available(),
+        }
+    }
+
+    /// print short report of capability detected in environment to stderr
+    pub(crate) fn print_briefing(&self) {
+        human_eprintln!("{}", "Detected environment of first run".bold());
+        let unknown = String::from("unknown");
+        human_eprintln!(
+            "   cargo version: {}",
+            self.cargo_version.as_ref().unwrap_or(&unknown)
+        );
+        human_eprintln!("   registry index: {}", self.index);
+        human_eprintln!(
+            "   filesystem: {}",
+            self.filesystem.as_ref().unwrap_or(&unknown)
+        );
+        let free_space = self.free_space.map_or(unknown.clone(), |size| {
+            convert_pretty(size).trim().to_string()
+        });
+        human_eprintln!("   free space: {free_space}");
+        let capabilities = [
+            (
+                self.index.contains("sparse"),
+                "sparse index detected, index cache can be wiped cheaply",
+            ),
+            (
+                self.global_cache_db,
+                "global cache database present, cargo also tracks last use of crates",
+            ),
+            (
+                self.trash_available,
+                "trash available, use --trash to make deletion recoverable",
+            ),
+        ];
+        for (available, message) in capabilities {
+            if available {
+                human_eprintln!("   {} {message}", "\u{2713}".green());
+            }
+        }
+        human_eprintln!(
+            "{}",
+            "cargo trim only deletes files when clean flags are passed. Use --dry-run to preview \
+             what would be removed"
+                .yellow()
+        );
+    }
+}
+
+// version of cargo which invoked cargo trim or cargo present in path
+fn cargo_version() -> Option<String> {
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| String::from("cargo"));
+    let output = Command::new(cargo).arg("--version").output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// check if registry index is sparse index. Git index contains .git folder
+/// while sparse index do not
+pub(crate) fn is_sparse_index(index: &Path) -> bool {
+    !index.join(".git").exists()
+}
+
+// type of registry index present in index directory
+fn index_type(index_dir: &Path) -> String {
+    let mut has_git = false;
+    let mut has_sparse = false;
+    if let Ok(entries) = fs::read_dir(index_dir) {
+        for entry in entries.filter_map(Result::ok) {
+            if is_sparse_index(&entry.path()) {
+                has_sparse = true;
+            } else {
+                has_git = true;
+            }
+        }
+    }
+    match (has_sparse, has_git) {
+        (true, true) => String::from("sparse and git"),
+        (true, false) => String::from("sparse"),
+        (false, true) => String::from("git"),
+        (false, false) => String::from("none"),
+    }
+}
+
+// filesystem type of mount point containing path. Only supported in linux
+fn filesystem(path: &Path) -> Option<String> {
+    let mounts = fs::read_to_string("/proc/mounts").ok()?;
+    let path = path.canonicalize().ok()?;
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let mount_point = fields.nth(1)?;
+            let filesystem = fields.next()?;
+            Some((mount_point, filesystem))
+        })
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.len())
+        .map(|(_, filesystem)| filesystem.to_string())
+}
+
+// free space available in filesystem containing path using df command
+fn free_space(path: &Path) -> Option<u64> {
+    let output = Command::new("df").arg("-Pk").arg(path).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let available = stdout.lines().nth(1)?.split_whitespace().nth(3)?;
+    available.parse::<u64>().ok().map(|kb| kb * 1024)
+}
@@ -0,0 +1,130 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::Serialize;
+
+use crate::crate_detail::CrateMetaData;
+use crate::log_file::{log_enabled, log_event};
+use crate::schema::Versioned;
+
+static NDJSON_MODE: AtomicBool = AtomicBool::new(false);
+static QUIET_MODE: AtomicBool = AtomicBool::new(false);
+static ERROR_EMITTED: AtomicBool = AtomicBool::new(false);
+
+/// Single action performed by cargo trim. In ndjson mode each event is written
+/// as one json line to stdout as soon as it happens
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub(crate) enum Event<'a> {
+    Scanned {
+        category: &'a str,
+        name: &'a str,
+        version: Option<String>,
+        size: u64,
+        path: &'a [PathBuf],
+    },
+    Deleted {
+        path: &'a Path,
+        size: u64,
+        dry_run: bool,
+    },
+    Freed {
+        category: &'a str,
+        reason: &'a str,
+        size: u64,
+        removed: usize,
+        dry_run: bool,
+    },
+    Skipped {
+        name: String,
+        reason: &'a str,
+    },
+    Error {
+        message: String,
+    },
+}
+
+impl<'a> Event<'a> {
+    /// create scanned event of crate present in cargo home
+    pub(crate) fn scanned(category: &'a str, crate_metadata: &'a CrateMetaData) -> Self {
+        Self::Scanned {
+            category,
+            name: crate_metadata.name(),
+            version: crate_metadata.version().map(ToString::to_string),
+            size: crate_metadata.size(),
+            path: crate_metadata.path(),
+        }
+    }
+}
+
+/// enable ndjson mode. Stdout is reserved for event so human readable output
+/// is written to stderr
+pub(crate) fn enable_ndjson() {
+    NDJSON_MODE.store(true, Ordering::Relaxed);
+}
+
+/// check if ndjson mode is enabled
+pub(crate) fn ndjson_enabled() -> bool {
+    NDJSON_MODE.load(Ordering::Relaxed)
+}
+
+/// enable quiet mode where human readable output is not printed
+pub(crate) fn enable_quiet() {
+    QUIET_MODE.store(true, Ordering::Relaxed);
+}
+
+/// disable quiet mode so final summary of summary mode can be printed
+pub(crate) fn disable_quiet() {
+    QUIET_MODE.store(false, Ordering::Relaxed);
+}
+
+/// check if quiet mode is enabled
+pub(crate) fn quiet_enabled() -> bool {
+    QUIET_MODE.load(Ordering::Relaxed)
+}
+
+/// check if any error event is emitted so partial failure can be reported
+pub(crate) fn error_emitted() -> bool {
+    ERROR_EMITTED.load(Ordering::Relaxed)
+}
+
+/// write event to log file if it is opened and as single json line to stdout
+/// if ndjson mode is enabled
+pub(crate) fn emit(event: &Event) {
+    if let Event::Error { .. } = event {
+        ERROR_EMITTED.store(true, Ordering::Relaxed);
+    }
+    if log_enabled() {
+        log_event(event);
+    }
+    if !ndjson_enabled() {
+        return;
+    }
+    if let Ok(line) = serde_json::to_string(&Versioned::new(event)) {
+        let mut stdout = std::io::stdout().lock();
+        // event stream is best effort and closed stdout should not abort clean
+        let _ = writeln!(stdout, "{line}");
+        let _ = stdout.flush();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::Event;
+
+    #[test]
+    fn test_event_json_line() {
+        let event = Event::Deleted {
+            path: Path::new("/tmp/serde-1.0.0"),
+            size: 10,
+            dry_run: true,
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"event":"deleted","path":"/tmp/serde-1.0.0","size":10,"dry_run":true}"#
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use owo_colors::OwoColorize;
+
+use crate::dir_path::DirPath;
+use crate::event::quiet_enabled;
+use crate::utils::{convert_pretty, get_size, print_dash, query_full_width, query_print};
+
+// cache directories of cargo home whose size is measured before first removal
+// inside them along with removed paths which do not belong to any directory
+static FREED_SPACE: Mutex<Option<FreedSpace>> = Mutex::new(None);
+
+struct FreedSpace {
+    categories: Vec<(&'static str, PathBuf, Option<u64>)>,
+    other: Vec<(PathBuf, u64)>,
+}
+
+/// start tracking space freed from cache directories of cargo home. Previous
+/// cargo home is no longer tracked
+pub(crate) fn track_freed_space(dir_path: &DirPath) {
+    let categories = [
+        ("Registry source", dir_path.src_dir()),
+        ("Registry archive", dir_path.cache_dir()),
+        ("Registry index", dir_path.index_dir()),
+        ("Git db", dir_path.db_dir()),
+        ("Git checkout", dir_path.checkout_dir()),
+        ("Bin", dir_path.bin_dir()),
+    ]
+    .into_iter()
+    .map(|(name, directory)| (name, directory.clone(), None))
+    .collect();
+    if let Ok(mut freed_space) = FREED_SPACE.lock() {
+        *freed_space = Some(FreedSpace {
+            categories,
+            other: Vec::new(),
+        });
+    }
+}
+
+/// measure size of directory containing path before anything is removed from
+/// it. Directory is only measured once so size before whole run is used.
+/// Nothing is measured in quiet mode since freed space is not printed
+pub(crate) fn measure_before_removal(path: &Path) {
+    if quiet_enabled() {
+        return;
+    }
+    let Ok(mut freed_space) = FREED_SPACE.lock() else {
+        return;
+    };
+    let Some(freed_space) = freed_space.as_mut() else {
+        return;
+    };
+    // whole registry or git directory contains multiple cache directories
+    let mut in_category = false;
+    for (_, directory, size_before) in &mut freed_space.categories {
+        if path.starts_with(&*directory) || directory.starts_with(path) {
+            in_category = true;
+            if size_before.is_none() {
+                *size_before = Some(get_size(directory).unwrap_or(0));
+            }
+        }
+    }
+    if !in_category
+        && !freed_space
+            .other
+            .iter()
+            .any(|(other_path, _)| path.starts_with(other_path))
+    {
+        // target and toolchain path do not belong to any cache directory
+        freed_space
+            .other
+            .push((path.to_path_buf(), get_size(path).unwrap_or(0)));
+    }
+}
+
+/// print space reclaimed from each category by comparing size measured
+/// before removal with current size. Nothing is printed if nothing was removed
+pub(crate) fn print_freed_space() {
+    let Some(freed_space) = FREED_SPACE.lock().ok().and_then(|mut freed| freed.take()) else {
+        return;
+    };
+    let mut freed_categories = freed_space
+        .categories
+        .iter()
+        .filter_map(|(name, directory, size_before)| {
+            size_before.map(|size_before| {
+                let size_after = get_size(directory).unwrap_or(0);
+                (*name, size_before.saturating_sub(size_after))
+            })
+        })
+        .collect::<Vec<_>>();
+    if !freed_space.other.is_empty() {
+        let other_freed = freed_space
+            .other
+            .iter()
+            .map(|(path, size_before)| size_before.saturating_sub(get_size(path).unwrap_or(0)))
+            .sum();
+        freed_categories.push(("Other", other_freed));
+    }
+    if freed_categories.is_empty() {
+        return;
+    }
+    print_dash(query_full_width());
+    human_println!("{}", "Space freed".bold());
+    for (name, freed) in &freed_categories {
+        query_print(&format!("   {name}"), &convert_pretty(*freed));
+    }
+    print_dash(query_full_width());
+    let total = freed_categories.iter().map(|(_, freed)| freed).sum();
+    query_print("Total freed space", &convert_pretty(total));
+}
@@ -1,132 +1,95 @@
 use std::fs;
-use std::path::Path;
+use std::time::Duration;
 
-use anyhow::{Context, Result};
 use owo_colors::OwoColorize;
 
-use crate::crate_detail::{CrateDetail, CrateMetaData};
-use crate::utils::delete_folder;
+use crate::crate_detail::CrateMetaData;
+use crate::event::{Event, emit};
+use crate::progress::{finish_progress, start_deletion};
+use crate::protect::skip_protected;
+use crate::utils::{confirm_removal, convert_pretty, delete_folder, verbosity};
 
 /// Store git dir folder information
-pub(crate) struct GitDir<'a> {
-    checkout_dir: &'a str,
-    db_dir: &'a str,
+pub(crate) struct GitDir {
+    older_than: Option<Duration>,
 }
 
-impl<'a> GitDir<'a> {
+impl GitDir {
     /// create new git dir
-    pub(crate) fn new(checkout_dir: &'a Path, db_dir: &'a Path) -> Result<Self> {
-        let checkout_dir = checkout_dir
-            .to_str()
-            .context("Failed checkout dir path conversion")?;
-        let db_dir = db_dir.to_str().context("Failed db dir path conversion")?;
-        Ok(Self {
-            checkout_dir,
-            db_dir,
-        })
+    pub(crate) fn new(older_than: Option<Duration>) -> Self {
+        Self { older_than }
     }
 
-    /// remove crates
-    pub(crate) fn remove_crate(
-        &self,
-        crate_detail: &CrateDetail,
-        crate_metadata: &CrateMetaData,
-        dry_run: bool,
-    ) -> bool {
-        let is_success = if crate_metadata.name().contains("-HEAD") {
-            remove_crate(
-                Path::new(&self.db_dir),
-                crate_detail,
-                crate_metadata,
-                dry_run,
-            )
-            .is_ok()
-        } else {
-            remove_crate(
-                Path::new(&self.checkout_dir),
-                crate_detail,
-                crate_metadata,
-                dry_run,
-            )
-            .is_ok()
-        };
-        if dry_run {
-            println!(
-                "{} {} {:?}",
-                "Dry run:".yellow(),
-                "Removed".red(),
-                crate_metadata.name(),
-            );
-            true
-        } else if is_success {
-            println!("{} {:?}", "Removed".red(), crate_metadata.name(),);
-            true
-        } else {
-            println!(r#"Failed to remove {:?}"#, crate_metadata.name(),);
-            false
-        }
-    }
-
-    /// Remove list of crates
-    pub(crate) fn remove_crate_list(
-        &self,
-        crate_detail: &CrateDetail,
-        list: &[CrateMetaData],
-        dry_run: bool,
-    ) -> (u64, usize) {
+    /// Remove list of crates. Protected crates and crates used within older
+    /// than duration are skipped
+    pub(crate) fn remove_crate_list(&self, list: &[CrateMetaData], dry_run: bool) -> (u64, usize) {
         let mut size_cleaned = 0;
         let mut crate_removed = 0;
-        for crate_metadata in list {
-            if self.remove_crate(crate_detail, crate_metadata, dry_run) {
+        let removable = list
+            .iter()
+            .filter(|crate_metadata| {
+                !skip_protected(crate_metadata)
+                    && self
+                        .older_than
+                        .is_none_or(|duration| crate_metadata.is_unused_for(duration))
+            })
+            .collect::<Vec<_>>();
+        if !dry_run {
+            start_deletion(removable.iter().copied().map(CrateMetaData::size).sum());
+        }
+        for crate_metadata in removable {
+            if !dry_run && !confirm_removal(crate_metadata) {
+                emit(&Event::Skipped {
+                    name: crate_metadata.full_name(),
+                    reason: "declined in interactive mode",
+                });
+                continue;
+            }
+            if remove_crate(crate_metadata, dry_run) {
                 size_cleaned += crate_metadata.size();
                 crate_removed += 1;
             }
         }
+        finish_progress();
         (size_cleaned, crate_removed)
     }
 }
 
-/// preform remove operation
-fn remove_crate(
-    location: &Path,
-    crate_detail: &CrateDetail,
-    crate_metadata: &CrateMetaData,
-    dry_run: bool,
-) -> Result<()> {
-    for entry in fs::read_dir(location)? {
-        let path = entry?.path();
-        let source = crate_detail.source_url_from_path(&path)?;
-        if &Some(source) == crate_metadata.source() {
-            // split name to split crate and rev sha
-            let name = crate_metadata.name();
-            let name = name.rsplitn(2, '-').collect::<Vec<&str>>();
-            let crate_name = name[1];
-            let rev_sha = name[0];
-            if path
-                .to_str()
-                .context("failed git directory crate path to str")?
-                .contains(crate_name)
-            {
-                if rev_sha.contains("HEAD") {
-                    delete_folder(&path, dry_run)?;
-                } else {
-                    for rev in fs::read_dir(&path)? {
-                        let path = rev?.path();
-                        let file_name = path
-                            .file_name()
-                            .context("Failed to get file name to check rev sha")?
-                            .to_str()
-                            .context("Failed rev sha file name to str conversion")?;
-                        if file_name == rev_sha {
-                            delete_folder(&path, dry_run)?;
-                        }
-                    }
-                    if fs::read_dir(&path)?.next().is_none() {
-                        delete_folder(&path, dry_run)?;
-                    }
-                }
+// remove crate. Paths found while scanning crate are removed so directory
+// whose name is not valid UTF-8 is removed as it is
+fn remove_crate(crate_metadata: &CrateMetaData, dry_run: bool) -> bool {
+    let is_checkout = crate_metadata
+        .git_id()
+        .is_some_and(|git_id| !git_id.is_database());
+    let mut is_success = !crate_metadata.path().is_empty();
+    for path in crate_metadata.path() {
+        is_success = matches!(delete_folder(path, dry_run), Ok(true)) && is_success;
+        // checkout directory of repository is removed with its last revision
+        if let Some(parent) = path.parent().filter(|_| is_checkout && !dry_run) {
+            if fs::read_dir(parent).is_ok_and(|mut entries| entries.next().is_none()) {
+                is_success = matches!(delete_folder(parent, dry_run), Ok(true)) && is_success;
             }
         }
     }
-    Ok(())
+    if dry_run {
+        if verbosity() >= 1 {
+            human_println!(
+                "{} {} {:?} ({})",
+                "Dry run:".yellow(),
+                "Removed".red(),
+                crate_metadata.name(),
+                convert_pretty(crate_metadata.size()).trim()
+            );
+        }
+        true
+    } else if is_success {
+        human_println!("{} {:?}", "Removed".red(), crate_metadata.name());
+        true
+    } else {
+        emit(&Event::Error {
+            message: format!("Failed to remove {:?}", crate_metadata.name()),
+        });
+        human_println!("Failed to remove {:?}", crate_metadata.name());
+        false
+    }
 }
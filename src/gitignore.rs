@@ -0,0 +1,129 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::utils::glob_match;
+
+// single pattern of .gitignore file along with folder containing it
+#[derive(Clone)]
+struct Pattern {
+    base: PathBuf,
+    glob: String,
+    anchored: bool,
+    negated: bool,
+    only_dir: bool,
+}
+
+/// Pattern of every .gitignore file found from registered directory till
+/// currently scanned folder. Only subset of gitignore syntax required to skip
+/// folder is supported
+#[derive(Clone, Default)]
+pub(crate) struct Gitignore {
+    patterns: Vec<Pattern>,
+}
+
+impl Gitignore {
+    /// return new rules with pattern of .gitignore file of directory added to
+    /// current rules. Current rules are returned as it is when directory do not
+    /// contain .gitignore file
+    pub(crate) fn with_dir(&self, directory: &Path) -> Self {
+        let mut gitignore = self.clone();
+        if let Ok(content) = fs::read_to_string(directory.join(".gitignore")) {
+            gitignore.patterns.extend(
+                content
+                    .lines()
+                    .filter_map(|line| parse_line(directory, line)),
+            );
+        }
+        gitignore
+    }
+
+    /// check if path is ignored. Last matching pattern decide result so negated
+    /// pattern can include path again
+    pub(crate) fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let mut ignored = false;
+        for pattern in &self.patterns {
+            if pattern.only_dir && !is_dir {
+                continue;
+            }
+            let Ok(relative) = path.strip_prefix(&pattern.base) else {
+                continue;
+            };
+            let matched = if pattern.anchored {
+                glob_match(
+                    &pattern.glob,
+                    &relative.to_string_lossy().replace('\\', "/"),
+                )
+            } else {
+                path.file_name()
+                    .is_some_and(|name| glob_match(&pattern.glob, &name.to_string_lossy()))
+            };
+            if matched {
+                ignored = !pattern.negated;
+            }
+        }
+        ignored
+    }
+}
+
+// parse single line of .gitignore file. Blank line and comment are skipped
+fn parse_line(base: &Path, line: &str) -> Option<Pattern> {
+    let line = line.trim_end();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let (negated, line) = match line.strip_prefix('!') {
+        Some(line) => (true, line),
+        None => (false, line),
+    };
+    let (only_dir, line) = match line.strip_suffix('/') {
+        Some(line) => (true, line),
+        None => (false, line),
+    };
+    // leading **/ matches in every folder same as pattern without slash
+    let line = line.strip_prefix("**/").unwrap_or(line);
+    let anchored = line.contains('/');
+    let glob = line.trim_start_matches('/');
+    if glob.is_empty() {
+        return None;
+    }
+    Some(Pattern {
+        base: base.to_path_buf(),
+        glob: glob.to_string(),
+        anchored,
+        negated,
+        only_dir,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::{Gitignore, parse_line};
+
+    #[test]
+    fn test_is_ignored() {
+        let base = Path::new("/projects");
+        let gitignore = Gitignore {
+            patterns: [
+                "# comment",
+                "build/",
+                "/vendor",
+                "docs/*.rs",
+                "*.tmp",
+                "!keep.tmp",
+            ]
+            .iter()
+            .filter_map(|line| parse_line(base, line))
+            .collect(),
+        };
+        assert!(gitignore.is_ignored(Path::new("/projects/app/build"), true));
+        assert!(!gitignore.is_ignored(Path::new("/projects/app/build"), false));
+        assert!(gitignore.is_ignored(Path::new("/projects/vendor"), true));
+        assert!(!gitignore.is_ignored(Path::new("/projects/app/vendor"), true));
+        assert!(gitignore.is_ignored(Path::new("/projects/docs/main.rs"), false));
+        assert!(gitignore.is_ignored(Path::new("/projects/app/cache.tmp"), true));
+        assert!(!gitignore.is_ignored(Path::new("/projects/app/keep.tmp"), true));
+        assert!(!gitignore.is_ignored(Path::new("/other/build"), true));
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::crate_detail::{CrateDetail, CrateMetaData};
+use crate::dir_path::DirPath;
+use crate::utils::{format_timestamp, get_size};
+
+// maximum number of snapshot kept in history file so file do not grow forever
+const MAX_SNAPSHOTS: usize = 1000;
+
+/// Size of each cache category at time of scan
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct SizeSnapshot {
+    taken: String,
+    bin: u64,
+    registry_source: u64,
+    registry_archive: u64,
+    git_checkout: u64,
+    git_db: u64,
+}
+
+impl SizeSnapshot {
+    /// take snapshot of category totals from scanned crate detail
+    pub(crate) fn new(crate_detail: &CrateDetail) -> Self {
+        Self {
+            taken: format_timestamp(SystemTime::now()),
+            bin: total_size(crate_detail.bin()),
+            registry_source: total_size(crate_detail.registry_crates_source()),
+            registry_archive: total_size(crate_detail.registry_crates_archive()),
+            git_checkout: total_size(crate_detail.git_crates_archive()),
+            git_db: total_size(crate_detail.git_crates_source()),
+        }
+    }
+
+    /// take snapshot by measuring cache directories. Used after clean since
+    /// scanned crate detail still contains removed crates
+    pub(crate) fn measure(dir_path: &DirPath) -> Self {
+        Self {
+            taken: format_timestamp(SystemTime::now()),
+            bin: get_size(dir_path.bin_dir()).unwrap_or(0),
+            registry_source: get_size(dir_path.src_dir()).unwrap_or(0),
+            registry_archive: get_size(dir_path.cache_dir()).unwrap_or(0),
+            git_checkout: get_size(dir_path.checkout_dir()).unwrap_or(0),
+            git_db: get_size(dir_path.db_dir()).unwrap_or(0),
+        }
+    }
+
+    pub(crate) fn taken(&self) -> &String {
+        &self.taken
+    }
+
+    /// size of registry crates source and archive
+    pub(crate) fn registry(&self) -> u64 {
+        self.registry_source + self.registry_archive
+    }
+
+    /// size of git crates checkout and database
+    pub(crate) fn git(&self) -> u64 {
+        self.git_checkout + self.git_db
+    }
+
+    pub(crate) fn bin(&self) -> u64 {
+        self.bin
+    }
+
+    pub(crate) fn total(&self) -> u64 {
+        self.bin + self.registry() + self.git()
+    }
+}
+
+/// Stores snapshot of cache size taken after each scan
+#[derive(Serialize, Deserialize, Default)]
+pub(crate) struct HistoryFile {
+    #[serde(default)]
+    snapshot: Vec<SizeSnapshot>,
+    #[serde(skip)]
+    location: PathBuf,
+}
+
+impl HistoryFile {
+    /// Read history file or create empty history if file is not present
+    pub(crate) fn init(history_file: &Path) -> Result<Self> {
+        let mut history = if history_file.exists() {
+            let content =
+                fs::read_to_string(history_file).context("failed to read history file content")?;
+            toml::from_str(&content).context("failed to convert string to History")?
+        } else {
+            Self::default()
+        };
+        history.location = history_file.to_path_buf();
+        Ok(history)
+    }
+
+    /// list of snapshot ordered from oldest to newest
+    pub(crate) fn snapshots(&self) -> &Vec<SizeSnapshot> {
+        &self.snapshot
+    }
+
+    /// add snapshot to history and save history file
+    pub(crate) fn record(&mut self, snapshot: SizeSnapshot) -> Result<()> {
+        self.snapshot.push(snapshot);
+        if self.snapshot.len() > MAX_SNAPSHOTS {
+            let excess = self.snapshot.len() - MAX_SNAPSHOTS;
+            self.snapshot.drain(..excess);
+        }
+        let serialized =
+            toml::to_string_pretty(&self).context("History cannot be converted to toml")?;
+        fs::write(&self.location, serialized).context("Failed to write history file")?;
+        Ok(())
+    }
+}
+
+// sum of size of all crates
+fn total_size(crates: &HashSet<CrateMetaData>) -> u64 {
+    crates.iter().map(CrateMetaData::size).sum()
+}
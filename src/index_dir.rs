@@ -0,0 +1,232 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use url::Url;
+
+use crate::crate_detail::CrateDetail;
+use crate::environment::is_sparse_index;
+use crate::utils::get_size;
+
+/// Stores information of single registry index present inside registry index
+/// directory. Git index is clone of index repository whereas sparse index only
+/// contains cache of index file fetched over http
+pub(crate) struct IndexDir {
+    path: PathBuf,
+    sparse: bool,
+    crates_io: bool,
+    size: u64,
+}
+
+impl IndexDir {
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(crate) fn is_sparse(&self) -> bool {
+        self.sparse
+    }
+
+    pub(crate) fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// check if index is old git clone of crates.io index
+    pub(crate) fn is_crates_io_git(&self) -> bool {
+        self.crates_io && !self.sparse
+    }
+
+    /// list cache files of index which are not modified within duration
+    pub(crate) fn stale_cache_files(&self, older_than: Duration) -> Result<Vec<(PathBuf, u64)>> {
+        let mut stale_files = Vec::new();
+        list_stale_files(&self.path.join(".cache"), older_than, &mut stale_files)?;
+        Ok(stale_files)
+    }
+}
+
+/// list all registry index present inside index directory
+pub(crate) fn list_index_dirs(index_dir: &Path) -> Result<Vec<IndexDir>> {
+    let mut index_dirs = Vec::new();
+    if !index_dir.exists() {
+        return Ok(index_dirs);
+    }
+    for entry in fs::read_dir(index_dir).context("failed to read index directory")? {
+        let path = entry?.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let fetch_head_file = path.join(".git").join("FETCH_HEAD");
+        let sparse = is_sparse_index(&path);
+        let crates_io = if sparse {
+            path.file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with("index.crates.io-"))
+        } else {
+            fs::read_to_string(fetch_head_file)
+                .is_ok_and(|content| content.contains("rust-lang/crates.io-index"))
+        };
+        index_dirs.push(IndexDir {
+            size: get_size(&path).unwrap_or(0),
+            path,
+            sparse,
+            crates_io,
+        });
+    }
+    index_dirs.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(index_dirs)
+}
+
+/// list crates.io git index clones which are obsolete because crates.io sparse
+/// index is also present and used by cargo. Nothing is obsolete when cargo is
+/// configured to access crates.io using git protocol
+pub(crate) fn obsolete_git_index<'a>(
+    cargo_home: &Path,
+    index_dirs: &'a [IndexDir],
+) -> Vec<&'a IndexDir> {
+    if crates_io_git_protocol(cargo_home) {
+        return Vec::new();
+    }
+    let has_sparse_crates_io = index_dirs
+        .iter()
+        .any(|index| index.crates_io && index.sparse);
+    if !has_sparse_crates_io {
+        return Vec::new();
+    }
+    index_dirs
+        .iter()
+        .filter(|index| index.is_crates_io_git())
+        .collect()
+}
+
+// check if crates.io protocol is set to git by environment variable or cargo
+// config file present in cargo home. Environment variable takes precedence
+fn crates_io_git_protocol(cargo_home: &Path) -> bool {
+    if let Ok(protocol) = std::env::var("CARGO_REGISTRIES_CRATES_IO_PROTOCOL") {
+        return protocol == "git";
+    }
+    ["config.toml", "config"].iter().any(|file_name| {
+        fs::read_to_string(cargo_home.join(file_name))
+            .ok()
+            .and_then(|content| toml::from_str::<toml::Value>(&content).ok())
+            .is_some_and(|config| {
+                config
+                    .get("registries")
+                    .and_then(|registries| registries.get("crates-io"))
+                    .and_then(|crates_io| crates_io.get("protocol"))
+                    .and_then(toml::Value::as_str)
+                    == Some("git")
+            })
+    })
+}
+
+/// list .cache folder of each registry index along with source url of registry
+pub(crate) fn index_cache_dirs(
+    index_dir: &Path,
+    crate_detail: &CrateDetail,
+) -> Result<Vec<(Url, PathBuf)>> {
+    let mut index_cache_dirs = Vec::new();
+    if index_dir.exists() {
+        for entry in fs::read_dir(index_dir).context("failed to read index directory")? {
+            let path = entry?.path();
+            if let Ok(source) = crate_detail.source_url_from_path(&path) {
+                index_cache_dirs.push((source, path.join(".cache")));
+            }
+        }
+    }
+    Ok(index_cache_dirs)
+}
+
+/// read json entry of crate version from index cache file of crate. Git and
+/// sparse index both store fetched index file inside .cache folder
+pub(crate) fn index_entry(index_cache_file: &Path, version: &str) -> Option<serde_json::Value> {
+    let content = fs::read(index_cache_file).ok()?;
+    // index cache file contains null separated version and json entry pairs
+    content
+        .split(|&byte| byte == 0)
+        .filter_map(|chunk| serde_json::from_slice::<serde_json::Value>(chunk).ok())
+        .find(|entry| entry.get("vers").and_then(serde_json::Value::as_str) == Some(version))
+}
+
+// recursively list files inside path which are not modified within duration
+fn list_stale_files(
+    path: &Path,
+    older_than: Duration,
+    stale_files: &mut Vec<(PathBuf, u64)>,
+) -> Result<()> {
+    if !path.is_dir() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(path).context("failed to read index cache directory")? {
+        let entry = entry?.path();
+        if entry.is_dir() {
+            list_stale_files(&entry, older_than, stale_files)?;
+            continue;
+        }
+        let metadata = entry.metadata()?;
+        let is_stale = metadata.modified().map_or(true, |modified| {
+            SystemTime::now()
+                .duration_since(modified)
+                .is_ok_and(|age| age > older_than)
+        });
+        if is_stale {
+            stale_files.push((entry, metadata.len()));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::{index_entry, list_index_dirs, obsolete_git_index};
+
+    #[test]
+    fn test_obsolete_git_index() {
+        let cargo_home =
+            std::env::temp_dir().join(format!("cargo_trim_index_dir_test_{}", std::process::id()));
+        let index_dir = cargo_home.join("registry").join("index");
+        let git_index = index_dir.join("github.com-1ecc6299db9ec823");
+        fs::create_dir_all(git_index.join(".git")).unwrap();
+        fs::write(
+            git_index.join(".git").join("FETCH_HEAD"),
+            "abc\t\tbranch 'master' of https://github.com/rust-lang/crates.io-index",
+        )
+        .unwrap();
+        assert!(obsolete_git_index(&cargo_home, &list_index_dirs(&index_dir).unwrap()).is_empty());
+        fs::create_dir_all(
+            index_dir
+                .join("index.crates.io-6f17d22bba15001f")
+                .join(".cache"),
+        )
+        .unwrap();
+        let index_dirs = list_index_dirs(&index_dir).unwrap();
+        let obsolete = obsolete_git_index(&cargo_home, &index_dirs);
+        assert_eq!(obsolete.len(), 1);
+        assert_eq!(obsolete[0].path(), git_index);
+        // git index is still used when crates.io protocol is configured as git
+        fs::write(
+            cargo_home.join("config.toml"),
+            "[registries.crates-io]\nprotocol = \"git\"\n",
+        )
+        .unwrap();
+        assert!(obsolete_git_index(&cargo_home, &index_dirs).is_empty());
+        fs::remove_dir_all(&cargo_home).unwrap();
+    }
+
+    #[test]
+    fn test_index_entry() {
+        let cache_file = std::env::temp_dir().join("cargo_trim_index_entry_test");
+        let mut content = b"\x03etag\x001.0.0\x00".to_vec();
+        content.extend_from_slice(br#"{"name":"foo","vers":"1.0.0","cksum":"aa","yanked":false}"#);
+        content.extend_from_slice(b"\x001.0.1\x00");
+        content.extend_from_slice(br#"{"name":"foo","vers":"1.0.1","cksum":"bb","yanked":true}"#);
+        content.push(0);
+        fs::write(&cache_file, content).unwrap();
+        let entry = index_entry(&cache_file, "1.0.1").unwrap();
+        assert_eq!(entry["cksum"], "bb");
+        assert_eq!(entry["yanked"], true);
+        assert!(index_entry(&cache_file, "2.0.0").is_none());
+        fs::remove_file(&cache_file).unwrap();
+    }
+}
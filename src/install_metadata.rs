@@ -0,0 +1,356 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::crate_detail::CrateMetaData;
+use crate::package_lock::lock_package_cache;
+
+// binaries placed in bin directory by rustup instead of cargo install
+const RUSTUP_PROXIES: [&str; 15] = [
+    "cargo",
+    "cargo-clippy",
+    "cargo-fmt",
+    "cargo-miri",
+    "clippy-driver",
+    "rls",
+    "rust-analyzer",
+    "rust-gdb",
+    "rust-gdbgui",
+    "rust-lldb",
+    "rustc",
+    "rustdoc",
+    "rustfmt",
+    "rustup",
+    "rustup-init",
+];
+
+/// Package installed by cargo install along with binaries it provides
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct InstalledPackage {
+    name: String,
+    version: String,
+    source: String,
+    features: Vec<String>,
+    all_features: bool,
+    no_default_features: bool,
+    bins: Vec<String>,
+}
+
+impl InstalledPackage {
+    // create package from package id in form of `name version (source)`
+    fn from_package_id(package_id: &str, bins: Vec<String>) -> Self {
+        let mut parts = package_id.splitn(3, ' ');
+        let name = parts.next().unwrap_or_default().to_string();
+        let version = parts.next().unwrap_or_default().to_string();
+        let source = parts
+            .next()
+            .unwrap_or_default()
+            .trim_start_matches('(')
+            .trim_end_matches(')')
+            .to_string();
+        Self {
+            name,
+            version,
+            source,
+            features: Vec::new(),
+            all_features: false,
+            no_default_features: false,
+            bins,
+        }
+    }
+
+    pub(crate) fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub(crate) fn version(&self) -> &String {
+        &self.version
+    }
+
+    pub(crate) fn features(&self) -> &Vec<String> {
+        &self.features
+    }
+
+    /// check if package is installed from crates.io so its latest version can
+    /// be searched
+    pub(crate) fn is_from_crates_io(&self) -> bool {
+        self.source.starts_with("registry+")
+            && (self.source.contains("github.com/rust-lang/crates.io-index")
+                || self.source.contains("index.crates.io"))
+    }
+
+    /// arguments of cargo install which install package again with same
+    /// features
+    pub(crate) fn install_args(&self) -> Vec<String> {
+        let mut args = vec![String::from("install"), self.name.clone()];
+        if !self.features.is_empty() {
+            args.push(String::from("--features"));
+            args.push(self.features.join(","));
+        }
+        if self.all_features {
+            args.push(String::from("--all-features"));
+        }
+        if self.no_default_features {
+            args.push(String::from("--no-default-features"));
+        }
+        args
+    }
+}
+
+/// Metadata of packages installed by cargo install read from .crates2.json and
+/// .crates.toml of cargo home. Both files are kept as parsed document so
+/// fields unknown to cargo trim are written back unchanged
+pub(crate) struct InstallMetadata {
+    crates2_file: PathBuf,
+    crates_toml_file: PathBuf,
+    crates2: Option<serde_json::Value>,
+    crates_toml: Option<toml::Value>,
+}
+
+impl InstallMetadata {
+    /// read install metadata files of cargo home. Missing file is treated as
+    /// empty since it is only created after first cargo install
+    pub(crate) fn load(cargo_home: &Path) -> Result<Self> {
+        let crates2_file = cargo_home.join(".crates2.json");
+        let crates_toml_file = cargo_home.join(".crates.toml");
+        let crates2 = if crates2_file.exists() {
+            let content = fs::read_to_string(&crates2_file)
+                .context("Failed to read .crates2.json of cargo home")?;
+            Some(serde_json::from_str(&content).context("Failed to parse .crates2.json")?)
+        } else {
+            None
+        };
+        let crates_toml = if crates_toml_file.exists() {
+            let content = fs::read_to_string(&crates_toml_file)
+                .context("Failed to read .crates.toml of cargo home")?;
+            Some(toml::from_str(&content).context("Failed to parse .crates.toml")?)
+        } else {
+            None
+        };
+        Ok(Self {
+            crates2_file,
+            crates_toml_file,
+            crates2,
+            crates_toml,
+        })
+    }
+
+    /// list installed packages. Features are only stored in .crates2.json so
+    /// package listed only in .crates.toml has no feature
+    pub(crate) fn packages(&self) -> Vec<InstalledPackage> {
+        if let Some(installs) = self
+            .crates2
+            .as_ref()
+            .and_then(|crates2| crates2.get("installs"))
+            .and_then(serde_json::Value::as_object)
+        {
+            return installs
+                .iter()
+                .map(|(package_id, install)| {
+                    let flag = |key| {
+                        install
+                            .get(key)
+                            .and_then(serde_json::Value::as_bool)
+                            .unwrap_or(false)
+                    };
+                    InstalledPackage {
+                        features: string_list(install.get("features")),
+                        all_features: flag("all_features"),
+                        no_default_features: flag("no_default_features"),
+                        ..InstalledPackage::from_package_id(
+                            package_id,
+                            string_list(install.get("bins")),
+                        )
+                    }
+                })
+                .collect();
+        }
+        self.crates_toml
+            .as_ref()
+            .and_then(|crates_toml| crates_toml.get("v1"))
+            .and_then(toml::Value::as_table)
+            .map(|v1| {
+                v1.iter()
+                    .map(|(package_id, bins)| {
+                        let bins = bins
+                            .as_array()
+                            .map(|bins| {
+                                bins.iter()
+                                    .filter_map(toml::Value::as_str)
+                                    .map(ToString::to_string)
+                                    .collect()
+                            })
+                            .unwrap_or_default();
+                        InstalledPackage::from_package_id(package_id, bins)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// package which installed binary. Binary name can contain .exe extension
+    pub(crate) fn package_of(&self, bin_name: &str) -> Option<InstalledPackage> {
+        let bin_name = bin_name.trim_end_matches(".exe");
+        self.packages().into_iter().find(|package| {
+            package
+                .bins
+                .iter()
+                .any(|bin| bin.trim_end_matches(".exe") == bin_name)
+        })
+    }
+
+    /// binaries which are neither installed by cargo install nor rustup proxy
+    /// such as leftover of failed install or manually copied binary
+    pub(crate) fn untracked_bins(&self, installed_bin: &[CrateMetaData]) -> Vec<CrateMetaData> {
+        installed_bin
+            .iter()
+            .filter(|bin| {
+                !RUSTUP_PROXIES.contains(&bin.name().trim_end_matches(".exe"))
+                    && self.package_of(bin.name()).is_none()
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// remove binary from both metadata files. Package entry is removed when
+    /// its last binary is removed so cargo do not treat it as installed
+    pub(crate) fn remove_bin(&mut self, bin_name: &str) -> Result<()> {
+        let bin_name = bin_name.trim_end_matches(".exe");
+        let is_bin = |value: &str| value.trim_end_matches(".exe") == bin_name;
+        if let Some(installs) = self
+            .crates2
+            .as_mut()
+            .and_then(|crates2| crates2.get_mut("installs"))
+            .and_then(serde_json::Value::as_object_mut)
+        {
+            installs.retain(|_, install| {
+                let Some(bins) = install
+                    .get_mut("bins")
+                    .and_then(serde_json::Value::as_array_mut)
+                else {
+                    return true;
+                };
+                let before = bins.len();
+                bins.retain(|bin| !bin.as_str().is_some_and(is_bin));
+                bins.len() == before || !bins.is_empty()
+            });
+        }
+        if let Some(v1) = self
+            .crates_toml
+            .as_mut()
+            .and_then(|crates_toml| crates_toml.get_mut("v1"))
+            .and_then(toml::Value::as_table_mut)
+        {
+            // toml map do not support retain so emptied package is removed later
+            let mut emptied = Vec::new();
+            for (package_id, bins) in v1.iter_mut() {
+                let Some(bins) = bins.as_array_mut() else {
+                    continue;
+                };
+                let before = bins.len();
+                bins.retain(|bin| !bin.as_str().is_some_and(is_bin));
+                if bins.len() != before && bins.is_empty() {
+                    emptied.push(package_id.clone());
+                }
+            }
+            for package_id in emptied {
+                v1.remove(&package_id);
+            }
+        }
+        self.save()
+    }
+
+    // write both metadata files back if they were present. Package cache lock
+    // is held so metadata is not written while cargo install is running
+    fn save(&self) -> Result<()> {
+        lock_package_cache()?;
+        if let Some(crates2) = &self.crates2 {
+            let content = serde_json::to_string(crates2)
+                .context("Failed to convert install metadata to json")?;
+            write_replace(&self.crates2_file, &content).context("Failed to write .crates2.json")?;
+        }
+        if let Some(crates_toml) = &self.crates_toml {
+            let content = toml::to_string(crates_toml)
+                .context("Failed to convert install metadata to toml")?;
+            write_replace(&self.crates_toml_file, &content)
+                .context("Failed to write .crates.toml")?;
+        }
+        Ok(())
+    }
+}
+
+// write content to temporary file next to path and rename it over path so
+// interrupted write never leaves truncated metadata file
+fn write_replace(path: &Path, content: &str) -> Result<()> {
+    let mut temporary_file = path.as_os_str().to_os_string();
+    temporary_file.push(".cargo-trim-tmp");
+    let temporary_file = PathBuf::from(temporary_file);
+    fs::write(&temporary_file, content)?;
+    if let Err(err) = fs::rename(&temporary_file, path) {
+        let _ = fs::remove_file(&temporary_file);
+        return Err(err.into());
+    }
+    Ok(())
+}
+
+fn string_list(value: Option<&serde_json::Value>) -> Vec<String> {
+    value
+        .and_then(serde_json::Value::as_array)
+        .map(|list| {
+            list.iter()
+                .filter_map(serde_json::Value::as_str)
+                .map(ToString::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::InstallMetadata;
+
+    #[test]
+    fn test_remove_bin() {
+        let cargo_home = std::env::temp_dir().join("cargo_trim_install_metadata_test");
+        let _ = fs::remove_dir_all(&cargo_home);
+        fs::create_dir_all(&cargo_home).unwrap();
+        let package_id = "ripgrep 13.0.0 (registry+https://github.com/rust-lang/crates.io-index)";
+        fs::write(
+            cargo_home.join(".crates2.json"),
+            format!(
+                r#"{{"installs":{{"{package_id}":{{"bins":["rg"],"features":["pcre2"],"profile":"release"}},"tool 0.1.0 (path+file:///tool)":{{"bins":["tool-a","tool-b"],"features":[]}}}}}}"#
+            ),
+        )
+        .unwrap();
+        fs::write(
+            cargo_home.join(".crates.toml"),
+            format!(
+                "[v1]\n\"{package_id}\" = [\"rg\"]\n\"tool 0.1.0 (path+file:///tool)\" = \
+                 [\"tool-a\", \"tool-b\"]\n"
+            ),
+        )
+        .unwrap();
+        let mut install_metadata = InstallMetadata::load(&cargo_home).unwrap();
+        let package = install_metadata.package_of("rg.exe").unwrap();
+        assert_eq!(package.name(), "ripgrep");
+        assert_eq!(package.version(), "13.0.0");
+        assert_eq!(package.features(), &vec![String::from("pcre2")]);
+        install_metadata.remove_bin("rg").unwrap();
+        install_metadata.remove_bin("tool-a").unwrap();
+        let install_metadata = InstallMetadata::load(&cargo_home).unwrap();
+        assert!(install_metadata.package_of("rg").is_none());
+        assert!(install_metadata.package_of("tool-a").is_none());
+        assert_eq!(
+            install_metadata.package_of("tool-b").unwrap().name(),
+            "tool"
+        );
+        let crates_toml = fs::read_to_string(cargo_home.join(".crates.toml")).unwrap();
+        assert!(!crates_toml.contains("ripgrep"));
+        assert!(crates_toml.contains("tool-b"));
+        fs::remove_dir_all(&cargo_home).unwrap();
+    }
+}
@@ -0,0 +1,125 @@
+//! Library used by `cargo trim` binary. It exposes scanning of `$CARGO_HOME`,
+//! size calculation and retention policy so other tools can embed cargo trim
+//! without parsing its output. Crates selected by retention [`Policy`] are
+//! removed with [`trim`] while every other operation is performed through
+//! [`Command`]
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use cargo_trim::{ConfigFile, CrateDetail, CrateList, DirPath, SizeCache, convert_pretty};
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let dir_path = DirPath::new(None)?;
+//! let config_file = ConfigFile::init(dir_path.config_file())?;
+//! let size_cache = SizeCache::init(dir_path.size_cache_file(), true)?;
+//! let mut crate_detail = CrateDetail::new(dir_path.index_dir(), dir_path.db_dir(), size_cache)?;
+//! let crate_list = CrateList::create_list(&dir_path, &config_file, &mut crate_detail)?;
+//! for crate_metadata in crate_list.installed_registry() {
+//!     if crate_metadata.is_unused_for(Duration::from_secs(30 * 24 * 60 * 60)) {
+//!         println!(
+//!             "{} is unused for 30 days and occupies {}",
+//!             crate_metadata.name(),
+//!             convert_pretty(crate_metadata.size())
+//!         );
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+#![warn(unreachable_pub, anonymous_parameters)]
+#![deny(unsafe_code)]
+#![deny(clippy::all)]
+#![warn(clippy::pedantic)]
+
+// print human readable line. Stdout is reserved for event stream in ndjson
+// mode so line is written to stderr instead. Color is removed when disabled
+macro_rules! human_println {
+    ($($arg:tt)*) => {
+        if $crate::event::quiet_enabled() {
+        } else if $crate::event::ndjson_enabled() {
+            eprintln!("{}", $crate::color::paint(format!($($arg)*)));
+        } else {
+            println!("{}", $crate::color::paint(format!($($arg)*)));
+        }
+    };
+}
+
+// print human readable diagnostic line to stderr so it never mixes with
+// document such as json written to stdout. Color is removed when disabled
+macro_rules! human_eprintln {
+    ($($arg:tt)*) => {
+        if !$crate::event::quiet_enabled() {
+            eprintln!("{}", $crate::color::paint(format!($($arg)*)));
+        }
+    };
+}
+
+// print human readable text without newline to stdout or stderr in ndjson mode.
+// Color is removed when disabled
+macro_rules! human_print {
+    ($($arg:tt)*) => {
+        if $crate::event::quiet_enabled() {
+        } else if $crate::event::ndjson_enabled() {
+            eprint!("{}", $crate::color::paint(format!($($arg)*)));
+        } else {
+            print!("{}", $crate::color::paint(format!($($arg)*)));
+        }
+    };
+}
+
+mod cargo_cache;
+mod color;
+mod command;
+mod completion_index;
+mod config_file;
+mod container;
+mod crate_detail;
+mod crate_id;
+mod deprecated_crate;
+mod dir_path;
+mod dry_run_summary;
+mod environment;
+mod event;
+mod freed_space;
+mod git_dir;
+mod gitignore;
+mod history_file;
+mod index_dir;
+mod install_metadata;
+mod list_crate;
+mod log_file;
+mod nested_cargo_home;
+mod network;
+mod package_lock;
+mod progress;
+mod protect;
+mod registries;
+mod registry_dir;
+mod removal_failure;
+mod report;
+mod restore_manifest;
+mod run_summary;
+mod running_process;
+mod rustup_dir;
+mod scan_snapshot;
+mod schema;
+mod sha256;
+mod size_cache;
+mod stats_file;
+mod target_dir;
+mod terminal;
+mod trash;
+mod trim;
+mod utils;
+
+pub use cargo_cache::translate_cargo_cache;
+pub use command::{Command, Outcome};
+pub use config_file::ConfigFile;
+pub use crate_detail::{CrateDetail, CrateMetaData};
+pub use dir_path::DirPath;
+pub use list_crate::CrateList;
+pub use size_cache::SizeCache;
+pub use trim::{Policy, Trimmed, trim};
+pub use utils::{convert_pretty, get_size, parse_duration};
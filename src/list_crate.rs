@@ -1,15 +1,28 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use anyhow::{Context, Result};
+use regex::Regex;
 use semver::Version;
 use serde::Deserialize;
 use url::Url;
 
 use crate::config_file::ConfigFile;
 use crate::crate_detail::{CrateDetail, CrateMetaData};
+use crate::crate_id::{CrateId, GitId};
+use crate::deprecated_crate::is_deprecated;
 use crate::dir_path::DirPath;
+use crate::index_dir::{index_cache_dirs, index_entry};
+use crate::registry_dir::index_cache_file;
+
+/// crates.io index url recorded in Cargo.lock
+const CRATES_IO_GIT_INDEX: &str = "https://github.com/rust-lang/crates.io-index";
+
+/// crates.io sparse index url
+const CRATES_IO_SPARSE_INDEX: &str = "https://index.crates.io/";
 
 /// struct store Cargo.toml file location
 pub(crate) struct CargoTomlLocation {
@@ -40,8 +53,8 @@ struct LockData {
 }
 
 impl LockData {
-    fn package(&self) -> &Option<Vec<Package>> {
-        &self.package
+    fn package(&self) -> Option<&Vec<Package>> {
+        self.package.as_ref()
     }
 }
 
@@ -61,13 +74,13 @@ impl Package {
         &self.version
     }
 
-    fn source(&self) -> &Option<String> {
-        &self.source
+    fn source(&self) -> Option<&String> {
+        self.source.as_ref()
     }
 }
 
 /// struct to store all crate list detail with its type
-pub(crate) struct CrateList {
+pub struct CrateList {
     installed_bin: Vec<CrateMetaData>,
     installed_crate_registry: Vec<CrateMetaData>,
     installed_crate_git: Vec<CrateMetaData>,
@@ -77,27 +90,26 @@ pub(crate) struct CrateList {
     used_crate_git: Vec<CrateMetaData>,
     orphan_crate_registry: Vec<CrateMetaData>,
     orphan_crate_git: Vec<CrateMetaData>,
+    deprecated_crate_registry: Vec<CrateMetaData>,
+    yanked_crate_registry: Vec<CrateMetaData>,
     cargo_toml_location: CargoTomlLocation,
 }
 
 impl CrateList {
     /// create list of all types of crate present in directory
-    pub(crate) fn create_list(
+    ///
+    /// # Errors
+    /// Return error if cargo home or project directory cannot be scanned
+    pub fn create_list(
         dir_path: &DirPath,
         config_file: &ConfigFile,
         crate_detail: &mut CrateDetail,
     ) -> Result<Self> {
-        let bin_dir = dir_path.bin_dir();
-        let cache_dir = dir_path.cache_dir();
-        let src_dir = dir_path.src_dir();
-        let checkout_dir = dir_path.checkout_dir();
         let db_dir = dir_path.db_dir();
 
         // list installed crates
-        let installed_bin = crate_detail.list_installed_bin(bin_dir)?;
-        let installed_crate_registry =
-            crate_detail.list_installed_crate_registry(src_dir, cache_dir)?;
-        let installed_crate_git = crate_detail.list_installed_crate_git(checkout_dir, db_dir)?;
+        let (installed_bin, installed_crate_registry, installed_crate_git) =
+            crate_detail.list_installed(dir_path)?;
 
         // list old registry crate
         let (old_crate_registry, old_crate_git) = list_old_crates(
@@ -118,6 +130,16 @@ impl CrateList {
             &used_crate_git,
         );
 
+        // list deprecated registry crate which are superseded by other crate
+        let deprecated_crate_registry = list_deprecated_crates(&installed_crate_registry);
+
+        // list registry crate whose version is yanked in registry index
+        let yanked_crate_registry = list_yanked_crates(
+            &installed_crate_registry,
+            crate_detail,
+            dir_path.index_dir(),
+        )?;
+
         Ok(Self {
             installed_bin,
             installed_crate_registry,
@@ -128,62 +150,187 @@ impl CrateList {
             used_crate_git,
             orphan_crate_registry,
             orphan_crate_git,
+            deprecated_crate_registry,
+            yanked_crate_registry,
             cargo_toml_location,
         })
     }
 
     /// provide list of installed bin
-    pub(crate) fn installed_bin(&self) -> &Vec<CrateMetaData> {
+    #[must_use]
+    pub fn installed_bin(&self) -> &Vec<CrateMetaData> {
         &self.installed_bin
     }
 
     /// provide list of installed registry
-    pub(crate) fn installed_registry(&self) -> &Vec<CrateMetaData> {
+    #[must_use]
+    pub fn installed_registry(&self) -> &Vec<CrateMetaData> {
         &self.installed_crate_registry
     }
 
     /// provide list of old registry
-    pub(crate) fn old_registry(&self) -> &Vec<CrateMetaData> {
+    #[must_use]
+    pub fn old_registry(&self) -> &Vec<CrateMetaData> {
         &self.old_crate_registry
     }
 
     /// provide list of used registry
-    pub(crate) fn used_registry(&self) -> &Vec<CrateMetaData> {
+    #[must_use]
+    pub fn used_registry(&self) -> &Vec<CrateMetaData> {
         &self.used_crate_registry
     }
 
     /// provide list o orphan registry
-    pub(crate) fn orphan_registry(&self) -> &Vec<CrateMetaData> {
+    #[must_use]
+    pub fn orphan_registry(&self) -> &Vec<CrateMetaData> {
         &self.orphan_crate_registry
     }
 
     /// provide list of installed git
-    pub(crate) fn installed_git(&self) -> &Vec<CrateMetaData> {
+    #[must_use]
+    pub fn installed_git(&self) -> &Vec<CrateMetaData> {
         &self.installed_crate_git
     }
 
     /// provide list of old git
-    pub(crate) fn old_git(&self) -> &Vec<CrateMetaData> {
+    #[must_use]
+    pub fn old_git(&self) -> &Vec<CrateMetaData> {
         &self.old_crate_git
     }
 
     /// provide list of used git
-    pub(crate) fn used_git(&self) -> &Vec<CrateMetaData> {
+    #[must_use]
+    pub fn used_git(&self) -> &Vec<CrateMetaData> {
         &self.used_crate_git
     }
 
     /// provide list of orphan git
-    pub(crate) fn orphan_git(&self) -> &Vec<CrateMetaData> {
+    #[must_use]
+    pub fn orphan_git(&self) -> &Vec<CrateMetaData> {
         &self.orphan_crate_git
     }
 
+    /// provide list of deprecated registry
+    #[must_use]
+    pub fn deprecated_registry(&self) -> &Vec<CrateMetaData> {
+        &self.deprecated_crate_registry
+    }
+
+    /// provide list of yanked registry
+    #[must_use]
+    pub fn yanked_registry(&self) -> &Vec<CrateMetaData> {
+        &self.yanked_crate_registry
+    }
+
+    /// list out installed crates whose name only differ by case. Such crates
+    /// collide with each other in case insensitive file system
+    pub(crate) fn case_collisions(&self) -> Vec<(CrateMetaData, CrateMetaData)> {
+        let mut collisions = Vec::new();
+        for installed_list in [self.installed_registry(), self.installed_git()] {
+            for (pos, crate_metadata) in installed_list.iter().enumerate() {
+                for other in &installed_list[pos + 1..] {
+                    if crate_metadata.name() != other.name()
+                        && crate_metadata.name().eq_ignore_ascii_case(other.name())
+                        && crate_metadata.version() == other.version()
+                        && crate_metadata.source() == other.source()
+                    {
+                        collisions.push((crate_metadata.clone(), other.clone()));
+                    }
+                }
+            }
+        }
+        collisions
+    }
+
+    /// resolve project from its path or from its directory name among scanned
+    /// projects
+    pub(crate) fn resolve_project(&self, project: &str) -> Result<PathBuf> {
+        let project_path = Path::new(project);
+        if project_path.exists() {
+            return project_path
+                .canonicalize()
+                .context("Failed to get canonical path of project");
+        }
+        let matched_projects = self
+            .cargo_toml_location
+            .location_path()
+            .iter()
+            .filter(|location| location.file_name() == Some(project_path.as_os_str()))
+            .collect::<Vec<_>>();
+        match matched_projects.as_slice() {
+            [location] => location
+                .canonicalize()
+                .context("Failed to get canonical path of project"),
+            [] => anyhow::bail!("Failed to find project {project:?} in registered directory"),
+            _ => {
+                anyhow::bail!(
+                    "Multiple project named {project:?} found pass project path instead of name"
+                )
+            }
+        }
+    }
+
+    /// list installed registry and git crates which are referenced by project
+    /// lock file
+    pub(crate) fn project_crates(
+        &self,
+        project: &Path,
+    ) -> Result<(Vec<CrateMetaData>, Vec<CrateMetaData>)> {
+        let (used_registry, used_git) = read_content(&[project.to_path_buf()])?;
+        Ok(list_installed_used_crates(
+            &self.installed_crate_registry,
+            &self.installed_crate_git,
+            &used_registry,
+            &used_git,
+        ))
+    }
+
+    /// list installed registry and git crates which are only referenced by
+    /// project and not by any other scanned project
+    pub(crate) fn project_exclusive_crates(
+        &self,
+        project: &Path,
+    ) -> Result<(Vec<CrateMetaData>, Vec<CrateMetaData>)> {
+        let (project_registry, project_git) = self.project_crates(project)?;
+        // project present inside project directory are workspace member of project
+        let other_projects = self
+            .cargo_toml_location
+            .location_path()
+            .iter()
+            .filter(|location| {
+                let location = location
+                    .canonicalize()
+                    .unwrap_or_else(|_| (*location).clone());
+                !location.starts_with(project)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        let (other_used_registry, other_used_git) = read_content(&other_projects)?;
+        let (other_registry, other_git) = list_installed_used_crates(
+            &self.installed_crate_registry,
+            &self.installed_crate_git,
+            &other_used_registry,
+            &other_used_git,
+        );
+        let exclusive_registry = project_registry
+            .into_iter()
+            .filter(|crate_metadata| !other_registry.contains(crate_metadata))
+            .collect();
+        let exclusive_git = project_git
+            .into_iter()
+            .filter(|crate_metadata| !other_git.contains(crate_metadata))
+            .collect();
+        Ok((exclusive_registry, exclusive_git))
+    }
+
     /// list out path of directory which contains cargo lock file
     pub(crate) fn cargo_toml_location(&self) -> &CargoTomlLocation {
         &self.cargo_toml_location
     }
 
     /// list crates which is both old and orphan
-    pub(crate) fn list_old_orphan_registry(&self) -> Vec<CrateMetaData> {
+    #[must_use]
+    pub fn list_old_orphan_registry(&self) -> Vec<CrateMetaData> {
         let mut old_orphan_registry = Vec::new();
         let orphan_list = self.orphan_registry();
         for crates in self.old_registry() {
@@ -194,8 +341,166 @@ impl CrateList {
         old_orphan_registry
     }
 
+    // group installed registry crates by source and name. Versions of each
+    // group are sorted by newest version first
+    fn registry_versions(&self) -> Vec<Vec<(CrateId, &CrateMetaData)>> {
+        let mut grouped_registry: HashMap<(Url, String), Vec<(CrateId, &CrateMetaData)>> =
+            HashMap::new();
+        for crate_metadata in self.installed_registry() {
+            if let Some(crate_id) = crate_metadata.crate_id() {
+                grouped_registry
+                    .entry((crate_id.registry().clone(), crate_id.name().clone()))
+                    .or_default()
+                    .push((crate_id, crate_metadata));
+            }
+        }
+        grouped_registry
+            .into_values()
+            .map(|mut versions| {
+                versions.sort_by(|(a, _), (b, _)| b.version().cmp(a.version()));
+                versions
+            })
+            .collect()
+    }
+
+    /// list registry crates which are older than newest keep number of
+    /// versions of crate from same source
+    #[must_use]
+    pub fn list_excess_version_registry(&self, keep: usize) -> Vec<CrateMetaData> {
+        let mut excess_version_registry = Vec::new();
+        for versions in self.registry_versions() {
+            excess_version_registry.extend(
+                versions
+                    .into_iter()
+                    .skip(keep)
+                    .map(|(_, crate_metadata)| crate_metadata.clone()),
+            );
+        }
+        excess_version_registry.sort();
+        excess_version_registry
+    }
+
+    /// list registry crates which have multiple versions from same source
+    /// except newest version and versions referenced by lock file of any
+    /// registered project
+    #[must_use]
+    pub fn list_duplicate_version_registry(&self) -> Vec<CrateMetaData> {
+        let mut duplicate_version_registry = Vec::new();
+        for versions in self.registry_versions() {
+            duplicate_version_registry.extend(
+                versions
+                    .into_iter()
+                    .skip(1)
+                    .filter(|(_, crate_metadata)| !self.used_registry().contains(crate_metadata))
+                    .map(|(_, crate_metadata)| crate_metadata.clone()),
+            );
+        }
+        duplicate_version_registry.sort();
+        duplicate_version_registry
+    }
+
+    /// list registry crates whose version is pre-release such as alpha, beta or
+    /// rc when stable version of same crate from same source is also cached.
+    /// Versions referenced by lock file of any registered project are kept
+    #[must_use]
+    pub fn list_prerelease_registry(&self) -> Vec<CrateMetaData> {
+        let mut prerelease_registry = Vec::new();
+        for versions in self.registry_versions() {
+            if !versions
+                .iter()
+                .any(|(crate_id, _)| crate_id.version().pre.is_empty())
+            {
+                continue;
+            }
+            prerelease_registry.extend(
+                versions
+                    .into_iter()
+                    .filter(|(crate_id, crate_metadata)| {
+                        !crate_id.version().pre.is_empty()
+                            && !self.used_registry().contains(crate_metadata)
+                    })
+                    .map(|(_, crate_metadata)| crate_metadata.clone()),
+            );
+        }
+        prerelease_registry.sort();
+        prerelease_registry
+    }
+
+    /// list installed registry and git crates ordered by how cheap they are to
+    /// evict. Orphan crates come first, then least recently used and larger
+    /// crates so deleting from start frees most space with least rebuild cost
+    pub(crate) fn list_eviction_candidates(&self) -> Vec<CrateMetaData> {
+        let mut candidates = Vec::new();
+        for crate_metadata in self.installed_registry() {
+            candidates.push((
+                self.orphan_registry().contains(crate_metadata),
+                crate_metadata,
+            ));
+        }
+        for crate_metadata in self.installed_git() {
+            candidates.push((self.orphan_git().contains(crate_metadata), crate_metadata));
+        }
+        candidates.sort_by_key(|(orphan, crate_metadata)| {
+            (
+                !orphan,
+                crate_metadata.last_used(),
+                Reverse(crate_metadata.size()),
+            )
+        });
+        candidates
+            .into_iter()
+            .map(|(_, crate_metadata)| crate_metadata.clone())
+            .collect()
+    }
+
+    /// keep only registry and git crates whose full name-version matches regex
+    /// so output and clean operation are restricted to those crates
+    pub fn retain_matching(&mut self, regex: &Regex) {
+        for list in [
+            &mut self.installed_crate_registry,
+            &mut self.installed_crate_git,
+            &mut self.old_crate_registry,
+            &mut self.old_crate_git,
+            &mut self.used_crate_registry,
+            &mut self.used_crate_git,
+            &mut self.orphan_crate_registry,
+            &mut self.orphan_crate_git,
+            &mut self.deprecated_crate_registry,
+            &mut self.yanked_crate_registry,
+        ] {
+            list.retain(|crate_metadata| regex.is_match(&crate_metadata.full_name()));
+        }
+    }
+
+    /// keep only registry crates whose source matches predicate. Git crates do
+    /// not belong to any registry so they are removed
+    pub(crate) fn retain_registry<F>(&mut self, mut predicate: F)
+    where
+        F: FnMut(&Url) -> bool,
+    {
+        for list in [
+            &mut self.installed_crate_registry,
+            &mut self.old_crate_registry,
+            &mut self.used_crate_registry,
+            &mut self.orphan_crate_registry,
+            &mut self.deprecated_crate_registry,
+            &mut self.yanked_crate_registry,
+        ] {
+            list.retain(|crate_metadata| crate_metadata.source().is_some_and(&mut predicate));
+        }
+        for list in [
+            &mut self.installed_crate_git,
+            &mut self.old_crate_git,
+            &mut self.used_crate_git,
+            &mut self.orphan_crate_git,
+        ] {
+            list.clear();
+        }
+    }
+
     /// list out git crates which is both old and orphan
-    pub(crate) fn list_old_orphan_git(&self) -> Vec<CrateMetaData> {
+    #[must_use]
+    pub fn list_old_orphan_git(&self) -> Vec<CrateMetaData> {
         let mut old_orphan_git = Vec::new();
         let orphan_list = self.orphan_git();
         for crates in self.old_git() {
@@ -207,12 +512,18 @@ impl CrateList {
     }
 }
 
+/// list registry crates pinned by lock file of project whether they are
+/// present in cache or not
+pub(crate) fn lock_registry_crates(project: &Path) -> Result<Vec<CrateMetaData>> {
+    Ok(read_content(&[project.to_path_buf()])?.0)
+}
+
 /// Read out content of cargo.lock file to list out crates present so can be
 /// used for orphan clean
 fn read_content(list: &[PathBuf]) -> Result<(Vec<CrateMetaData>, Vec<CrateMetaData>)> {
     let mut present_crate_registry = Vec::new();
     let mut present_crate_git = Vec::new();
-    for lock in list.iter() {
+    for lock in list {
         let mut lock_folder = lock.clone();
         lock_folder.push("Cargo.lock");
         if lock_folder.exists() {
@@ -228,12 +539,24 @@ fn read_content(list: &[PathBuf]) -> Result<(Vec<CrateMetaData>, Vec<CrateMetaDa
                         if source.contains("registry+") {
                             let url = Url::from_str(&source.replace("registry+", ""))
                                 .context("Failed registry source url kind conversion")?;
+                            let version = Version::parse(version)
+                                .context("failed Cargo.lock semver version parse")?;
+                            // Cargo.lock always record crates.io git index url even when crates
+                            // are downloaded using sparse index
+                            if url.as_str() == CRATES_IO_GIT_INDEX {
+                                present_crate_registry.push(CrateMetaData::new(
+                                    name.to_string(),
+                                    Some(version.clone()),
+                                    0,
+                                    Some(
+                                        Url::from_str(CRATES_IO_SPARSE_INDEX)
+                                            .context("Failed crates.io sparse url conversion")?,
+                                    ),
+                                ));
+                            }
                             present_crate_registry.push(CrateMetaData::new(
                                 name.to_string(),
-                                Some(
-                                    Version::parse(version)
-                                        .context("failed Cargo.lock semver version parse")?,
-                                ),
+                                Some(version),
                                 0,
                                 Some(url),
                             ));
@@ -261,15 +584,12 @@ fn read_content(list: &[PathBuf]) -> Result<(Vec<CrateMetaData>, Vec<CrateMetaDa
                                 url_with_kind = rev_sha_vec[0];
                             }
                             let rev_short_form = &rev_sha_vec[1][..=6];
-                            let full_name = format!("{name}-{rev_short_form}");
                             let url = Url::from_str(&url_with_kind.replace("git+", "")).context(
                                 "Failed git source url kind with query params conversion",
                             )?;
-                            present_crate_git.push(CrateMetaData::new(
-                                full_name,
-                                None,
+                            present_crate_git.push(CrateMetaData::new_git(
+                                GitId::new(url, name.to_string(), rev_short_form.to_string()),
                                 0,
-                                Some(url),
                             ));
                         }
                         if source.contains("sparse+") {
@@ -326,15 +646,17 @@ fn list_old_crates(
             let file_name = entry
                 .file_name()
                 .context("failed to get sold crate db dir file name")?
-                .to_str()
-                .context("Failed to convert db dir entry file name to str")?;
+                .to_string_lossy();
             let rev_value = latest_rev_value(&entry)?;
             let full_name = format!("{file_name}-{rev_value}");
             full_name_list.push(full_name);
         }
         for crate_metadata in installed_crate_git {
             let crate_name = crate_metadata.name();
-            if !crate_name.contains("-HEAD") && !full_name_list.contains(crate_name) {
+            let is_database = crate_metadata
+                .git_id()
+                .is_some_and(|git_id| git_id.is_database());
+            if !is_database && !full_name_list.contains(crate_name) {
                 old_crate_git.push(crate_metadata.clone());
             }
         }
@@ -352,7 +674,7 @@ fn list_used_crates(
     let mut used_crate_registry = Vec::new();
     let mut used_crate_git = Vec::new();
     let mut cargo_toml_location = CargoTomlLocation::new();
-    let config_directory = config_file.directory().clone();
+    let config_directory = config_file.directory();
     // read a Cargo.lock file and determine out a used registry and git crate
     for path in &config_directory {
         let list_cargo_toml = config_file.list_cargo_toml(Path::new(path))?;
@@ -364,7 +686,7 @@ fn list_used_crates(
     used_crate_registry.sort();
     used_crate_registry.dedup();
     used_crate_git.sort();
-    used_crate_registry.dedup();
+    used_crate_git.dedup();
     Ok((cargo_toml_location, used_crate_registry, used_crate_git))
 }
 
@@ -383,24 +705,7 @@ fn list_orphan_crates(
         }
     }
     for crates in installed_crate_git {
-        let crate_name = crates.name();
-        if crate_name.contains("-HEAD") {
-            let split_installed = crate_name.rsplitn(2, '-').collect::<Vec<&str>>();
-            if used_crate_git.is_empty() {
-                orphan_crate_git.push(crates.clone());
-            }
-            let mut used_in_project = false;
-            for used in used_crate_git {
-                if used.name().contains(split_installed[1]) {
-                    used_in_project = true;
-                    // Break if found to be used one time no need to check for other
-                    break;
-                }
-            }
-            if !used_in_project {
-                orphan_crate_git.push(crates.clone());
-            }
-        } else if !used_crate_git.contains(crates) {
+        if !is_git_used(crates, used_crate_git) {
             orphan_crate_git.push(crates.clone());
         }
     }
@@ -411,6 +716,93 @@ fn list_orphan_crates(
     (orphan_crate_registry, orphan_crate_git)
 }
 
+// check if installed git crate is referenced by git dependency of lock file.
+// Checkout is used when its repository and revision is pinned by lock file
+// while database is used when any revision of its repository is pinned
+fn is_git_used(installed: &CrateMetaData, used_crate_git: &[CrateMetaData]) -> bool {
+    let Some(installed_id) = installed.git_id() else {
+        return false;
+    };
+    used_crate_git
+        .iter()
+        .filter_map(CrateMetaData::git_id)
+        .any(|used_id| {
+            installed_id.same_repository(&used_id)
+                && (installed_id.is_database() || installed_id.same_revision(&used_id))
+        })
+}
+
+/// list installed crates which are used. Installed crates which are not listed
+/// by this function are orphan crates
+fn list_installed_used_crates(
+    installed_crate_registry: &[CrateMetaData],
+    installed_crate_git: &[CrateMetaData],
+    used_crate_registry: &[CrateMetaData],
+    used_crate_git: &[CrateMetaData],
+) -> (Vec<CrateMetaData>, Vec<CrateMetaData>) {
+    let (orphan_crate_registry, orphan_crate_git) = list_orphan_crates(
+        installed_crate_registry,
+        installed_crate_git,
+        used_crate_registry,
+        used_crate_git,
+    );
+    let installed_used_registry = installed_crate_registry
+        .iter()
+        .filter(|crate_metadata| !orphan_crate_registry.contains(crate_metadata))
+        .cloned()
+        .collect();
+    let installed_used_git = installed_crate_git
+        .iter()
+        .filter(|crate_metadata| !orphan_crate_git.contains(crate_metadata))
+        .cloned()
+        .collect();
+    (installed_used_registry, installed_used_git)
+}
+
+/// list deprecated crates i.e crates which are renamed or superseded by other
+/// crate or marked as deprecated in crates.io metadata
+fn list_deprecated_crates(installed_crate_registry: &[CrateMetaData]) -> Vec<CrateMetaData> {
+    let mut deprecated_crate_registry = installed_crate_registry
+        .iter()
+        .filter(|crate_metadata| is_deprecated(crate_metadata.name()))
+        .cloned()
+        .collect::<Vec<_>>();
+    deprecated_crate_registry.sort();
+    deprecated_crate_registry.dedup();
+    deprecated_crate_registry
+}
+
+/// list registry crates whose cached version is marked as yanked in index
+/// cache of its registry
+fn list_yanked_crates(
+    installed_crate_registry: &[CrateMetaData],
+    crate_detail: &CrateDetail,
+    index_dir: &Path,
+) -> Result<Vec<CrateMetaData>> {
+    let index_cache_dir = index_cache_dirs(index_dir, crate_detail)?;
+    let mut yanked_crate_registry = installed_crate_registry
+        .iter()
+        .filter(|crate_metadata| {
+            let Some(crate_id) = crate_metadata.crate_id() else {
+                return false;
+            };
+            let version = crate_id.version().to_string();
+            index_cache_dir
+                .iter()
+                .filter(|(source, _)| source == crate_id.registry())
+                .any(|(_, cache_dir)| {
+                    index_entry(&index_cache_file(cache_dir, crate_id.name()), &version)
+                        .and_then(|entry| entry.get("yanked")?.as_bool())
+                        == Some(true)
+                })
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    yanked_crate_registry.sort();
+    yanked_crate_registry.dedup();
+    Ok(yanked_crate_registry)
+}
+
 /// get latest commit rev value from git repository
 fn latest_rev_value(path: &Path) -> Result<String> {
     let mut fetch_head_file = PathBuf::new();
@@ -420,3 +812,59 @@ fn latest_rev_value(path: &Path) -> Result<String> {
     // read first 7 value which is same as hash for git based checkout folder
     Ok(content[..7].to_string())
 }
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use url::Url;
+
+    use super::list_orphan_crates;
+    use crate::crate_detail::CrateMetaData;
+    use crate::crate_id::GitId;
+
+    fn git_crate(name: &str, rev: &str, url: &str) -> CrateMetaData {
+        CrateMetaData::new_git(
+            GitId::new(
+                Url::from_str(url).unwrap(),
+                name.to_string(),
+                rev.to_string(),
+            ),
+            0,
+        )
+    }
+
+    #[test]
+    fn test_list_orphan_git_crates() {
+        let installed = [
+            git_crate(
+                "tokio-1a2b3c4d5e6f7a8b",
+                "HEAD",
+                "https://github.com/tokio-rs/tokio",
+            ),
+            git_crate(
+                "tokio-1a2b3c4d5e6f7a8b",
+                "abcdef1",
+                "https://github.com/tokio-rs/tokio",
+            ),
+            git_crate(
+                "tokio-1a2b3c4d5e6f7a8b",
+                "1234567",
+                "https://github.com/tokio-rs/tokio",
+            ),
+            git_crate(
+                "stale-0f0f0f0f0f0f0f0f",
+                "HEAD",
+                "https://example.com/stale",
+            ),
+        ];
+        // package name differ from repository name and url has .git suffix
+        let used = [git_crate(
+            "tokio-util",
+            "abcdef1",
+            "https://github.com/Tokio-rs/tokio.git",
+        )];
+        let (_, orphan_git) = list_orphan_crates(&[], &installed, &[], &used);
+        assert_eq!(orphan_git, vec![installed[3].clone(), installed[2].clone()]);
+    }
+}
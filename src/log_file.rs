@@ -0,0 +1,234 @@
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::event::Event;
+use crate::schema::Versioned;
+use crate::utils::{convert_pretty, format_timestamp, verbosity};
+
+static LOG_FILE: Mutex<Option<File>> = Mutex::new(None);
+static JSON_LOG_FILE: Mutex<Option<File>> = Mutex::new(None);
+
+/// Severity of log line
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = match self {
+            Self::Error => "ERROR",
+            Self::Warn => "WARN",
+            Self::Info => "INFO",
+            Self::Debug => "DEBUG",
+        };
+        write!(f, "{level:<5}")
+    }
+}
+
+/// Machine readable record of single deleted path
+#[derive(Serialize)]
+struct DeleteRecord<'a> {
+    timestamp: String,
+    path: &'a Path,
+    category: &'static str,
+    size: u64,
+    dry_run: bool,
+}
+
+/// open log file in append mode so log of previous runs are kept as audit
+/// trail
+pub(crate) fn open_log_file(path: &Path) -> Result<()> {
+    *lock(&LOG_FILE) = Some(open_append(path)?);
+    Ok(())
+}
+
+/// open json log file in append mode where each deleted path is written as
+/// single json line
+pub(crate) fn open_json_log_file(path: &Path) -> Result<()> {
+    *lock(&JSON_LOG_FILE) = Some(open_append(path)?);
+    Ok(())
+}
+
+/// check if log file or json log file is opened
+pub(crate) fn log_enabled() -> bool {
+    lock(&LOG_FILE).is_some() || lock(&JSON_LOG_FILE).is_some()
+}
+
+/// write timestamped line to log file. Debug lines are only written when
+/// verbosity is increased
+pub(crate) fn log(level: LogLevel, message: &str) {
+    if level == LogLevel::Debug && verbosity() == 0 {
+        return;
+    }
+    if let Some(file) = lock(&LOG_FILE).as_mut() {
+        // logging is best effort and failure to write should not abort clean
+        let _ = writeln!(
+            file,
+            "{} {level} {message}",
+            format_timestamp(SystemTime::now())
+        );
+    }
+}
+
+/// write event to log file and deleted path to json log file
+pub(crate) fn log_event(event: &Event) {
+    if let Event::Deleted {
+        path,
+        size,
+        dry_run,
+    } = event
+    {
+        log_json(&DeleteRecord {
+            timestamp: format_timestamp(SystemTime::now()),
+            path,
+            category: path_category(path),
+            size: *size,
+            dry_run: *dry_run,
+        });
+    }
+    let (level, message) = match event {
+        Event::Scanned {
+            category,
+            name,
+            version,
+            size,
+            ..
+        } => (
+            LogLevel::Debug,
+            format!(
+                "scanned {category} {name}{} ({})",
+                version
+                    .as_ref()
+                    .map_or_else(String::new, |version| format!("-{version}")),
+                convert_pretty(*size).trim()
+            ),
+        ),
+        Event::Deleted {
+            path,
+            size,
+            dry_run,
+        } => (
+            LogLevel::Info,
+            format!(
+                "{}deleted {} ({})",
+                dry_run_prefix(*dry_run),
+                path.display(),
+                convert_pretty(*size).trim()
+            ),
+        ),
+        Event::Freed {
+            category,
+            reason,
+            size,
+            removed,
+            dry_run,
+        } => (
+            LogLevel::Info,
+            format!(
+                "{}freed {} from {removed} {category} by {reason}",
+                dry_run_prefix(*dry_run),
+                convert_pretty(*size).trim()
+            ),
+        ),
+        Event::Skipped { name, reason } => (LogLevel::Warn, format!("skipped {name}: {reason}")),
+        Event::Error { message } => (LogLevel::Error, message.clone()),
+    };
+    log(level, &message);
+}
+
+// write delete record as single line to json log file
+fn log_json(record: &DeleteRecord) {
+    if let Some(file) = lock(&JSON_LOG_FILE).as_mut() {
+        if let Ok(line) = serde_json::to_string(&Versioned::new(record)) {
+            // logging is best effort and failure to write should not abort clean
+            let _ = writeln!(file, "{line}");
+        }
+    }
+}
+
+// category of deleted path based on cargo home and rustup home layout
+fn path_category(path: &Path) -> &'static str {
+    let components = path
+        .components()
+        .filter_map(|component| component.as_os_str().to_str())
+        .collect::<Vec<_>>();
+    for pair in components.windows(2) {
+        let category = match pair {
+            ["registry", "cache"] => "registry_cache",
+            ["registry", "index"] => "registry_index",
+            ["registry", "src"] => "registry_src",
+            ["git", "checkouts"] => "git_checkout",
+            ["git", "db"] => "git_db",
+            ["toolchains", _] => "rustup",
+            _ => continue,
+        };
+        return category;
+    }
+    match components.last() {
+        Some(&"registry") => "registry",
+        Some(&"git") => "git",
+        _ if components.contains(&"target") => "target",
+        _ if components.contains(&"bin") => "bin",
+        _ => "other",
+    }
+}
+
+// open file in append mode creating it if not exists
+fn open_append(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open log file {}", path.display()))
+}
+
+// lock log file ignoring poison since log is best effort
+fn lock(file: &Mutex<Option<File>>) -> std::sync::MutexGuard<'_, Option<File>> {
+    file.lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+// prefix of action performed in dry run
+fn dry_run_prefix(dry_run: bool) -> &'static str {
+    if dry_run { "dry run " } else { "" }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::path_category;
+
+    #[test]
+    fn test_path_category() {
+        assert_eq!(
+            path_category(Path::new(
+                "/home/user/.cargo/registry/src/index.crates.io-6f17d22bba15001f/serde-1.0.0"
+            )),
+            "registry_src"
+        );
+        assert_eq!(
+            path_category(Path::new("/home/user/.cargo/git/db/serde-0123456789abcdef")),
+            "git_db"
+        );
+        assert_eq!(
+            path_category(Path::new("/home/user/.cargo/registry")),
+            "registry"
+        );
+        assert_eq!(
+            path_category(Path::new("/home/user/project/target/debug")),
+            "target"
+        );
+    }
+}
@@ -3,31 +3,35 @@
 #![deny(clippy::all)]
 #![warn(clippy::pedantic)]
 
-mod command;
-mod config_file;
-mod crate_detail;
-mod dir_path;
-mod git_dir;
-mod list_crate;
-mod registry_dir;
-mod utils;
-
 use std::env;
+use std::path::Path;
+use std::process::ExitCode;
 
 use anyhow::Result;
+use cargo_trim::{Command, translate_cargo_cache};
 use clap::Parser;
 
-fn main() -> Result<()> {
-    let args = env::args();
+fn main() -> Result<ExitCode> {
+    let args = env::args().collect::<Vec<_>>();
+    // binary installed or linked as cargo-cache accepts cargo-cache arguments
+    let is_cargo_cache = args
+        .first()
+        .and_then(|program| Path::new(program).file_stem())
+        .is_some_and(|stem| stem == "cargo-cache");
     let mut command_args = Vec::new();
-    for (pos, param) in args.enumerate() {
-        if pos == 1 && param == "trim" {
+    for (pos, param) in args.into_iter().enumerate() {
+        if pos == 1 && (param == "trim" || (is_cargo_cache && param == "cache")) {
             continue;
         }
         command_args.push(param);
     }
+    if is_cargo_cache {
+        let translated = translate_cargo_cache(&command_args[1..])?;
+        command_args.truncate(1);
+        command_args.extend(translated);
+    }
 
-    let command = command::Command::parse_from(command_args);
-    command.run()?;
-    Ok(())
+    let command = Command::parse_from(command_args);
+    let outcome = command.run()?;
+    Ok(ExitCode::from(command.exit_code(outcome)))
 }
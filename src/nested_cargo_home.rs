@@ -0,0 +1,71 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::utils::get_size;
+
+// folders of cargo home which contains downloaded cache
+const CACHE_FOLDERS: [&str; 2] = ["registry", "git"];
+
+/// stores information of cargo home created inside project directory by tools
+/// which set cargo home to project local .cargo folder
+#[derive(Debug, Clone)]
+pub(crate) struct NestedCargoHome {
+    path: PathBuf,
+    caches: Vec<PathBuf>,
+    size: u64,
+}
+
+impl NestedCargoHome {
+    /// scan .cargo folder of project. Return None if project do not have
+    /// registry or git cache inside .cargo folder
+    pub(crate) fn new(project: &Path) -> Result<Option<Self>> {
+        let path = project.join(".cargo");
+        let mut caches = Vec::new();
+        let mut size = 0;
+        for folder in CACHE_FOLDERS {
+            let cache = path.join(folder);
+            if cache.is_dir() {
+                size += get_size(&cache).context("failed to get size of nested cargo home")?;
+                caches.push(cache);
+            }
+        }
+        if caches.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Self { path, caches, size }))
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// registry and git cache folder present inside nested cargo home
+    pub(crate) fn caches(&self) -> &Vec<PathBuf> {
+        &self.caches
+    }
+
+    pub(crate) fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+/// list all nested cargo home of projects. Nested cargo home shared by
+/// multiple project is only listed once
+pub(crate) fn list_nested_cargo_homes(projects: &[PathBuf]) -> Result<Vec<NestedCargoHome>> {
+    let mut nested_cargo_homes: Vec<NestedCargoHome> = Vec::new();
+    for project in projects {
+        let path = project.join(".cargo");
+        if nested_cargo_homes
+            .iter()
+            .any(|existing| existing.path == path)
+        {
+            continue;
+        }
+        if let Some(nested_cargo_home) = NestedCargoHome::new(project)? {
+            nested_cargo_homes.push(nested_cargo_home);
+        }
+    }
+    nested_cargo_homes.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(nested_cargo_homes)
+}
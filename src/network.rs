@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use url::Url;
+
+/// Default time given to each url to respond
+pub(crate) const DEFAULT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Check reachability of all provided url in parallel. Each url gets its own
+/// thread so one dead mirror cannot block checking other url. Url which do not
+/// respond within timeout are classified as unreachable
+pub(crate) fn check_reachability(urls: &[Url], timeout: Duration) -> HashMap<Url, bool> {
+    let (sender, receiver) = mpsc::channel();
+    for url in urls {
+        let sender = sender.clone();
+        let url = url.clone();
+        thread::spawn(move || {
+            let reachable = is_reachable(&url, timeout);
+            // receiver may already be dropped after deadline so ignore error
+            let _ = sender.send((url, reachable));
+        });
+    }
+    drop(sender);
+    let mut reachability = urls
+        .iter()
+        .map(|url| (url.clone(), false))
+        .collect::<HashMap<_, _>>();
+    // address resolution cannot be time bounded so give one extra timeout for
+    // all thread to report before treating unanswered url as unreachable
+    let deadline = Instant::now() + timeout * 2;
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+        match receiver.recv_timeout(remaining) {
+            Ok((url, reachable)) => {
+                reachability.insert(url, reachable);
+            }
+            Err(_) => break,
+        }
+    }
+    reachability
+}
+
+/// check if connection can be opened to url host
+fn is_reachable(url: &Url, timeout: Duration) -> bool {
+    let (Some(host), Some(port)) = (url.host_str(), url.port_or_known_default()) else {
+        return false;
+    };
+    match (host, port).to_socket_addrs() {
+        Ok(addresses) => {
+            addresses
+                .into_iter()
+                .any(|address| TcpStream::connect_timeout(&address, timeout).is_ok())
+        }
+        Err(_) => false,
+    }
+}
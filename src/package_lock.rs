@@ -0,0 +1,219 @@
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+
+use crate::event::{Event, emit};
+use crate::running_process::running_cargo_processes;
+
+/// lock files used by cargo to guard package cache. Lock is acquired in same
+/// order as cargo so waiting on both never deadlocks
+pub(crate) const LOCK_FILES: [&str; 2] = [".package-cache", ".package-cache-mutate"];
+
+static PACKAGE_LOCK: Mutex<PackageLock> = Mutex::new(PackageLock {
+    mode: LockMode::Fail,
+    cargo_home: None,
+    files: Vec::new(),
+    forced: false,
+    contended: false,
+    process_checked: false,
+});
+
+/// Behavior when package cache lock is held by other cargo process or cargo
+/// and rustc is running
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LockMode {
+    /// return error without removing anything
+    Fail,
+    /// block until other process releases lock
+    Wait,
+    /// remove files without holding lock
+    Force,
+}
+
+// package cache lock of cargo home which is currently trimmed
+struct PackageLock {
+    mode: LockMode,
+    cargo_home: Option<PathBuf>,
+    files: Vec<File>,
+    forced: bool,
+    contended: bool,
+    process_checked: bool,
+}
+
+/// set behavior used when package cache lock is held by other process
+pub(crate) fn set_lock_mode(mode: LockMode) {
+    if let Ok(mut package_lock) = PACKAGE_LOCK.lock() {
+        package_lock.mode = mode;
+    }
+}
+
+/// set cargo home whose package cache lock is acquired before its registry or
+/// git cache is removed. Lock of previously used cargo home is released
+pub(crate) fn set_lock_cargo_home(cargo_home: &Path) {
+    if let Ok(mut package_lock) = PACKAGE_LOCK.lock() {
+        package_lock.cargo_home = Some(cargo_home.to_path_buf());
+        package_lock.files.clear();
+        package_lock.forced = false;
+        package_lock.contended = false;
+        package_lock.process_checked = false;
+    }
+}
+
+/// acquire package cache lock if path is inside registry or git cache of
+/// cargo home. Lock is held till cargo home changes so cargo started in
+/// meantime waits for removal to finish
+///
+/// # Errors
+/// Return error if lock is held by other process and neither wait nor force
+/// mode is used
+pub(crate) fn lock_for_removal(path: &Path) -> Result<()> {
+    let Ok(mut package_lock) = PACKAGE_LOCK.lock() else {
+        return Ok(());
+    };
+    let Some(cargo_home) = package_lock.cargo_home.clone() else {
+        return Ok(());
+    };
+    if path.starts_with(cargo_home.join("registry")) || path.starts_with(cargo_home.join("git")) {
+        package_lock.acquire(&cargo_home)?;
+    }
+    Ok(())
+}
+
+/// acquire package cache lock of cargo home. Return false when lock is not
+/// held because it is forced
+///
+/// # Errors
+/// Return error if lock is held by other process and neither wait nor force
+/// mode is used
+pub(crate) fn lock_package_cache() -> Result<bool> {
+    let Ok(mut package_lock) = PACKAGE_LOCK.lock() else {
+        return Ok(false);
+    };
+    let Some(cargo_home) = package_lock.cargo_home.clone() else {
+        return Ok(false);
+    };
+    package_lock.acquire(&cargo_home)?;
+    Ok(!package_lock.forced)
+}
+
+/// release package cache lock of cargo home
+pub(crate) fn unlock_package_cache() {
+    if let Ok(mut package_lock) = PACKAGE_LOCK.lock() {
+        package_lock.files.clear();
+        package_lock.forced = false;
+    }
+}
+
+impl PackageLock {
+    // acquire lock on every lock file unless lock is already held or forced
+    fn acquire(&mut self, cargo_home: &Path) -> Result<()> {
+        if !self.files.is_empty() || self.forced {
+            return Ok(());
+        }
+        if !self.process_checked {
+            self.check_running_processes()?;
+            self.process_checked = true;
+        }
+        let mut files = Vec::new();
+        for name in LOCK_FILES {
+            let path = cargo_home.join(name);
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(&path)
+                .with_context(|| format!("Failed to open lock file {}", path.display()))?;
+            match file.try_lock() {
+                Ok(()) => files.push(file),
+                Err(TryLockError::WouldBlock) => match self.mode {
+                    LockMode::Fail => {
+                        return self.fail(format!(
+                            "Package cache lock of {} is held by running cargo. Pass --wait to \
+                             wait for it or --force to remove files anyway",
+                            cargo_home.display()
+                        ));
+                    }
+                    LockMode::Wait => {
+                        human_println!(
+                            "{}",
+                            "Blocking waiting for package cache lock held by running cargo"
+                                .yellow()
+                        );
+                        file.lock()
+                            .context("Failed to wait for package cache lock")?;
+                        files.push(file);
+                    }
+                    LockMode::Force => {
+                        human_println!(
+                            "{}",
+                            "Package cache lock is held by running cargo, removing files anyway"
+                                .yellow()
+                        );
+                        self.forced = true;
+                        return Ok(());
+                    }
+                },
+                // file system without lock support cannot be coordinated
+                Err(TryLockError::Error(_)) => {}
+            }
+        }
+        self.files = files;
+        Ok(())
+    }
+
+    // check running cargo and rustc processes since build started before lock
+    // is acquired can still read source which is about to be removed
+    fn check_running_processes(&mut self) -> Result<()> {
+        let processes = running_cargo_processes();
+        if processes.is_empty() {
+            return Ok(());
+        }
+        let list = processes
+            .iter()
+            .map(|process| format!("{} ({})", process.name(), process.pid()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        match self.mode {
+            LockMode::Fail => self.fail(format!(
+                "Cargo or rustc is running: {list}. Removing cache during build corrupts it. Pass \
+                 --wait to wait for them or --force to remove files anyway"
+            )),
+            LockMode::Wait => {
+                human_println!(
+                    "{}",
+                    format!("Waiting for running cargo or rustc to finish: {list}").yellow()
+                );
+                while !running_cargo_processes().is_empty() {
+                    thread::sleep(Duration::from_secs(1));
+                }
+                Ok(())
+            }
+            LockMode::Force => {
+                human_println!(
+                    "{}",
+                    format!("Cargo or rustc is running: {list}. Removing files anyway").yellow()
+                );
+                Ok(())
+            }
+        }
+    }
+
+    // return error with message. Clean operation only report failure of each
+    // crate so message is shown once
+    fn fail(&mut self, message: String) -> Result<()> {
+        if !self.contended {
+            self.contended = true;
+            emit(&Event::Error {
+                message: message.clone(),
+            });
+            human_println!("{}", message.red());
+        }
+        anyhow::bail!(message)
+    }
+}
@@ -0,0 +1,191 @@
+use std::io::{IsTerminal, Write};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::utils::convert_pretty;
+
+// minimum duration between two redraw of progress line
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+static PROGRESS: Mutex<Progress> = Mutex::new(Progress {
+    enabled: false,
+    stage: Stage::Idle,
+    started: None,
+    last_draw: None,
+    drawn: false,
+});
+
+#[derive(Clone, Copy)]
+enum Stage {
+    Idle,
+    Scan { directories: u64 },
+    Delete { deleted: u64, total: u64 },
+}
+
+// state of progress line shown in stderr
+struct Progress {
+    enabled: bool,
+    stage: Stage,
+    started: Option<Instant>,
+    last_draw: Option<Instant>,
+    drawn: bool,
+}
+
+impl Progress {
+    // start new stage if progress is enabled
+    fn start(&mut self, stage: Stage) {
+        if self.enabled {
+            self.stage = stage;
+            self.started = Some(Instant::now());
+            self.last_draw = None;
+        }
+    }
+
+    // redraw progress line if enough time passed since last draw
+    fn draw(&mut self) {
+        let now = Instant::now();
+        if self
+            .last_draw
+            .is_some_and(|last_draw| now.duration_since(last_draw) < REDRAW_INTERVAL)
+        {
+            return;
+        }
+        let line = match self.stage {
+            Stage::Idle => return,
+            Stage::Scan { directories } => format!("Scanning {directories} directories"),
+            Stage::Delete { deleted, total } => {
+                let elapsed = self
+                    .started
+                    .map_or(Duration::ZERO, |started| started.elapsed());
+                format!(
+                    "Deleting {} / {} {:>3}% ETA {}",
+                    convert_pretty(deleted).trim(),
+                    convert_pretty(total).trim(),
+                    percentage(deleted, total),
+                    eta(deleted, total, elapsed)
+                        .map_or_else(|| String::from("--"), |eta| format!("{}s", eta.as_secs()))
+                )
+            }
+        };
+        let mut stderr = std::io::stderr();
+        let _ = write!(stderr, "\r\x1b[2K{line}");
+        let _ = stderr.flush();
+        self.last_draw = Some(now);
+        self.drawn = true;
+    }
+
+    // clear progress line and stop current stage
+    fn finish(&mut self) {
+        if self.drawn {
+            let mut stderr = std::io::stderr();
+            let _ = write!(stderr, "\r\x1b[2K");
+            let _ = stderr.flush();
+        }
+        self.stage = Stage::Idle;
+        self.drawn = false;
+    }
+}
+
+/// enable progress line for scan and deletion. Progress is only shown when
+/// both stdout and stderr are terminal so redirected output is never
+/// polluted
+pub(crate) fn enable_progress() {
+    if std::io::stdout().is_terminal() && std::io::stderr().is_terminal() {
+        with_progress(|progress| progress.enabled = true);
+    }
+}
+
+/// disable progress line so it is not mixed with other progress display such
+/// as progress pane of tui
+pub(crate) fn disable_progress() {
+    with_progress(|progress| progress.enabled = false);
+}
+
+/// start showing number of scanned directories
+pub(crate) fn start_scan() {
+    with_progress(|progress| progress.start(Stage::Scan { directories: 0 }));
+}
+
+/// increase number of scanned directories
+pub(crate) fn scanned_directory() {
+    with_progress(|progress| {
+        if let Stage::Scan { directories } = &mut progress.stage {
+            *directories += 1;
+            progress.draw();
+        }
+    });
+}
+
+/// start showing deleted bytes out of total bytes to delete
+pub(crate) fn start_deletion(total: u64) {
+    with_progress(|progress| progress.start(Stage::Delete { deleted: 0, total }));
+}
+
+/// check if deleted bytes are tracked so deletion can report each file
+pub(crate) fn deletion_in_progress() -> bool {
+    with_progress(|progress| matches!(progress.stage, Stage::Delete { .. }))
+}
+
+/// increase deleted bytes
+pub(crate) fn deleted_bytes(size: u64) {
+    with_progress(|progress| {
+        if let Stage::Delete { deleted, .. } = &mut progress.stage {
+            *deleted += size;
+            progress.draw();
+        }
+    });
+}
+
+/// clear progress line
+pub(crate) fn finish_progress() {
+    with_progress(Progress::finish);
+}
+
+// run function with lock of global progress
+fn with_progress<F, T>(function: F) -> T
+where
+    F: FnOnce(&mut Progress) -> T,
+{
+    let mut progress = PROGRESS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    function(&mut progress)
+}
+
+// percentage of completed bytes capped at 100
+fn percentage(done: u64, total: u64) -> u64 {
+    (done.min(total) * 100).checked_div(total).unwrap_or(100)
+}
+
+// estimated remaining time based on average speed so far
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn eta(done: u64, total: u64, elapsed: Duration) -> Option<Duration> {
+    if done == 0 || elapsed.is_zero() {
+        return None;
+    }
+    let remaining = total.saturating_sub(done) as f64;
+    let speed = done as f64 / elapsed.as_secs_f64();
+    Some(Duration::from_secs((remaining / speed).ceil() as u64))
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{eta, percentage};
+
+    #[test]
+    fn test_progress_estimate() {
+        assert_eq!(percentage(50, 200), 25);
+        assert_eq!(percentage(10, 0), 100);
+        assert_eq!(
+            eta(100, 300, Duration::from_secs(10)),
+            Some(Duration::from_secs(20))
+        );
+        assert_eq!(eta(0, 300, Duration::from_secs(10)), None);
+    }
+}
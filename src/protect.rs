@@ -0,0 +1,125 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use owo_colors::OwoColorize;
+
+use crate::crate_detail::{CrateDetail, CrateMetaData};
+use crate::event::{Event, emit};
+use crate::registry_dir::index_cache_file;
+
+static PROTECTED: Mutex<Option<Protected>> = Mutex::new(None);
+
+// protect list of config file along with path of every protected crate
+struct Protected {
+    entries: Vec<String>,
+    path: Vec<PathBuf>,
+}
+
+/// enable protection of crates listed in protect section of config file. Path
+/// of protected registry and git crates along with index entry required to use
+/// them are never removed even when parent folder is wiped. Protection of
+/// previously scanned cargo home is replaced
+pub(crate) fn enable_protection(entries: &[String], crate_detail: &CrateDetail, index_dir: &Path) {
+    if entries.is_empty() {
+        set_protected(None);
+        return;
+    }
+    let protected_crates = [
+        crate_detail.registry_crates_archive(),
+        crate_detail.registry_crates_source(),
+        crate_detail.git_crates_archive(),
+        crate_detail.git_crates_source(),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|crate_metadata| crate_metadata.is_protected(entries))
+    .collect::<Vec<_>>();
+    let mut path = Vec::new();
+    for crate_metadata in protected_crates {
+        path.extend(crate_metadata.path().iter().cloned());
+        if crate_metadata.crate_id().is_none() {
+            continue;
+        }
+        // registry crate is stored inside folder with same name as its index
+        for crate_path in crate_metadata.path() {
+            if let Some(registry_name) = crate_path.parent().and_then(Path::file_name) {
+                path.extend(index_path(
+                    &index_dir.join(registry_name),
+                    crate_metadata.name(),
+                ));
+            }
+        }
+    }
+    path.sort();
+    path.dedup();
+    set_protected(Some(Protected {
+        entries: entries.to_vec(),
+        path,
+    }));
+}
+
+// replace protected crates
+fn set_protected(protected: Option<Protected>) {
+    if let Ok(mut current) = PROTECTED.lock() {
+        *current = protected;
+    }
+}
+
+// run check against protected crates. False is returned when nothing is
+// protected
+fn with_protected(check: impl FnOnce(&Protected) -> bool) -> bool {
+    PROTECTED
+        .lock()
+        .is_ok_and(|protected| protected.as_ref().is_some_and(check))
+}
+
+/// check if crate is protected. Skipped event is emitted for protected crate
+pub(crate) fn skip_protected(crate_metadata: &CrateMetaData) -> bool {
+    let is_protected = with_protected(|protected| crate_metadata.is_protected(&protected.entries));
+    if is_protected {
+        emit(&Event::Skipped {
+            name: crate_metadata.full_name(),
+            reason: "protected",
+        });
+        human_println!(
+            r#"{} "{}""#,
+            "Skipped protected".yellow(),
+            crate_metadata.full_name()
+        );
+    }
+    is_protected
+}
+
+/// check if path is protected or contains protected path inside it
+pub(crate) fn contains_protected_path(path: &Path) -> bool {
+    with_protected(|protected| {
+        protected
+            .path
+            .iter()
+            .any(|protected_path| protected_path.starts_with(path))
+    })
+}
+
+/// check if path is path of protected crate
+pub(crate) fn is_protected_path(path: &Path) -> bool {
+    with_protected(|protected| {
+        protected
+            .path
+            .iter()
+            .any(|protected_path| protected_path == path)
+    })
+}
+
+// index path required to find source of registry crate and to resolve it
+// offline. Git index is kept completely since source is read from its git
+// folder
+fn index_path(registry_index: &Path, name: &str) -> Vec<PathBuf> {
+    if registry_index.join(".git").exists() {
+        vec![registry_index.to_path_buf()]
+    } else {
+        vec![
+            registry_index.join("config.json"),
+            index_cache_file(&registry_index.join(".cache"), name),
+        ]
+    }
+}
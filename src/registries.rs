@@ -0,0 +1,129 @@
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use url::Url;
+
+/// crates.io index url for git and sparse protocol
+const CRATES_IO_INDEX: [&str; 2] = [
+    "https://github.com/rust-lang/crates.io-index",
+    "https://index.crates.io/",
+];
+
+/// check if index url is one of crates.io
+pub(crate) fn is_crates_io_index(url: &Url) -> bool {
+    CRATES_IO_INDEX.contains(&url.as_str())
+}
+
+/// Name of registries configured in cargo config file along with crates.io.
+/// Used to show and filter crates per registry
+pub(crate) struct Registries {
+    names: Vec<(String, Url)>,
+}
+
+impl Registries {
+    /// load registries configured in `[registries]` table of cargo config file
+    /// present in cargo home
+    pub(crate) fn load(cargo_home: &Path) -> Result<Self> {
+        let mut names = Vec::new();
+        for index in CRATES_IO_INDEX {
+            names.push((
+                String::from("crates-io"),
+                Url::from_str(index).context("Failed to parse crates.io index url")?,
+            ));
+        }
+        for file_name in ["config.toml", "config"] {
+            let config_file = cargo_home.join(file_name);
+            if !config_file.exists() {
+                continue;
+            }
+            let content =
+                fs::read_to_string(&config_file).context("Failed to read cargo config file")?;
+            let config: toml::Value =
+                toml::from_str(&content).context("Failed to parse cargo config file")?;
+            let Some(registries) = config.get("registries").and_then(toml::Value::as_table)
+            else {
+                continue;
+            };
+            for (name, registry) in registries {
+                let index = registry.get("index").and_then(toml::Value::as_str);
+                // registry index can be prefixed with protocol used to access it
+                if let Some(Ok(url)) = index.map(|index| Url::from_str(strip_protocol(index))) {
+                    names.push((name.clone(), url));
+                }
+            }
+        }
+        Ok(Self { names })
+    }
+
+    /// name of registry of source. Registry which is not configured is named
+    /// by its host
+    pub(crate) fn name(&self, source: &Url) -> String {
+        self.names
+            .iter()
+            .find(|(_, index)| same_registry(index, source))
+            .map_or_else(
+                || source.host_str().unwrap_or(source.as_str()).to_string(),
+                |(name, _)| name.clone(),
+            )
+    }
+
+    /// check if source belongs to registry with provided name, host or index
+    /// url
+    pub(crate) fn matches(&self, name: &str, source: &Url) -> bool {
+        self.name(source) == name
+            || source.host_str() == Some(name)
+            || Url::from_str(strip_protocol(name)).is_ok_and(|url| same_registry(&url, source))
+    }
+}
+
+// remove sparse or git protocol prefix from index url
+fn strip_protocol(index: &str) -> &str {
+    index
+        .strip_prefix("sparse+")
+        .or_else(|| index.strip_prefix("registry+"))
+        .unwrap_or(index)
+}
+
+// source of sparse registry only contains scheme and host so only host is
+// compared for such source
+fn same_registry(index: &Url, source: &Url) -> bool {
+    index.host_str() == source.host_str()
+        && (source.path() == "/"
+            || index.path().trim_end_matches('/') == source.path().trim_end_matches('/'))
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use url::Url;
+
+    use super::Registries;
+
+    #[test]
+    fn test_registry_name() {
+        let registries = Registries {
+            names: vec![
+                (
+                    String::from("crates-io"),
+                    Url::from_str("https://index.crates.io/").unwrap(),
+                ),
+                (
+                    String::from("private"),
+                    Url::from_str("https://git.example.com/index").unwrap(),
+                ),
+            ],
+        };
+        let sparse = Url::from_str("https://index.crates.io").unwrap();
+        let private = Url::from_str("https://git.example.com/index/").unwrap();
+        let other = Url::from_str("https://mirror.example.org").unwrap();
+        assert_eq!(registries.name(&sparse), "crates-io");
+        assert_eq!(registries.name(&private), "private");
+        assert_eq!(registries.name(&other), "mirror.example.org");
+        assert!(registries.matches("private", &private));
+        assert!(registries.matches("mirror.example.org", &other));
+        assert!(!registries.matches("private", &sparse));
+    }
+}
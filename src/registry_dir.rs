@@ -1,34 +1,33 @@
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use anyhow::{Context, Result};
 use owo_colors::OwoColorize;
 
 use crate::crate_detail::{CrateDetail, CrateMetaData};
-use crate::utils::delete_folder;
+use crate::event::{Event, emit};
+use crate::progress::{finish_progress, start_deletion};
+use crate::protect::skip_protected;
+use crate::restore_manifest::{RestoreEntry, RestoreManifest, index_checksum};
+use crate::utils::{confirm_removal, convert_pretty, delete_folder, get_size, verbosity};
 
 /// Stores .cargo/registry cache & src information
-pub(crate) struct RegistryDir<'a> {
-    cache_dir: &'a str,
-    src_dir: &'a str,
-    index_cache_dir: Vec<String>,
+pub(crate) struct RegistryDir {
+    index_cache_dir: Vec<PathBuf>,
     installed_crate: Vec<CrateMetaData>,
+    older_than: Option<Duration>,
+    restore_manifest: RestoreManifest,
 }
 
-impl<'a> RegistryDir<'a> {
+impl RegistryDir {
     /// Create new registry dir
     pub(crate) fn new(
-        cache_dir: &'a Path,
-        src_dir: &'a Path,
         index_dir: &Path,
         installed_crate: &[CrateMetaData],
+        older_than: Option<Duration>,
+        restore_manifest: RestoreManifest,
     ) -> Result<Self> {
-        let cache_dir = cache_dir
-            .to_str()
-            .context("failed to convert cache dir to str")?;
-        let src_dir = src_dir
-            .to_str()
-            .context("failed to convert src dir to str")?;
         let mut index_cache_dir = Vec::new();
         // read a index .cache dir folder for each registry by analyzing index folder
         if index_dir.exists() {
@@ -36,49 +35,50 @@ impl<'a> RegistryDir<'a> {
                 let mut entry = entry?.path();
                 entry.push(".cache");
                 if entry.exists() {
-                    index_cache_dir.push(
-                        entry
-                            .to_str()
-                            .context("Unable to convert index cache folder to str")?
-                            .to_string(),
-                    );
+                    index_cache_dir.push(entry);
                 }
             }
         }
 
         Ok(Self {
-            cache_dir,
-            src_dir,
             index_cache_dir,
             installed_crate: installed_crate.to_owned(),
+            older_than,
+            restore_manifest,
         })
     }
 
-    /// Remove crate from src & cache directory
+    /// replace installed crate list with list from latest scan
+    pub(crate) fn set_installed_crate(&mut self, installed_crate: &[CrateMetaData]) {
+        installed_crate.clone_into(&mut self.installed_crate);
+    }
+
+    /// Remove crate from src & cache directory along with its archived source.
+    /// Paths found while scanning crate are removed so directory whose name
+    /// is not valid semver is removed as it is. Return if crate is removed
+    /// completely along with size freed
     pub(crate) fn remove_crate(
         &mut self,
         crate_detail: &CrateDetail,
         crate_metadata: &CrateMetaData,
         dry_run: bool,
-    ) -> Result<bool> {
-        // remove crate from cache dir
-        let mut is_success = remove_crate(
-            Path::new(&self.cache_dir),
-            crate_detail,
-            crate_metadata,
-            dry_run,
-        )
-        .is_ok();
-
-        // remove crate from index dir
-        is_success = remove_crate(
-            Path::new(&self.src_dir),
-            crate_detail,
-            crate_metadata,
-            dry_run,
-        )
-        .is_ok()
-            && is_success;
+    ) -> Result<(bool, u64)> {
+        let mut is_success = !crate_metadata.path().is_empty();
+        for path in crate_metadata.path() {
+            is_success = matches!(delete_folder(path, dry_run), Ok(true)) && is_success;
+        }
+        // size of path left behind is not freed
+        let size_freed = if is_success {
+            crate_metadata.size()
+        } else {
+            let size_left = crate_metadata
+                .path()
+                .iter()
+                .filter(|path| path.exists())
+                .map(|path| get_size(path).unwrap_or(0))
+                .sum();
+            crate_metadata.size().saturating_sub(size_left)
+        };
 
         let index_cache = self.index_cache_dir.clone();
 
@@ -86,12 +86,15 @@ impl<'a> RegistryDir<'a> {
         // name from installed crate name owned locally by it so when two version of
         // same crate is deleted it properly remove index cache
         for index_cache_dir in &index_cache {
-            let index = Path::new(&index_cache_dir);
+            let index = index_cache_dir.as_path();
             let source = crate_detail
                 .source_url_from_path(index.parent().context("Failed to get index parent")?)?;
-            if &Some(source) == crate_metadata.source() {
+            if Some(&source) == crate_metadata.source() {
+                // index cache file name is always lowercase so crate which only differ by
+                // case share same index cache
                 let same_name_list = self.installed_crate.iter().filter(|&x| {
-                    x.name() == crate_metadata.name() && x.source() == crate_metadata.source()
+                    x.name().eq_ignore_ascii_case(crate_metadata.name())
+                        && x.source() == crate_metadata.source()
                 });
                 if same_name_list.count() == 1 {
                     is_success =
@@ -101,43 +104,72 @@ impl<'a> RegistryDir<'a> {
                 self.installed_crate.retain(|x| x != crate_metadata);
             }
         }
+        let crate_id = crate_metadata
+            .crate_id()
+            .context("Failed to get registry crate id")?;
         if dry_run {
-            println!(
-                r#"{} {} "{}-{}""#,
-                "Dry run:".yellow(),
-                "Removed".red(),
-                crate_metadata.name(),
-                crate_metadata
-                    .version()
-                    .clone()
-                    .context("Failed to convert crate version")?,
-            );
-            Ok(true)
+            if verbosity() >= 1 {
+                human_println!(
+                    r#"{} {} "{crate_id}" ({})"#,
+                    "Dry run:".yellow(),
+                    "Removed".red(),
+                    convert_pretty(crate_metadata.size()).trim()
+                );
+            }
+            Ok((true, size_freed))
         } else if is_success {
-            println!(
-                r#"{} "{}-{}""#,
-                "Removed".red(),
-                crate_metadata.name(),
-                crate_metadata
-                    .version()
-                    .clone()
-                    .context("Failed to convert crate version")?,
-            );
-            Ok(true)
+            human_println!(r#"{} "{crate_id}""#, "Removed".red());
+            Ok((true, size_freed))
         } else {
-            println!(
-                r#"Failed to remove "{}-{}""#,
-                crate_metadata.name(),
-                crate_metadata
-                    .version()
-                    .clone()
-                    .context("Failed to convert crate version")?,
-            );
-            Ok(false)
+            emit(&Event::Error {
+                message: format!(r#"Failed to remove "{crate_id}""#),
+            });
+            human_println!(r#"Failed to remove "{crate_id}""#);
+            Ok((false, size_freed))
         }
     }
 
-    /// Remove list of crates
+    // create restore entry of crate along with checksum read from index cache
+    fn restore_entry(
+        &self,
+        crate_detail: &CrateDetail,
+        crate_metadata: &CrateMetaData,
+    ) -> Option<RestoreEntry> {
+        let crate_id = crate_metadata.crate_id()?;
+        let version = crate_id.version().to_string();
+        let checksum = self.index_cache_dir.iter().find_map(|index_cache_dir| {
+            let index = index_cache_dir.as_path();
+            let source = crate_detail.source_url_from_path(index.parent()?).ok()?;
+            if &source == crate_id.registry() {
+                index_checksum(&index_cache_file(index, crate_id.name()), &version)
+            } else {
+                None
+            }
+        });
+        Some(RestoreEntry::new(
+            crate_id.name(),
+            &version,
+            checksum,
+            crate_id.registry(),
+        ))
+    }
+
+    /// add crates which are about to be removed to restore manifest so they
+    /// can be downloaded again by restore
+    pub(crate) fn record_restore(
+        &mut self,
+        crate_detail: &CrateDetail,
+        crates: &[&CrateMetaData],
+    ) -> Result<()> {
+        let entries = crates
+            .iter()
+            .filter_map(|crate_metadata| self.restore_entry(crate_detail, crate_metadata))
+            .collect();
+        self.restore_manifest.extend(entries)
+    }
+
+    /// Remove list of crates. Protected crates and crates used within older
+    /// than duration are skipped
     pub(crate) fn remove_crate_list(
         &mut self,
         crate_detail: &CrateDetail,
@@ -146,53 +178,55 @@ impl<'a> RegistryDir<'a> {
     ) -> Result<(u64, usize)> {
         let mut size_cleaned = 0;
         let mut crate_removed = 0;
-        for crate_metadata in list {
-            if self.remove_crate(crate_detail, crate_metadata, dry_run)? {
-                size_cleaned += crate_metadata.size();
+        let older_than = self.older_than;
+        let removable = list
+            .iter()
+            .filter(|crate_metadata| {
+                !skip_protected(crate_metadata)
+                    && older_than.is_none_or(|duration| crate_metadata.is_unused_for(duration))
+            })
+            .collect::<Vec<_>>();
+        if !dry_run {
+            // manifest is written before deletion so crates can be restored even
+            // if deletion is interrupted
+            self.record_restore(crate_detail, &removable)?;
+            start_deletion(removable.iter().copied().map(CrateMetaData::size).sum());
+        }
+        for crate_metadata in removable {
+            if !dry_run && !confirm_removal(crate_metadata) {
+                emit(&Event::Skipped {
+                    name: crate_metadata.full_name(),
+                    reason: "declined in interactive mode",
+                });
+                continue;
+            }
+            let (is_removed, size_freed) =
+                self.remove_crate(crate_detail, crate_metadata, dry_run)?;
+            size_cleaned += size_freed;
+            if is_removed {
                 crate_removed += 1;
             }
         }
+        finish_progress();
         Ok((size_cleaned, crate_removed))
     }
 }
 
-/// Remove crates which name is provided to delete
-fn remove_crate(
-    path: &Path,
-    crate_detail: &CrateDetail,
-    crate_metadata: &CrateMetaData,
-    dry_run: bool,
-) -> Result<()> {
-    if path.exists() {
-        for entry in fs::read_dir(path)? {
-            let path = entry?.path();
-            let source = crate_detail.source_url_from_path(&path)?;
-            if &Some(source) == crate_metadata.source() {
-                for entry in fs::read_dir(path)? {
-                    let path = entry?.path();
-                    let crate_name = crate_metadata.name();
-                    let crate_version = crate_metadata
-                        .version()
-                        .clone()
-                        .context("Failed to get crate version")?;
-                    if path
-                        .to_str()
-                        .context("Failed to get crate name path to str")?
-                        .contains(&format!("{crate_name}-{crate_version}"))
-                    {
-                        delete_folder(&path, dry_run)?;
-                    }
-                }
-            }
-        }
-    }
+/// determine crate index cache location and remove crate index cache
+fn remove_index_cache(path: &Path, crate_metadata: &CrateMetaData, dry_run: bool) -> Result<()> {
+    let index_cache_file = index_cache_file(path, crate_metadata.name());
+    anyhow::ensure!(
+        delete_folder(&index_cache_file, dry_run)?,
+        "Failed to remove {}",
+        index_cache_file.display()
+    );
     Ok(())
 }
 
-/// determine crate index cache location and remove crate index cache
-fn remove_index_cache(path: &Path, crate_metadata: &CrateMetaData, dry_run: bool) -> Result<()> {
+/// location of index cache file of crate inside index .cache directory
+pub(crate) fn index_cache_file(path: &Path, name: &str) -> PathBuf {
     let mut crate_index_cache_location = path.to_path_buf();
-    let name = crate_metadata.name();
+    let name = &name.to_lowercase();
     match name.len() {
         1 => {
             crate_index_cache_location.push("1");
@@ -212,9 +246,8 @@ fn remove_index_cache(path: &Path, crate_metadata: &CrateMetaData, dry_run: bool
             crate_index_cache_location.push(&name[2..4]);
             crate_index_cache_location.push(name);
         }
-    };
-    delete_folder(&crate_index_cache_location, dry_run)?;
-    Ok(())
+    }
+    crate_index_cache_location
 }
 
 /// check if any index cache folder is empty if it is removed directory. First
@@ -227,7 +260,11 @@ fn remove_empty_index_cache_dir(path: &Path, dry_run: bool) -> Result<()> {
         }
     }
     if fs::read_dir(path).map(|mut i| i.next().is_none())? {
-        delete_folder(path, dry_run)?;
+        anyhow::ensure!(
+            delete_folder(path, dry_run)?,
+            "Failed to remove {}",
+            path.display()
+        );
     }
     Ok(())
 }
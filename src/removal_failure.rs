@@ -0,0 +1,44 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use owo_colors::OwoColorize;
+
+use crate::event::{Event, emit};
+use crate::utils::{print_dash, query_full_width};
+
+// paths which could not be removed during current run along with reason
+static REMOVAL_FAILURES: Mutex<Vec<(PathBuf, String)>> = Mutex::new(Vec::new());
+
+/// record path which could not be removed so remaining paths are still
+/// removed and failure is reported at end of run
+pub(crate) fn record_removal_failure(path: &Path, err: &anyhow::Error) {
+    emit(&Event::Error {
+        message: format!("Failed to remove {}: {err:#}", path.display()),
+    });
+    if let Ok(mut failures) = REMOVAL_FAILURES.lock() {
+        failures.push((path.to_path_buf(), format!("{err:#}")));
+    }
+}
+
+/// print paths which could not be removed along with reason. Nothing is
+/// printed if every path was removed
+pub(crate) fn print_removal_failures() {
+    let failures = REMOVAL_FAILURES
+        .lock()
+        .map(|mut failures| std::mem::take(&mut *failures))
+        .unwrap_or_default();
+    if failures.is_empty() {
+        return;
+    }
+    print_dash(query_full_width());
+    human_println!(
+        "{}",
+        format!("Failed to remove {} paths", failures.len())
+            .red()
+            .bold()
+    );
+    for (path, reason) in &failures {
+        human_println!("   {} ({reason})", path.display());
+    }
+    print_dash(query_full_width());
+}
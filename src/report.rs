@@ -0,0 +1,450 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::crate_detail::{CrateDetail, CrateMetaData};
+use crate::dir_path::DirPath;
+use crate::list_crate::CrateList;
+use crate::schema::{SCHEMA_VERSION, Versioned};
+use crate::stats_file::{CleanAction, StatsFile};
+use crate::utils::{
+    convert_pretty, csv_field, disk_usage_enabled, format_timestamp, get_disk_usage, get_size,
+    last_modified,
+};
+
+/// Structured information of crate used for machine readable output
+#[derive(Serialize, Deserialize)]
+pub(crate) struct CrateReport {
+    id: Option<String>,
+    name: String,
+    version: Option<String>,
+    size: u64,
+    category: String,
+    source: Option<Url>,
+    path: Vec<PathBuf>,
+    last_used: Option<String>,
+}
+
+impl CrateReport {
+    fn new(crate_metadata: &CrateMetaData, category: &str) -> Self {
+        let id = crate_metadata
+            .crate_id()
+            .map(|crate_id| crate_id.pkgid())
+            .or_else(|| crate_metadata.git_id().map(|git_id| git_id.pkgid()));
+        Self {
+            id,
+            name: crate_metadata.name().clone(),
+            version: crate_metadata.version().map(ToString::to_string),
+            size: crate_metadata.size(),
+            category: category.to_string(),
+            source: crate_metadata.source().cloned(),
+            path: crate_metadata.path().clone(),
+            last_used: crate_metadata.last_used().map(format_timestamp),
+        }
+    }
+
+    /// package id spec of crate or name with version when id is not available
+    pub(crate) fn key(&self) -> String {
+        self.id.clone().unwrap_or_else(|| match &self.version {
+            Some(version) => format!("{}@{version}", self.name),
+            None => self.name.clone(),
+        })
+    }
+
+    pub(crate) fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+/// Structured size information of cargo home folder
+#[derive(Serialize, Deserialize)]
+pub(crate) struct FolderReport {
+    category: String,
+    path: PathBuf,
+    size: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    disk_usage: Option<u64>,
+    count: Option<usize>,
+}
+
+impl FolderReport {
+    fn new(category: &str, path: &Path, count: Option<usize>) -> Self {
+        Self {
+            category: category.to_string(),
+            path: path.to_path_buf(),
+            size: get_size(path).unwrap_or(0),
+            disk_usage: disk_usage_enabled().then(|| get_disk_usage(path).unwrap_or(0)),
+            count,
+        }
+    }
+
+    pub(crate) fn category(&self) -> &String {
+        &self.category
+    }
+
+    pub(crate) fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// allocated disk usage of folder. Only present in disk usage mode
+    pub(crate) fn disk_usage(&self) -> Option<u64> {
+        self.disk_usage
+    }
+}
+
+/// Structured query output
+#[derive(Serialize, Deserialize)]
+pub(crate) struct QueryReport {
+    folders: Vec<FolderReport>,
+    total: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    total_disk_usage: Option<u64>,
+}
+
+impl QueryReport {
+    /// create query report from group of folder reports. First folder of each
+    /// group contains all other folder of group so only its size is used for
+    /// calculating total size
+    pub(crate) fn new(folder_groups: Vec<Vec<FolderReport>>) -> Self {
+        let total = folder_groups
+            .iter()
+            .filter_map(|group| group.first())
+            .map(|folder| folder.size)
+            .sum();
+        let total_disk_usage = folder_groups
+            .iter()
+            .filter_map(|group| group.first())
+            .map(|folder| folder.disk_usage)
+            .sum();
+        Self {
+            folders: folder_groups.into_iter().flatten().collect(),
+            total,
+            total_disk_usage,
+        }
+    }
+
+    pub(crate) fn folders(&self) -> &Vec<FolderReport> {
+        &self.folders
+    }
+
+    /// total size of all folder groups
+    pub(crate) fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// total allocated disk usage of all folder groups. Only present in disk
+    /// usage mode
+    pub(crate) fn total_disk_usage(&self) -> Option<u64> {
+        self.total_disk_usage
+    }
+}
+
+/// create crate report for all crates of list
+pub(crate) fn crate_reports(list: &[CrateMetaData], category: &str) -> Vec<CrateReport> {
+    list.iter()
+        .map(|crate_metadata| CrateReport::new(crate_metadata, category))
+        .collect()
+}
+
+/// create folder report of bin directory
+pub(crate) fn query_bin_report(dir_path: &DirPath, crate_list: &CrateList) -> Vec<FolderReport> {
+    vec![FolderReport::new(
+        "bin",
+        dir_path.bin_dir(),
+        Some(crate_list.installed_bin().len()),
+    )]
+}
+
+/// create folder report of registry directory. First report is of whole
+/// registry directory
+pub(crate) fn query_registry_report(
+    dir_path: &DirPath,
+    crate_list: &CrateList,
+    crate_detail: &CrateDetail,
+) -> Vec<FolderReport> {
+    vec![
+        FolderReport::new(
+            "registry",
+            dir_path.registry_dir(),
+            Some(crate_list.installed_registry().len()),
+        ),
+        FolderReport::new(
+            "registry_cache",
+            dir_path.cache_dir(),
+            Some(crate_detail.registry_crates_archive().len()),
+        ),
+        FolderReport::new("registry_index", dir_path.index_dir(), None),
+        FolderReport::new(
+            "registry_src",
+            dir_path.src_dir(),
+            Some(crate_detail.registry_crates_source().len()),
+        ),
+    ]
+}
+
+/// create folder report of git directory. First report is of whole git
+/// directory
+pub(crate) fn query_git_report(
+    dir_path: &DirPath,
+    crate_list: &CrateList,
+    crate_detail: &CrateDetail,
+) -> Vec<FolderReport> {
+    vec![
+        FolderReport::new(
+            "git",
+            dir_path.git_dir(),
+            Some(crate_list.installed_git().len()),
+        ),
+        FolderReport::new(
+            "git_checkout",
+            dir_path.checkout_dir(),
+            Some(crate_detail.git_crates_archive().len()),
+        ),
+        FolderReport::new(
+            "git_db",
+            dir_path.db_dir(),
+            Some(crate_detail.git_crates_source().len()),
+        ),
+    ]
+}
+
+/// create csv inventory of all bin, registry and git crates with size of
+/// source and archive part and last modified time
+pub(crate) fn inventory_csv(crate_list: &CrateList, crate_detail: &CrateDetail) -> String {
+    let mut content =
+        String::from("category,name,version,source,source_size,archive_size,last_modified\n");
+    let inventory = [
+        ("bin", crate_list.installed_bin(), None, None),
+        (
+            "registry",
+            crate_list.installed_registry(),
+            Some(crate_detail.registry_crates_source()),
+            Some(crate_detail.registry_crates_archive()),
+        ),
+        (
+            "git",
+            crate_list.installed_git(),
+            Some(crate_detail.git_crates_source()),
+            Some(crate_detail.git_crates_archive()),
+        ),
+    ];
+    for (category, list, source_set, archive_set) in inventory {
+        for crate_metadata in list {
+            // bin do not have separate source and archive so whole size is source size
+            let source_size = source_set.map_or(crate_metadata.size(), |set| {
+                set.get(crate_metadata).map_or(0, CrateMetaData::size)
+            });
+            let archive_size = archive_set
+                .and_then(|set| set.get(crate_metadata))
+                .map_or(0, CrateMetaData::size);
+            let last_modified = last_modified(crate_metadata.path())
+                .map(format_timestamp)
+                .unwrap_or_default();
+            let row = [
+                category.to_string(),
+                crate_metadata.name().clone(),
+                crate_metadata
+                    .version()
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+                crate_metadata
+                    .source()
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+                source_size.to_string(),
+                archive_size.to_string(),
+                last_modified,
+            ];
+            let row = row.iter().map(|field| csv_field(field)).collect::<Vec<_>>();
+            content.push_str(&row.join(","));
+            content.push('\n');
+        }
+    }
+    content
+}
+
+/// Summary of single run of cargo trim
+#[derive(Serialize)]
+struct RunReport<'a> {
+    machine: String,
+    actions: &'a [CleanAction],
+    total_size: u64,
+    total_removed: usize,
+    cache: QueryReport,
+    crates: Vec<CrateReport>,
+}
+
+/// Summary file written by cargo trim read back for aggregation. Summary
+/// written by older version do not contain schema version, machine name and
+/// crates
+#[derive(Deserialize)]
+pub(crate) struct SummaryFile {
+    #[serde(default)]
+    schema_version: u32,
+    #[serde(default)]
+    machine: Option<String>,
+    cache: QueryReport,
+    #[serde(default)]
+    crates: Vec<CrateReport>,
+}
+
+impl SummaryFile {
+    /// read json summary file
+    pub(crate) fn read(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .context(format!("Failed to read summary file {}", path.display()))?;
+        let summary: Self = serde_json::from_str(&content)
+            .context(format!("Failed to parse summary file {}", path.display()))?;
+        anyhow::ensure!(
+            summary.schema_version <= SCHEMA_VERSION,
+            "Summary file {} uses schema version {} which is newer than supported version {}",
+            path.display(),
+            summary.schema_version,
+            SCHEMA_VERSION
+        );
+        Ok(summary)
+    }
+
+    pub(crate) fn machine(&self) -> Option<&String> {
+        self.machine.as_ref()
+    }
+
+    pub(crate) fn cache(&self) -> &QueryReport {
+        &self.cache
+    }
+
+    pub(crate) fn crates(&self) -> &Vec<CrateReport> {
+        &self.crates
+    }
+}
+
+/// Write summary of run to all requested output sinks along with regular
+/// table output
+pub(crate) struct Reporter {
+    summary_file: Option<PathBuf>,
+    markdown_file: Option<PathBuf>,
+}
+
+impl Reporter {
+    pub(crate) fn new(summary_file: Option<PathBuf>, markdown_file: Option<PathBuf>) -> Self {
+        Self {
+            summary_file,
+            markdown_file,
+        }
+    }
+
+    /// write run summary to json summary file and markdown file if provided
+    pub(crate) fn report(
+        &self,
+        dir_path: &DirPath,
+        crate_list: &CrateList,
+        crate_detail: &CrateDetail,
+        stats_file: &StatsFile,
+    ) -> Result<()> {
+        if self.summary_file.is_none() && self.markdown_file.is_none() {
+            return Ok(());
+        }
+        let actions = stats_file.session();
+        let mut crates = crate_reports(crate_list.installed_registry(), "registry");
+        crates.append(&mut crate_reports(crate_list.installed_git(), "git"));
+        let run_report = RunReport {
+            machine: machine_name(),
+            actions,
+            total_size: actions.iter().map(CleanAction::size).sum(),
+            total_removed: actions.iter().map(CleanAction::removed).sum(),
+            cache: QueryReport::new(vec![
+                query_bin_report(dir_path, crate_list),
+                query_registry_report(dir_path, crate_list, crate_detail),
+                query_git_report(dir_path, crate_list, crate_detail),
+            ]),
+            crates,
+        };
+        if let Some(summary_file) = &self.summary_file {
+            let content = serde_json::to_string_pretty(&Versioned::new(&run_report))
+                .context("Failed to convert run summary to json")?;
+            fs::write(summary_file, content).context("Failed to write summary file")?;
+        }
+        if let Some(markdown_file) = &self.markdown_file {
+            fs::write(markdown_file, markdown_report(&run_report))
+                .context("Failed to write markdown report")?;
+        }
+        Ok(())
+    }
+}
+
+// name of current machine used to identify summary file in aggregation
+fn machine_name() -> String {
+    ["HOSTNAME", "COMPUTERNAME"]
+        .iter()
+        .find_map(|key| std::env::var(key).ok())
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| String::from("unknown"))
+}
+
+// convert run report to markdown document
+fn markdown_report(run_report: &RunReport) -> String {
+    let mut lines = vec![
+        String::from("# cargo trim report"),
+        String::new(),
+        String::from("## Clean actions"),
+        String::new(),
+    ];
+    if run_report.actions.is_empty() {
+        lines.push(String::from("No clean action performed"));
+    } else {
+        lines.push(String::from(
+            "| Category | Reason | Removed | Size | Dry run |",
+        ));
+        lines.push(String::from("|---|---|---:|---:|---|"));
+        for action in run_report.actions {
+            lines.push(format!(
+                "| {} | {} | {} | {} | {} |",
+                action.category(),
+                action.reason(),
+                action.removed(),
+                convert_pretty(action.size()).trim(),
+                action.dry_run()
+            ));
+        }
+        lines.push(format!(
+            "| **Total** | | {} | {} | |",
+            run_report.total_removed,
+            convert_pretty(run_report.total_size).trim()
+        ));
+    }
+    lines.push(String::new());
+    lines.push(String::from("## Cache size"));
+    lines.push(String::new());
+    lines.push(String::from("| Folder | Count | Size |"));
+    lines.push(String::from("|---|---:|---:|"));
+    for folder in &run_report.cache.folders {
+        lines.push(format!(
+            "| {} | {} | {} |",
+            folder.category,
+            folder
+                .count
+                .map(|count| count.to_string())
+                .unwrap_or_default(),
+            convert_pretty(folder.size).trim()
+        ));
+    }
+    lines.push(format!(
+        "| **Total** | | {} |",
+        convert_pretty(run_report.cache.total).trim()
+    ));
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+/// print value as pretty json
+pub(crate) fn print_json<T: Serialize>(value: &T) -> Result<()> {
+    let content = serde_json::to_string_pretty(&Versioned::new(value))
+        .context("Failed to convert report to json")?;
+    human_println!("{content}");
+    Ok(())
+}
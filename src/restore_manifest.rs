@@ -0,0 +1,130 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::index_dir::index_entry;
+use crate::utils::format_timestamp;
+
+/// Metadata of removed registry crate which is enough to download its exact
+/// .crate file again
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub(crate) struct RestoreEntry {
+    name: String,
+    version: String,
+    checksum: Option<String>,
+    registry: Url,
+}
+
+impl RestoreEntry {
+    pub(crate) fn new(name: &str, version: &str, checksum: Option<String>, registry: &Url) -> Self {
+        Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            checksum,
+            registry: registry.clone(),
+        }
+    }
+
+    pub(crate) fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub(crate) fn version(&self) -> &String {
+        &self.version
+    }
+
+    pub(crate) fn checksum(&self) -> Option<&String> {
+        self.checksum.as_ref()
+    }
+
+    pub(crate) fn registry(&self) -> &Url {
+        &self.registry
+    }
+}
+
+/// Manifest of registry crates removed by last trim
+#[derive(Serialize, Deserialize, Default)]
+pub(crate) struct RestoreManifest {
+    #[serde(default)]
+    taken: String,
+    #[serde(default)]
+    removed: Vec<RestoreEntry>,
+    #[serde(skip)]
+    location: PathBuf,
+}
+
+impl RestoreManifest {
+    /// create empty manifest for current run. Manifest of previous trim is
+    /// only replaced once registry crate is removed
+    pub(crate) fn new(manifest_file: &Path) -> Self {
+        Self {
+            taken: format_timestamp(SystemTime::now()),
+            removed: Vec::new(),
+            location: manifest_file.to_path_buf(),
+        }
+    }
+
+    /// Read manifest written by last trim or create empty manifest if file is
+    /// not present
+    pub(crate) fn init(manifest_file: &Path) -> Result<Self> {
+        let mut manifest = if manifest_file.exists() {
+            let content = fs::read_to_string(manifest_file)
+                .context("failed to read restore manifest content")?;
+            toml::from_str(&content).context("failed to convert string to restore manifest")?
+        } else {
+            Self::default()
+        };
+        manifest.location = manifest_file.to_path_buf();
+        Ok(manifest)
+    }
+
+    pub(crate) fn taken(&self) -> &String {
+        &self.taken
+    }
+
+    pub(crate) fn removed(&self) -> &Vec<RestoreEntry> {
+        &self.removed
+    }
+
+    /// add crates which are about to be removed and save manifest
+    pub(crate) fn extend(&mut self, entries: Vec<RestoreEntry>) -> Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        for entry in entries {
+            if !self.removed.contains(&entry) {
+                self.removed.push(entry);
+            }
+        }
+        self.save()
+    }
+
+    /// keep only entries which satisfy predicate and save manifest
+    pub(crate) fn retain<F>(&mut self, predicate: F) -> Result<()>
+    where
+        F: FnMut(&RestoreEntry) -> bool,
+    {
+        self.removed.retain(predicate);
+        self.save()
+    }
+
+    // write manifest to file
+    fn save(&self) -> Result<()> {
+        let serialized = toml::to_string_pretty(&self)
+            .context("Restore manifest cannot be converted to toml")?;
+        fs::write(&self.location, serialized).context("Failed to write restore manifest")?;
+        Ok(())
+    }
+}
+
+/// read checksum of crate version from registry index cache file of crate
+pub(crate) fn index_checksum(index_cache_file: &Path, version: &str) -> Option<String> {
+    index_entry(index_cache_file, version)?
+        .get("cksum")
+        .and_then(serde_json::Value::as_str)
+        .map(ToString::to_string)
+}
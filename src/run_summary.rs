@@ -0,0 +1,79 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use owo_colors::OwoColorize;
+
+use crate::event::{disable_quiet, enable_quiet};
+use crate::utils::{convert_pretty, print_dash};
+
+// number of removed item and freed size of each category across all cargo
+// homes of current run
+static CATEGORY_TOTALS: Mutex<BTreeMap<String, (usize, u64)>> = Mutex::new(BTreeMap::new());
+static SUMMARY_MODE: AtomicBool = AtomicBool::new(false);
+
+/// enable summary mode where only per category totals table is printed at end
+/// of run. Other human readable output is hidden like in quiet mode
+pub(crate) fn enable_summary_mode() {
+    SUMMARY_MODE.store(true, Ordering::Relaxed);
+    enable_quiet();
+}
+
+/// add removed item and freed size of clean action to its category total
+pub(crate) fn record_category_total(category: &str, size: u64, removed: usize) {
+    if let Ok(mut totals) = CATEGORY_TOTALS.lock() {
+        let (total_removed, total_size) = totals.entry(category.to_string()).or_default();
+        *total_removed += removed;
+        *total_size += size;
+    }
+}
+
+/// check if any clean action was recorded in current run
+pub(crate) fn clean_recorded() -> bool {
+    CATEGORY_TOTALS
+        .lock()
+        .is_ok_and(|totals| !totals.is_empty())
+}
+
+/// print only number of bytes freed by current run so it can be read by
+/// script. Number is printed even in quiet mode
+pub(crate) fn print_freed_bytes() {
+    let freed = CATEGORY_TOTALS.lock().map_or(0, |totals| {
+        totals.values().map(|(_, size)| size).sum::<u64>()
+    });
+    println!("{freed}");
+}
+
+/// print per category totals table if summary mode is enabled
+pub(crate) fn print_summary(dry_run: bool) {
+    if !SUMMARY_MODE.load(Ordering::Relaxed) {
+        return;
+    }
+    let Ok(totals) = CATEGORY_TOTALS.lock() else {
+        return;
+    };
+    disable_quiet();
+    let dash_len = 76;
+    let freed_title = if dry_run { "RECLAIMABLE" } else { "FREED" };
+    print_dash(dash_len);
+    human_println!(
+        "{}",
+        format!("{:<44}{:>16}{:>16}", "CATEGORY", "REMOVED", freed_title).bold()
+    );
+    print_dash(dash_len);
+    for (category, (removed, size)) in totals.iter() {
+        human_println!(
+            "{:<44}{:>16}{:>16}",
+            category,
+            removed,
+            convert_pretty(*size)
+        );
+    }
+    print_dash(dash_len);
+    human_println!(
+        "{:<44}{:>16}{:>16}",
+        "total",
+        totals.values().map(|(removed, _)| removed).sum::<usize>(),
+        convert_pretty(totals.values().map(|(_, size)| size).sum())
+    );
+}
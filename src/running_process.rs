@@ -0,0 +1,101 @@
+use std::path::Path;
+use std::process::Command;
+
+// name of processes which read cargo home while building
+const CARGO_PROCESSES: [&str; 2] = ["cargo", "rustc"];
+
+/// Running process of current user
+pub(crate) struct RunningProcess {
+    pid: u32,
+    parent_pid: u32,
+    name: String,
+}
+
+impl RunningProcess {
+    /// process id
+    pub(crate) fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// process name
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// list running cargo and rustc processes of current user. Ancestors of
+/// current process such as cargo running cargo trim as subcommand are not
+/// listed. Empty list is returned when processes cannot be listed
+pub(crate) fn running_cargo_processes() -> Vec<RunningProcess> {
+    let processes = list_processes();
+    let mut ancestors = vec![std::process::id()];
+    while let Some(parent_pid) = processes
+        .iter()
+        .find(|process| Some(&process.pid) == ancestors.last())
+        .map(|process| process.parent_pid)
+    {
+        // pid 0 is parent of init process
+        if parent_pid == 0 || ancestors.contains(&parent_pid) {
+            break;
+        }
+        ancestors.push(parent_pid);
+    }
+    processes
+        .into_iter()
+        .filter(|process| {
+            CARGO_PROCESSES.contains(&process.name.as_str()) && !ancestors.contains(&process.pid)
+        })
+        .collect()
+}
+
+// list processes of current user using ps. Process listing is not supported
+// in windows
+fn list_processes() -> Vec<RunningProcess> {
+    if cfg!(target_os = "windows") {
+        return Vec::new();
+    }
+    let Ok(output) = Command::new("ps")
+        .args(["-x", "-o", "pid=,ppid=,comm="])
+        .output()
+    else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(parse_ps_line)
+        .collect()
+}
+
+// parse single line of ps output. Command is full path in macos so only file
+// name is used
+fn parse_ps_line(line: &str) -> Option<RunningProcess> {
+    let mut parts = line.split_whitespace();
+    let pid = parts.next()?.parse().ok()?;
+    let parent_pid = parts.next()?.parse().ok()?;
+    let command = parts.collect::<Vec<_>>().join(" ");
+    let name = Path::new(&command)
+        .file_name()?
+        .to_string_lossy()
+        .to_string();
+    Some(RunningProcess {
+        pid,
+        parent_pid,
+        name,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_ps_line;
+
+    #[test]
+    fn test_parse_ps_line() {
+        let process = parse_ps_line("  4821   4790 /Users/dev/.cargo/bin/cargo").unwrap();
+        assert_eq!(process.pid, 4821);
+        assert_eq!(process.parent_pid, 4790);
+        assert_eq!(process.name, "cargo");
+        let process = parse_ps_line("12 1 rustc").unwrap();
+        assert_eq!(process.name, "rustc");
+        assert!(parse_ps_line("PID PPID COMMAND").is_none());
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use std::{env, fs};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::utils::{get_size, last_modified};
+
+/// Part of rustup settings file used to find default toolchain and toolchain
+/// overridden for directory
+#[derive(Deserialize, Default)]
+struct RustupSettings {
+    default_toolchain: Option<String>,
+    #[serde(default)]
+    overrides: HashMap<String, String>,
+}
+
+/// Part of rust-toolchain.toml file used to find toolchain pinned by project
+#[derive(Deserialize)]
+struct ToolchainFile {
+    toolchain: ToolchainSection,
+}
+
+#[derive(Deserialize)]
+struct ToolchainSection {
+    channel: Option<String>,
+}
+
+/// stores information of toolchain installed by rustup
+pub(crate) struct Toolchain {
+    name: String,
+    path: PathBuf,
+    size: u64,
+    docs_size: u64,
+    modified: Option<SystemTime>,
+}
+
+impl Toolchain {
+    pub(crate) fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(crate) fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// size of documentation installed by rust-docs and other component
+    pub(crate) fn docs_size(&self) -> u64 {
+        self.docs_size
+    }
+
+    /// last modified time of toolchain folder or its direct children
+    pub(crate) fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+
+    /// check if toolchain is referred by provided name. Toolchain can be
+    /// referred with or without host triple
+    pub(crate) fn matches(&self, name: &str) -> bool {
+        self.name == name || self.name.starts_with(&format!("{name}-"))
+    }
+}
+
+/// stores information of rustup home directory
+pub(crate) struct RustupDir {
+    path: PathBuf,
+    toolchains: Vec<Toolchain>,
+    default_toolchain: Option<String>,
+    override_toolchains: Vec<String>,
+}
+
+impl RustupDir {
+    /// scan rustup home directory. Return None if rustup is not installed
+    pub(crate) fn new() -> Result<Option<Self>> {
+        let Some(path) = rustup_home() else {
+            return Ok(None);
+        };
+        if !path.is_dir() {
+            return Ok(None);
+        }
+        let settings = fs::read_to_string(path.join("settings.toml"))
+            .ok()
+            .and_then(|content| toml::from_str::<RustupSettings>(&content).ok())
+            .unwrap_or_default();
+        let mut toolchains = Vec::new();
+        let toolchains_dir = path.join("toolchains");
+        if toolchains_dir.is_dir() {
+            for entry in fs::read_dir(&toolchains_dir).context("failed to read toolchains")? {
+                let entry = entry?.path();
+                if !entry.is_dir() {
+                    continue;
+                }
+                let name = entry
+                    .file_name()
+                    .context("failed to get file name of toolchain")?
+                    .to_string_lossy()
+                    .to_string();
+                let mut children = vec![entry.clone()];
+                for child in fs::read_dir(&entry).context("failed to read toolchain")? {
+                    children.push(child?.path());
+                }
+                toolchains.push(Toolchain {
+                    name,
+                    size: get_size(&entry).context("failed to get size of toolchain")?,
+                    docs_size: get_size(&entry.join("share").join("doc")).unwrap_or(0),
+                    modified: last_modified(&children),
+                    path: entry,
+                });
+            }
+        }
+        toolchains.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(Some(Self {
+            path,
+            toolchains,
+            default_toolchain: settings.default_toolchain,
+            override_toolchains: settings.overrides.into_values().collect(),
+        }))
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(crate) fn toolchains(&self) -> &Vec<Toolchain> {
+        &self.toolchains
+    }
+
+    /// check if toolchain is default toolchain of rustup. Default toolchain
+    /// can be stored with or without host triple
+    pub(crate) fn is_default(&self, toolchain: &Toolchain) -> bool {
+        self.default_toolchain
+            .as_ref()
+            .is_some_and(|default| toolchain.matches(default))
+    }
+
+    /// check if toolchain is set as directory override by rustup override set
+    pub(crate) fn is_overridden(&self, toolchain: &Toolchain) -> bool {
+        self.override_toolchains
+            .iter()
+            .any(|name| toolchain.matches(name))
+    }
+
+    /// path of download cache and temporary directory of rustup
+    pub(crate) fn download_dirs(&self) -> [PathBuf; 2] {
+        [self.path.join("downloads"), self.path.join("tmp")]
+    }
+}
+
+// rustup home directory respecting rustup home environment variable
+fn rustup_home() -> Option<PathBuf> {
+    env::var_os("RUSTUP_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs_next::home_dir().map(|home| home.join(".rustup")))
+}
+
+/// list toolchains pinned by rust-toolchain.toml or rust-toolchain file of
+/// projects. Same as rustup toolchain file of nearest ancestor is used
+pub(crate) fn project_toolchains(projects: &[PathBuf]) -> Vec<String> {
+    let mut toolchains = projects
+        .iter()
+        .filter_map(|project| project.ancestors().find_map(read_toolchain_file))
+        .collect::<Vec<_>>();
+    toolchains.sort_unstable();
+    toolchains.dedup();
+    toolchains
+}
+
+// read toolchain of rust-toolchain.toml or legacy rust-toolchain file present
+// in directory. Legacy file can contain toml or only toolchain name
+fn read_toolchain_file(dir: &Path) -> Option<String> {
+    ["rust-toolchain.toml", "rust-toolchain"]
+        .iter()
+        .find_map(|name| fs::read_to_string(dir.join(name)).ok())
+        .and_then(|content| match toml::from_str::<ToolchainFile>(&content) {
+            Ok(toolchain_file) => toolchain_file.toolchain.channel,
+            Err(_) => Some(content.trim().to_string()).filter(|name| !name.is_empty()),
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::project_toolchains;
+
+    #[test]
+    fn test_project_toolchains() {
+        let dir = std::env::temp_dir().join(format!(
+            "cargo_trim_project_toolchains_{}",
+            std::process::id()
+        ));
+        let workspace = dir.join("workspace");
+        let member = workspace.join("member");
+        let legacy = dir.join("legacy");
+        fs::create_dir_all(&member).unwrap();
+        fs::create_dir_all(&legacy).unwrap();
+        fs::write(
+            workspace.join("rust-toolchain.toml"),
+            "[toolchain]\nchannel = \"nightly-2024-01-01\"\n",
+        )
+        .unwrap();
+        fs::write(legacy.join("rust-toolchain"), "1.70.0\n").unwrap();
+        assert_eq!(
+            project_toolchains(&[member, legacy, dir.join("none")]),
+            ["1.70.0", "nightly-2024-01-01"]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
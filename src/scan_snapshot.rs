@@ -0,0 +1,112 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::dir_path::DirPath;
+
+/// Stores time of scan along with modified time of cache directories so
+/// deletion based on old scan can be detected
+pub(crate) struct ScanSnapshot {
+    taken: SystemTime,
+    modified: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl ScanSnapshot {
+    /// take snapshot of current state of cache directories
+    pub(crate) fn new(dir_path: &DirPath) -> Self {
+        Self {
+            taken: SystemTime::now(),
+            modified: cache_dir_modified(dir_path),
+        }
+    }
+
+    /// check if snapshot is older than max age or any cache directory is
+    /// added, removed or modified after snapshot was taken
+    pub(crate) fn is_stale(&self, dir_path: &DirPath, max_age: Duration) -> bool {
+        let too_old = self
+            .taken
+            .elapsed()
+            .map_or(true, |elapsed| elapsed > max_age);
+        too_old || self.modified != cache_dir_modified(dir_path)
+    }
+
+    /// update modified time of cache directories after they are changed by
+    /// cargo trim itself. Time of scan is kept so snapshot still expires
+    pub(crate) fn refresh(&mut self, dir_path: &DirPath) {
+        self.modified = cache_dir_modified(dir_path);
+    }
+}
+
+/// modified time of directories whose content is changed when crate is
+/// downloaded, extracted or removed
+pub(crate) fn cache_dir_modified(dir_path: &DirPath) -> Vec<(PathBuf, Option<SystemTime>)> {
+    let mut directories = vec![
+        dir_path.bin_dir().clone(),
+        dir_path.cache_dir().clone(),
+        dir_path.src_dir().clone(),
+        dir_path.checkout_dir().clone(),
+        dir_path.db_dir().clone(),
+    ];
+    // each registry and git checkout have their own sub directory
+    for parent in [
+        dir_path.cache_dir(),
+        dir_path.src_dir(),
+        dir_path.checkout_dir(),
+    ] {
+        directories.extend(sub_directories(parent));
+    }
+    directories.sort();
+    directories
+        .into_iter()
+        .map(|directory| {
+            let modified = directory
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            (directory, modified)
+        })
+        .collect()
+}
+
+// list direct sub directories of path
+fn sub_directories(path: &Path) -> Vec<PathBuf> {
+    fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|entry| entry.is_dir())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::time::Duration;
+
+    use super::ScanSnapshot;
+    use crate::dir_path::DirPath;
+
+    #[test]
+    fn test_is_stale() {
+        let cargo_home =
+            std::env::temp_dir().join(format!("cargo_trim_scan_snapshot_{}", std::process::id()));
+        let registry_src = cargo_home.join("registry").join("src");
+        fs::create_dir_all(registry_src.join("index.crates.io-6f17d22bba15001f")).unwrap();
+        let dir_path = DirPath::new(Some(cargo_home.clone())).unwrap();
+        let max_age = Duration::from_hours(1);
+
+        let mut snapshot = ScanSnapshot::new(&dir_path);
+        assert!(!snapshot.is_stale(&dir_path, max_age));
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(snapshot.is_stale(&dir_path, Duration::from_millis(1)));
+
+        // new registry directory is detected until snapshot is refreshed
+        fs::create_dir_all(registry_src.join("example.com-1ecc6299db9ec823")).unwrap();
+        assert!(snapshot.is_stale(&dir_path, max_age));
+        snapshot.refresh(&dir_path);
+        assert!(!snapshot.is_stale(&dir_path, max_age));
+        fs::remove_dir_all(&cargo_home).unwrap();
+    }
+}
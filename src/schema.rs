@@ -0,0 +1,88 @@
+use serde::Serialize;
+
+/// Version of json output format written by ndjson events, json log, summary
+/// file and json format of query, list and aggregate. Version is increased only
+/// when field is removed, renamed or its type is changed. New field can be
+/// added without increasing version so reader should ignore unknown field
+pub(crate) const SCHEMA_VERSION: u32 = 1;
+
+/// Json object along with schema version of output format as its first field
+#[derive(Serialize)]
+pub(crate) struct Versioned<'a, T: Serialize> {
+    schema_version: u32,
+    #[serde(flatten)]
+    value: &'a T,
+}
+
+impl<'a, T: Serialize> Versioned<'a, T> {
+    pub(crate) fn new(value: &'a T) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::{Path, PathBuf};
+
+    use super::Versioned;
+    use crate::event::Event;
+
+    // output format is frozen so any change of these lines is breaking change
+    // which requires increase of schema version
+    #[test]
+    fn test_event_schema() {
+        let path = [PathBuf::from("/tmp/serde-1.0.0")];
+        let events = [
+            (
+                Event::Scanned {
+                    category: "registry",
+                    name: "serde",
+                    version: Some(String::from("1.0.0")),
+                    size: 10,
+                    path: &path,
+                },
+                r#"{"schema_version":1,"event":"scanned","category":"registry","name":"serde","version":"1.0.0","size":10,"path":["/tmp/serde-1.0.0"]}"#,
+            ),
+            (
+                Event::Deleted {
+                    path: Path::new("/tmp/serde-1.0.0"),
+                    size: 10,
+                    dry_run: true,
+                },
+                r#"{"schema_version":1,"event":"deleted","path":"/tmp/serde-1.0.0","size":10,"dry_run":true}"#,
+            ),
+            (
+                Event::Freed {
+                    category: "registry",
+                    reason: "old",
+                    size: 10,
+                    removed: 1,
+                    dry_run: false,
+                },
+                r#"{"schema_version":1,"event":"freed","category":"registry","reason":"old","size":10,"removed":1,"dry_run":false}"#,
+            ),
+            (
+                Event::Skipped {
+                    name: String::from("serde"),
+                    reason: "protected",
+                },
+                r#"{"schema_version":1,"event":"skipped","name":"serde","reason":"protected"}"#,
+            ),
+            (
+                Event::Error {
+                    message: String::from("failed"),
+                },
+                r#"{"schema_version":1,"event":"error","message":"failed"}"#,
+            ),
+        ];
+        for (event, line) in events {
+            assert_eq!(
+                serde_json::to_string(&Versioned::new(&event)).unwrap(),
+                line
+            );
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::get_size;
+
+/// Size of path along with modified time of path when size was calculated
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+struct SizeEntry {
+    modified_secs: u64,
+    modified_nanos: u32,
+    size: u64,
+}
+
+/// Stores size of crate directory and file keyed by path and modified time so
+/// repeated scan do not need to walk whole directory again
+#[derive(Serialize, Deserialize, Default)]
+pub struct SizeCache {
+    #[serde(default)]
+    entries: BTreeMap<String, SizeEntry>,
+    #[serde(skip)]
+    location: PathBuf,
+    #[serde(skip)]
+    enabled: bool,
+    #[serde(skip)]
+    modified: bool,
+    #[serde(skip)]
+    recomputed: BTreeSet<String>,
+}
+
+impl SizeCache {
+    /// Read size cache file. When cache is disabled old content is ignored so
+    /// every size is calculated again and saved as fresh cache
+    ///
+    /// # Errors
+    /// Return error if existing size cache file cannot be read
+    pub fn init(cache_file: &Path, enabled: bool) -> Result<Self> {
+        let mut size_cache = if enabled && cache_file.exists() {
+            let content =
+                fs::read_to_string(cache_file).context("failed to read size cache file content")?;
+            // corrupted cache is not fatal and is rebuilt from scratch
+            toml::from_str(&content).unwrap_or_default()
+        } else {
+            Self::default()
+        };
+        size_cache.location = cache_file.to_path_buf();
+        size_cache.enabled = enabled;
+        Ok(size_cache)
+    }
+
+    /// return size of path using cached value if modified time of path is
+    /// not changed after size was cached
+    pub(crate) fn get_size(&mut self, path: &Path) -> Result<u64> {
+        let Some((modified_secs, modified_nanos)) = modified_time(path) else {
+            return get_size(path);
+        };
+        let key = path.to_string_lossy().to_string();
+        if self.enabled {
+            if let Some(entry) = self.entries.get(&key) {
+                if entry.modified_secs == modified_secs && entry.modified_nanos == modified_nanos {
+                    return Ok(entry.size);
+                }
+            }
+        }
+        let size = get_size(path)?;
+        self.recomputed.insert(key.clone());
+        self.entries.insert(
+            key,
+            SizeEntry {
+                modified_secs,
+                modified_nanos,
+                size,
+            },
+        );
+        self.modified = true;
+        Ok(size)
+    }
+
+    /// create copy of size cache which can be used by other thread and merged
+    /// back afterwards
+    pub(crate) fn fork(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            location: self.location.clone(),
+            enabled: self.enabled,
+            modified: false,
+            recomputed: BTreeSet::new(),
+        }
+    }
+
+    /// merge size calculated by forked size cache. Only size recomputed by fork
+    /// is merged so entry updated by other fork is not replaced by stale copy
+    pub(crate) fn merge(&mut self, mut other: Self) {
+        for key in other.recomputed {
+            if let Some(entry) = other.entries.remove(&key) {
+                self.entries.insert(key.clone(), entry);
+                self.recomputed.insert(key);
+                self.modified = true;
+            }
+        }
+    }
+
+    /// save size cache in file if any value was changed. Entries of path which
+    /// no longer exists are removed before saving
+    ///
+    /// # Errors
+    /// Return error if size cache file cannot be written
+    pub fn save(&mut self) -> Result<()> {
+        let entry_count = self.entries.len();
+        self.entries.retain(|path, _| Path::new(path).exists());
+        if self.modified || entry_count != self.entries.len() {
+            let serialized =
+                toml::to_string_pretty(&self).context("Size cache cannot be converted to toml")?;
+            fs::write(&self.location, serialized).context("Failed to write size cache file")?;
+            self.modified = false;
+        }
+        Ok(())
+    }
+}
+
+// modified time of path as seconds and nanoseconds since unix epoch
+fn modified_time(path: &Path) -> Option<(u64, u32)> {
+    let modified = path.metadata().ok()?.modified().ok()?;
+    let duration = modified.duration_since(UNIX_EPOCH).ok()?;
+    Some((duration.as_secs(), duration.subsec_nanos()))
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::SizeCache;
+
+    #[test]
+    fn test_size_cache_reuse_and_invalidate() {
+        let dir =
+            std::env::temp_dir().join(format!("cargo_trim_size_cache_{}", std::process::id()));
+        let crate_dir = dir.join("crate");
+        fs::create_dir_all(&crate_dir).unwrap();
+        fs::write(crate_dir.join("lib.rs"), "1234").unwrap();
+        let cache_file = dir.join("size_cache.toml");
+
+        let mut size_cache = SizeCache::init(&cache_file, true).unwrap();
+        assert_eq!(size_cache.get_size(&crate_dir).unwrap(), 4);
+        size_cache.save().unwrap();
+
+        // modify file without touching directory so cached size is returned
+        fs::write(crate_dir.join("lib.rs"), "12345678").unwrap();
+        let mut size_cache = SizeCache::init(&cache_file, true).unwrap();
+        assert_eq!(size_cache.get_size(&crate_dir).unwrap(), 4);
+
+        // disabled cache always rescan
+        let mut size_cache = SizeCache::init(&cache_file, false).unwrap();
+        assert_eq!(size_cache.get_size(&crate_dir).unwrap(), 8);
+
+        // adding new file changes directory modified time
+        fs::write(crate_dir.join("main.rs"), "12").unwrap();
+        let mut size_cache = SizeCache::init(&cache_file, true).unwrap();
+        assert_eq!(size_cache.get_size(&crate_dir).unwrap(), 10);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_merge_recomputed_only() {
+        let dir = std::env::temp_dir().join(format!(
+            "cargo_trim_size_cache_merge_{}",
+            std::process::id()
+        ));
+        let first = dir.join("first");
+        let second = dir.join("second");
+        fs::create_dir_all(&first).unwrap();
+        fs::create_dir_all(&second).unwrap();
+        fs::write(first.join("lib.rs"), "1234").unwrap();
+        fs::write(second.join("lib.rs"), "12").unwrap();
+        let first_key = first.to_string_lossy().to_string();
+
+        let mut size_cache = SizeCache::init(&dir.join("size_cache.toml"), true).unwrap();
+        assert_eq!(size_cache.get_size(&first).unwrap(), 4);
+        let mut first_fork = size_cache.fork();
+        let mut second_fork = size_cache.fork();
+        fs::write(first.join("main.rs"), "12345678").unwrap();
+        assert_eq!(first_fork.get_size(&first).unwrap(), 12);
+        assert_eq!(second_fork.get_size(&second).unwrap(), 2);
+        size_cache.merge(first_fork);
+        // stale copy of first entry held by second fork must not be merged
+        size_cache.merge(second_fork);
+        assert_eq!(size_cache.entries[&first_key].size, 12);
+        assert_eq!(size_cache.get_size(&second).unwrap(), 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::event::{Event, emit};
+use crate::run_summary::record_category_total;
+
+/// Single clean action performed in current run
+#[derive(Serialize, Clone)]
+pub(crate) struct CleanAction {
+    category: String,
+    reason: String,
+    size: u64,
+    removed: usize,
+    dry_run: bool,
+}
+
+impl CleanAction {
+    pub(crate) fn category(&self) -> &String {
+        &self.category
+    }
+
+    pub(crate) fn reason(&self) -> &String {
+        &self.reason
+    }
+
+    pub(crate) fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub(crate) fn removed(&self) -> usize {
+        self.removed
+    }
+
+    pub(crate) fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+}
+
+/// Stores cumulative statistics of space freed by cargo trim over its lifetime
+#[derive(Serialize, Deserialize, Default)]
+pub(crate) struct StatsFile {
+    #[serde(default)]
+    total_freed: u64,
+    #[serde(default)]
+    total_removed: usize,
+    #[serde(default)]
+    category: BTreeMap<String, u64>,
+    #[serde(default)]
+    reason: BTreeMap<String, u64>,
+    #[serde(skip)]
+    location: PathBuf,
+    #[serde(skip)]
+    modified: bool,
+    #[serde(skip)]
+    session: Vec<CleanAction>,
+}
+
+impl StatsFile {
+    /// Read stats file or create default stats if file is not present
+    pub(crate) fn init(stats_file: &Path) -> Result<Self> {
+        let mut stats = if stats_file.exists() {
+            let content =
+                fs::read_to_string(stats_file).context("failed to read stats file content")?;
+            toml::from_str(&content).context("failed to convert string to Stats")?
+        } else {
+            Self::default()
+        };
+        stats.location = stats_file.to_path_buf();
+        Ok(stats)
+    }
+
+    /// total size freed till now
+    pub(crate) fn total_freed(&self) -> u64 {
+        self.total_freed
+    }
+
+    /// total number of crates or directory removed till now
+    pub(crate) fn total_removed(&self) -> usize {
+        self.total_removed
+    }
+
+    /// size freed per category
+    pub(crate) fn category(&self) -> &BTreeMap<String, u64> {
+        &self.category
+    }
+
+    /// size freed per reason
+    pub(crate) fn reason(&self) -> &BTreeMap<String, u64> {
+        &self.reason
+    }
+
+    /// clean actions performed in current run including dry run
+    pub(crate) fn session(&self) -> &Vec<CleanAction> {
+        &self.session
+    }
+
+    /// record freed size for category and reason. Dry run are only recorded in
+    /// current run session and never saved as lifetime stats
+    pub(crate) fn record(
+        &mut self,
+        category: &str,
+        reason: &str,
+        size: u64,
+        removed: usize,
+        dry_run: bool,
+    ) {
+        self.session.push(CleanAction {
+            category: category.to_string(),
+            reason: reason.to_string(),
+            size,
+            removed,
+            dry_run,
+        });
+        record_category_total(category, size, removed);
+        emit(&Event::Freed {
+            category,
+            reason,
+            size,
+            removed,
+            dry_run,
+        });
+        if dry_run || removed == 0 {
+            return;
+        }
+        self.total_freed += size;
+        self.total_removed += removed;
+        *self.category.entry(category.to_string()).or_default() += size;
+        *self.reason.entry(reason.to_string()).or_default() += size;
+        self.modified = true;
+    }
+
+    /// save stats in file if any value was recorded
+    pub(crate) fn save(&self) -> Result<()> {
+        if self.modified {
+            let serialized =
+                toml::to_string_pretty(&self).context("Stats cannot be converted to toml")?;
+            fs::write(&self.location, serialized).context("Failed to write stats file")?;
+        }
+        Ok(())
+    }
+}
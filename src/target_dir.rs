@@ -0,0 +1,268 @@
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use std::{env, fs};
+
+use anyhow::{Context, Result};
+
+use crate::utils::{get_size, last_modified};
+
+/// stores information of profile folder present inside target directory
+#[derive(Debug, Clone)]
+pub(crate) struct TargetProfile {
+    name: String,
+    path: PathBuf,
+    size: u64,
+    build_size: u64,
+    deps_size: u64,
+    incremental_size: u64,
+    modified: Option<SystemTime>,
+}
+
+impl TargetProfile {
+    pub(crate) fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(crate) fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// folder which contains build script binary and their `OUT_DIR` output
+    pub(crate) fn build_dir(&self) -> PathBuf {
+        self.path.join("build")
+    }
+
+    pub(crate) fn build_size(&self) -> u64 {
+        self.build_size
+    }
+
+    pub(crate) fn deps_size(&self) -> u64 {
+        self.deps_size
+    }
+
+    /// folder which contains incremental compilation cache of each crate
+    pub(crate) fn incremental_dir(&self) -> PathBuf {
+        self.path.join("incremental")
+    }
+
+    pub(crate) fn incremental_size(&self) -> u64 {
+        self.incremental_size
+    }
+
+    /// size of final artifacts and other content which is neither build
+    /// script output, dependency nor incremental compilation cache
+    pub(crate) fn other_size(&self) -> u64 {
+        self.size
+            .saturating_sub(self.build_size)
+            .saturating_sub(self.deps_size)
+            .saturating_sub(self.incremental_size)
+    }
+
+    /// last modified time of profile folder or its direct children
+    pub(crate) fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+
+    /// artifacts of units whose fingerprint is built by rustc other than
+    /// provided rustc hashes. Fingerprint, build script output and dependency
+    /// artifact of unit are named after unit as name-metadata with optional lib
+    /// prefix and extension. Unit without readable fingerprint is kept
+    pub(crate) fn artifacts_built_by_other_rustc(
+        &self,
+        rustc_hashes: &[u64],
+    ) -> Result<Vec<PathBuf>> {
+        let fingerprint_dir = self.path.join(".fingerprint");
+        if !fingerprint_dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut stale_units = HashSet::new();
+        for entry in fs::read_dir(&fingerprint_dir).context("failed to read fingerprint folder")? {
+            let unit_dir = entry?.path();
+            let is_stale = fingerprint_rustc_hash(&unit_dir)
+                .is_some_and(|rustc_hash| !rustc_hashes.contains(&rustc_hash));
+            if is_stale {
+                if let Some(unit) = unit_dir.file_name().and_then(OsStr::to_str) {
+                    stale_units.insert(unit.to_string());
+                }
+            }
+        }
+        let mut artifacts = Vec::new();
+        if stale_units.is_empty() {
+            return Ok(artifacts);
+        }
+        for folder in [".fingerprint", "build", "deps"] {
+            let Ok(entries) = fs::read_dir(self.path.join(folder)) else {
+                continue;
+            };
+            for entry in entries {
+                let path = entry?.path();
+                let Some(file_name) = path.file_name().and_then(OsStr::to_str) else {
+                    continue;
+                };
+                let stem = file_name.split('.').next().unwrap_or(file_name);
+                if stale_units.contains(stem)
+                    || stem
+                        .strip_prefix("lib")
+                        .is_some_and(|stem| stale_units.contains(stem))
+                {
+                    artifacts.push(path);
+                }
+            }
+        }
+        artifacts.sort();
+        Ok(artifacts)
+    }
+}
+
+/// hash of rustc version recorded by cargo in fingerprint json of unit
+/// folder present inside .fingerprint folder of profile
+pub(crate) fn fingerprint_rustc_hash(unit_dir: &Path) -> Option<u64> {
+    fs::read_dir(unit_dir)
+        .ok()?
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .find_map(|path| {
+            let content = fs::read_to_string(path).ok()?;
+            serde_json::from_str::<serde_json::Value>(&content)
+                .ok()?
+                .get("rustc")?
+                .as_u64()
+        })
+}
+
+/// stores information of target directory of project
+#[derive(Debug, Clone)]
+pub(crate) struct TargetDir {
+    path: PathBuf,
+    size: u64,
+    profiles: Vec<TargetProfile>,
+}
+
+impl TargetDir {
+    /// scan target directory of project. Return None if project do not have
+    /// target directory
+    pub(crate) fn new(project: &Path) -> Result<Option<Self>> {
+        let path = project.join(target_dir_name());
+        if !path.is_dir() {
+            return Ok(None);
+        }
+        Self::from_path(path).map(Some)
+    }
+
+    /// scan target directory present at path
+    pub(crate) fn from_path(path: PathBuf) -> Result<Self> {
+        let mut profiles = Vec::new();
+        for entry in fs::read_dir(&path).context("failed to read target directory")? {
+            let entry = entry?.path();
+            if !entry.is_dir() {
+                continue;
+            }
+            let name = entry
+                .file_name()
+                .context("failed to get file name of target profile")?
+                .to_string_lossy()
+                .to_string();
+            let mut children = vec![entry.clone()];
+            for child in fs::read_dir(&entry).context("failed to read target profile")? {
+                children.push(child?.path());
+            }
+            profiles.push(TargetProfile {
+                name,
+                size: get_size(&entry).context("failed to get size of target profile")?,
+                // profile such as doc do not contain build and deps folder
+                build_size: get_size(&entry.join("build")).unwrap_or(0),
+                deps_size: get_size(&entry.join("deps")).unwrap_or(0),
+                incremental_size: get_size(&entry.join("incremental")).unwrap_or(0),
+                modified: last_modified(&children),
+                path: entry,
+            });
+        }
+        profiles.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(Self {
+            size: get_size(&path).context("failed to get size of target directory")?,
+            path,
+            profiles,
+        })
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(crate) fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub(crate) fn profiles(&self) -> &Vec<TargetProfile> {
+        &self.profiles
+    }
+}
+
+/// return name of target directory respecting cargo target dir environment
+/// variable
+pub(crate) fn target_dir_name() -> String {
+    env::var("CARGO_BUILD_TARGET_DIR")
+        .unwrap_or_else(|_| env::var("CARGO_TARGET_DIR").unwrap_or_else(|_| String::from("target")))
+}
+
+/// list all target directory of projects. Target directory shared by
+/// multiple project is only listed once
+pub(crate) fn list_target_dirs(projects: &[PathBuf]) -> Result<Vec<TargetDir>> {
+    let mut target_dirs: Vec<TargetDir> = Vec::new();
+    for project in projects {
+        let path = project.join(target_dir_name());
+        if target_dirs.iter().any(|existing| existing.path == path) {
+            continue;
+        }
+        if let Some(target_dir) = TargetDir::new(project)? {
+            target_dirs.push(target_dir);
+        }
+    }
+    target_dirs.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(target_dirs)
+}
+
+/// find cargo target directories inside root. Directory is only treated as
+/// target directory when it contains CACHEDIR.TAG created by cargo or
+/// `.rustc_info.json` so unrelated folder named target is never reported.
+/// Symlink and unreadable directory are skipped
+pub(crate) fn discover_target_dirs(root: &Path) -> Result<Vec<PathBuf>> {
+    let mut target_dirs = Vec::new();
+    if !root.is_dir() {
+        anyhow::bail!("{} is not a directory", root.display());
+    }
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(directory) = pending.pop() {
+        if is_cargo_target_dir(&directory) {
+            target_dirs.push(directory);
+            continue;
+        }
+        let Ok(entries) = fs::read_dir(&directory) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            crate::utils::throttle_io();
+            if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                pending.push(entry.path());
+            }
+        }
+    }
+    target_dirs.sort();
+    Ok(target_dirs)
+}
+
+// check marker files which cargo creates at root of target directory
+fn is_cargo_target_dir(path: &Path) -> bool {
+    path.join(".rustc_info.json").is_file()
+        || fs::read_to_string(path.join("CACHEDIR.TAG"))
+            .is_ok_and(|content| content.contains("created by cargo"))
+}
@@ -0,0 +1,85 @@
+use std::fs::File;
+use std::io::IsTerminal;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static TERMINAL_WIDTH: OnceLock<Option<usize>> = OnceLock::new();
+static WIDE_MODE: AtomicBool = AtomicBool::new(false);
+
+/// show full crate name and path in table instead of truncating them to
+/// terminal width
+pub(crate) fn enable_wide_mode() {
+    WIDE_MODE.store(true, Ordering::Relaxed);
+}
+
+/// width available for table column after reserving width of other columns.
+/// Return none when text should not be truncated since wide mode is enabled or
+/// terminal width is unknown such as when output is piped
+pub(crate) fn available_width(reserved: usize) -> Option<usize> {
+    if WIDE_MODE.load(Ordering::Relaxed) {
+        return None;
+    }
+    terminal_width().map(|width| width.saturating_sub(reserved))
+}
+
+/// truncate middle of text longer than width so start and end of name or path
+/// which differ the most stay visible
+pub(crate) fn fit_width(text: &str, width: usize) -> String {
+    let length = text.chars().count();
+    if length <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let kept = width - 1;
+    let head = text.chars().take(kept - kept / 2);
+    let tail = text.chars().skip(length - kept / 2);
+    head.chain(std::iter::once('\u{2026}'))
+        .chain(tail)
+        .collect()
+}
+
+// width of terminal read from COLUMNS or from stty when stdout is terminal
+fn terminal_width() -> Option<usize> {
+    *TERMINAL_WIDTH.get_or_init(|| {
+        if let Some(columns) = std::env::var("COLUMNS")
+            .ok()
+            .and_then(|columns| columns.parse().ok())
+            .filter(|&columns| columns > 0)
+        {
+            return Some(columns);
+        }
+        if cfg!(target_os = "windows") || !std::io::stdout().is_terminal() {
+            return None;
+        }
+        // stty reports size of terminal connected to its stdin
+        let tty = File::open("/dev/tty").ok()?;
+        let output = Command::new("stty")
+            .arg("size")
+            .stdin(Stdio::from(tty))
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        String::from_utf8_lossy(&output.stdout)
+            .split_whitespace()
+            .nth(1)?
+            .parse()
+            .ok()
+            .filter(|&columns| columns > 0)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::fit_width;
+
+    #[test]
+    fn test_fit_width() {
+        assert_eq!(fit_width("serde-1.0.152", 20), "serde-1.0.152");
+        assert_eq!(fit_width("serde_derive-1.0.152", 11), "serde…0.152");
+        assert_eq!(fit_width("abc", 1), "…");
+        assert_eq!(fit_width("abc", 0), "");
+    }
+}
@@ -0,0 +1,241 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+use url::Url;
+
+use crate::utils::format_timestamp;
+
+static TRASH_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// enable trash mode so deleted file and folder are moved to trash directory
+/// instead of being removed permanently
+pub(crate) fn enable_trash_mode() -> Result<()> {
+    let trash_dir = default_trash_dir().context("Cannot get trash directory location")?;
+    let _ = TRASH_DIR.set(trash_dir);
+    Ok(())
+}
+
+/// return trash directory if trash mode is enabled
+pub(crate) fn trash_dir() -> Option<&'static PathBuf> {
+    TRASH_DIR.get()
+}
+
+/// check if trash directory exists or can be created
+pub(crate) fn trash_available() -> bool {
+    default_trash_dir()
+        .is_some_and(|trash_dir| trash_dir.exists() || trash_dir.parent().is_some_and(Path::exists))
+}
+
+// system trash directory following freedesktop trash specification in linux
+// and user trash in macos. Other platform uses cargo trim holding directory
+fn default_trash_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "linux") {
+        Some(dirs_next::data_dir()?.join("Trash"))
+    } else if cfg!(target_os = "macos") {
+        Some(dirs_next::home_dir()?.join(".Trash"))
+    } else {
+        Some(
+            dirs_next::data_local_dir()?
+                .join("cargo-trim")
+                .join("trash"),
+        )
+    }
+}
+
+/// move file or folder to trash directory. Symbolic link is trashed as link
+/// without touching its target. In linux trash info file is also written after
+/// file is moved so file can be restored from file manager
+pub(crate) fn move_to_trash(path: &Path, trash_dir: &Path) -> Result<PathBuf> {
+    let path = absolute_path(path)?;
+    let file_name = path
+        .file_name()
+        .context("Failed to get file name of trashed file")?
+        .to_string_lossy()
+        .to_string();
+    let is_freedesktop = cfg!(target_os = "linux");
+    let files_dir = if is_freedesktop {
+        trash_dir.join("files")
+    } else {
+        trash_dir.to_path_buf()
+    };
+    let info_dir = trash_dir.join("info");
+    fs::create_dir_all(&files_dir).context("Failed to create trash directory")?;
+    // find unique name so file with same name trashed before is not overwritten
+    let mut trash_name = file_name.clone();
+    let mut counter = 1;
+    while files_dir.join(&trash_name).symlink_metadata().is_ok()
+        || (is_freedesktop && info_dir.join(format!("{trash_name}.trashinfo")).exists())
+    {
+        trash_name = format!("{file_name}.{counter}");
+        counter += 1;
+    }
+    let destination = files_dir.join(&trash_name);
+    // rename fails when trash is present in other file system so copy content
+    // before removing original
+    if fs::rename(&path, &destination).is_err() {
+        if let Err(err) = copy_recursively(&path, &destination) {
+            // partially copied content is removed so trash do not contain
+            // incomplete copy of file which is still present
+            let _ = remove_entry(&destination);
+            return Err(err.context("Failed to copy file to trash"));
+        }
+        remove_entry(&path)?;
+    }
+    if is_freedesktop {
+        fs::create_dir_all(&info_dir).context("Failed to create trash info directory")?;
+        let deletion_date = format_timestamp(SystemTime::now());
+        let info = format!(
+            "[Trash Info]\nPath={}\nDeletionDate={}\n",
+            encode_path(&path)?,
+            deletion_date.trim_end_matches('Z')
+        );
+        fs::write(info_dir.join(format!("{trash_name}.trashinfo")), info)
+            .context("Failed to write trash info file")?;
+    }
+    Ok(destination)
+}
+
+// absolute path of file. Only parent is canonicalized so symbolic link itself
+// is trashed instead of its target
+fn absolute_path(path: &Path) -> Result<PathBuf> {
+    let file_name = path
+        .file_name()
+        .context("Failed to get file name of trashed file")?;
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let parent = parent
+        .canonicalize()
+        .context("Failed to get absolute path of trashed file")?;
+    Ok(parent.join(file_name))
+}
+
+// percent encode path for trash info file as required by freedesktop trash
+// specification
+fn encode_path(path: &Path) -> Result<String> {
+    let url = Url::from_file_path(path)
+        .map_err(|()| anyhow::anyhow!("Failed to encode path {}", path.display()))?;
+    Ok(url.path().to_string())
+}
+
+// remove file, symbolic link or directory without following symbolic link
+fn remove_entry(path: &Path) -> Result<()> {
+    if path.symlink_metadata()?.is_dir() {
+        fs::remove_dir_all(path)?;
+    } else {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+// copy file or directory recursively to destination. Symbolic link is copied
+// as link so link cycle is never followed
+fn copy_recursively(source: &Path, destination: &Path) -> Result<()> {
+    let file_type = source.symlink_metadata()?.file_type();
+    if file_type.is_symlink() {
+        copy_symlink(source, destination)?;
+    } else if file_type.is_dir() {
+        fs::create_dir_all(destination)?;
+        for entry in fs::read_dir(source)? {
+            let entry_path = entry?.path();
+            let file_name = entry_path
+                .file_name()
+                .context("Failed to get file name of entry")?;
+            copy_recursively(&entry_path, &destination.join(file_name))?;
+        }
+    } else {
+        fs::copy(source, destination)?;
+    }
+    Ok(())
+}
+
+// create symbolic link at destination pointing to same target as source
+#[cfg(unix)]
+fn copy_symlink(source: &Path, destination: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(source)?, destination)?;
+    Ok(())
+}
+
+// create symbolic link at destination pointing to same target as source
+#[cfg(windows)]
+fn copy_symlink(source: &Path, destination: &Path) -> Result<()> {
+    let target = fs::read_link(source)?;
+    if fs::metadata(source).is_ok_and(|metadata| metadata.is_dir()) {
+        std::os::windows::fs::symlink_dir(target, destination)?;
+    } else {
+        std::os::windows::fs::symlink_file(target, destination)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::{copy_recursively, move_to_trash};
+
+    #[test]
+    fn test_move_to_trash() {
+        let dir = std::env::temp_dir().join(format!("cargo_trim_trash_{}", std::process::id()));
+        let trash_dir = dir.join("trash");
+        let crate_dir = dir.join("crate-0.1.0");
+        fs::create_dir_all(&crate_dir).unwrap();
+        fs::write(crate_dir.join("lib.rs"), "fn main() {}").unwrap();
+
+        let first = move_to_trash(&crate_dir, &trash_dir).unwrap();
+        assert!(!crate_dir.exists());
+        assert!(first.join("lib.rs").exists());
+
+        // trashing file with same name again keeps previous trashed file
+        fs::create_dir_all(&crate_dir).unwrap();
+        let second = move_to_trash(&crate_dir, &trash_dir).unwrap();
+        assert_ne!(first, second);
+        assert!(first.exists() && second.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_trash_symlink() {
+        let dir =
+            std::env::temp_dir().join(format!("cargo_trim_trash_link_{}", std::process::id()));
+        let trash_dir = dir.join("trash");
+        let target = dir.join("target dir");
+        fs::create_dir_all(&target).unwrap();
+        fs::write(target.join("lib.rs"), "fn main() {}").unwrap();
+        let link = dir.join("link dir");
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        let trashed = move_to_trash(&link, &trash_dir).unwrap();
+        assert!(link.symlink_metadata().is_err());
+        assert!(target.join("lib.rs").exists());
+        assert!(trashed.symlink_metadata().unwrap().file_type().is_symlink());
+        if cfg!(target_os = "linux") {
+            let info =
+                fs::read_to_string(trash_dir.join("info").join("link dir.trashinfo")).unwrap();
+            assert!(info.contains("/link%20dir\n"));
+        }
+
+        // link cycle is copied as link instead of being followed
+        let cycle = dir.join("cycle");
+        fs::create_dir_all(&cycle).unwrap();
+        std::os::unix::fs::symlink(&cycle, cycle.join("self")).unwrap();
+        let copied = dir.join("copied");
+        copy_recursively(&cycle, &copied).unwrap();
+        assert!(
+            copied
+                .join("self")
+                .symlink_metadata()
+                .unwrap()
+                .file_type()
+                .is_symlink()
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,139 @@
+use anyhow::Result;
+
+use crate::config_file::ConfigFile;
+use crate::crate_detail::{CrateDetail, CrateMetaData};
+use crate::dir_path::DirPath;
+use crate::git_dir::GitDir;
+use crate::list_crate::CrateList;
+use crate::package_lock::{set_lock_cargo_home, unlock_package_cache};
+use crate::protect::enable_protection;
+use crate::registry_dir::RegistryDir;
+use crate::restore_manifest::RestoreManifest;
+
+/// Retention policy which select registry and git crates removed by [`trim`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Policy {
+    /// Remove crates whose newer version is also present
+    Old,
+    /// Remove crates which are not used by lock file of any registered project
+    Orphan,
+    /// Remove crates which are both old and orphan
+    OldOrphan,
+    /// Remove every registry and git crate
+    All,
+}
+
+/// Number of crates removed by [`trim`] along with size freed by them
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Trimmed {
+    removed: usize,
+    size: u64,
+}
+
+impl Trimmed {
+    /// number of registry and git crates removed
+    #[must_use]
+    pub fn removed(&self) -> usize {
+        self.removed
+    }
+
+    /// size freed by removed crates in bytes
+    #[must_use]
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+/// Remove registry and git crates selected by policy from cargo home of
+/// `dir_path`. Package cache lock of cargo home is held while crates are
+/// removed, crates protected by config file are skipped and removed registry
+/// crates are recorded in restore manifest same as `cargo trim` run. Nothing is
+/// removed in dry run but size which would be freed is still returned
+///
+/// ```no_run
+/// use cargo_trim::{
+///     ConfigFile, CrateDetail, CrateList, DirPath, Policy, SizeCache, convert_pretty, trim,
+/// };
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let dir_path = DirPath::new(None)?;
+/// let config_file = ConfigFile::init(dir_path.config_file())?;
+/// let size_cache = SizeCache::init(dir_path.size_cache_file(), true)?;
+/// let mut crate_detail = CrateDetail::new(dir_path.index_dir(), dir_path.db_dir(), size_cache)?;
+/// let crate_list = CrateList::create_list(&dir_path, &config_file, &mut crate_detail)?;
+/// let trimmed = trim(
+///     &dir_path,
+///     &config_file,
+///     &crate_detail,
+///     &crate_list,
+///     Policy::OldOrphan,
+///     true,
+/// )?;
+/// println!(
+///     "{} crates would be removed freeing {}",
+///     trimmed.removed(),
+///     convert_pretty(trimmed.size())
+/// );
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+/// Return error if package cache lock cannot be acquired or restore manifest
+/// cannot be written
+pub fn trim(
+    dir_path: &DirPath,
+    config_file: &ConfigFile,
+    crate_detail: &CrateDetail,
+    crate_list: &CrateList,
+    policy: Policy,
+    dry_run: bool,
+) -> Result<Trimmed> {
+    let (registry_crates, git_crates) = policy_crates(crate_list, policy);
+    set_lock_cargo_home(dir_path.cargo_home());
+    enable_protection(&config_file.protect(), crate_detail, dir_path.index_dir());
+    let mut registry_crates_location = RegistryDir::new(
+        dir_path.index_dir(),
+        crate_list.installed_registry(),
+        None,
+        RestoreManifest::new(dir_path.restore_file()),
+    )?;
+    let registry_result =
+        registry_crates_location.remove_crate_list(crate_detail, &registry_crates, dry_run);
+    let trimmed = registry_result.map(|(registry_size, registry_removed)| {
+        let (git_size, git_removed) = GitDir::new(None).remove_crate_list(&git_crates, dry_run);
+        Trimmed {
+            removed: registry_removed + git_removed,
+            size: registry_size + git_size,
+        }
+    });
+    // lock is released so cargo can be run by caller after trim
+    unlock_package_cache();
+    trimmed
+}
+
+// registry and git crates selected by policy
+fn policy_crates(
+    crate_list: &CrateList,
+    policy: Policy,
+) -> (Vec<CrateMetaData>, Vec<CrateMetaData>) {
+    match policy {
+        Policy::Old => (
+            crate_list.old_registry().clone(),
+            crate_list.old_git().clone(),
+        ),
+        Policy::Orphan => (
+            crate_list.orphan_registry().clone(),
+            crate_list.orphan_git().clone(),
+        ),
+        Policy::OldOrphan => (
+            crate_list.list_old_orphan_registry(),
+            crate_list.list_old_orphan_git(),
+        ),
+        Policy::All => (
+            crate_list.installed_registry().clone(),
+            crate_list.installed_git().clone(),
+        ),
+    }
+}
@@ -1,13 +1,100 @@
-use std::collections::HashSet;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
+use clap::ValueEnum;
 use owo_colors::OwoColorize;
 use semver::Version;
 
 use crate::crate_detail::CrateMetaData;
+use crate::deprecated_crate::is_deprecated;
+use crate::dry_run_summary::record_dry_run_path;
+use crate::event::{Event, emit, ndjson_enabled};
+use crate::freed_space::measure_before_removal;
+use crate::log_file::log_enabled;
+use crate::package_lock::lock_for_removal;
+use crate::progress::{deleted_bytes, deletion_in_progress, scanned_directory};
+use crate::protect::{contains_protected_path, is_protected_path};
+use crate::removal_failure::record_removal_failure;
+use crate::terminal::{available_width, fit_width};
+use crate::trash::{move_to_trash, trash_dir};
+
+// number of io operation performed between sleep in nice mode
+const NICE_BATCH_SIZE: usize = 256;
+// duration of sleep between batches in nice mode
+const NICE_SLEEP_DURATION: Duration = Duration::from_millis(20);
+// minimum width of crate name column of crate table
+const MIN_NAME_WIDTH: usize = 44;
+// width below which column is not shrunk to fit terminal
+const MIN_TRUNCATED_WIDTH: usize = 20;
+
+static NICE_MODE: AtomicBool = AtomicBool::new(false);
+static NICE_IO_COUNT: AtomicUsize = AtomicUsize::new(0);
+static VERBOSITY: AtomicU8 = AtomicU8::new(0);
+static DISK_USAGE_MODE: AtomicBool = AtomicBool::new(false);
+static CRATE_SORT: OnceLock<(Option<CrateSort>, bool)> = OnceLock::new();
+static SIZE_UNIT: OnceLock<(SizeUnit, bool)> = OnceLock::new();
+// state of interactive mode. Mode is disabled until enabled and changes to all
+// or quit based on answer of user
+static INTERACTIVE_MODE: AtomicU8 = AtomicU8::new(INTERACTIVE_DISABLED);
+const INTERACTIVE_DISABLED: u8 = 0;
+const INTERACTIVE_ASK: u8 = 1;
+const INTERACTIVE_ALL: u8 = 2;
+const INTERACTIVE_QUIT: u8 = 3;
+
+/// set verbosity level of dry run output. Level 0 only show total of each
+/// category, level 1 also list crates and level 2 list every path with size
+pub(crate) fn set_verbosity(level: u8) {
+    VERBOSITY.store(level, Ordering::Relaxed);
+}
+
+/// return verbosity level of dry run output
+pub(crate) fn verbosity() -> u8 {
+    VERBOSITY.load(Ordering::Relaxed)
+}
+
+/// enable disk usage mode which report allocated disk usage along with
+/// apparent size in query output
+pub(crate) fn enable_disk_usage_mode() {
+    DISK_USAGE_MODE.store(true, Ordering::Relaxed);
+}
+
+/// check if disk usage mode is enabled
+pub(crate) fn disk_usage_enabled() -> bool {
+    DISK_USAGE_MODE.load(Ordering::Relaxed)
+}
+
+/// enable nice mode which lower priority of process and throttle scan and
+/// deletion io
+pub(crate) fn enable_nice_mode() {
+    NICE_MODE.store(true, Ordering::Relaxed);
+    let pid = std::process::id().to_string();
+    // priority change is best effort so failure of command is ignored
+    let _ = std::process::Command::new("renice")
+        .args(["-n", "19", "-p", &pid])
+        .output();
+    if cfg!(target_os = "linux") {
+        let _ = std::process::Command::new("ionice")
+            .args(["-c", "3", "-p", &pid])
+            .output();
+    }
+}
+
+/// sleep after each batch of io operation if nice mode is enabled
+pub(crate) fn throttle_io() {
+    if NICE_MODE.load(Ordering::Relaxed)
+        && (NICE_IO_COUNT.fetch_add(1, Ordering::Relaxed) + 1).is_multiple_of(NICE_BATCH_SIZE)
+    {
+        std::thread::sleep(NICE_SLEEP_DURATION);
+    }
+}
 
 /// split name and semver version part from crates full name
 pub(crate) fn split_name_version(full_name: &str) -> Result<(String, Version)> {
@@ -24,31 +111,157 @@ pub(crate) fn split_name_version(full_name: &str) -> Result<(String, Version)> {
     }
     let (clear_name_vec, version_vec) = version_split.split_at(version_start_position);
     let clear_name = clear_name_vec.join("-");
-    let version = Version::from_str(version_vec.join("-").as_str())
+    if let Ok(version) = Version::from_str(version_vec.join("-").as_str()) {
+        return Ok((clear_name, version));
+    }
+    // fallback to numeric suffix heuristic for name which do not contain valid
+    // semver version such as pre 2018 artifact
+    let (name, version) = name
+        .rsplit_once('-')
         .context("Failed to parse semver version from splitted parts")?;
-    Ok((clear_name, version))
+    let version =
+        coerce_version(version).context("Failed to parse semver version from splitted parts")?;
+    Ok((name.to_string(), version))
 }
 
-/// delete folder with folder path provided
-pub(crate) fn delete_folder(path: &Path, dry_run: bool) -> Result<()> {
+// coerce numeric version such as v1, 1.2 or 1.2.3.4 to semver version
+fn coerce_version(version: &str) -> Option<Version> {
+    let version = version.strip_prefix('v').unwrap_or(version);
+    let mut parts = version
+        .split('.')
+        .map(|part| part.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    if parts.is_empty() {
+        return None;
+    }
+    parts.resize(parts.len().max(3), 0);
+    Some(Version::new(parts[0], parts[1], parts[2]))
+}
+
+/// delete folder with folder path provided. Folder is moved to trash instead
+/// when trash mode is enabled. In dry run path along with its size is only
+/// shown in most verbose level. Path which cannot be removed is recorded along
+/// with reason and false is returned so remaining paths are still removed
+///
+/// # Errors
+/// Return error if package cache lock is held by running cargo
+pub(crate) fn delete_folder(path: &Path, dry_run: bool) -> Result<bool> {
+    if !dry_run {
+        lock_for_removal(path)?;
+    }
+    if contains_protected_path(path) {
+        return delete_unprotected(path, dry_run);
+    }
     if path.exists() {
-        if path.is_file() {
-            if dry_run {
-                println!("{} {} {path:?}", "Dry run:".yellow(), "Removed".red());
-            } else {
-                fs::remove_file(path)?;
+        if dry_run {
+            let size = get_size(path).unwrap_or(0);
+            record_dry_run_path(path, size);
+            if verbosity() >= 2 {
+                let action = if trash_dir().is_some() {
+                    "Trashed"
+                } else {
+                    "Removed"
+                };
+                human_println!(
+                    "{} {} {} ({})",
+                    "Dry run:".yellow(),
+                    action.red(),
+                    path.display(),
+                    convert_pretty(size).trim()
+                );
             }
-        } else if path.is_dir() {
-            if dry_run {
-                println!("{} {} {path:?}", "Dry run:".yellow(), "Removed".red());
+            emit(&Event::Deleted {
+                path,
+                size,
+                dry_run,
+            });
+        } else {
+            measure_before_removal(path);
+            // size is only calculated for event stream and log as removal do not need it
+            let size = if ndjson_enabled() || log_enabled() {
+                get_size(path).unwrap_or(0)
             } else {
-                fs::remove_dir_all(path)?;
+                0
+            };
+            if let Err(err) = remove_path(path) {
+                record_removal_failure(path, &err);
+                return Ok(false);
             }
+            emit(&Event::Deleted {
+                path,
+                size,
+                dry_run,
+            });
+        }
+    }
+    Ok(true)
+}
+
+// remove file or directory or move it to trash when trash mode is enabled
+fn remove_path(path: &Path) -> Result<()> {
+    if let Some(trash_dir) = trash_dir() {
+        throttle_io();
+        if deletion_in_progress() {
+            deleted_bytes(get_size(path).unwrap_or(0));
+        }
+        move_to_trash(path, trash_dir)?;
+    } else if path.is_file() {
+        throttle_io();
+        deleted_file(path);
+        fs::remove_file(path)?;
+    } else if path.is_dir() {
+        // removing file one by one is slower so it is only done when io is
+        // throttled or deleted bytes are shown in progress
+        if NICE_MODE.load(Ordering::Relaxed) || deletion_in_progress() {
+            remove_dir_by_entry(path)?;
+        } else {
+            fs::remove_dir_all(path)?;
+        }
+    }
+    Ok(())
+}
+
+// delete content of folder which contains path of protected crate while
+// keeping protected path. Return false if any content cannot be removed
+fn delete_unprotected(path: &Path, dry_run: bool) -> Result<bool> {
+    if is_protected_path(path) {
+        emit(&Event::Skipped {
+            name: path.display().to_string(),
+            reason: "protected",
+        });
+        return Ok(true);
+    }
+    let mut is_removed = true;
+    for entry in fs::read_dir(path)? {
+        is_removed = delete_folder(&entry?.path(), dry_run)? && is_removed;
+    }
+    Ok(is_removed)
+}
+
+// remove directory recursively while throttling io and recording progress
+// between files
+fn remove_dir_by_entry(path: &Path) -> Result<()> {
+    for entry in fs::read_dir(path)? {
+        let entry_path = entry?.path();
+        throttle_io();
+        if entry_path.is_dir() && !entry_path.is_symlink() {
+            remove_dir_by_entry(&entry_path)?;
+        } else {
+            deleted_file(&entry_path);
+            fs::remove_file(&entry_path)?;
         }
     }
+    fs::remove_dir(path)?;
     Ok(())
 }
 
+// record size of file which is about to be deleted in progress
+fn deleted_file(path: &Path) {
+    if deletion_in_progress() {
+        deleted_bytes(path.symlink_metadata().map_or(0, |metadata| metadata.len()));
+    }
+}
+
 /// delete index .cache file
 pub(crate) fn delete_index_cache(index_dir: &Path, dry_run: bool) -> Result<()> {
     for entry in fs::read_dir(index_dir)? {
@@ -59,20 +272,92 @@ pub(crate) fn delete_index_cache(index_dir: &Path, dry_run: bool) -> Result<()>
                 .file_name()
                 .context("Failed to obtain index .cache file name")?;
             if folder_name == ".cache" {
-                delete_folder(&folder, dry_run)?;
+                anyhow::ensure!(
+                    delete_folder(&folder, dry_run)?,
+                    "Failed to remove {}",
+                    folder.display()
+                );
             }
         }
     }
     Ok(())
 }
 
+/// ask user for confirmation and return true if user answer yes
+pub(crate) fn ask_confirmation(question: &str) -> Result<bool> {
+    let mut input = String::new();
+    human_print!("{question} (y/N) ");
+    std::io::stdout()
+        .flush()
+        .context("failed to flush output stream")?;
+    std::io::stdin()
+        .read_line(&mut input)
+        .context("error: unable to read user input")?;
+    let input = input.trim().to_ascii_lowercase();
+    Ok(["y", "yes"].contains(&input.as_str()))
+}
+
+/// enable interactive mode where confirmation is asked before removing each
+/// crate
+pub(crate) fn enable_interactive_mode() {
+    INTERACTIVE_MODE.store(INTERACTIVE_ASK, Ordering::Relaxed);
+}
+
+/// ask confirmation before removing crate in interactive mode similar to rm -i.
+/// Answer a removes current and all remaining crates and q skips current and
+/// all remaining crates. Return true if crate should be removed
+pub(crate) fn confirm_removal(crate_metadata: &CrateMetaData) -> bool {
+    match INTERACTIVE_MODE.load(Ordering::Relaxed) {
+        INTERACTIVE_ASK => {}
+        INTERACTIVE_QUIT => return false,
+        _ => return true,
+    }
+    human_println!(
+        "{} ({})",
+        crate_metadata.full_name().bold(),
+        convert_pretty(crate_metadata.size()).trim()
+    );
+    for path in crate_metadata.path() {
+        human_println!("   {}", path.display());
+    }
+    loop {
+        human_print!("Remove crate? [y]es/[n]o/[a]ll/[q]uit ");
+        let _ = std::io::stdout().flush();
+        let mut input = String::new();
+        // closed or unreadable stdin is treated as quit so nothing is removed
+        // without confirmation
+        if std::io::stdin().read_line(&mut input).unwrap_or(0) == 0 {
+            INTERACTIVE_MODE.store(INTERACTIVE_QUIT, Ordering::Relaxed);
+            return false;
+        }
+        match input.trim().to_ascii_lowercase().as_str() {
+            "y" | "yes" => return true,
+            "n" | "no" => return false,
+            "a" | "all" => {
+                INTERACTIVE_MODE.store(INTERACTIVE_ALL, Ordering::Relaxed);
+                return true;
+            }
+            "q" | "quit" => {
+                INTERACTIVE_MODE.store(INTERACTIVE_QUIT, Ordering::Relaxed);
+                return false;
+            }
+            _ => {}
+        }
+    }
+}
+
 ///  get size of directory
-pub(crate) fn get_size(path: &Path) -> Result<u64> {
+///
+/// # Errors
+/// Return error if directory or its content cannot be read
+pub fn get_size(path: &Path) -> Result<u64> {
     let mut total_size = 0;
     if path.is_dir() {
         for entry in fs::read_dir(path)? {
             let entry_path = entry?.path();
+            throttle_io();
             if entry_path.is_dir() {
+                scanned_directory();
                 total_size += get_size(&entry_path)?;
             } else {
                 total_size += entry_path.metadata()?.len();
@@ -84,33 +369,194 @@ pub(crate) fn get_size(path: &Path) -> Result<u64> {
     Ok(total_size)
 }
 
+/// get allocated disk usage of directory similar to du. Unlike `get_size`
+/// sparse files and partially used filesystem blocks are counted by space
+/// they actually take on disk
+pub(crate) fn get_disk_usage(path: &Path) -> Result<u64> {
+    let mut total_usage = 0;
+    if path.is_dir() {
+        total_usage += allocated_size(&path.metadata()?);
+        for entry in fs::read_dir(path)? {
+            let entry_path = entry?.path();
+            throttle_io();
+            if entry_path.is_dir() {
+                total_usage += get_disk_usage(&entry_path)?;
+            } else {
+                total_usage += allocated_size(&entry_path.metadata()?);
+            }
+        }
+    } else {
+        total_usage += allocated_size(&path.metadata()?);
+    }
+    Ok(total_usage)
+}
+
+// size allocated for file on disk. st_blocks is always counted in 512 byte
+// unit on unix
+#[cfg(unix)]
+fn allocated_size(metadata: &fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.blocks() * 512
+}
+
+// size allocated for file on disk. Size is rounded up to default NTFS cluster
+// size since cluster size cannot be read from metadata
+#[cfg(not(unix))]
+fn allocated_size(metadata: &fs::Metadata) -> u64 {
+    const CLUSTER_SIZE: u64 = 4096;
+    metadata.len().div_ceil(CLUSTER_SIZE) * CLUSTER_SIZE
+}
+
+/// device and inode of file used to detect hardlinks of same file
+#[cfg(unix)]
+#[allow(clippy::unnecessary_wraps)]
+pub(crate) fn file_identity(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+/// device and inode of file used to detect hardlinks of same file. Identity of
+/// file cannot be read from metadata on other platform
+#[cfg(not(unix))]
+pub(crate) fn file_identity(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// create new directory inside temporary directory which can only be accessed
+/// by current user. Directory is never reused so file placed in it by other
+/// user cannot be picked up
+pub(crate) fn create_private_temp_dir(prefix: &str) -> Result<PathBuf> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.subsec_nanos());
+    for _ in 0..100 {
+        let path = std::env::temp_dir().join(format!(
+            "{prefix}-{}-{nanos}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        match private_dir_builder().create(&path) {
+            Ok(()) => return Ok(path),
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {}
+            Err(err) => return Err(err).context("Failed to create temporary directory"),
+        }
+    }
+    anyhow::bail!("Failed to create unique temporary directory")
+}
+
+// builder of directory which is only accessible by owner
+#[cfg(unix)]
+fn private_dir_builder() -> fs::DirBuilder {
+    use std::os::unix::fs::DirBuilderExt;
+    let mut builder = fs::DirBuilder::new();
+    builder.mode(0o700);
+    builder
+}
+
+// builder of directory which is only accessible by owner. Temporary directory
+// of user is already private on other platform
+#[cfg(not(unix))]
+fn private_dir_builder() -> fs::DirBuilder {
+    fs::DirBuilder::new()
+}
+
+/// list all files and sub directories present inside directory recursively
+/// along with their size. Size of directory is sum of its entries so directory
+/// tree is only walked once
+pub(crate) fn list_entry_sizes(path: &Path) -> Result<Vec<(PathBuf, u64)>> {
+    let mut entries = Vec::new();
+    collect_entry_sizes(path, &mut entries)?;
+    Ok(entries)
+}
+
+// push entries of directory along with their size and return total size of
+// directory. Directory is pushed before its entries and its size is filled
+// once all entries are walked
+fn collect_entry_sizes(path: &Path, entries: &mut Vec<(PathBuf, u64)>) -> Result<u64> {
+    let mut total_size = 0;
+    for entry in fs::read_dir(path)? {
+        let entry_path = entry?.path();
+        throttle_io();
+        if entry_path.is_dir() {
+            scanned_directory();
+            let index = entries.len();
+            entries.push((entry_path.clone(), 0));
+            let size = collect_entry_sizes(&entry_path, entries)?;
+            entries[index].1 = size;
+            total_size += size;
+        } else {
+            let size = entry_path.metadata()?.len();
+            entries.push((entry_path, size));
+            total_size += size;
+        }
+    }
+    Ok(total_size)
+}
+
+/// unit used to show size
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum SizeUnit {
+    Auto,
+    B,
+    Kb,
+    Mb,
+    Gb,
+}
+
+/// set unit used to show size. Binary unit use base 1024 such as KiB and MiB
+/// instead of base 1000
+pub(crate) fn set_size_unit(unit: SizeUnit, binary: bool) {
+    let _ = SIZE_UNIT.set((unit, binary));
+}
+
 /// Convert size to pretty number
+#[must_use]
+pub fn convert_pretty(num: u64) -> String {
+    let (unit, binary) = SIZE_UNIT.get().copied().unwrap_or((SizeUnit::Auto, false));
+    format_size(num, unit, binary)
+}
+
+// format size in unit. Auto unit use largest unit in which size is at least 1
 #[allow(
     clippy::cast_precision_loss,
     clippy::cast_possible_truncation,
     clippy::cast_sign_loss
 )]
-pub(crate) fn convert_pretty(num: u64) -> String {
-    if num == 0 {
-        return "  0.000 B".to_string();
-    }
-    let num = num as f64;
-    let units = ["B", "kB", "MB", "GB", "TB"];
-    let factor = (num.log10() / 3_f64).floor();
-    let power_factor = if factor >= units.len() as f64 {
-        (units.len() - 1) as f64
+fn format_size(num: u64, unit: SizeUnit, binary: bool) -> String {
+    let (base, units) = if binary {
+        (1024_u64, ["B", "KiB", "MiB", "GiB", "TiB"])
     } else {
-        factor
+        (1000_u64, ["B", "kB", "MB", "GB", "TB"])
+    };
+    let power = match unit {
+        SizeUnit::Auto => (1..units.len())
+            .take_while(|&power| base.pow(power as u32) <= num)
+            .last()
+            .unwrap_or(0),
+        SizeUnit::B => 0,
+        SizeUnit::Kb => 1,
+        SizeUnit::Mb => 2,
+        SizeUnit::Gb => 3,
     };
-    let pretty_bytes = format!("{:7.3}", num / 1000_f64.powf(power_factor));
-    let unit = units[power_factor as usize];
-    format!("{pretty_bytes} {unit}")
+    let pretty_bytes = format!("{:7.3}", num as f64 / base.pow(power as u32) as f64);
+    format!("{pretty_bytes} {}", units[power])
+}
+
+/// format age in largest whole unit such as 12d, 3h or 5m
+pub(crate) fn format_age(seconds: u64) -> String {
+    match seconds {
+        0..60 => format!("{seconds}s"),
+        60..3600 => format!("{}m", seconds / 60),
+        3600..86400 => format!("{}h", seconds / 3600),
+        _ => format!("{}d", seconds / 86400),
+    }
 }
 
 /// show title
 pub(crate) fn show_title(title: &str, first_width: usize, second_width: usize, dash_len: usize) {
     print_dash(dash_len);
-    println!(
+    human_println!(
         "|{:^first_width$}|{:^second_width$}|",
         title.bold(),
         "SIZE".bold(),
@@ -127,14 +573,14 @@ pub(crate) fn show_total_count(
     dash_len: usize,
 ) {
     if data.is_empty() {
-        println!(
+        human_println!(
             "|{:^first_width$}|{:^second_width$}|",
             "NONE".red(),
             convert_pretty(0).red(),
         );
     }
     print_dash(dash_len);
-    println!(
+    human_println!(
         "|{:^first_width$}|{:^second_width$}|",
         format!("Total no of crates:- {}", data.len()).blue(),
         convert_pretty(size).blue(),
@@ -144,7 +590,7 @@ pub(crate) fn show_total_count(
 
 /// print dash
 pub(crate) fn print_dash(len: usize) {
-    println!("{}", "-".repeat(len));
+    human_println!("{}", "-".repeat(len));
 }
 
 /// top crates help to list out top n crates
@@ -153,42 +599,116 @@ pub(crate) fn show_top_number_crates(
     crate_type: &str,
     number: usize,
 ) {
-    // sort crates by size
-    let mut crates = crates.iter().collect::<Vec<_>>();
-    crates.sort_by_key(|a| std::cmp::Reverse(a.size()));
+    let mut crates = crates.iter().cloned().collect::<Vec<_>>();
+    sort_crates(&mut crates, CrateSort::Size);
     let top_number = std::cmp::min(crates.len(), number);
     let title = format!("Top {top_number} {crate_type}");
-    let mut listed_crates = Vec::new();
-    for &crate_metadata in crates.iter().take(top_number) {
-        listed_crates.push(crate_metadata.clone());
+    show_crate_table(&crates[..top_number], &title);
+}
+
+/// order of crates in top and list output
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum CrateSort {
+    Name,
+    Size,
+    Versions,
+    Age,
+}
+
+/// set order of crates in top and list output. When sort is not provided top
+/// output is sorted by size and list output by name
+pub(crate) fn set_crate_sort(sort: Option<CrateSort>, reverse: bool) {
+    let _ = CRATE_SORT.set((sort, reverse));
+}
+
+/// sort crates by order set by user or by default order. Size sort show
+/// largest crates first, versions sort show crates having most versions first
+/// and age sort show least recently used crates first
+pub(crate) fn sort_crates(crates: &mut [CrateMetaData], default: CrateSort) {
+    let (sort, reverse) = CRATE_SORT.get().copied().unwrap_or((None, false));
+    match sort.unwrap_or(default) {
+        CrateSort::Name => crates.sort(),
+        CrateSort::Size => crates.sort_by_key(|crate_metadata| Reverse(crate_metadata.size())),
+        CrateSort::Versions => {
+            let mut version_count: HashMap<String, usize> = HashMap::new();
+            for crate_metadata in crates.iter() {
+                *version_count.entry(base_name(crate_metadata)).or_default() += 1;
+            }
+            crates.sort_by_cached_key(|crate_metadata| {
+                let name = base_name(crate_metadata);
+                (Reverse(version_count[&name]), name, crate_metadata.clone())
+            });
+        }
+        CrateSort::Age => crates.sort_by_key(CrateMetaData::last_used),
     }
-    crate_list_type(&listed_crates[..top_number], &title);
+    if reverse {
+        crates.reverse();
+    }
+}
+
+// name of crate without version or revision of git crate
+fn base_name(crate_metadata: &CrateMetaData) -> String {
+    crate_metadata.git_id().map_or_else(
+        || crate_metadata.name().clone(),
+        |git_id| git_id.name().clone(),
+    )
 }
 
 // list certain crate type to terminal
 pub(crate) fn crate_list_type(crate_metadata_list: &[CrateMetaData], title: &str) {
-    let first_width = 44;
+    let mut crate_metadata_list = crate_metadata_list.to_vec();
+    sort_crates(&mut crate_metadata_list, CrateSort::Name);
+    show_crate_table(&crate_metadata_list, title);
+}
+
+// show table of crates in provided order. Name column grows to fit longest
+// name and name is truncated when table is wider than terminal
+fn show_crate_table(crate_metadata_list: &[CrateMetaData], title: &str) {
     let second_width = 16;
+    let names = crate_metadata_list
+        .iter()
+        .map(|crate_metadata| {
+            if let Some(crate_id) = crate_metadata.crate_id() {
+                // mark deprecated crate so it can be easily identified in list
+                let deprecated_mark = if is_deprecated(crate_metadata.name()) {
+                    " (deprecated)"
+                } else {
+                    ""
+                };
+                format!("{crate_id}{deprecated_mark}")
+            } else {
+                crate_metadata.name().clone()
+            }
+        })
+        .collect::<Vec<_>>();
+    let longest = names
+        .iter()
+        .map(|name| name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(title.chars().count())
+        .max(format!("Total no of crates:- {}", names.len()).len())
+        .max(MIN_NAME_WIDTH);
+    let first_width = available_width(second_width + 3).map_or(longest, |available| {
+        longest.min(available.max(MIN_TRUNCATED_WIDTH))
+    });
     let dash_len = first_width + second_width + 3;
-    crate::utils::show_title(title, first_width, second_width, dash_len);
+    crate::utils::show_title(
+        &fit_width(title, first_width),
+        first_width,
+        second_width,
+        dash_len,
+    );
 
     let mut total_size = 0;
-    for crate_metadata in crate_metadata_list {
+    for (crate_metadata, name) in crate_metadata_list.iter().zip(names) {
         let size = crate_metadata.size();
         total_size += size;
-        if let Some(version) = crate_metadata.version() {
-            println!(
-                "|{:^first_width$}|{:^second_width$}|",
-                format!("{}-{version}", crate_metadata.name()),
-                convert_pretty(size)
-            );
-        } else {
-            println!(
-                "|{:^first_width$}|{:^second_width$}|",
-                format!("{}", crate_metadata.name()),
-                convert_pretty(size)
-            );
-        }
+        human_println!(
+            "|{:^first_width$}|{:^second_width$}|",
+            fit_width(&name, first_width),
+            convert_pretty(size)
+        );
     }
     crate::utils::show_total_count(
         crate_metadata_list,
@@ -199,8 +719,16 @@ pub(crate) fn crate_list_type(crate_metadata_list: &[CrateMetaData], title: &str
     );
 }
 
+// first column shrinks to fit narrow terminal. Binary unit such as MiB is one
+// character longer than decimal unit
 fn query_param_widths() -> (usize, usize) {
-    (50, 10)
+    let second_width = match SIZE_UNIT.get() {
+        Some((_, true)) => 11,
+        _ => 10,
+    };
+    let first_width = available_width(second_width + 1)
+        .map_or(50, |available| available.clamp(MIN_TRUNCATED_WIDTH, 50));
+    (first_width, second_width)
 }
 
 pub(crate) fn query_full_width() -> usize {
@@ -208,16 +736,209 @@ pub(crate) fn query_full_width() -> usize {
     a + b + 1
 }
 
+// first param longer than its column uses remaining width of terminal before
+// it is truncated
 pub(crate) fn query_print(first_param: &str, second_param: &str) {
     let (first_path_width, second_path_width) = query_param_widths();
-    println!("{first_param:first_path_width$} {second_param:>second_path_width$}",);
+    let first_param = available_width(second_path_width + 1).map_or_else(
+        || first_param.to_string(),
+        |available| fit_width(first_param, available.max(first_path_width)),
+    );
+    human_println!("{first_param:first_path_width$} {second_param:>second_path_width$}");
+}
+
+/// return latest modified time among all paths
+pub(crate) fn last_modified(paths: &[PathBuf]) -> Option<SystemTime> {
+    paths
+        .iter()
+        .filter_map(|path| fs::metadata(path).ok()?.modified().ok())
+        .max()
+}
+
+/// return latest modified or accessed time of path and its direct children.
+/// Access time of directory is ignored since listing directory including scan
+/// of cargo trim itself updates it. Access time is only used where file system
+/// provides it
+pub(crate) fn last_used(path: &Path) -> Option<SystemTime> {
+    let mut paths = vec![path.to_path_buf()];
+    if path.is_dir() {
+        if let Ok(entries) = fs::read_dir(path) {
+            paths.extend(entries.filter_map(|entry| Some(entry.ok()?.path())));
+        }
+    }
+    paths
+        .iter()
+        .filter_map(|path| fs::metadata(path).ok())
+        .flat_map(|metadata| {
+            let accessed = metadata.accessed().ok().filter(|_| !metadata.is_dir());
+            [metadata.modified().ok(), accessed]
+        })
+        .flatten()
+        .max()
+}
+
+/// format system time as UTC timestamp in form of YYYY-MM-DDTHH:MM:SSZ
+pub(crate) fn format_timestamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let days = i64::try_from(seconds / 86400).unwrap_or(i64::MAX);
+    let time_of_day = seconds % 86400;
+    // convert days since epoch to civil date
+    let shifted_days = days + 719_468;
+    let era = shifted_days.div_euclid(146_097);
+    let day_of_era = shifted_days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        time_of_day / 3600,
+        time_of_day % 3600 / 60,
+        time_of_day % 60
+    )
+}
+
+/// parse human readable duration such as 30d, 2w, 12h, 45m or 10s
+///
+/// # Errors
+/// Return error if input is not number followed by supported unit
+pub fn parse_duration(input: &str) -> Result<Duration> {
+    let input = input.trim();
+    let unit_position = input
+        .find(|c: char| !c.is_ascii_digit())
+        .context("Duration must end with unit s, m, h, d or w")?;
+    let (number, unit) = input.split_at(unit_position);
+    let number = number
+        .parse::<u64>()
+        .context("Failed to parse number part of duration")?;
+    let multiplier = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => anyhow::bail!("Invalid duration unit {unit}. Supported units are s, m, h, d and w"),
+    };
+    let seconds = number
+        .checked_mul(multiplier)
+        .context("Duration is too large")?;
+    Ok(Duration::from_secs(seconds))
+}
+
+/// parse human readable size such as 500MB, 10GB or 1024. Units are 1000
+/// based same as size shown by cargo trim while KiB, MiB, GiB and TiB are 1024
+/// based
+pub(crate) fn parse_size(input: &str) -> Result<u64> {
+    let input = input.trim();
+    let unit_position = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(unit_position);
+    let number = number
+        .parse::<u64>()
+        .context("Failed to parse number part of size")?;
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "kb" => 1000,
+        "mb" => 1000_u64.pow(2),
+        "gb" => 1000_u64.pow(3),
+        "tb" => 1000_u64.pow(4),
+        "kib" => 1024,
+        "mib" => 1024_u64.pow(2),
+        "gib" => 1024_u64.pow(3),
+        "tib" => 1024_u64.pow(4),
+        _ => anyhow::bail!(
+            "Invalid size unit {unit}. Supported units are B, kB, MB, GB, TB, KiB, MiB, GiB and TiB"
+        ),
+    };
+    number.checked_mul(multiplier).context("Size is too large")
+}
+
+/// check if text matches glob pattern. `*` matches any number of character
+/// and `?` matches exactly one character
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+    let (mut pattern_pos, mut text_pos) = (0, 0);
+    // position of last star in pattern and text position matched by it
+    let mut backtrack = None;
+    while text_pos < text.len() {
+        match pattern.get(pattern_pos) {
+            Some('*') => {
+                backtrack = Some((pattern_pos, text_pos));
+                pattern_pos += 1;
+            }
+            Some(&character) if character == '?' || character == text[text_pos] => {
+                pattern_pos += 1;
+                text_pos += 1;
+            }
+            _ => {
+                let Some((star_pos, star_text_pos)) = backtrack else {
+                    return false;
+                };
+                pattern_pos = star_pos + 1;
+                text_pos = star_text_pos + 1;
+                backtrack = Some((star_pos, star_text_pos + 1));
+            }
+        }
+    }
+    pattern[pattern_pos..]
+        .iter()
+        .all(|&character| character == '*')
+}
+
+/// parse selection such as "1 3 5-7" into list of zero based index. Every
+/// provided number must be between 1 and max
+pub(crate) fn parse_selection(input: &str, max: usize) -> Result<Vec<usize>> {
+    let mut selection = Vec::new();
+    for part in input.split([' ', ',']).filter(|part| !part.is_empty()) {
+        let (start, end) = part.split_once('-').unwrap_or((part, part));
+        let start = start
+            .parse::<usize>()
+            .with_context(|| format!("Invalid selection {part}"))?;
+        let end = end
+            .parse::<usize>()
+            .with_context(|| format!("Invalid selection {part}"))?;
+        if start == 0 || end > max || start > end {
+            anyhow::bail!("Selection {part} is out of range 1-{max}");
+        }
+        selection.extend((start - 1)..end);
+    }
+    selection.sort_unstable();
+    selection.dedup();
+    Ok(selection)
+}
+
+/// escape field for csv output
+pub(crate) fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use std::time::{Duration, UNIX_EPOCH};
+
     use semver::Version;
 
-    use super::{convert_pretty, split_name_version};
+    use super::{
+        CrateSort, SizeUnit, convert_pretty, csv_field, format_size, format_timestamp, get_size,
+        glob_match, list_entry_sizes, parse_duration, parse_selection, parse_size, sort_crates,
+        split_name_version,
+    };
+    use crate::crate_detail::CrateMetaData;
 
     #[test]
     fn test_split_name_version() {
@@ -279,4 +1000,165 @@ mod test {
             "93453.982 TB".to_string()
         );
     }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0, SizeUnit::Auto, true), "  0.000 B");
+        assert_eq!(format_size(1536, SizeUnit::Auto, true), "  1.500 KiB");
+        assert_eq!(
+            format_size(874_940_334, SizeUnit::Auto, true),
+            "834.408 MiB"
+        );
+        assert_eq!(format_size(0, SizeUnit::Mb, false), "  0.000 MB");
+        assert_eq!(
+            format_size(8_849_909_404, SizeUnit::Mb, false),
+            "8849.909 MB"
+        );
+        assert_eq!(
+            format_size(1_073_741_824, SizeUnit::Gb, true),
+            "  1.000 GiB"
+        );
+        assert_eq!(format_size(1234, SizeUnit::B, false), "1234.000 B");
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            format_timestamp(UNIX_EPOCH + Duration::from_secs(951_782_401)),
+            "2000-02-29T00:00:01Z"
+        );
+        assert_eq!(
+            format_timestamp(UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+            "2023-11-14T22:13:20Z"
+        );
+    }
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("serde"), "serde");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("10s").unwrap(), Duration::from_secs(10));
+        assert_eq!(parse_duration("45m").unwrap(), Duration::from_mins(45));
+        assert_eq!(parse_duration("12h").unwrap(), Duration::from_hours(12));
+        assert_eq!(parse_duration("30d").unwrap(), Duration::from_hours(720));
+        assert_eq!(parse_duration("2w").unwrap(), Duration::from_hours(336));
+        assert!(parse_duration("30").is_err());
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("3y").is_err());
+    }
+
+    #[test]
+    fn test_parse_selection() {
+        assert_eq!(parse_selection("1 3", 5).unwrap(), vec![0, 2]);
+        assert_eq!(parse_selection("2-4,1", 5).unwrap(), vec![0, 1, 2, 3]);
+        assert_eq!(parse_selection("5 5", 5).unwrap(), vec![4]);
+        assert!(parse_selection("0", 5).is_err());
+        assert!(parse_selection("6", 5).is_err());
+        assert!(parse_selection("4-2", 5).is_err());
+        assert!(parse_selection("a", 5).is_err());
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("serde*", "serde"));
+        assert!(glob_match("serde*", "serde_json"));
+        assert!(glob_match("windows-*", "windows-sys"));
+        assert!(!glob_match("windows-*", "windows"));
+        assert!(glob_match("*-sys", "libz-sys"));
+        assert!(glob_match("tokio-?.*", "tokio-1.25.0"));
+        assert!(glob_match("a*b*c", "aXXbYYc"));
+        assert!(!glob_match("a*b*c", "aXXbYY"));
+        assert!(glob_match("exact", "exact"));
+        assert!(!glob_match("exact", "exactly"));
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024").unwrap(), 1024);
+        assert_eq!(parse_size("10kB").unwrap(), 10_000);
+        assert_eq!(parse_size("5 MB").unwrap(), 5_000_000);
+        assert_eq!(parse_size("2gb").unwrap(), 2_000_000_000);
+        assert_eq!(parse_size("1TB").unwrap(), 1_000_000_000_000);
+        assert_eq!(parse_size("3MiB").unwrap(), 3 * 1024 * 1024);
+        assert!(parse_size("GB").is_err());
+        assert!(parse_size("3PB").is_err());
+    }
+
+    #[test]
+    fn test_split_name_version_fallback() {
+        assert_eq!(
+            split_name_version("old_crate-1.2").unwrap(),
+            ("old_crate".to_string(), Version::new(1, 2, 0))
+        );
+        assert_eq!(
+            split_name_version("legacy-name-v3").unwrap(),
+            ("legacy-name".to_string(), Version::new(3, 0, 0))
+        );
+        assert_eq!(
+            split_name_version("four-part-1.2.3.4").unwrap(),
+            ("four-part".to_string(), Version::new(1, 2, 3))
+        );
+        assert!(split_name_version("no_version").is_err());
+        assert!(split_name_version("broken-abc").is_err());
+    }
+
+    #[test]
+    fn test_sort_crates() {
+        let crate_metadata = |name: &str, version: &str, size: u64| {
+            CrateMetaData::new(
+                name.to_string(),
+                Some(Version::parse(version).unwrap()),
+                size,
+                None,
+            )
+        };
+        let mut crates = vec![
+            crate_metadata("serde", "1.0.0", 10),
+            crate_metadata("anyhow", "1.0.0", 30),
+            crate_metadata("serde", "1.0.1", 20),
+        ];
+        sort_crates(&mut crates, CrateSort::Size);
+        assert_eq!(
+            crates.iter().map(CrateMetaData::size).collect::<Vec<_>>(),
+            vec![30, 20, 10]
+        );
+        sort_crates(&mut crates, CrateSort::Versions);
+        assert_eq!(
+            crates
+                .iter()
+                .map(CrateMetaData::full_name)
+                .collect::<Vec<_>>(),
+            vec!["serde-1.0.0", "serde-1.0.1", "anyhow-1.0.0"]
+        );
+    }
+
+    #[test]
+    fn test_list_entry_sizes() {
+        let dir =
+            std::env::temp_dir().join(format!("cargo_trim_entry_sizes_{}", std::process::id()));
+        let nested = dir.join("src").join("bin");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(dir.join("Cargo.toml"), "1234").unwrap();
+        std::fs::write(dir.join("src").join("lib.rs"), "12").unwrap();
+        std::fs::write(nested.join("main.rs"), "123").unwrap();
+        let entries = list_entry_sizes(&dir).unwrap();
+        assert_eq!(entries.len(), 5);
+        for (path, size) in &entries {
+            assert_eq!(*size, get_size(path).unwrap());
+        }
+        let src = entries
+            .iter()
+            .position(|(path, _)| *path == dir.join("src"));
+        let lib = entries
+            .iter()
+            .position(|(path, _)| *path == dir.join("src").join("lib.rs"));
+        assert!(src < lib);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -1,4 +1,8 @@
-use std::process::{Command, Stdio};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+use serde_json::Value;
 
 fn run_cargo_trim(args: &[&str]) {
     let status = Command::new("cargo")
@@ -53,3 +57,457 @@ fn test_set_help() {
 fn test_unset_help() {
     run_cargo_trim(&["help", "unset"]);
 }
+
+const INDEX: &str = "index.crates.io-1949cf8c6b5b557f";
+
+// temporary cargo home containing sparse crates.io index with registry crates
+// foo 1.0.0, foo 1.1.0 and bar 0.1.0. Directory is removed when dropped
+struct Fixture {
+    dir: PathBuf,
+}
+
+impl Fixture {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("cargo_trim_{name}_{}", std::process::id()));
+        if dir.exists() {
+            fs::remove_dir_all(&dir).unwrap();
+        }
+        let fixture = Self { dir };
+        let index = fixture.index();
+        fs::create_dir_all(&index).unwrap();
+        fs::write(
+            index.join("config.json"),
+            r#"{"dl":"https://static.crates.io/crates","api":"https://crates.io"}"#,
+        )
+        .unwrap();
+        fs::create_dir_all(fixture.archive("foo-1.0.0").parent().unwrap()).unwrap();
+        for crate_name in ["foo-1.0.0", "foo-1.1.0", "bar-0.1.0"] {
+            fs::write(fixture.archive(crate_name), format!("archive {crate_name}")).unwrap();
+            let source = fixture.source(crate_name);
+            fs::create_dir_all(source.join("src")).unwrap();
+            fs::write(source.join(".cargo-ok"), "ok").unwrap();
+            fs::write(
+                source.join("src").join("lib.rs"),
+                format!("// {crate_name}"),
+            )
+            .unwrap();
+            fs::write(source.join("LICENSE"), "license text").unwrap();
+        }
+        fixture
+    }
+
+    fn cargo_home(&self) -> PathBuf {
+        self.dir.join("cargo_home")
+    }
+
+    fn index(&self) -> PathBuf {
+        self.cargo_home().join("registry").join("index").join(INDEX)
+    }
+
+    fn archive(&self, crate_name: &str) -> PathBuf {
+        self.cargo_home()
+            .join("registry")
+            .join("cache")
+            .join(INDEX)
+            .join(format!("{crate_name}.crate"))
+    }
+
+    fn source(&self, crate_name: &str) -> PathBuf {
+        self.cargo_home()
+            .join("registry")
+            .join("src")
+            .join(INDEX)
+            .join(crate_name)
+    }
+
+    // run cargo trim against fixture cargo home with config files kept inside
+    // fixture directory
+    fn trim(&self, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_cargo-trim"))
+            .env("XDG_CONFIG_HOME", self.dir.join("config"))
+            .arg("trim")
+            .arg("--cargo-home")
+            .arg(self.cargo_home())
+            .arg("--force")
+            .args(args)
+            .output()
+            .unwrap()
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.dir).ok();
+    }
+}
+
+// parse ndjson events written to stdout
+fn events(output: &Output) -> Vec<Value> {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+// list path of deleted events
+fn deleted_paths(events: &[Value]) -> Vec<PathBuf> {
+    events
+        .iter()
+        .filter(|event| event["event"] == "deleted")
+        .map(|event| PathBuf::from(event["path"].as_str().unwrap()))
+        .collect()
+}
+
+// test old crates are only removed outside of dry run and exit code reports
+// nothing to trim afterwards
+#[test]
+fn test_old_crates() {
+    let fixture = Fixture::new("old_crates");
+    let output = fixture.trim(&["--old", "--dry-run"]);
+    assert!(output.status.success());
+    assert!(fixture.source("foo-1.0.0").exists());
+    assert!(fixture.archive("foo-1.0.0").exists());
+
+    let output = fixture.trim(&["--old", "--ndjson"]);
+    assert!(output.status.success());
+    let events = events(&output);
+    let mut deleted = deleted_paths(&events);
+    deleted.sort();
+    assert_eq!(
+        deleted,
+        vec![fixture.archive("foo-1.0.0"), fixture.source("foo-1.0.0")]
+    );
+    assert!(events.iter().any(|event| event["event"] == "freed"
+        && event["category"] == "registry"
+        && event["removed"] == 1));
+    assert!(!fixture.source("foo-1.0.0").exists());
+    assert!(!fixture.archive("foo-1.0.0").exists());
+    assert!(fixture.source("foo-1.1.0").exists());
+    assert!(fixture.archive("bar-0.1.0").exists());
+
+    let output = fixture.trim(&["--old", "--exit-code"]);
+    assert_eq!(output.status.code(), Some(3));
+}
+
+// test protected crate is kept by old crates removal
+#[test]
+fn test_protect() {
+    let fixture = Fixture::new("protect");
+    assert!(fixture.trim(&["protect", "foo"]).status.success());
+    let output = fixture.trim(&["--old", "--ndjson"]);
+    assert!(output.status.success());
+    assert!(deleted_paths(&events(&output)).is_empty());
+    assert!(fixture.source("foo-1.0.0").exists());
+    assert!(fixture.archive("foo-1.0.0").exists());
+}
+
+// test json list output contains old crate
+#[test]
+fn test_list_json() {
+    let fixture = Fixture::new("list_json");
+    let output = fixture.trim(&["list", "--old", "--format", "json"]);
+    assert!(output.status.success());
+    let list: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let old = list["old"].as_array().unwrap();
+    assert_eq!(old.len(), 1);
+    assert_eq!(old[0]["name"], "foo");
+    assert_eq!(old[0]["version"], "1.0.0");
+}
+
+// test verify only removes archive whose checksum does not match index entry
+#[test]
+fn test_verify_delete() {
+    let fixture = Fixture::new("verify_delete");
+    let index_cache = fixture.index().join(".cache").join("3").join("b");
+    fs::create_dir_all(&index_cache).unwrap();
+    fs::write(
+        index_cache.join("bar"),
+        "1\0{\"name\":\"bar\",\"vers\":\"0.1.0\",\"cksum\":\"0000\"}\0",
+    )
+    .unwrap();
+    let output = fixture.trim(&["verify", "--delete", "--ndjson"]);
+    assert!(output.status.success());
+    assert_eq!(
+        deleted_paths(&events(&output)),
+        vec![fixture.archive("bar-0.1.0")]
+    );
+    assert!(!fixture.archive("bar-0.1.0").exists());
+    assert!(fixture.source("bar-0.1.0").exists());
+    assert!(fixture.archive("foo-1.0.0").exists());
+}
+
+// test repair removes partial download and partial extraction
+#[test]
+fn test_repair() {
+    let fixture = Fixture::new("repair");
+    fs::write(fixture.archive("foo-1.0.0"), "").unwrap();
+    fs::remove_file(fixture.source("bar-0.1.0").join(".cargo-ok")).unwrap();
+    let output = fixture.trim(&["repair", "--exit-code"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(!fixture.archive("foo-1.0.0").exists());
+    assert!(fixture.source("foo-1.0.0").exists());
+    assert!(!fixture.source("bar-0.1.0").exists());
+    assert!(fixture.archive("bar-0.1.0").exists());
+}
+
+// test identical files of registry sources are replaced with hardlink
+#[cfg(unix)]
+#[test]
+fn test_dedupe() {
+    use std::os::unix::fs::MetadataExt;
+
+    let fixture = Fixture::new("dedupe");
+    assert!(fixture.trim(&["dedupe"]).status.success());
+    let inode = |crate_name: &str| {
+        fs::metadata(fixture.source(crate_name).join("LICENSE"))
+            .unwrap()
+            .ino()
+    };
+    assert_eq!(inode("foo-1.0.0"), inode("foo-1.1.0"));
+    assert_eq!(inode("foo-1.0.0"), inode("bar-0.1.0"));
+    assert_ne!(
+        fs::metadata(fixture.source("foo-1.0.0").join("src").join("lib.rs"))
+            .unwrap()
+            .ino(),
+        fs::metadata(fixture.source("foo-1.1.0").join("src").join("lib.rs"))
+            .unwrap()
+            .ino()
+    );
+}
+
+// test uninstall removes binary along with its install metadata entry
+#[test]
+fn test_uninstall() {
+    let fixture = Fixture::new("uninstall");
+    let cargo_home = fixture.cargo_home();
+    let bin_dir = cargo_home.join("bin");
+    fs::create_dir_all(&bin_dir).unwrap();
+    fs::write(bin_dir.join("tool"), "tool").unwrap();
+    fs::write(bin_dir.join("other"), "other").unwrap();
+    let package = "tool 0.1.0 (registry+https://github.com/rust-lang/crates.io-index)";
+    fs::write(
+        cargo_home.join(".crates2.json"),
+        format!("{{\"installs\":{{\"{package}\":{{\"bins\":[\"tool\"]}}}}}}"),
+    )
+    .unwrap();
+    fs::write(
+        cargo_home.join(".crates.toml"),
+        format!("[v1]\n\"{package}\" = [\"tool\"]\n"),
+    )
+    .unwrap();
+    let output = fixture.trim(&["uninstall", "tool", "--ndjson"]);
+    assert!(output.status.success());
+    assert_eq!(deleted_paths(&events(&output)), vec![bin_dir.join("tool")]);
+    assert!(!bin_dir.join("tool").exists());
+    assert!(bin_dir.join("other").exists());
+    assert!(
+        !fs::read_to_string(cargo_home.join(".crates2.json"))
+            .unwrap()
+            .contains(package)
+    );
+    assert!(
+        !fs::read_to_string(cargo_home.join(".crates.toml"))
+            .unwrap()
+            .contains(package)
+    );
+
+    let output = fixture.trim(&["uninstall", "missing"]);
+    assert!(!output.status.success());
+}
+
+// test archived registry sources are extracted again with same content
+#[test]
+fn test_archive_unarchive() {
+    let fixture = Fixture::new("archive_unarchive");
+    assert!(
+        fixture
+            .trim(&["archive", "--unused-for", "0s"])
+            .status
+            .success()
+    );
+    assert!(!fixture.source("foo-1.0.0").exists());
+    let archived = fixture
+        .source("foo-1.0.0")
+        .parent()
+        .unwrap()
+        .join("foo-1.0.0.tar.gz");
+    assert!(archived.is_file());
+
+    assert!(fixture.trim(&["unarchive", "--all"]).status.success());
+    assert!(!archived.exists());
+    assert_eq!(
+        fs::read_to_string(fixture.source("foo-1.0.0").join("src").join("lib.rs")).unwrap(),
+        "// foo-1.0.0"
+    );
+}
+
+// test only archives used by lock file of project are exported
+#[test]
+fn test_export_vendor() {
+    let fixture = Fixture::new("export_vendor");
+    let project = fixture.dir.join("project");
+    let vendor = fixture.dir.join("vendor");
+    fs::create_dir_all(&project).unwrap();
+    fs::write(
+        project.join("Cargo.toml"),
+        "[package]\nname = \"project\"\nversion = \"0.1.0\"\n",
+    )
+    .unwrap();
+    fs::write(
+        project.join("Cargo.lock"),
+        "version = 3\n\n[[package]]\nname = \"foo\"\nversion = \"1.1.0\"\nsource = \
+         \"registry+https://github.com/rust-lang/crates.io-index\"\n\n[[package]]\nname = \
+         \"project\"\nversion = \"0.1.0\"\ndependencies = [\"foo\"]\n",
+    )
+    .unwrap();
+    let output = fixture.trim(&[
+        "export-vendor",
+        vendor.to_str().unwrap(),
+        "--project",
+        project.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    assert_eq!(
+        fs::read_to_string(vendor.join("foo-1.1.0.crate")).unwrap(),
+        "archive foo-1.1.0"
+    );
+    assert!(!vendor.join("foo-1.0.0.crate").exists());
+    assert!(!vendor.join("bar-0.1.0.crate").exists());
+    assert!(fixture.archive("foo-1.1.0").exists());
+}
+
+// test check exit code depends on maximum size
+#[test]
+fn test_check() {
+    let fixture = Fixture::new("check");
+    assert_eq!(
+        fixture.trim(&["check", "--max-size", "1B"]).status.code(),
+        Some(5)
+    );
+    assert_eq!(
+        fixture.trim(&["check", "--max-size", "1GB"]).status.code(),
+        Some(0)
+    );
+}
+
+// test summary files are aggregated into json fleet report
+#[test]
+fn test_aggregate() {
+    let fixture = Fixture::new("aggregate");
+    let summary_file = fixture.dir.join("summary.json");
+    let output = fixture.trim(&["--old", "--summary-file", summary_file.to_str().unwrap()]);
+    assert!(output.status.success());
+    let summary: Value = serde_json::from_str(&fs::read_to_string(&summary_file).unwrap()).unwrap();
+    assert_eq!(summary["total_removed"], 1);
+    let total = summary["cache"]["total"].as_u64().unwrap();
+
+    let output = fixture.trim(&[
+        "aggregate",
+        "--format",
+        "json",
+        summary_file.to_str().unwrap(),
+        summary_file.to_str().unwrap(),
+    ]);
+    assert!(output.status.success());
+    let report: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["total"], total * 2);
+    assert_eq!(report["machines"].as_array().unwrap().len(), 2);
+}
+
+fn run_in(dir: &Path, program: &str, args: &[&str], cargo_home: &Path) {
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(dir)
+        .env("CARGO_HOME", cargo_home)
+        .env_remove("CARGO_TARGET_DIR")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{program} {} failed: {}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+// test shallow copy of git db is written outside of cargo home and cargo can
+// still build offline and update git dependency afterwards
+#[test]
+fn test_git_shallow() {
+    let dir = std::env::temp_dir().join(format!("cargo_trim_git_shallow_{}", std::process::id()));
+    let upstream = dir.join("upstream");
+    let app = dir.join("app");
+    let cargo_home = dir.join("cargo_home");
+    let shallow_dir = dir.join("shallow");
+    fs::create_dir_all(upstream.join("src")).unwrap();
+    fs::create_dir_all(app.join("src")).unwrap();
+    let git = |args: &[&str]| {
+        let mut git_args = vec!["-c", "user.name=test", "-c", "user.email=test@example.com"];
+        git_args.extend(args);
+        run_in(&upstream, "git", &git_args, &cargo_home);
+    };
+    git(&["init", "--quiet"]);
+    fs::write(
+        upstream.join("Cargo.toml"),
+        "[package]\nname = \"foo\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )
+    .unwrap();
+    for version in 1..=3 {
+        fs::write(
+            upstream.join("src").join("lib.rs"),
+            format!("pub fn version() -> u32 {{ {version} }}\n"),
+        )
+        .unwrap();
+        git(&["add", "-A"]);
+        git(&["commit", "--quiet", "-m", &version.to_string()]);
+    }
+    fs::write(
+        app.join("Cargo.toml"),
+        format!(
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\nfoo = \
+             {{ git = \"file://{}\", branch = \"master\" }}\n",
+            upstream.display()
+        ),
+    )
+    .unwrap();
+    fs::write(
+        app.join("src").join("main.rs"),
+        "fn main() {\n    println!(\"{}\", foo::version());\n}\n",
+    )
+    .unwrap();
+    git(&["branch", "--move", "master"]);
+    run_in(&app, "cargo", &["build", "--quiet"], &cargo_home);
+
+    let status = Command::new("cargo")
+        .stdout(Stdio::null())
+        .env("XDG_CONFIG_HOME", dir.join("config"))
+        .arg("run")
+        .arg("--")
+        .arg("trim")
+        .arg("--cargo-home")
+        .arg(&cargo_home)
+        .arg("git")
+        .arg("--shallow")
+        .arg(&shallow_dir)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let db = fs::read_dir(cargo_home.join("git").join("db"))
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap();
+    assert!(!db.path().join("shallow").exists());
+    assert!(shallow_dir.join(db.file_name()).join("shallow").is_file());
+
+    fs::remove_dir_all(app.join("target")).unwrap();
+    run_in(
+        &app,
+        "cargo",
+        &["build", "--offline", "--quiet"],
+        &cargo_home,
+    );
+    git(&["commit", "--quiet", "--allow-empty", "-m", "4"]);
+    run_in(&app, "cargo", &["update", "--quiet"], &cargo_home);
+    fs::remove_dir_all(&dir).unwrap();
+}